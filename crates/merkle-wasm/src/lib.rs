@@ -0,0 +1,484 @@
+//! Client-side proof generation for claim frontends.
+//!
+//! Build with `wasm-pack build crates/merkle-wasm --target web`. The page
+//! loads the published allocations file (a JSON array of
+//! `{ "wallet": "<base58>", "amount": <u64> }`, where the array position is
+//! the leaf index), checks that its root matches the on-chain one, and
+//! derives the connected wallet's proof without any proof server:
+//!
+//! ```js
+//! const allocations = new Allocations(await (await fetch(allocationsUri)).text());
+//! if (allocations.root() !== onChainRootHex) throw new Error("stale allocations file");
+//! const { index, amount, proof } = allocations.proofFor(wallet.toBase58());
+//! ```
+//!
+//! Before `initialize`, the same file yields the campaign's commitments, so
+//! the snapshot hash is derived rather than chosen and can't drift from the
+//! root. A page or audit script can also re-check a live campaign against
+//! the file:
+//!
+//! ```js
+//! const snapshotHash = allocations.snapshotHash(); // `initialize`'s snapshot_hash
+//! allocations.checkCommitments(onChainRootHex, onChainSnapshotHashHex); // throws on mismatch
+//! ```
+//!
+//! Trees of millions of leaves make an allocations file too large for a page
+//! to fetch. The export step splits the proofs into shards by the leading
+//! bits of each wallet and writes a manifest; a page then fetches only the
+//! connected wallet's shard:
+//!
+//! ```js
+//! const shards = allocations.proofShards(12); // export, up to 4096 shards; consumes `allocations`
+//! for (const { prefix } of JSON.parse(shards.manifest()).shards) {
+//!     write(`${prefix}.json`, shards.shard(prefix));
+//! }
+//! const shard = await (await fetch(`${shardPrefix(wallet, 12)}.json`)).text(); // page
+//! const { index, amount, proof } = proofFromShard(shard, wallet);
+//! ```
+//!
+//! Campaigns using `LeafEncoding::EvmPacked` load the file with
+//! `Allocations.evmPacked(json)` instead. Its wallets may also be `0x`-prefixed
+//! EVM addresses, so the same file and root serve the Solidity distributor.
+
+use std::collections::BTreeMap;
+
+use merkle::{Allocation, Hash, LeafKind, MerkleTree, Snapshot};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[derive(Deserialize)]
+struct Entry {
+    wallet: String,
+    amount: u64,
+}
+
+/// Most leading wallet bits a shard can be keyed by; 65536 shards at most.
+const MAX_PREFIX_BITS: u8 = 16;
+
+/// A parsed allocations file and the tree built from it.
+#[wasm_bindgen]
+pub struct Allocations {
+    leaf_kind: LeafKind,
+    wallets: Vec<Vec<u8>>,
+    amounts: Vec<u64>,
+    tree: MerkleTree,
+}
+
+/// A claimant's leaf and proof, ready for `claim`'s arguments.
+#[wasm_bindgen(getter_with_clone)]
+pub struct ClaimProof {
+    pub index: u64,
+    pub amount: u64,
+    /// Hex-encoded sibling hashes, bottom up
+    pub proof: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl Allocations {
+    #[wasm_bindgen(constructor)]
+    pub fn new(json: &str) -> Result<Allocations, JsError> {
+        let decode = |wallet: &str| decode_wallet(wallet).map(Vec::from);
+        Self::build(json, LeafKind::Native, decode, |index, wallet, amount| {
+            merkle::leaf(index, wallet.try_into().expect("decoded as 32 bytes"), amount)
+        })
+    }
+
+    /// Tree of `abi.encodePacked` leaves; wallets are base58 pubkeys or
+    /// `0x`-prefixed EVM addresses.
+    #[wasm_bindgen(js_name = evmPacked)]
+    pub fn evm_packed(json: &str) -> Result<Allocations, JsError> {
+        Self::build(json, LeafKind::EvmPacked, decode_account, merkle::evm_leaf)
+    }
+
+    /// Hex-encoded root, to compare with the campaign's `merkle_root`.
+    pub fn root(&self) -> String {
+        hex::encode(self.tree.root())
+    }
+
+    /// Sum of all amounts, for `initialize`'s `total_allocation`.
+    #[wasm_bindgen(js_name = totalAmount)]
+    pub fn total_amount(&self) -> Result<u64, JsError> {
+        self.amounts
+            .iter()
+            .try_fold(0u64, |total, amount| total.checked_add(*amount))
+            .ok_or_else(|| JsError::new("total amount overflows u64"))
+    }
+
+    /// Hex-encoded `merkle::snapshot_hash` of this file, for `initialize`'s
+    /// `snapshot_hash`.
+    #[wasm_bindgen(js_name = snapshotHash)]
+    pub fn snapshot_hash(&self) -> Result<String, JsError> {
+        let leaf_count = self.amounts.len() as u64;
+        Ok(hex::encode(merkle::snapshot_hash(&self.tree.root(), leaf_count, self.total_amount()?)))
+    }
+
+    /// Fails unless both on-chain commitments were built from this file.
+    #[wasm_bindgen(js_name = checkCommitments)]
+    pub fn check_commitments(&self, root: &str, snapshot_hash: &str) -> Result<(), JsError> {
+        if decode_hash(root)? != self.tree.root() {
+            return Err(JsError::new("merkle root was not built from this allocations file"));
+        }
+        if decode_hash(snapshot_hash)? != decode_hash(&self.snapshot_hash()?)? {
+            return Err(JsError::new("snapshot hash was not derived from this allocations file"));
+        }
+        Ok(())
+    }
+
+    /// Every leaf's proof, sharded by the leading `prefix_bits` bits of its
+    /// wallet, so all of a wallet's allocations land in one shard. Takes
+    /// over this tree rather than copying it, which matters at the sizes
+    /// sharding is for.
+    #[wasm_bindgen(js_name = proofShards)]
+    pub fn proof_shards(self, prefix_bits: u8) -> Result<ProofShards, JsError> {
+        check_prefix_bits(prefix_bits)?;
+        let mut shards: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        for (index, wallet) in self.wallets.iter().enumerate() {
+            shards.entry(wallet_prefix(wallet, prefix_bits)).or_default().push(index);
+        }
+        Ok(ProofShards { allocations: self, prefix_bits, shards })
+    }
+
+    /// The canonical `merkle::Snapshot` encoding of this file. Operators
+    /// building from the same allocations get byte-identical output, ending
+    /// in the root.
+    pub fn canonical(&self) -> Vec<u8> {
+        let allocations = self
+            .wallets
+            .iter()
+            .zip(&self.amounts)
+            .enumerate()
+            .map(|(index, (wallet, amount))| Allocation {
+                index: index as u64,
+                account: wallet.clone(),
+                amount: *amount,
+            })
+            .collect();
+        Snapshot::new(self.leaf_kind, allocations)
+            .expect("indices are positions and accounts were decoded")
+            .encode()
+    }
+
+    /// Leaf and proof of `wallet`'s first allocation.
+    #[wasm_bindgen(js_name = proofFor)]
+    pub fn proof_for(&self, wallet: &str) -> Result<ClaimProof, JsError> {
+        let wallet = decode_account(wallet)?;
+        let index = self
+            .wallets
+            .iter()
+            .position(|w| *w == wallet)
+            .ok_or_else(|| JsError::new("wallet has no allocation"))?;
+        let proof = self.tree.proof(index).expect("index comes from the leaf list");
+        Ok(ClaimProof {
+            index: index as u64,
+            amount: self.amounts[index],
+            proof: proof.iter().map(hex::encode).collect(),
+        })
+    }
+}
+
+/// An allocations tree with its leaves grouped into shards by wallet
+/// prefix; a shard's proofs are only derived when it is written out.
+#[wasm_bindgen]
+pub struct ProofShards {
+    allocations: Allocations,
+    prefix_bits: u8,
+    // Leaf indices of each non-empty shard, keyed by prefix
+    shards: BTreeMap<u32, Vec<usize>>,
+}
+
+#[derive(Serialize)]
+struct Manifest {
+    root: String,
+    leaf_count: usize,
+    prefix_bits: u8,
+    shards: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize)]
+struct ManifestEntry {
+    prefix: u32,
+    count: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Shard {
+    root: String,
+    prefix_bits: u8,
+    prefix: u32,
+    claims: Vec<ShardClaim>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ShardClaim {
+    wallet: String,
+    index: u64,
+    amount: u64,
+    proof: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl ProofShards {
+    /// JSON manifest: the root, leaf count, `prefix_bits` and every
+    /// non-empty shard's prefix and claim count.
+    pub fn manifest(&self) -> Result<String, JsError> {
+        let manifest = Manifest {
+            root: self.allocations.root(),
+            leaf_count: self.allocations.amounts.len(),
+            prefix_bits: self.prefix_bits,
+            shards: self
+                .shards
+                .iter()
+                .map(|(prefix, indices)| ManifestEntry { prefix: *prefix, count: indices.len() })
+                .collect(),
+        };
+        Ok(serde_json::to_string(&manifest)?)
+    }
+
+    /// JSON of the shard for `prefix`, or `undefined` if no wallet has it.
+    pub fn shard(&self, prefix: u32) -> Result<Option<String>, JsError> {
+        let Some(indices) = self.shards.get(&prefix) else {
+            return Ok(None);
+        };
+        let allocations = &self.allocations;
+        let claims = indices
+            .iter()
+            .map(|index| {
+                let proof = allocations.tree.proof(*index).expect("index comes from the leaf list");
+                ShardClaim {
+                    wallet: encode_account(&allocations.wallets[*index]),
+                    index: *index as u64,
+                    amount: allocations.amounts[*index],
+                    proof: proof.iter().map(hex::encode).collect(),
+                }
+            })
+            .collect();
+        let shard = Shard {
+            root: allocations.root(),
+            prefix_bits: self.prefix_bits,
+            prefix,
+            claims,
+        };
+        Ok(Some(serde_json::to_string(&shard)?))
+    }
+}
+
+impl Allocations {
+    fn build(
+        json: &str,
+        leaf_kind: LeafKind,
+        decode: impl Fn(&str) -> Result<Vec<u8>, JsError>,
+        leaf: impl Fn(u64, &[u8], u64) -> Hash,
+    ) -> Result<Allocations, JsError> {
+        let entries: Vec<Entry> = serde_json::from_str(json)?;
+        let mut wallets = Vec::with_capacity(entries.len());
+        let mut amounts = Vec::with_capacity(entries.len());
+        for entry in entries {
+            wallets.push(decode(&entry.wallet)?);
+            amounts.push(entry.amount);
+        }
+        let leaves = wallets
+            .iter()
+            .zip(&amounts)
+            .enumerate()
+            .map(|(index, (wallet, amount))| leaf(index as u64, wallet, *amount))
+            .collect();
+        Ok(Allocations { leaf_kind, wallets, amounts, tree: MerkleTree::from_leaves(leaves) })
+    }
+}
+
+/// Checks a canonical snapshot file against a campaign's hex `merkle_root`:
+/// the file must decode, rebuild the root it ends in, and that root must be
+/// the campaign's.
+#[wasm_bindgen(js_name = verifyRoot)]
+pub fn verify_root(snapshot: &[u8], root: &str) -> Result<(), JsError> {
+    let snapshot = Snapshot::decode(snapshot).map_err(|err| JsError::new(&err.to_string()))?;
+    if snapshot.root() != decode_hash(root)? {
+        return Err(JsError::new("merkle root was not built from this snapshot"));
+    }
+    Ok(())
+}
+
+/// Shard holding `wallet`'s proofs in an export by `proofShards(prefix_bits)`.
+#[wasm_bindgen(js_name = shardPrefix)]
+pub fn shard_prefix(wallet: &str, prefix_bits: u8) -> Result<u32, JsError> {
+    check_prefix_bits(prefix_bits)?;
+    Ok(wallet_prefix(&decode_account(wallet)?, prefix_bits))
+}
+
+/// Leaf and proof of `wallet`'s first allocation in a fetched shard. Check
+/// the proof with `verify` against the on-chain root; a shard is only as
+/// trustworthy as the host serving it.
+#[wasm_bindgen(js_name = proofFromShard)]
+pub fn proof_from_shard(shard: &str, wallet: &str) -> Result<ClaimProof, JsError> {
+    let shard: Shard = serde_json::from_str(shard)?;
+    let wallet = decode_account(wallet)?;
+    for claim in shard.claims {
+        if decode_account(&claim.wallet)? == wallet {
+            return Ok(ClaimProof { index: claim.index, amount: claim.amount, proof: claim.proof });
+        }
+    }
+    Err(JsError::new("wallet has no allocation in this shard"))
+}
+
+/// Checks a proof locally against `root`, exactly as the program will.
+#[wasm_bindgen]
+pub fn verify(
+    wallet: &str,
+    index: u64,
+    amount: u64,
+    proof: Vec<String>,
+    root: &str,
+) -> Result<bool, JsError> {
+    let leaf = merkle::leaf(index, &decode_wallet(wallet)?, amount);
+    let proof = proof.iter().map(|node| decode_hash(node)).collect::<Result<Vec<_>, _>>()?;
+    Ok(merkle::verify(&leaf, &proof, &decode_hash(root)?))
+}
+
+/// `verify` for `abi.encodePacked` leaves; `wallet` may be an EVM address.
+#[wasm_bindgen(js_name = verifyEvmPacked)]
+pub fn verify_evm_packed(
+    wallet: &str,
+    index: u64,
+    amount: u64,
+    proof: Vec<String>,
+    root: &str,
+) -> Result<bool, JsError> {
+    let leaf = merkle::evm_leaf(index, &decode_account(wallet)?, amount);
+    let proof = proof.iter().map(|node| decode_hash(node)).collect::<Result<Vec<_>, _>>()?;
+    Ok(merkle::verify(&leaf, &proof, &decode_hash(root)?))
+}
+
+/// Hex-encoded `merkle::claim_commitment` for `commit_claim`; `salt` is 32
+/// random bytes, hex-encoded, kept until `claim_ticketed` reveals them.
+#[wasm_bindgen(js_name = claimCommitment)]
+pub fn claim_commitment(wallet: &str, index: u64, amount: u64, salt: &str) -> Result<String, JsError> {
+    let commitment = merkle::claim_commitment(index, &decode_wallet(wallet)?, amount, &decode_hash(salt)?);
+    Ok(hex::encode(commitment))
+}
+
+fn decode_wallet(wallet: &str) -> Result<[u8; 32], JsError> {
+    let mut bytes = [0u8; 32];
+    match bs58::decode(wallet).onto(&mut bytes) {
+        Ok(32) => Ok(bytes),
+        _ => Err(JsError::new(&format!("invalid wallet address: {wallet}"))),
+    }
+}
+
+// A base58 Solana pubkey or a `0x`-prefixed 20-byte EVM address
+fn decode_account(account: &str) -> Result<Vec<u8>, JsError> {
+    match account.strip_prefix("0x") {
+        Some(address) => {
+            let mut bytes = [0u8; 20];
+            hex::decode_to_slice(address, &mut bytes)?;
+            Ok(bytes.to_vec())
+        }
+        None => decode_wallet(account).map(Vec::from),
+    }
+}
+
+// Inverse of `decode_account`
+fn encode_account(account: &[u8]) -> String {
+    if account.len() == 20 {
+        format!("0x{}", hex::encode(account))
+    } else {
+        bs58::encode(account).into_string()
+    }
+}
+
+// The leading `prefix_bits` bits of the account, big-endian
+fn wallet_prefix(account: &[u8], prefix_bits: u8) -> u32 {
+    let leading = u32::from_be_bytes([0, 0, account[0], account[1]]);
+    leading >> (MAX_PREFIX_BITS - prefix_bits)
+}
+
+fn check_prefix_bits(prefix_bits: u8) -> Result<(), JsError> {
+    if prefix_bits > MAX_PREFIX_BITS {
+        return Err(JsError::new(&format!("prefix_bits must be at most {MAX_PREFIX_BITS}")));
+    }
+    Ok(())
+}
+
+fn decode_hash(node: &str) -> Result<Hash, JsError> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(node, &mut hash)?;
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A base58 wallet whose first two bytes are `leading`
+    fn wallet(leading: [u8; 2], fill: u8) -> String {
+        let mut bytes = [fill; 32];
+        bytes[..2].copy_from_slice(&leading);
+        bs58::encode(bytes).into_string()
+    }
+
+    fn allocations(wallets: &[String]) -> Allocations {
+        let entries: Vec<String> = wallets
+            .iter()
+            .enumerate()
+            .map(|(index, wallet)| format!(r#"{{"wallet":"{wallet}","amount":{}}}"#, 100 * (index + 1)))
+            .collect();
+        Allocations::new(&format!("[{}]", entries.join(","))).unwrap()
+    }
+
+    #[test]
+    fn prefixes_split_at_the_bit_boundaries() {
+        assert_eq!(wallet_prefix(&[0x00, 0x0f], 12), 0);
+        assert_eq!(wallet_prefix(&[0x00, 0x10], 12), 1);
+        assert_eq!(wallet_prefix(&[0x7f, 0xff], 1), 0);
+        assert_eq!(wallet_prefix(&[0x80, 0x00], 1), 1);
+        assert_eq!(wallet_prefix(&[0xff, 0xff], 12), 4095);
+        assert_eq!(wallet_prefix(&[0xff, 0xff], MAX_PREFIX_BITS), 0xffff);
+        assert_eq!(wallet_prefix(&[0xff, 0xff], 0), 0);
+        // EVM addresses shard by their leading bits too
+        assert_eq!(wallet_prefix(&[0xab; 20], 8), 0xab);
+        assert_eq!(shard_prefix(&wallet([0x12, 0x34], 1), 8).unwrap(), 0x12);
+        assert_eq!(shard_prefix(&format!("0x{}", "cd".repeat(20)), 4).unwrap(), 0xc);
+    }
+
+    #[test]
+    fn manifest_counts_every_non_empty_shard() {
+        let wallets = [
+            wallet([0x00, 0x00], 1),
+            wallet([0x0f, 0xff], 2),
+            wallet([0x10, 0x00], 3),
+            wallet([0xff, 0xff], 4),
+        ];
+        let allocations = allocations(&wallets);
+        let root = allocations.root();
+        let shards = allocations.proof_shards(4).unwrap();
+
+        let manifest: serde_json::Value = serde_json::from_str(&shards.manifest().unwrap()).unwrap();
+        let expected = serde_json::json!({
+            "root": root,
+            "leaf_count": 4,
+            "prefix_bits": 4,
+            "shards": [
+                { "prefix": 0, "count": 2 },
+                { "prefix": 1, "count": 1 },
+                { "prefix": 15, "count": 1 },
+            ],
+        });
+        assert_eq!(manifest, expected);
+        assert!(shards.shard(2).unwrap().is_none());
+    }
+
+    #[test]
+    fn proofs_from_a_shard_verify_against_the_root() {
+        let wallets: Vec<String> = (0..9u8).map(|n| wallet([n * 29, n], n)).collect();
+        let allocations = allocations(&wallets);
+        let root = allocations.root();
+        let shards = allocations.proof_shards(3).unwrap();
+
+        for (index, wallet) in wallets.iter().enumerate() {
+            let prefix = shard_prefix(wallet, 3).unwrap();
+            let shard = shards.shard(prefix).unwrap().expect("the wallet's shard exists");
+            let claim = proof_from_shard(&shard, wallet).unwrap();
+            assert_eq!((claim.index, claim.amount), (index as u64, 100 * (index as u64 + 1)));
+            assert!(verify(wallet, claim.index, claim.amount, claim.proof, &root).unwrap(), "wallet {index}");
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Leaf encodings must be injective and the plain and vesting encodings must
+//! never produce the same hash.
+//!
+//! `cargo +nightly fuzz run leaf_encoding` from `crates/merkle`.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug, PartialEq)]
+struct Fields {
+    index: u64,
+    wallet: [u8; 32],
+    amount: u64,
+    cliff: i64,
+    duration: i64,
+    tge_bps: u16,
+}
+
+fn plain(f: &Fields) -> merkle::Hash {
+    merkle::leaf(f.index, &f.wallet, f.amount)
+}
+
+fn vesting(f: &Fields) -> merkle::Hash {
+    merkle::vesting_leaf(f.index, &f.wallet, f.amount, f.cliff, f.duration, f.tge_bps)
+}
+
+fuzz_target!(|pair: (Fields, Fields)| {
+    let (a, b) = pair;
+    let same_leaf = (a.index, a.wallet, a.amount) == (b.index, b.wallet, b.amount);
+    assert_eq!(plain(&a) == plain(&b), same_leaf);
+    assert_eq!(vesting(&a) == vesting(&b), a == b);
+    assert_ne!(plain(&a), vesting(&b));
+});
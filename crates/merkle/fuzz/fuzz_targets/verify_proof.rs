@@ -0,0 +1,95 @@
+//! Builds a tree with the reference builder, checks that its proof verifies,
+//! then applies one mutation to the leaf fields, proof or root and checks
+//! that the mutated claim no longer verifies.
+//!
+//! `cargo +nightly fuzz run verify_proof` from `crates/merkle`.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use merkle::{Hash, MerkleTree};
+
+#[derive(Arbitrary, Debug)]
+struct Allocation {
+    wallet: [u8; 32],
+    amount: u64,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Mutation {
+    Index(u64),
+    Wallet([u8; 32]),
+    Amount(u64),
+    ProofByte { node: u8, byte: u8, xor: u8 },
+    RootByte { byte: u8, xor: u8 },
+    SwapNodes(u8, u8),
+    DropNode(u8),
+    PushNode(Hash),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    allocations: Vec<Allocation>,
+    pick: u16,
+    mutation: Mutation,
+}
+
+fuzz_target!(|input: Input| {
+    let Input { allocations, pick, mutation } = input;
+    if allocations.is_empty() || allocations.len() > 256 {
+        return;
+    }
+    let leaves = allocations
+        .iter()
+        .enumerate()
+        .map(|(index, a)| merkle::leaf(index as u64, &a.wallet, a.amount))
+        .collect();
+    let tree = MerkleTree::from_leaves(leaves);
+    let root = tree.root();
+
+    let index = pick as usize % allocations.len();
+    let Allocation { wallet, amount } = allocations[index];
+    let proof = tree.proof(index).unwrap();
+    let leaf = merkle::leaf(index as u64, &wallet, amount);
+    assert!(merkle::verify(&leaf, &proof, &root), "reference proof rejected");
+
+    let (mut m_index, mut m_wallet, mut m_amount) = (index as u64, wallet, amount);
+    let (mut m_proof, mut m_root) = (proof.clone(), root);
+    match mutation {
+        Mutation::Index(i) => m_index = i,
+        Mutation::Wallet(w) => m_wallet = w,
+        Mutation::Amount(a) => m_amount = a,
+        Mutation::ProofByte { node, byte, xor } => {
+            if let Some(n) = m_proof.get_mut(node as usize % proof.len().max(1)) {
+                n[byte as usize % 32] ^= xor;
+            }
+        }
+        Mutation::RootByte { byte, xor } => m_root[byte as usize % 32] ^= xor,
+        Mutation::SwapNodes(a, b) => {
+            if !m_proof.is_empty() {
+                let len = m_proof.len();
+                m_proof.swap(a as usize % len, b as usize % len);
+            }
+        }
+        Mutation::DropNode(node) => {
+            if !m_proof.is_empty() {
+                let len = m_proof.len();
+                m_proof.remove(node as usize % len);
+            }
+        }
+        Mutation::PushNode(node) => m_proof.push(node),
+    }
+
+    let unchanged = (m_index, m_wallet, m_amount) == (index as u64, wallet, amount)
+        && m_proof == proof
+        && m_root == root;
+    if unchanged {
+        return;
+    }
+    let m_leaf = merkle::leaf(m_index, &m_wallet, m_amount);
+    assert!(
+        !merkle::verify(&m_leaf, &m_proof, &m_root),
+        "mutated claim verified: {mutation:?}"
+    );
+});
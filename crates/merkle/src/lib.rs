@@ -0,0 +1,306 @@
+//! Leaf encoding and proof verification for merkledrop allocation trees.
+//!
+//! The program, the LiteSVM harness and off-chain tree builders all depend on
+//! this crate, so they can't drift apart on hashing conventions. It is
+//! `no_std` and builds for SBF (keccak via the `sol_keccak256` syscall) as
+//! well as for the host and `wasm32-unknown-unknown` (keccak via `sha3`).
+//!
+//! Trees use sorted-pair hashing: each parent is `keccak(min || max)` of its
+//! children, so proofs carry no left/right flags. An odd node out is carried
+//! up a level unchanged. The `alloc` feature adds [`MerkleTree`] for building
+//! trees and proofs, [`SegmentedTree`] for trees grown with [`append_root`],
+//! and [`Snapshot`], a canonical binary form of an allocation list that
+//! always builds the same root.
+
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+mod tree;
+#[cfg(feature = "alloc")]
+pub use tree::{MerkleTree, SegmentedTree};
+#[cfg(feature = "alloc")]
+mod snapshot;
+#[cfg(feature = "alloc")]
+pub use snapshot::*;
+
+pub type Hash = [u8; 32];
+
+/// Keccak-256 of the concatenation of `vals`.
+#[cfg(target_os = "solana")]
+pub fn hashv(vals: &[&[u8]]) -> Hash {
+    extern "C" {
+        fn sol_keccak256(vals: *const u8, val_len: u64, hash_result: *mut u8) -> u64;
+    }
+    let mut hash = [0u8; 32];
+    unsafe {
+        sol_keccak256(vals as *const _ as *const u8, vals.len() as u64, hash.as_mut_ptr());
+    }
+    hash
+}
+
+/// Keccak-256 of the concatenation of `vals`.
+#[cfg(not(target_os = "solana"))]
+pub fn hashv(vals: &[&[u8]]) -> Hash {
+    use sha3::{Digest, Keccak256};
+    let mut hasher = Keccak256::new();
+    for val in vals {
+        hasher.update(val);
+    }
+    hasher.finalize().into()
+}
+
+/// Plain allocation leaf: `keccak(index || wallet || amount)`, integers little-endian.
+pub fn leaf(index: u64, wallet: &[u8; 32], amount: u64) -> Hash {
+    hashv(&[&index.to_le_bytes(), wallet, &amount.to_le_bytes()])
+}
+
+/// EVM-parity leaf: `keccak256(abi.encodePacked(uint256 index, account, uint256 amount))`,
+/// as a Solidity distributor hashes it. `account` is a 20-byte EVM address or a
+/// 32-byte Solana pubkey (`bytes32`), so one tree can carry both; the two
+/// preimage lengths differ, so neither verifies as the other.
+pub fn evm_leaf(index: u64, account: &[u8], amount: u64) -> Hash {
+    hashv(&[&uint256(index), account, &uint256(amount)])
+}
+
+// Big-endian, left-padded to 32 bytes like a Solidity `uint256`
+fn uint256(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Vesting leaf: the plain leaf's fields followed by `cliff || duration || tge_bps`.
+/// The longer preimage means a plain leaf never verifies as a vesting one.
+pub fn vesting_leaf(
+    index: u64,
+    wallet: &[u8; 32],
+    amount: u64,
+    cliff: i64,
+    duration: i64,
+    tge_bps: u16,
+) -> Hash {
+    hashv(&[
+        &index.to_le_bytes(),
+        wallet,
+        &amount.to_le_bytes(),
+        &cliff.to_le_bytes(),
+        &duration.to_le_bytes(),
+        &tge_bps.to_le_bytes(),
+    ])
+}
+
+/// Cohort leaf: the plain leaf's fields followed by `cohort_id`, which picks
+/// the `Cohort` account whose window and vesting schedule apply.
+pub fn cohort_leaf(index: u64, wallet: &[u8; 32], amount: u64, cohort_id: u32) -> Hash {
+    hashv(&[&index.to_le_bytes(), wallet, &amount.to_le_bytes(), &cohort_id.to_le_bytes()])
+}
+
+/// Custom leaf: the plain leaf's fields under a domain tag, followed by
+/// `fields` the program doesn't interpret (lockups, chains, categories). The
+/// tag keeps every such leaf apart from plain, vesting and cohort leaves.
+pub fn tagged_leaf(index: u64, wallet: &[u8; 32], amount: u64, fields: &[u8]) -> Hash {
+    hashv(&[b"merkledrop-leaf", &index.to_le_bytes(), wallet, &amount.to_le_bytes(), fields])
+}
+
+/// EVM-parity custom leaf: `keccak256(abi.encodePacked(uint256 index, account,
+/// uint256 amount, fields))`. With a 32-byte `account` and non-empty
+/// `fields`, the preimage is longer than any [`evm_leaf`]'s, so neither
+/// verifies as the other.
+pub fn evm_tagged_leaf(index: u64, account: &[u8], amount: u64, fields: &[u8]) -> Hash {
+    hashv(&[&uint256(index), account, &uint256(amount), fields])
+}
+
+/// Snapshot commitment of an allocation list: its root, leaf count and
+/// total amount under a domain tag. A campaign initialized with this as its
+/// `snapshot_hash` can't carry a root built from different data, which
+/// `assert_snapshot_hash` checks on-chain.
+pub fn snapshot_hash(root: &Hash, leaf_count: u64, total_amount: u64) -> Hash {
+    hashv(&[b"merkledrop-snapshot", root, &leaf_count.to_le_bytes(), &total_amount.to_le_bytes()])
+}
+
+/// Ticket commitment for a commit-reveal claim: the claim's leaf fields
+/// and a secret `salt` under a domain tag, so the commitment reveals
+/// nothing until `claim_ticketed` opens it.
+pub fn claim_commitment(index: u64, wallet: &[u8; 32], amount: u64, salt: &[u8; 32]) -> Hash {
+    hashv(&[b"merkledrop-ticket", &index.to_le_bytes(), wallet, &amount.to_le_bytes(), salt])
+}
+
+/// Parent of two sibling nodes.
+pub fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    if a <= b {
+        hashv(&[a, b])
+    } else {
+        hashv(&[b, a])
+    }
+}
+
+/// Root after appending a segment of leaves, built as its own tree, to a
+/// tree with root `root`. A proof against `root` stays valid as a proof
+/// against the old root, so the program keeps accepting it.
+pub fn append_root(root: &Hash, segment_root: &Hash) -> Hash {
+    hash_pair(root, segment_root)
+}
+
+/// Folds `leaf` up through `proof`, yielding the root it commits to.
+pub fn root_from_proof(leaf: &Hash, proof: &[Hash]) -> Hash {
+    proof.iter().fold(*leaf, |hash, node| hash_pair(&hash, node))
+}
+
+pub fn verify(leaf: &Hash, proof: &[Hash], root: &Hash) -> bool {
+    root_from_proof(leaf, proof) == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: [u8; 32] = [7; 32];
+
+    fn leaves() -> [Hash; 3] {
+        [leaf(0, &WALLET, 10), leaf(1, &WALLET, 20), leaf(2, &WALLET, 30)]
+    }
+
+    #[test]
+    fn keccak_matches_known_digest() {
+        // keccak256("")
+        assert_eq!(
+            hashv(&[]),
+            [
+                0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+                0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+                0x5d, 0x85, 0xa4, 0x70,
+            ]
+        );
+        assert_eq!(hashv(&[b"ab", b"c"]), hashv(&[b"abc"]));
+    }
+
+    #[test]
+    fn pair_hashing_is_order_independent() {
+        let [a, b, _] = leaves();
+        assert_eq!(hash_pair(&a, &b), hash_pair(&b, &a));
+        assert_eq!(hash_pair(&a, &b), hashv(&[a.min(b).as_ref(), a.max(b).as_ref()]));
+    }
+
+    #[test]
+    fn proofs_verify_with_an_odd_leaf_carried_up() {
+        let [a, b, c] = leaves();
+        // Three leaves: `c` has no sibling and joins one level up
+        let root = hash_pair(&hash_pair(&a, &b), &c);
+        assert!(verify(&a, &[b, c], &root));
+        assert!(verify(&b, &[a, c], &root));
+        assert!(verify(&c, &[hash_pair(&a, &b)], &root));
+        // A single leaf is its own root
+        assert!(verify(&a, &[], &a));
+    }
+
+    #[test]
+    fn tampered_proofs_fail() {
+        let [a, b, c] = leaves();
+        let root = hash_pair(&hash_pair(&a, &b), &c);
+        let mut flipped = b;
+        flipped[0] ^= 1;
+        assert!(!verify(&a, &[flipped, c], &root));
+        assert!(!verify(&leaf(0, &WALLET, 11), &[b, c], &root));
+        assert!(!verify(&a, &[b], &root));
+        assert!(!verify(&a, &[b, c, c], &root));
+        assert!(!verify(&a, &[c, b], &root));
+    }
+
+    #[test]
+    fn appended_root_folds_in_the_segment() {
+        let [a, b, c] = leaves();
+        let root = hash_pair(&a, &b);
+        let grown = append_root(&root, &c);
+        assert!(verify(&a, &[b, c], &grown));
+        assert!(verify(&c, &[root], &grown));
+    }
+
+    #[test]
+    fn leaves_hash_their_documented_preimages() {
+        assert_eq!(
+            leaf(3, &WALLET, 500),
+            hashv(&[&3u64.to_le_bytes(), &WALLET, &500u64.to_le_bytes()])
+        );
+        assert_eq!(
+            vesting_leaf(3, &WALLET, 500, 60, 3_600, 1_000),
+            hashv(&[
+                &3u64.to_le_bytes(),
+                &WALLET,
+                &500u64.to_le_bytes(),
+                &60i64.to_le_bytes(),
+                &3_600i64.to_le_bytes(),
+                &1_000u16.to_le_bytes(),
+            ])
+        );
+        assert_eq!(
+            cohort_leaf(3, &WALLET, 500, 9),
+            hashv(&[&3u64.to_le_bytes(), &WALLET, &500u64.to_le_bytes(), &9u32.to_le_bytes()])
+        );
+        assert_eq!(
+            tagged_leaf(3, &WALLET, 500, b"lockup"),
+            hashv(&[
+                b"merkledrop-leaf",
+                &3u64.to_le_bytes(),
+                &WALLET,
+                &500u64.to_le_bytes(),
+                b"lockup"
+            ])
+        );
+    }
+
+    #[test]
+    fn evm_leaves_use_big_endian_words() {
+        let mut index = [0u8; 32];
+        index[31] = 3;
+        let mut amount = [0u8; 32];
+        amount[30..].copy_from_slice(&500u16.to_be_bytes());
+        let address = [0xaa; 20];
+        assert_eq!(evm_leaf(3, &address, 500), hashv(&[&index, &address, &amount]));
+        assert_eq!(evm_tagged_leaf(3, &address, 500, &[]), evm_leaf(3, &address, 500));
+        assert_eq!(
+            evm_tagged_leaf(3, &WALLET, 500, b"x"),
+            hashv(&[&index, &WALLET, &amount, b"x"])
+        );
+        // An address and a pubkey never share a preimage
+        assert_ne!(evm_leaf(3, &WALLET[..20], 500), evm_leaf(3, &WALLET, 500));
+    }
+
+    #[test]
+    fn leaf_kinds_are_domain_separated() {
+        let plain = leaf(3, &WALLET, 500);
+        assert_ne!(plain, vesting_leaf(3, &WALLET, 500, 0, 0, 0));
+        assert_ne!(plain, cohort_leaf(3, &WALLET, 500, 0));
+        assert_ne!(plain, tagged_leaf(3, &WALLET, 500, &[]));
+        assert_ne!(plain, evm_leaf(3, &WALLET, 500));
+    }
+
+    #[test]
+    fn commitments_bind_every_field() {
+        let root = leaf(0, &WALLET, 1);
+        let snapshot = snapshot_hash(&root, 10, 1_000);
+        assert_eq!(
+            snapshot,
+            hashv(&[b"merkledrop-snapshot", &root, &10u64.to_le_bytes(), &1_000u64.to_le_bytes()])
+        );
+        assert_ne!(snapshot, snapshot_hash(&root, 11, 1_000));
+        assert_ne!(snapshot, snapshot_hash(&root, 10, 1_001));
+
+        let salt = [9; 32];
+        let ticket = claim_commitment(3, &WALLET, 500, &salt);
+        assert_eq!(
+            ticket,
+            hashv(&[
+                b"merkledrop-ticket",
+                &3u64.to_le_bytes(),
+                &WALLET,
+                &500u64.to_le_bytes(),
+                &salt
+            ])
+        );
+        assert_ne!(ticket, claim_commitment(3, &WALLET, 500, &[8; 32]));
+        assert_ne!(ticket, claim_commitment(4, &WALLET, 500, &salt));
+    }
+}
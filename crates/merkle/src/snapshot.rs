@@ -0,0 +1,198 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{evm_leaf, leaf, Hash, MerkleTree};
+
+/// Leading bytes of every encoded snapshot.
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"MDSN";
+/// Version of the layout written by [`Snapshot::encode`].
+pub const SNAPSHOT_FORMAT_VERSION: u8 = 1;
+/// Sorted-pair keccak-256, the only tree hash this crate builds.
+pub const HASH_KECCAK_SORTED_PAIRS: u8 = 0;
+/// Version of the leaf encodings in this crate; matches the program's
+/// `LEAF_FORMAT_VERSION`.
+pub const LEAF_FORMAT_VERSION: u8 = 1;
+
+/// How a snapshot's leaves are hashed, numbered like the program's
+/// `LeafEncoding`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LeafKind {
+    /// [`leaf`]; accounts are 32-byte Solana pubkeys
+    Native = 0,
+    /// [`evm_leaf`]; accounts are 32-byte pubkeys or 20-byte EVM addresses
+    EvmPacked = 1,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    pub index: u64,
+    pub account: Vec<u8>,
+    pub amount: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// Not an encoded snapshot, or one in a layout, hash or leaf format this
+    /// crate doesn't write
+    Unsupported,
+    /// Ends early or runs on past the root
+    Truncated,
+    TrailingBytes,
+    /// An account that isn't 32 bytes, or 20 under `EvmPacked`
+    InvalidAccount(u64),
+    /// Indices must run 0..count with no gaps or repeats
+    IndexGap(u64),
+    /// The trailing root isn't the one the entries build
+    RootMismatch,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => f.write_str("not a snapshot in a supported format"),
+            Self::Truncated => f.write_str("snapshot is truncated"),
+            Self::TrailingBytes => f.write_str("snapshot has bytes after its root"),
+            Self::InvalidAccount(index) => write!(f, "account of leaf {index} has an invalid length"),
+            Self::IndexGap(index) => write!(f, "leaf {index} is missing or repeated"),
+            Self::RootMismatch => f.write_str("snapshot root does not match its entries"),
+        }
+    }
+}
+
+/// An allocation list in canonical order, which always builds the same tree.
+///
+/// [`Snapshot::encode`] writes it as
+/// `magic || format version || hash || leaf format version || leaf kind ||
+/// count (u64) || entries || root`, where each entry is
+/// `index (u64) || account length (u8) || account || amount (u64)`, sorted
+/// by index, with integers little-endian. Two operators with the same
+/// allocations get byte-identical files, so comparing file hashes compares
+/// roots, and anyone holding the file can check it against a campaign's
+/// `merkle_root`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    leaf_kind: LeafKind,
+    allocations: Vec<Allocation>,
+    root: Hash,
+}
+
+impl Snapshot {
+    /// Sorts `allocations` by index; they must cover 0..len exactly once.
+    pub fn new(leaf_kind: LeafKind, mut allocations: Vec<Allocation>) -> Result<Self, SnapshotError> {
+        allocations.sort_by_key(|allocation| allocation.index);
+        for (position, allocation) in allocations.iter().enumerate() {
+            if allocation.index != position as u64 {
+                return Err(SnapshotError::IndexGap(position as u64));
+            }
+            if !matches!((leaf_kind, allocation.account.len()), (_, 32) | (LeafKind::EvmPacked, 20)) {
+                return Err(SnapshotError::InvalidAccount(allocation.index));
+            }
+        }
+        let mut snapshot = Self { leaf_kind, allocations, root: Hash::default() };
+        snapshot.root = snapshot.tree().root();
+        Ok(snapshot)
+    }
+
+    pub fn leaf_kind(&self) -> LeafKind {
+        self.leaf_kind
+    }
+
+    pub fn allocations(&self) -> &[Allocation] {
+        &self.allocations
+    }
+
+    pub fn root(&self) -> Hash {
+        self.root
+    }
+
+    pub fn leaves(&self) -> Vec<Hash> {
+        self.allocations
+            .iter()
+            .map(|a| match self.leaf_kind {
+                LeafKind::Native => leaf(a.index, a.account[..].try_into().expect("checked in new"), a.amount),
+                LeafKind::EvmPacked => evm_leaf(a.index, &a.account, a.amount),
+            })
+            .collect()
+    }
+
+    pub fn tree(&self) -> MerkleTree {
+        MerkleTree::from_leaves(self.leaves())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.allocations.len() * 49 + 32);
+        bytes.extend_from_slice(&SNAPSHOT_MAGIC);
+        bytes.extend_from_slice(&[
+            SNAPSHOT_FORMAT_VERSION,
+            HASH_KECCAK_SORTED_PAIRS,
+            LEAF_FORMAT_VERSION,
+            self.leaf_kind as u8,
+        ]);
+        bytes.extend_from_slice(&(self.allocations.len() as u64).to_le_bytes());
+        for allocation in &self.allocations {
+            bytes.extend_from_slice(&allocation.index.to_le_bytes());
+            bytes.push(allocation.account.len() as u8);
+            bytes.extend_from_slice(&allocation.account);
+            bytes.extend_from_slice(&allocation.amount.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.root);
+        bytes
+    }
+
+    /// Parses an encoded snapshot and rebuilds its tree to check the root.
+    pub fn decode(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut reader = Reader(bytes);
+        let header = reader.take(8)?;
+        if header[..4] != SNAPSHOT_MAGIC
+            || header[4] != SNAPSHOT_FORMAT_VERSION
+            || header[5] != HASH_KECCAK_SORTED_PAIRS
+            || header[6] != LEAF_FORMAT_VERSION
+        {
+            return Err(SnapshotError::Unsupported);
+        }
+        let leaf_kind = match header[7] {
+            0 => LeafKind::Native,
+            1 => LeafKind::EvmPacked,
+            _ => return Err(SnapshotError::Unsupported),
+        };
+        let count = reader.u64()?;
+        let mut allocations = Vec::new();
+        for _ in 0..count {
+            let index = reader.u64()?;
+            let account_len = reader.take(1)?[0] as usize;
+            let account = reader.take(account_len)?.to_vec();
+            let amount = reader.u64()?;
+            allocations.push(Allocation { index, account, amount });
+        }
+        let root: Hash = reader.take(32)?.try_into().expect("took 32 bytes");
+        if !reader.0.is_empty() {
+            return Err(SnapshotError::TrailingBytes);
+        }
+        // Canonical means already sorted, not merely sortable
+        if let Some(position) = allocations.iter().enumerate().position(|(i, a)| a.index != i as u64) {
+            return Err(SnapshotError::IndexGap(position as u64));
+        }
+        let snapshot = Self::new(leaf_kind, allocations)?;
+        if snapshot.root != root {
+            return Err(SnapshotError::RootMismatch);
+        }
+        Ok(snapshot)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.0.len() < len {
+            return Err(SnapshotError::Truncated);
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("took 8 bytes")))
+    }
+}
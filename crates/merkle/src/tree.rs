@@ -0,0 +1,183 @@
+use alloc::vec::Vec;
+
+use crate::{append_root, hash_pair, Hash};
+
+/// Every layer of a tree, leaves first, kept so proofs are cheap to extract.
+pub struct MerkleTree {
+    layers: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn from_leaves(leaves: Vec<Hash>) -> Self {
+        let mut layers = Vec::new();
+        layers.push(leaves);
+        while layers[layers.len() - 1].len() > 1 {
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [odd] => *odd,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Self { layers }
+    }
+
+    /// Root of the tree; all zeroes for an empty one.
+    pub fn root(&self) -> Hash {
+        self.layers[self.layers.len() - 1].first().copied().unwrap_or_default()
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    /// Sibling path of leaf `index`, bottom up, or `None` past the last leaf.
+    /// Levels where the node was carried up unchanged contribute nothing.
+    pub fn proof(&self, index: usize) -> Option<Vec<Hash>> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+        let mut proof = Vec::new();
+        let mut position = index;
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(position ^ 1) {
+                proof.push(*sibling);
+            }
+            position >>= 1;
+        }
+        Some(proof)
+    }
+}
+
+/// A tree grown by appending segments, as `append_allocations` does on chain.
+///
+/// Each segment is its own [`MerkleTree`], and the root folds the segment
+/// roots together with [`append_root`]. Leaf indices run on across segments.
+pub struct SegmentedTree {
+    segments: Vec<MerkleTree>,
+}
+
+impl SegmentedTree {
+    pub fn new(leaves: Vec<Hash>) -> Self {
+        Self { segments: alloc::vec![MerkleTree::from_leaves(leaves)] }
+    }
+
+    /// Appends `leaves` after the last leaf and returns the segment's root,
+    /// the one `append_allocations` takes.
+    pub fn append(&mut self, leaves: Vec<Hash>) -> Hash {
+        let segment = MerkleTree::from_leaves(leaves);
+        let root = segment.root();
+        self.segments.push(segment);
+        root
+    }
+
+    pub fn root(&self) -> Hash {
+        let (first, rest) = self.segments.split_first().expect("at least one segment");
+        rest.iter().fold(first.root(), |root, segment| append_root(&root, &segment.root()))
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.segments.iter().map(MerkleTree::leaf_count).sum()
+    }
+
+    /// Proof of leaf `index` against the current root. Leaves of earlier
+    /// segments also verify against the root their segment was appended to
+    /// with the prefix of this proof they were first published with.
+    pub fn proof(&self, index: usize) -> Option<Vec<Hash>> {
+        let mut offset = 0;
+        let mut prefix_root = Hash::default();
+        for (position, segment) in self.segments.iter().enumerate() {
+            if index < offset + segment.leaf_count() {
+                let mut proof = segment.proof(index - offset)?;
+                if position > 0 {
+                    proof.push(prefix_root);
+                }
+                proof.extend(self.segments[position + 1..].iter().map(MerkleTree::root));
+                return Some(proof);
+            }
+            prefix_root = match position {
+                0 => segment.root(),
+                _ => append_root(&prefix_root, &segment.root()),
+            };
+            offset += segment.leaf_count();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{leaf, verify};
+
+    fn leaves(range: core::ops::Range<u64>) -> Vec<Hash> {
+        range.map(|index| leaf(index, &[index as u8; 32], index * 10)).collect()
+    }
+
+    #[test]
+    fn every_proof_verifies_for_odd_and_even_sizes() {
+        for size in 1..=9 {
+            let leaves = leaves(0..size);
+            let tree = MerkleTree::from_leaves(leaves.clone());
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert!(verify(leaf, &proof, &tree.root()), "size {size}, index {index}");
+            }
+            assert!(tree.proof(size as usize).is_none());
+        }
+    }
+
+    #[test]
+    fn proofs_fail_for_another_leaf() {
+        let leaves = leaves(0..5);
+        let tree = MerkleTree::from_leaves(leaves.clone());
+        let proof = tree.proof(4).unwrap();
+        assert!(!verify(&leaves[3], &proof, &tree.root()));
+    }
+
+    #[test]
+    fn empty_tree_has_a_zero_root() {
+        assert_eq!(MerkleTree::from_leaves(Vec::new()).root(), [0; 32]);
+    }
+
+    #[test]
+    fn single_segment_matches_the_plain_tree() {
+        let leaves = leaves(0..7);
+        let segmented = SegmentedTree::new(leaves.clone());
+        let plain = MerkleTree::from_leaves(leaves);
+        assert_eq!(segmented.root(), plain.root());
+        for index in 0..7 {
+            assert_eq!(segmented.proof(index), plain.proof(index));
+        }
+    }
+
+    #[test]
+    fn segmented_root_folds_segment_roots() {
+        let first = leaves(0..5);
+        let second = leaves(5..8);
+        let third = leaves(8..9);
+        let mut tree = SegmentedTree::new(first.clone());
+        let old_root = tree.root();
+        assert_eq!(tree.append(second.clone()), MerkleTree::from_leaves(second.clone()).root());
+        tree.append(third.clone());
+
+        let [a, b, c] = [first, second, third].map(|leaves| MerkleTree::from_leaves(leaves).root());
+        let expected = append_root(&append_root(&a, &b), &c);
+        assert_eq!(tree.root(), expected);
+        assert_eq!(tree.leaf_count(), 9);
+
+        let all = leaves(0..9);
+        for (index, leaf) in all.iter().enumerate() {
+            let proof = tree.proof(index).unwrap();
+            assert!(verify(leaf, &proof, &tree.root()), "index {index}");
+        }
+        assert!(tree.proof(9).is_none());
+        // A first-segment proof, cut before the appended roots, still verifies
+        // against the root it was published under
+        let proof = tree.proof(2).unwrap();
+        assert!(verify(&all[2], &proof[..proof.len() - 2], &old_root));
+    }
+}
@@ -0,0 +1,65 @@
+//! Claim status from a single `State` read.
+//!
+//! The program records claims in a per-index bitmap. `ClaimSet` holds it so
+//! services can answer "has index N claimed?" from one `getAccountInfo`
+//! without re-implementing the bit layout.
+
+use std::ops::RangeInclusive;
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::{InterfaceError, State};
+
+/// The current round's recorded claims.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimSet {
+    total_claims: u64,
+    bitmap: Vec<u8>,
+}
+
+impl ClaimSet {
+    /// Reads the set out of a decoded `State`. Compressed-ledger campaigns
+    /// record claims in their tree instead, so they have no set here, and a
+    /// migrated round has none until its earlier claims are all converted.
+    pub fn from_state(state: &State) -> Result<Self, InterfaceError> {
+        if state.claim_ledger != Pubkey::default() {
+            return Err(InterfaceError::CompressedLedger);
+        }
+        if state.legacy_claims {
+            return Err(InterfaceError::LegacyClaimsPending);
+        }
+        Ok(Self {
+            total_claims: state.total_claims,
+            bitmap: state.claim_bitmap.clone(),
+        })
+    }
+
+    /// `from_state` on raw account data, as a predicate over indices.
+    pub fn from_account_data(data: &[u8]) -> Result<impl Fn(u64) -> bool, InterfaceError> {
+        let set = Self::from_state(&State::decode(data)?)?;
+        Ok(move |index| set.is_claimed(index))
+    }
+
+    /// Whether `index` is recorded as claimed, as `claim` decides it;
+    /// always false past `total_claims`.
+    pub fn is_claimed(&self, index: u64) -> bool {
+        index < self.total_claims && bit_set(&self.bitmap, index)
+    }
+
+    /// Claimed indices as runs, e.g. `[0..=4, 9..=9]`, for reading a
+    /// campaign's progress at a glance instead of probing index by index.
+    pub fn claimed_ranges(&self) -> Vec<RangeInclusive<u64>> {
+        let mut ranges: Vec<RangeInclusive<u64>> = Vec::new();
+        for index in (0..self.total_claims).filter(|&index| self.is_claimed(index)) {
+            match ranges.last_mut() {
+                Some(run) if *run.end() + 1 == index => *run = *run.start()..=index,
+                _ => ranges.push(index..=index),
+            }
+        }
+        ranges
+    }
+}
+
+fn bit_set(bits: &[u8], index: u64) -> bool {
+    bits.get((index / 8) as usize).is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+}
@@ -0,0 +1,51 @@
+//! Seeds and limits the program publishes as IDL constants.
+
+/// Seeds of the program's PDAs; `pda` derives every address from these.
+pub const STATE_SEED: &[u8] = b"state";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const WALLET_CLAIMS_SEED: &[u8] = b"wallet_claims";
+pub const RELAYER_SEED: &[u8] = b"relayer";
+pub const METADATA_SEED: &[u8] = b"metadata";
+pub const STATS_SEED: &[u8] = b"stats";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const TREASURY_TOKENS_SEED: &[u8] = b"treasury_tokens";
+pub const BONUS_VAULT_SEED: &[u8] = b"bonus_vault";
+pub const BADGE_MINT_SEED: &[u8] = b"badge";
+pub const LEDGER_AUTHORITY_SEED: &[u8] = b"ledger";
+pub const COHORT_SEED: &[u8] = b"cohort";
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const VESTING_SEED: &[u8] = b"vesting";
+pub const SUMMARY_SEED: &[u8] = b"summary";
+pub const ROLLOVER_SEED: &[u8] = b"rollover";
+pub const EPOCH_SEED: &[u8] = b"epoch";
+pub const RAFFLE_SEED: &[u8] = b"raffle";
+pub const RAFFLE_PRIZE_SEED: &[u8] = b"raffle_prize";
+pub const UNWRAP_SEED: &[u8] = b"unwrap";
+pub const TICKET_SEED: &[u8] = b"ticket";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const VOID_SEED: &[u8] = b"void";
+pub const BUILD_INFO_SEED: &[u8] = b"build_info";
+pub const ROOT_SCHEDULE_SEED: &[u8] = b"root_schedule";
+/// Derived under each campaign's own program id, so siblings can check it.
+pub const FAMILY_CLAIM_SEED: &[u8] = b"family_claim";
+pub const VESTING_HANDOFF_SEED: &[u8] = b"vesting_handoff";
+pub const VESTING_SENDER_SEED: &[u8] = b"vesting_sender";
+pub const BONUS_LOTTERY_SEED: &[u8] = b"bonus_lottery";
+pub const BONUS_TICKET_SEED: &[u8] = b"bonus_ticket";
+pub const LEGACY_CLAIMS_SEED: &[u8] = b"legacy_claims";
+/// Derived under the calling program's id, not this one.
+pub const CALLER_AUTHORITY_SEED: &[u8] = b"merkledrop_caller";
+
+/// Version of the leaf encodings in the `merkle` crate; trees built for one
+/// version don't verify under another.
+pub const LEAF_FORMAT_VERSION: u8 = 1;
+/// Largest `claim_capacity` `resize_state` grows a round's bitmap to: one
+/// bit per leaf of the deepest tree a proof covers.
+pub const MAX_CLAIM_CAPACITY: u64 = 1 << 24;
+/// Largest `claim_capacity` a round is created with: an 8KiB bitmap, within
+/// what one instruction can allocate.
+pub const MAX_INIT_CLAIM_CAPACITY: u64 = 1 << 16;
+/// Every claim's `proof` argument is padded to this many nodes.
+pub const MAX_PROOF_LEN: u8 = 24;
+/// Most indices one `emit_unclaimed` call covers.
+pub const MAX_UNCLAIMED_PAGE: u64 = 2_048;
@@ -0,0 +1,1285 @@
+//! Mirrors of the program's events, in declaration order.
+//!
+//! Fields are only ever appended, so an event emitted by a newer program
+//! decodes with an older mirror; the extra trailing bytes are ignored.
+
+use anchor_lang::prelude::*;
+
+use crate::{CampaignStatus, GateKind, LeafEncoding, Rounding};
+
+/// A program event with its Anchor discriminator (`sha256("event:<Name>")[..8]`).
+pub trait Event: AnchorDeserialize {
+    const DISCRIMINATOR: [u8; 8];
+
+    /// Decodes `data` as emitted: discriminator, then the Borsh-encoded event.
+    fn decode(data: &[u8]) -> Option<Self> {
+        let mut body = data.strip_prefix(&Self::DISCRIMINATOR)?;
+        Self::deserialize(&mut body).ok()
+    }
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AirdropInitialized {
+    pub authority: Pubkey,
+    pub snapshot_hash: [u8; 32],
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub allocations_hash: [u8; 32],
+    pub allocations_uri: String,
+    pub total_allocation: u64,
+}
+
+impl Event for AirdropInitialized {
+    const DISCRIMINATOR: [u8; 8] = [218, 5, 88, 115, 246, 124, 154, 187];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Claimed {
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub fee: u64, // operator's cut of `amount`; the recipient got the rest
+    pub penalty: u64, // late-claim haircut, paid to the treasury
+    pub net_amount: u64,
+    pub bonus: u64, // early-claim top-up from the bonus reserve, on top of `net_amount`
+    pub index: u64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+    pub campaign: Pubkey, // the campaign's `State` account
+    pub mint: Pubkey,
+    pub root_version: u32, // which of the campaign's roots the claim was proven against
+    pub vault_balance: u64, // left in the vault that paid, after the claim
+}
+
+impl Event for Claimed {
+    const DISCRIMINATOR: [u8; 8] = [217, 192, 123, 72, 108, 150, 248, 33];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CpiClaimed {
+    pub caller_program: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub fee: u64, // operator's cut of `amount`; the recipient got the rest
+    pub penalty: u64, // late-claim haircut, paid to the treasury
+    pub net_amount: u64,
+    pub bonus: u64, // early-claim top-up from the bonus reserve, on top of `net_amount`
+    pub index: u64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+impl Event for CpiClaimed {
+    const DISCRIMINATOR: [u8; 8] = [68, 70, 160, 157, 54, 228, 73, 113];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DomainClaimed {
+    pub domain: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub fee: u64, // operator's cut of `amount`; the recipient got the rest
+    pub penalty: u64, // late-claim haircut, paid to the treasury
+    pub net_amount: u64,
+    pub bonus: u64, // early-claim top-up from the bonus reserve, on top of `net_amount`
+    pub index: u64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+impl Event for DomainClaimed {
+    const DISCRIMINATOR: [u8; 8] = [49, 210, 34, 231, 50, 95, 203, 125];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchDistributed {
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+impl Event for BatchDistributed {
+    const DISCRIMINATOR: [u8; 8] = [32, 64, 245, 235, 1, 44, 74, 140];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AirdropClosed {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+    pub closes_at: i64, // last second claims are accepted; `timestamp` unless a close grace applied
+}
+
+impl Event for AirdropClosed {
+    const DISCRIMINATOR: [u8; 8] = [196, 85, 30, 72, 165, 151, 163, 126];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CrankBountyPaid {
+    pub cranker: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+impl Event for CrankBountyPaid {
+    const DISCRIMINATOR: [u8; 8] = [71, 52, 222, 151, 153, 107, 154, 154];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigLocked {
+    pub merkle_root: [u8; 32],
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub timestamp: i64,
+}
+
+impl Event for ConfigLocked {
+    const DISCRIMINATOR: [u8; 8] = [111, 41, 37, 92, 41, 202, 238, 59];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AuthorityRenounced {
+    pub previous_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Event for AuthorityRenounced {
+    const DISCRIMINATOR: [u8; 8] = [159, 245, 163, 106, 200, 40, 59, 129];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaultAuthorityRotated {
+    pub old_snapshot_hash: [u8; 32],
+    pub new_snapshot_hash: [u8; 32],
+    pub old_vault_auth: Pubkey,
+    pub new_vault_auth: Pubkey,
+    pub new_vault: Pubkey,
+    pub moved_amount: u64,
+    pub timestamp: i64,
+}
+
+impl Event for VaultAuthorityRotated {
+    const DISCRIMINATOR: [u8; 8] = [212, 84, 163, 69, 241, 224, 177, 87];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FundingVaultUpdated {
+    pub vault: Pubkey,
+    pub registered: bool,
+    pub timestamp: i64,
+}
+
+impl Event for FundingVaultUpdated {
+    const DISCRIMINATOR: [u8; 8] = [108, 37, 111, 128, 187, 116, 255, 15];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CrankBountyUpdated {
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+impl Event for CrankBountyUpdated {
+    const DISCRIMINATOR: [u8; 8] = [163, 109, 162, 15, 196, 237, 37, 155];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CampaignMetadataUpdated {
+    pub name: String,
+    pub symbol: String,
+    pub timestamp: i64,
+}
+
+impl Event for CampaignMetadataUpdated {
+    const DISCRIMINATOR: [u8; 8] = [38, 121, 114, 51, 171, 167, 106, 197];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TermsHashUpdated {
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl Event for TermsHashUpdated {
+    const DISCRIMINATOR: [u8; 8] = [90, 162, 159, 32, 227, 117, 204, 248];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimGateUpdated {
+    pub kind: GateKind,
+    pub gate_key: Pubkey,
+    pub min_amount: u64,
+    pub timestamp: i64,
+}
+
+impl Event for ClaimGateUpdated {
+    const DISCRIMINATOR: [u8; 8] = [176, 249, 137, 107, 29, 169, 142, 23];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeafEncodingUpdated {
+    pub encoding: LeafEncoding,
+    pub merkle_root: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl Event for LeafEncodingUpdated {
+    const DISCRIMINATOR: [u8; 8] = [159, 5, 81, 250, 135, 49, 242, 198];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EpochCreated {
+    pub epoch: u64,
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub total_allocation: u64,
+    pub timestamp: i64,
+}
+
+impl Event for EpochCreated {
+    const DISCRIMINATOR: [u8; 8] = [191, 150, 240, 63, 59, 212, 233, 124];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EpochClaimed {
+    pub epoch: u64,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub index: u64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+impl Event for EpochClaimed {
+    const DISCRIMINATOR: [u8; 8] = [198, 206, 41, 166, 62, 139, 19, 35];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RaffleRequested {
+    pub snapshot_hash: [u8; 32],
+    pub randomness_account: Pubkey,
+    pub commit_slot: u64,
+    pub winner_count: u32,
+    pub pool_amount: u64,
+    pub timestamp: i64,
+}
+
+impl Event for RaffleRequested {
+    const DISCRIMINATOR: [u8; 8] = [159, 207, 17, 76, 171, 77, 16, 155];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RaffleSettled {
+    pub snapshot_hash: [u8; 32],
+    pub randomness: [u8; 32],
+    pub winners: Vec<u64>,
+    pub timestamp: i64,
+}
+
+impl Event for RaffleSettled {
+    const DISCRIMINATOR: [u8; 8] = [233, 134, 180, 156, 208, 35, 79, 201];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RafflePrizeClaimed {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub prize: u64,
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+impl Event for RafflePrizeClaimed {
+    const DISCRIMINATOR: [u8; 8] = [242, 169, 153, 51, 18, 12, 152, 69];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaultBelowLowWaterMark {
+    pub vault_balance: u64,
+    pub low_water_mark: u64,
+    pub timestamp: i64,
+}
+
+impl Event for VaultBelowLowWaterMark {
+    const DISCRIMINATOR: [u8; 8] = [2, 152, 184, 121, 135, 20, 33, 252];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimReserved {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub release_ts: i64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl Event for ClaimReserved {
+    const DISCRIMINATOR: [u8; 8] = [73, 12, 182, 14, 204, 35, 241, 132];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingStarted {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub penalty: u64, // late-claim haircut paid to the treasury at the claim; `amount` is what vests
+    pub start_ts: i64,
+    pub cliff: i64,
+    pub duration: i64,
+    pub tge_bps: u16,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl Event for VestingStarted {
+    const DISCRIMINATOR: [u8; 8] = [190, 137, 96, 76, 234, 107, 36, 240];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestedReleased {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64, // newly vested; `fee` of it went to the treasury
+    pub fee: u64,
+    pub released: u64, // running total, `amount` included
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+impl Event for VestedReleased {
+    const DISCRIMINATOR: [u8; 8] = [74, 224, 153, 126, 96, 136, 232, 246];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowWithdrawn {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub penalty: u64, // late-claim haircut, paid to the treasury
+    pub net_amount: u64,
+    pub bonus: u64, // early-claim top-up from the bonus reserve, on top of `net_amount`
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+impl Event for EscrowWithdrawn {
+    const DISCRIMINATOR: [u8; 8] = [43, 206, 174, 47, 105, 219, 216, 239];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisputePeriodUpdated {
+    pub dispute_period: i64,
+    pub timestamp: i64,
+}
+
+impl Event for DisputePeriodUpdated {
+    const DISCRIMINATOR: [u8; 8] = [117, 70, 228, 229, 41, 80, 83, 153];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LatePenaltyUpdated {
+    pub start_ts: i64,
+    pub ramp: i64,
+    pub penalty_bps: u16,
+    pub timestamp: i64,
+}
+
+impl Event for LatePenaltyUpdated {
+    const DISCRIMINATOR: [u8; 8] = [206, 1, 43, 120, 193, 156, 147, 92];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EarlyBonusUpdated {
+    pub bonus_bps: u16,
+    pub end_ts: i64,
+    pub timestamp: i64,
+}
+
+impl Event for EarlyBonusUpdated {
+    const DISCRIMINATOR: [u8; 8] = [74, 208, 129, 254, 117, 20, 77, 109];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BonusReserveWithdrawn {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+impl Event for BonusReserveWithdrawn {
+    const DISCRIMINATOR: [u8; 8] = [10, 31, 46, 149, 168, 50, 102, 220];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FeesWithdrawn {
+    pub destination: Pubkey,
+    pub token_amount: u64,
+    pub lamports: u64,
+    pub tokens_withdrawn: u64,
+    pub lamports_withdrawn: u64,
+    pub timestamp: i64,
+}
+
+impl Event for FeesWithdrawn {
+    const DISCRIMINATOR: [u8; 8] = [234, 15, 0, 119, 148, 241, 40, 21];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OperatorFeeUpdated {
+    pub fee_bps: u16,
+    pub fee_account: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Event for OperatorFeeUpdated {
+    const DISCRIMINATOR: [u8; 8] = [64, 190, 228, 154, 58, 60, 175, 175];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IndexFreezeUpdated {
+    pub index: u64,
+    pub frozen: bool,
+    pub timestamp: i64,
+}
+
+impl Event for IndexFreezeUpdated {
+    const DISCRIMINATOR: [u8; 8] = [75, 21, 230, 97, 62, 231, 87, 45];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LowWaterMarkUpdated {
+    pub low_water_mark: u64,
+    pub timestamp: i64,
+}
+
+impl Event for LowWaterMarkUpdated {
+    const DISCRIMINATOR: [u8; 8] = [118, 21, 5, 76, 167, 245, 146, 189];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WalletCapUpdated {
+    pub wallet_cap: u64,
+    pub timestamp: i64,
+}
+
+impl Event for WalletCapUpdated {
+    const DISCRIMINATOR: [u8; 8] = [86, 225, 222, 15, 127, 221, 253, 212];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CloseGraceUpdated {
+    pub close_grace: i64,
+    pub timestamp: i64,
+}
+
+impl Event for CloseGraceUpdated {
+    const DISCRIMINATOR: [u8; 8] = [16, 156, 155, 146, 42, 85, 181, 133];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimsResumed {
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
+impl Event for ClaimsResumed {
+    const DISCRIMINATOR: [u8; 8] = [15, 28, 5, 215, 88, 207, 225, 172];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimWindowUpdated {
+    pub new_start_ts: i64,
+    pub new_duration: i64,
+    pub timestamp: i64,
+}
+
+impl Event for ClaimWindowUpdated {
+    const DISCRIMINATOR: [u8; 8] = [115, 153, 13, 169, 85, 240, 105, 127];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MaxStartLeadUpdated {
+    pub max_start_lead: i64,
+    pub timestamp: i64,
+}
+
+impl Event for MaxStartLeadUpdated {
+    const DISCRIMINATOR: [u8; 8] = [105, 31, 28, 9, 51, 21, 42, 8];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MerkleRootUpdated {
+    pub old_root: [u8; 32],
+    pub new_root: [u8; 32],
+    pub new_total_claims: u64,
+    pub new_allocations_hash: [u8; 32],
+    pub new_allocations_uri: String,
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32, // of `new_root`
+    pub vault_balance: u64,
+}
+
+impl Event for MerkleRootUpdated {
+    const DISCRIMINATOR: [u8; 8] = [115, 162, 36, 72, 29, 55, 39, 134];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StateResized {
+    pub old_capacity: u64,
+    pub new_capacity: u64,
+    pub space: u64,
+    pub timestamp: i64,
+}
+
+impl Event for StateResized {
+    const DISCRIMINATOR: [u8; 8] = [20, 49, 241, 9, 36, 232, 111, 144];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StateMigrated {
+    pub from_len: u64,
+    pub to_len: u64,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+impl Event for StateMigrated {
+    const DISCRIMINATOR: [u8; 8] = [251, 132, 96, 175, 77, 20, 95, 78];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CampaignRolledOver {
+    pub previous_round: u32,
+    pub previous_snapshot_hash: [u8; 32],
+    pub new_snapshot_hash: [u8; 32],
+    pub new_merkle_root: [u8; 32],
+    pub rolled_amount: u64,
+    pub timestamp: i64,
+}
+
+impl Event for CampaignRolledOver {
+    const DISCRIMINATOR: [u8; 8] = [180, 86, 165, 137, 23, 123, 236, 229];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnclaimedIndices {
+    pub round: u32,
+    pub range_start: u64,
+    pub range_end: u64, // exclusive
+    pub unclaimed_count: u32,
+    pub unclaimed: Vec<u8>, // bit i (LSB first) set = `range_start + i` is unclaimed
+}
+
+impl Event for UnclaimedIndices {
+    const DISCRIMINATOR: [u8; 8] = [241, 79, 98, 27, 227, 33, 199, 194];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CompressedLedgerInitialized {
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub timestamp: i64,
+}
+
+impl Event for CompressedLedgerInitialized {
+    const DISCRIMINATOR: [u8; 8] = [18, 8, 64, 39, 168, 126, 0, 17];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WormholeEmitterUpdated {
+    pub emitter_chain: u16,
+    pub emitter: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl Event for WormholeEmitterUpdated {
+    const DISCRIMINATOR: [u8; 8] = [195, 123, 203, 190, 116, 39, 60, 38];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaaClaimRedeemed {
+    pub index: u64,
+    pub emitter_chain: u16,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+impl Event for VaaClaimRedeemed {
+    const DISCRIMINATOR: [u8; 8] = [243, 60, 21, 35, 4, 122, 196, 74];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimBadgeEnabled {
+    pub badge_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Event for ClaimBadgeEnabled {
+    const DISCRIMINATOR: [u8; 8] = [147, 32, 59, 238, 91, 65, 175, 105];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RelayerFeeUpdated {
+    pub relayer_fee: u64,
+    pub timestamp: i64,
+}
+
+impl Event for RelayerFeeUpdated {
+    const DISCRIMINATOR: [u8; 8] = [42, 16, 43, 117, 147, 102, 138, 191];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RelayerUpdated {
+    pub relayer: Pubkey,
+    pub registered: bool,
+    pub timestamp: i64,
+}
+
+impl Event for RelayerUpdated {
+    const DISCRIMINATOR: [u8; 8] = [166, 12, 250, 34, 211, 198, 204, 222];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RelayedClaim {
+    pub wallet: Pubkey,
+    pub relayer: Pubkey,
+    pub index: u64,
+    pub relayer_fee: u64, // tokens of the allocation paid to the relayer; `Claimed.net_amount` excludes them
+    pub relayer_claims: u64, // the relayer's running claim count, including this one
+    pub timestamp: i64,
+}
+
+impl Event for RelayedClaim {
+    const DISCRIMINATOR: [u8; 8] = [198, 165, 213, 212, 10, 83, 255, 223];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UsdPricingUpdated {
+    pub price_feed: [u8; 32],
+    pub max_price_age: u64,
+    pub max_price_conf_bps: u16,
+    pub timestamp: i64,
+}
+
+impl Event for UsdPricingUpdated {
+    const DISCRIMINATOR: [u8; 8] = [60, 39, 219, 69, 206, 229, 97, 161];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UsdClaimPriced {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub usd_amount: u64, // leaf amount, micro-USD
+    pub price: i64, // Pyth price, scaled by 10^exponent
+    pub exponent: i32,
+    pub amount: u64, // tokens the leaf converted to; `Claimed.amount`
+    pub timestamp: i64,
+}
+
+impl Event for UsdClaimPriced {
+    const DISCRIMINATOR: [u8; 8] = [218, 231, 225, 88, 82, 13, 235, 211];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CohortUpdated {
+    pub cohort_id: u32,
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub cliff: i64,
+    pub duration: i64,
+    pub tge_bps: u16,
+    pub timestamp: i64,
+}
+
+impl Event for CohortUpdated {
+    const DISCRIMINATOR: [u8; 8] = [249, 150, 50, 181, 39, 124, 106, 116];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CohortClaimed {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub cohort_id: u32,
+    pub timestamp: i64,
+}
+
+impl Event for CohortClaimed {
+    const DISCRIMINATOR: [u8; 8] = [146, 116, 197, 89, 34, 70, 195, 214];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Finalized {
+    pub round: u32,
+    pub snapshot_hash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub claimed_count: u64,
+    pub claimed_amount: u64,
+    pub unclaimed_amount: u64,
+    pub timestamp: i64,
+}
+
+impl Event for Finalized {
+    const DISCRIMINATOR: [u8; 8] = [4, 77, 242, 80, 20, 152, 247, 252];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StatusChanged {
+    pub round: u32,
+    pub from: CampaignStatus,
+    pub to: CampaignStatus,
+    pub timestamp: i64,
+}
+
+impl Event for StatusChanged {
+    const DISCRIMINATOR: [u8; 8] = [146, 235, 222, 125, 145, 246, 34, 240];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RevealDelayUpdated {
+    pub reveal_delay: u64,
+    pub timestamp: i64,
+}
+
+impl Event for RevealDelayUpdated {
+    const DISCRIMINATOR: [u8; 8] = [10, 63, 0, 238, 250, 67, 207, 85];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimCommitted {
+    pub wallet: Pubkey,
+    pub commitment: [u8; 32],
+    pub reveal_slot: u64,
+    pub timestamp: i64,
+}
+
+impl Event for ClaimCommitted {
+    const DISCRIMINATOR: [u8; 8] = [60, 174, 207, 84, 26, 148, 209, 29];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub fee_treasury: Pubkey,
+    pub creation_fee: u64,
+    pub timestamp: i64,
+}
+
+impl Event for ConfigUpdated {
+    const DISCRIMINATOR: [u8; 8] = [40, 241, 230, 122, 11, 19, 198, 194];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CreationFeePaid {
+    pub authority: Pubkey,
+    pub fee_treasury: Pubkey,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+impl Event for CreationFeePaid {
+    const DISCRIMINATOR: [u8; 8] = [84, 35, 59, 163, 228, 149, 86, 178];
+}
+
+/// One setting changed by an admin instruction; values are Borsh-encoded.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String, // the `State` field's name
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigChanged {
+    pub authority: Pubkey,
+    pub changes: Vec<FieldChange>,
+    pub timestamp: i64,
+}
+
+impl Event for ConfigChanged {
+    const DISCRIMINATOR: [u8; 8] = [147, 25, 86, 98, 98, 77, 78, 192];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IndexVoided {
+    pub index: u64,
+    pub reason_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl Event for IndexVoided {
+    const DISCRIMINATOR: [u8; 8] = [30, 28, 17, 153, 168, 238, 160, 139];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DustThresholdUpdated {
+    pub dust_threshold: u64,
+    pub community_pool: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Event for DustThresholdUpdated {
+    const DISCRIMINATOR: [u8; 8] = [108, 175, 1, 4, 231, 41, 82, 32];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DustSwept {
+    pub indices: Vec<u64>,
+    pub total_amount: u64,
+    pub community_pool: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Event for DustSwept {
+    const DISCRIMINATOR: [u8; 8] = [131, 70, 179, 205, 208, 80, 13, 168];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BuildInfoRecorded {
+    pub program_version: String,
+    pub git_commit: [u8; 20],
+    pub toolchain: String,
+    pub deployed_slot: u64,
+    pub timestamp: i64,
+}
+
+impl Event for BuildInfoRecorded {
+    const DISCRIMINATOR: [u8; 8] = [45, 224, 2, 120, 241, 105, 237, 149];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AllocationsAppended {
+    pub old_root: [u8; 32],
+    pub segment_root: [u8; 32],
+    pub new_root: [u8; 32],
+    pub first_index: u64,
+    pub segment_claims: u64,
+    pub segment_amount: u64,
+    pub new_allocations_hash: [u8; 32],
+    pub new_allocations_uri: String,
+    pub root_version: u32,
+    pub timestamp: i64,
+}
+
+impl Event for AllocationsAppended {
+    const DISCRIMINATOR: [u8; 8] = [116, 20, 207, 117, 66, 87, 154, 178];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RootScheduled {
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub total_allocation: u64,
+    pub activation_ts: i64,
+    pub allocations_hash: [u8; 32],
+    pub allocations_uri: String,
+    pub timestamp: i64,
+}
+
+impl Event for RootScheduled {
+    const DISCRIMINATOR: [u8; 8] = [19, 161, 211, 113, 72, 73, 230, 8];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RootScheduleCleared {
+    pub dropped: u8,
+    pub timestamp: i64,
+}
+
+impl Event for RootScheduleCleared {
+    const DISCRIMINATOR: [u8; 8] = [14, 79, 179, 54, 105, 212, 3, 220];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RoundingUpdated {
+    pub rounding: Rounding,
+    pub timestamp: i64,
+}
+
+impl Event for RoundingUpdated {
+    const DISCRIMINATOR: [u8; 8] = [14, 151, 186, 2, 102, 220, 115, 8];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FamilyUpdated {
+    pub family_id: [u8; 32],
+    pub siblings: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+impl Event for FamilyUpdated {
+    const DISCRIMINATOR: [u8; 8] = [209, 31, 252, 210, 15, 38, 243, 178];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FamilyJoined {
+    pub family_id: [u8; 32],
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Event for FamilyJoined {
+    const DISCRIMINATOR: [u8; 8] = [167, 129, 194, 123, 100, 143, 175, 76];
+}
+
+/// How `claim_with_leaf` rebuilt a custom leaf; the fields are the leaf's
+/// own, past `(index, wallet, amount)`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum LeafSchema {
+    /// `merkle::tagged_leaf`
+    Tagged { fields: Vec<u8> },
+    /// `merkle::evm_tagged_leaf`
+    EvmPacked { fields: Vec<u8> },
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeafClaimed {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub leaf: [u8; 32],
+    pub schema: LeafSchema,
+}
+
+impl Event for LeafClaimed {
+    const DISCRIMINATOR: [u8; 8] = [201, 153, 186, 247, 43, 192, 163, 37];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CohortVaultUpdated {
+    pub cohort_id: u32,
+    pub vault: Pubkey,
+    pub clawback_ts: i64, // zero = never clawed back
+    pub timestamp: i64,
+}
+
+impl Event for CohortVaultUpdated {
+    const DISCRIMINATOR: [u8; 8] = [200, 4, 59, 219, 86, 145, 10, 126];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CohortClawedBack {
+    pub cohort_id: u32,
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+impl Event for CohortClawedBack {
+    const DISCRIMINATOR: [u8; 8] = [78, 145, 136, 185, 64, 255, 20, 154];
+}
+
+/// Which account fills a slot of a vesting handoff instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum HandoffAccount {
+    Fixed { key: Pubkey, is_writable: bool },
+    Sender,
+    SenderTokens,
+    Recipient { is_writable: bool },
+    Free { is_writable: bool, is_signer: bool },
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingHandoffUpdated {
+    pub program: Pubkey, // default = claims pay out directly
+    pub data: Vec<u8>,
+    pub amount_offset: u16,
+    pub accounts: Vec<HandoffAccount>,
+    pub timestamp: i64,
+}
+
+impl Event for VestingHandoffUpdated {
+    const DISCRIMINATOR: [u8; 8] = [106, 236, 87, 10, 159, 52, 215, 242];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingHandedOff {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub program: Pubkey,
+    pub amount: u64, // net of the operator fee and late penalty, early bonus included
+    pub timestamp: i64,
+}
+
+impl Event for VestingHandedOff {
+    const DISCRIMINATOR: [u8; 8] = [143, 241, 80, 89, 149, 236, 120, 104];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BonusLotteryUpdated {
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+impl Event for BonusLotteryUpdated {
+    const DISCRIMINATOR: [u8; 8] = [35, 56, 76, 161, 128, 64, 78, 87];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BonusTicketEntered {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub bonus: u64,
+    pub entry_slot: u64,
+    pub timestamp: i64,
+}
+
+impl Event for BonusTicketEntered {
+    const DISCRIMINATOR: [u8; 8] = [107, 26, 121, 210, 161, 173, 204, 203];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BonusTicketRevealed {
+    pub index: u64,
+    pub bucket: u8,
+    pub timestamp: i64,
+}
+
+impl Event for BonusTicketRevealed {
+    const DISCRIMINATOR: [u8; 8] = [249, 191, 182, 10, 215, 104, 117, 201];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BonusLotterySettled {
+    pub pool: u64,
+    pub requested: u64,
+    pub cutoff_bucket: u8, // the bucket count (64) = every bucket is paid in full
+    pub timestamp: i64,
+}
+
+impl Event for BonusLotterySettled {
+    const DISCRIMINATOR: [u8; 8] = [223, 42, 221, 185, 195, 18, 120, 166];
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BonusTicketRedeemed {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub bucket: u8,
+    pub bonus: u64, // zero for a losing bucket
+    pub timestamp: i64,
+}
+
+impl Event for BonusTicketRedeemed {
+    const DISCRIMINATOR: [u8; 8] = [168, 240, 28, 218, 252, 158, 158, 105];
+}
+
+/// Every event the program emits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MerkledropEvent {
+    AirdropInitialized(AirdropInitialized),
+    Claimed(Claimed),
+    CpiClaimed(CpiClaimed),
+    DomainClaimed(DomainClaimed),
+    BatchDistributed(BatchDistributed),
+    AirdropClosed(AirdropClosed),
+    CrankBountyPaid(CrankBountyPaid),
+    ConfigLocked(ConfigLocked),
+    AuthorityRenounced(AuthorityRenounced),
+    VaultAuthorityRotated(VaultAuthorityRotated),
+    FundingVaultUpdated(FundingVaultUpdated),
+    CrankBountyUpdated(CrankBountyUpdated),
+    CampaignMetadataUpdated(CampaignMetadataUpdated),
+    TermsHashUpdated(TermsHashUpdated),
+    ClaimGateUpdated(ClaimGateUpdated),
+    LeafEncodingUpdated(LeafEncodingUpdated),
+    EpochCreated(EpochCreated),
+    EpochClaimed(EpochClaimed),
+    RaffleRequested(RaffleRequested),
+    RaffleSettled(RaffleSettled),
+    RafflePrizeClaimed(RafflePrizeClaimed),
+    VaultBelowLowWaterMark(VaultBelowLowWaterMark),
+    ClaimReserved(ClaimReserved),
+    VestingStarted(VestingStarted),
+    VestedReleased(VestedReleased),
+    EscrowWithdrawn(EscrowWithdrawn),
+    DisputePeriodUpdated(DisputePeriodUpdated),
+    LatePenaltyUpdated(LatePenaltyUpdated),
+    EarlyBonusUpdated(EarlyBonusUpdated),
+    BonusReserveWithdrawn(BonusReserveWithdrawn),
+    FeesWithdrawn(FeesWithdrawn),
+    OperatorFeeUpdated(OperatorFeeUpdated),
+    IndexFreezeUpdated(IndexFreezeUpdated),
+    LowWaterMarkUpdated(LowWaterMarkUpdated),
+    WalletCapUpdated(WalletCapUpdated),
+    CloseGraceUpdated(CloseGraceUpdated),
+    ClaimsResumed(ClaimsResumed),
+    ClaimWindowUpdated(ClaimWindowUpdated),
+    MaxStartLeadUpdated(MaxStartLeadUpdated),
+    MerkleRootUpdated(MerkleRootUpdated),
+    StateResized(StateResized),
+    StateMigrated(StateMigrated),
+    CampaignRolledOver(CampaignRolledOver),
+    UnclaimedIndices(UnclaimedIndices),
+    CompressedLedgerInitialized(CompressedLedgerInitialized),
+    WormholeEmitterUpdated(WormholeEmitterUpdated),
+    VaaClaimRedeemed(VaaClaimRedeemed),
+    ClaimBadgeEnabled(ClaimBadgeEnabled),
+    RelayerFeeUpdated(RelayerFeeUpdated),
+    RelayerUpdated(RelayerUpdated),
+    RelayedClaim(RelayedClaim),
+    UsdPricingUpdated(UsdPricingUpdated),
+    UsdClaimPriced(UsdClaimPriced),
+    CohortUpdated(CohortUpdated),
+    CohortClaimed(CohortClaimed),
+    Finalized(Finalized),
+    StatusChanged(StatusChanged),
+    RevealDelayUpdated(RevealDelayUpdated),
+    ClaimCommitted(ClaimCommitted),
+    ConfigUpdated(ConfigUpdated),
+    CreationFeePaid(CreationFeePaid),
+    ConfigChanged(ConfigChanged),
+    IndexVoided(IndexVoided),
+    DustThresholdUpdated(DustThresholdUpdated),
+    DustSwept(DustSwept),
+    BuildInfoRecorded(BuildInfoRecorded),
+    AllocationsAppended(AllocationsAppended),
+    RootScheduled(RootScheduled),
+    RootScheduleCleared(RootScheduleCleared),
+    RoundingUpdated(RoundingUpdated),
+    FamilyUpdated(FamilyUpdated),
+    FamilyJoined(FamilyJoined),
+    LeafClaimed(LeafClaimed),
+    CohortVaultUpdated(CohortVaultUpdated),
+    CohortClawedBack(CohortClawedBack),
+    VestingHandoffUpdated(VestingHandoffUpdated),
+    VestingHandedOff(VestingHandedOff),
+    BonusLotteryUpdated(BonusLotteryUpdated),
+    BonusTicketEntered(BonusTicketEntered),
+    BonusTicketRevealed(BonusTicketRevealed),
+    BonusLotterySettled(BonusLotterySettled),
+    BonusTicketRedeemed(BonusTicketRedeemed),
+}
+
+impl MerkledropEvent {
+    /// Decodes an event from its discriminator-prefixed bytes; `None` for
+    /// unknown discriminators, such as events added after this release.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+        match discriminator {
+            AirdropInitialized::DISCRIMINATOR => AirdropInitialized::decode(data).map(Self::AirdropInitialized),
+            Claimed::DISCRIMINATOR => Claimed::decode(data).map(Self::Claimed),
+            CpiClaimed::DISCRIMINATOR => CpiClaimed::decode(data).map(Self::CpiClaimed),
+            DomainClaimed::DISCRIMINATOR => DomainClaimed::decode(data).map(Self::DomainClaimed),
+            BatchDistributed::DISCRIMINATOR => BatchDistributed::decode(data).map(Self::BatchDistributed),
+            AirdropClosed::DISCRIMINATOR => AirdropClosed::decode(data).map(Self::AirdropClosed),
+            CrankBountyPaid::DISCRIMINATOR => CrankBountyPaid::decode(data).map(Self::CrankBountyPaid),
+            ConfigLocked::DISCRIMINATOR => ConfigLocked::decode(data).map(Self::ConfigLocked),
+            AuthorityRenounced::DISCRIMINATOR => AuthorityRenounced::decode(data).map(Self::AuthorityRenounced),
+            VaultAuthorityRotated::DISCRIMINATOR => VaultAuthorityRotated::decode(data).map(Self::VaultAuthorityRotated),
+            FundingVaultUpdated::DISCRIMINATOR => FundingVaultUpdated::decode(data).map(Self::FundingVaultUpdated),
+            CrankBountyUpdated::DISCRIMINATOR => CrankBountyUpdated::decode(data).map(Self::CrankBountyUpdated),
+            CampaignMetadataUpdated::DISCRIMINATOR => CampaignMetadataUpdated::decode(data).map(Self::CampaignMetadataUpdated),
+            TermsHashUpdated::DISCRIMINATOR => TermsHashUpdated::decode(data).map(Self::TermsHashUpdated),
+            ClaimGateUpdated::DISCRIMINATOR => ClaimGateUpdated::decode(data).map(Self::ClaimGateUpdated),
+            LeafEncodingUpdated::DISCRIMINATOR => LeafEncodingUpdated::decode(data).map(Self::LeafEncodingUpdated),
+            EpochCreated::DISCRIMINATOR => EpochCreated::decode(data).map(Self::EpochCreated),
+            EpochClaimed::DISCRIMINATOR => EpochClaimed::decode(data).map(Self::EpochClaimed),
+            RaffleRequested::DISCRIMINATOR => RaffleRequested::decode(data).map(Self::RaffleRequested),
+            RaffleSettled::DISCRIMINATOR => RaffleSettled::decode(data).map(Self::RaffleSettled),
+            RafflePrizeClaimed::DISCRIMINATOR => RafflePrizeClaimed::decode(data).map(Self::RafflePrizeClaimed),
+            VaultBelowLowWaterMark::DISCRIMINATOR => VaultBelowLowWaterMark::decode(data).map(Self::VaultBelowLowWaterMark),
+            ClaimReserved::DISCRIMINATOR => ClaimReserved::decode(data).map(Self::ClaimReserved),
+            VestingStarted::DISCRIMINATOR => VestingStarted::decode(data).map(Self::VestingStarted),
+            VestedReleased::DISCRIMINATOR => VestedReleased::decode(data).map(Self::VestedReleased),
+            EscrowWithdrawn::DISCRIMINATOR => EscrowWithdrawn::decode(data).map(Self::EscrowWithdrawn),
+            DisputePeriodUpdated::DISCRIMINATOR => DisputePeriodUpdated::decode(data).map(Self::DisputePeriodUpdated),
+            LatePenaltyUpdated::DISCRIMINATOR => LatePenaltyUpdated::decode(data).map(Self::LatePenaltyUpdated),
+            EarlyBonusUpdated::DISCRIMINATOR => EarlyBonusUpdated::decode(data).map(Self::EarlyBonusUpdated),
+            BonusReserveWithdrawn::DISCRIMINATOR => BonusReserveWithdrawn::decode(data).map(Self::BonusReserveWithdrawn),
+            FeesWithdrawn::DISCRIMINATOR => FeesWithdrawn::decode(data).map(Self::FeesWithdrawn),
+            OperatorFeeUpdated::DISCRIMINATOR => OperatorFeeUpdated::decode(data).map(Self::OperatorFeeUpdated),
+            IndexFreezeUpdated::DISCRIMINATOR => IndexFreezeUpdated::decode(data).map(Self::IndexFreezeUpdated),
+            LowWaterMarkUpdated::DISCRIMINATOR => LowWaterMarkUpdated::decode(data).map(Self::LowWaterMarkUpdated),
+            WalletCapUpdated::DISCRIMINATOR => WalletCapUpdated::decode(data).map(Self::WalletCapUpdated),
+            CloseGraceUpdated::DISCRIMINATOR => CloseGraceUpdated::decode(data).map(Self::CloseGraceUpdated),
+            ClaimsResumed::DISCRIMINATOR => ClaimsResumed::decode(data).map(Self::ClaimsResumed),
+            ClaimWindowUpdated::DISCRIMINATOR => ClaimWindowUpdated::decode(data).map(Self::ClaimWindowUpdated),
+            MaxStartLeadUpdated::DISCRIMINATOR => MaxStartLeadUpdated::decode(data).map(Self::MaxStartLeadUpdated),
+            MerkleRootUpdated::DISCRIMINATOR => MerkleRootUpdated::decode(data).map(Self::MerkleRootUpdated),
+            StateResized::DISCRIMINATOR => StateResized::decode(data).map(Self::StateResized),
+            StateMigrated::DISCRIMINATOR => StateMigrated::decode(data).map(Self::StateMigrated),
+            CampaignRolledOver::DISCRIMINATOR => CampaignRolledOver::decode(data).map(Self::CampaignRolledOver),
+            UnclaimedIndices::DISCRIMINATOR => UnclaimedIndices::decode(data).map(Self::UnclaimedIndices),
+            CompressedLedgerInitialized::DISCRIMINATOR => CompressedLedgerInitialized::decode(data).map(Self::CompressedLedgerInitialized),
+            WormholeEmitterUpdated::DISCRIMINATOR => WormholeEmitterUpdated::decode(data).map(Self::WormholeEmitterUpdated),
+            VaaClaimRedeemed::DISCRIMINATOR => VaaClaimRedeemed::decode(data).map(Self::VaaClaimRedeemed),
+            ClaimBadgeEnabled::DISCRIMINATOR => ClaimBadgeEnabled::decode(data).map(Self::ClaimBadgeEnabled),
+            RelayerFeeUpdated::DISCRIMINATOR => RelayerFeeUpdated::decode(data).map(Self::RelayerFeeUpdated),
+            RelayerUpdated::DISCRIMINATOR => RelayerUpdated::decode(data).map(Self::RelayerUpdated),
+            RelayedClaim::DISCRIMINATOR => RelayedClaim::decode(data).map(Self::RelayedClaim),
+            UsdPricingUpdated::DISCRIMINATOR => UsdPricingUpdated::decode(data).map(Self::UsdPricingUpdated),
+            UsdClaimPriced::DISCRIMINATOR => UsdClaimPriced::decode(data).map(Self::UsdClaimPriced),
+            CohortUpdated::DISCRIMINATOR => CohortUpdated::decode(data).map(Self::CohortUpdated),
+            CohortClaimed::DISCRIMINATOR => CohortClaimed::decode(data).map(Self::CohortClaimed),
+            Finalized::DISCRIMINATOR => Finalized::decode(data).map(Self::Finalized),
+            StatusChanged::DISCRIMINATOR => StatusChanged::decode(data).map(Self::StatusChanged),
+            RevealDelayUpdated::DISCRIMINATOR => RevealDelayUpdated::decode(data).map(Self::RevealDelayUpdated),
+            ClaimCommitted::DISCRIMINATOR => ClaimCommitted::decode(data).map(Self::ClaimCommitted),
+            ConfigUpdated::DISCRIMINATOR => ConfigUpdated::decode(data).map(Self::ConfigUpdated),
+            CreationFeePaid::DISCRIMINATOR => CreationFeePaid::decode(data).map(Self::CreationFeePaid),
+            ConfigChanged::DISCRIMINATOR => ConfigChanged::decode(data).map(Self::ConfigChanged),
+            IndexVoided::DISCRIMINATOR => IndexVoided::decode(data).map(Self::IndexVoided),
+            DustThresholdUpdated::DISCRIMINATOR => DustThresholdUpdated::decode(data).map(Self::DustThresholdUpdated),
+            DustSwept::DISCRIMINATOR => DustSwept::decode(data).map(Self::DustSwept),
+            BuildInfoRecorded::DISCRIMINATOR => BuildInfoRecorded::decode(data).map(Self::BuildInfoRecorded),
+            AllocationsAppended::DISCRIMINATOR => AllocationsAppended::decode(data).map(Self::AllocationsAppended),
+            RootScheduled::DISCRIMINATOR => RootScheduled::decode(data).map(Self::RootScheduled),
+            RootScheduleCleared::DISCRIMINATOR => RootScheduleCleared::decode(data).map(Self::RootScheduleCleared),
+            RoundingUpdated::DISCRIMINATOR => RoundingUpdated::decode(data).map(Self::RoundingUpdated),
+            FamilyUpdated::DISCRIMINATOR => FamilyUpdated::decode(data).map(Self::FamilyUpdated),
+            FamilyJoined::DISCRIMINATOR => FamilyJoined::decode(data).map(Self::FamilyJoined),
+            LeafClaimed::DISCRIMINATOR => LeafClaimed::decode(data).map(Self::LeafClaimed),
+            CohortVaultUpdated::DISCRIMINATOR => CohortVaultUpdated::decode(data).map(Self::CohortVaultUpdated),
+            CohortClawedBack::DISCRIMINATOR => CohortClawedBack::decode(data).map(Self::CohortClawedBack),
+            VestingHandoffUpdated::DISCRIMINATOR => VestingHandoffUpdated::decode(data).map(Self::VestingHandoffUpdated),
+            VestingHandedOff::DISCRIMINATOR => VestingHandedOff::decode(data).map(Self::VestingHandedOff),
+            BonusLotteryUpdated::DISCRIMINATOR => BonusLotteryUpdated::decode(data).map(Self::BonusLotteryUpdated),
+            BonusTicketEntered::DISCRIMINATOR => BonusTicketEntered::decode(data).map(Self::BonusTicketEntered),
+            BonusTicketRevealed::DISCRIMINATOR => BonusTicketRevealed::decode(data).map(Self::BonusTicketRevealed),
+            BonusLotterySettled::DISCRIMINATOR => BonusLotterySettled::decode(data).map(Self::BonusLotterySettled),
+            BonusTicketRedeemed::DISCRIMINATOR => BonusTicketRedeemed::decode(data).map(Self::BonusTicketRedeemed),
+            _ => None,
+        }
+    }
+
+    /// Decodes the data of an `emit_cpi!` self-invocation, which prefixes
+    /// the event with Anchor's event instruction tag.
+    pub fn decode_cpi(ix_data: &[u8]) -> Option<Self> {
+        Self::decode(ix_data.strip_prefix(&EVENT_IX_TAG.to_le_bytes())?)
+    }
+}
+
+/// Anchor's tag for `emit_cpi!` self-invocations.
+pub const EVENT_IX_TAG: u64 = 0x1d9a_cb51_2ea5_45e4;
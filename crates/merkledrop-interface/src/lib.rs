@@ -0,0 +1,69 @@
+//! Stable account and event layouts of the merkledrop program, for
+//! integrators that read its accounts or index its events without linking
+//! the program crate.
+//!
+//! The program only ever appends fields to `State` and to events, and this
+//! crate follows semver against that: a minor release adds mirrored fields,
+//! variants and PDAs; removing or reordering anything is a major release.
+//! Because fields are appended, a mirror keeps decoding accounts and events
+//! written by newer program versions; it just doesn't see the new fields.
+//!
+//! ```ignore
+//! let state = merkledrop_interface::State::decode(&account.data)?;
+//! if let Some(MerkledropEvent::Claimed(claimed)) = MerkledropEvent::decode_cpi(&ix.data) {
+//!     // ...
+//! }
+//! let is_claimed = merkledrop_interface::ClaimSet::from_account_data(&account.data)?;
+//! ```
+
+use anchor_lang::prelude::*;
+
+mod claim_set;
+mod constants;
+mod events;
+pub mod pda;
+mod reconcile;
+mod remediation;
+mod state;
+mod window;
+
+pub use claim_set::*;
+pub use constants::*;
+pub use events::*;
+pub use reconcile::*;
+pub use remediation::*;
+pub use state::*;
+pub use window::*;
+
+pub const ID: Pubkey = anchor_lang::solana_program::pubkey!("FuE9G24fmey6LT21ra4kxGJ7QYnzaeVV7MnSXPrrVGg4");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterfaceError {
+    /// Wrong discriminator or undecodable data
+    NotAState,
+    /// Written by this older `State` version; run `migrate_state` first
+    OutdatedState(u8),
+    /// Claims live in a compressed ledger tree, not in `State`
+    CompressedLedger,
+    /// Claims from before migration aren't all in the bitmap until
+    /// `resize_state` has converted them
+    LegacyClaimsPending,
+}
+
+impl std::fmt::Display for InterfaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAState => f.write_str("account data is not a merkledrop State"),
+            Self::OutdatedState(version) => write!(
+                f,
+                "State version {version} predates this interface (needs {STATE_VERSION}); migrate it first"
+            ),
+            Self::CompressedLedger => f.write_str("campaign records claims in a compressed ledger"),
+            Self::LegacyClaimsPending => {
+                f.write_str("claims from before migration are still being converted by resize_state")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InterfaceError {}
@@ -0,0 +1,196 @@
+//! Program-derived addresses, with the program's seeds.
+
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, State, ID};
+
+/// The SPL Token program, which every claim passes.
+pub const TOKEN_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// The campaign's `State`.
+pub fn state() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATE_SEED], &ID)
+}
+
+/// Signer over a round's vault; the vault is this address's ATA for the mint.
+pub fn vault_authority(snapshot_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, snapshot_hash], &ID)
+}
+
+/// A wallet's per-round claim total, checked against the wallet cap.
+pub fn wallet_claims(state: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WALLET_CLAIMS_SEED, state.as_ref(), wallet.as_ref()], &ID)
+}
+
+/// A relayer's allowlist entry and claim counters.
+pub fn relayer(state: &Pubkey, relayer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RELAYER_SEED, state.as_ref(), relayer.as_ref()], &ID)
+}
+
+pub fn campaign_metadata(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[METADATA_SEED, state.as_ref()], &ID)
+}
+
+pub fn claim_stats(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATS_SEED, state.as_ref()], &ID)
+}
+
+pub fn treasury(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED, state.as_ref()], &ID)
+}
+
+/// Token account collecting fees and penalties in kind.
+pub fn treasury_tokens(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_TOKENS_SEED, state.as_ref()], &ID)
+}
+
+pub fn bonus_vault(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BONUS_VAULT_SEED, state.as_ref()], &ID)
+}
+
+/// Non-transferable Token-2022 mint of claim badges.
+pub fn badge_mint(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BADGE_MINT_SEED, state.as_ref()], &ID)
+}
+
+/// Authority of the compressed claim ledger tree.
+pub fn ledger_authority(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LEDGER_AUTHORITY_SEED, state.as_ref()], &ID)
+}
+
+/// A cohort's claim window and vesting schedule.
+pub fn cohort(state: &Pubkey, cohort_id: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COHORT_SEED, state.as_ref(), &cohort_id.to_le_bytes()], &ID)
+}
+
+pub fn claim_escrow(snapshot_hash: &[u8; 32], index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ESCROW_SEED, snapshot_hash, &index.to_le_bytes()], &ID)
+}
+
+pub fn vesting(snapshot_hash: &[u8; 32], index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VESTING_SEED, snapshot_hash, &index.to_le_bytes()], &ID)
+}
+
+/// Permanent outcome of a finished round, written by `finalize_airdrop`.
+pub fn summary(snapshot_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SUMMARY_SEED, snapshot_hash], &ID)
+}
+
+pub fn rollover_summary(snapshot_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROLLOVER_SEED, snapshot_hash], &ID)
+}
+
+pub fn epoch_root(state: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EPOCH_SEED, state.as_ref(), &epoch.to_le_bytes()], &ID)
+}
+
+pub fn raffle(snapshot_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RAFFLE_SEED, snapshot_hash], &ID)
+}
+
+pub fn raffle_prize(raffle: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RAFFLE_PRIZE_SEED, raffle.as_ref(), &index.to_le_bytes()], &ID)
+}
+
+/// Temporary wSOL account `claim_unwrapped` closes into the wallet.
+pub fn unwrap_account(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UNWRAP_SEED, wallet.as_ref()], &ID)
+}
+
+/// A wallet's `commit_claim` ticket, closed by `claim_ticketed`.
+pub fn ticket(state: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TICKET_SEED, state.as_ref(), wallet.as_ref()], &ID)
+}
+
+/// Program-wide protocol config, created by `init_config`.
+pub fn config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &ID)
+}
+
+/// Build metadata of the deployed program, written by `record_build_info`.
+pub fn build_info() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUILD_INFO_SEED], &ID)
+}
+
+/// Record of an allocation retired by `void_index`.
+pub fn voided_index(snapshot_hash: &[u8; 32], index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOID_SEED, snapshot_hash, &index.to_le_bytes()], &ID)
+}
+
+/// Roots queued by `schedule_root` for `activate_scheduled_root`.
+pub fn root_schedule(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROOT_SCHEDULE_SEED, state.as_ref()], &ID)
+}
+
+/// A wallet's pick of one campaign in a family, under that campaign's
+/// `program`; `join_family` takes the siblings' addresses.
+pub fn family_claim(program: &Pubkey, family_id: &[u8; 32], wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FAMILY_CLAIM_SEED, family_id, wallet.as_ref()], program)
+}
+
+/// Instruction template `claim_to_vesting` invokes.
+pub fn vesting_handoff(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VESTING_HANDOFF_SEED, state.as_ref()], &ID)
+}
+
+/// Sender of every lock `claim_to_vesting` opens; holds the claim in its
+/// token account until the vesting program takes it.
+pub fn vesting_sender(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VESTING_SENDER_SEED, state.as_ref()], &ID)
+}
+
+/// Draw buckets of the campaign's bonus lottery.
+pub fn bonus_lottery(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BONUS_LOTTERY_SEED, state.as_ref()], &ID)
+}
+
+/// Bonus lottery entry of leaf `index`.
+pub fn bonus_ticket(snapshot_hash: &[u8; 32], index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BONUS_TICKET_SEED, snapshot_hash, &index.to_le_bytes()], &ID)
+}
+
+/// Claims a migrated round recorded before `State::version`, kept until
+/// `resize_state` has converted them.
+pub fn legacy_claims(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LEGACY_CLAIMS_SEED, state.as_ref()], &ID)
+}
+
+/// Signer of the program's self-CPI events; every event-emitting
+/// instruction passes it with the program itself.
+pub fn event_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"__event_authority"], &ID)
+}
+
+/// Accounts every `claim` of the campaign passes, for an address lookup
+/// table. A claim that loads them from the table carries only its wallet,
+/// token account and proof inline, so more claims fit in a transaction.
+/// The vault authority moves with each round's snapshot hash, so extend the
+/// table after a rollover.
+pub fn claim_lookup_addresses(state: &State) -> Vec<Pubkey> {
+    let campaign = self::state().0;
+    let mut addresses = vec![
+        campaign,
+        vault_authority(&state.snapshot_hash).0,
+        state.vault,
+        state.mint,
+        TOKEN_PROGRAM_ID,
+        event_authority().0,
+        ID,
+    ];
+    if state.fee_account != Pubkey::default() {
+        addresses.push(state.fee_account);
+    }
+    if state.stats_enabled {
+        addresses.push(claim_stats(&campaign).0);
+    }
+    if state.bonus_vault != Pubkey::default() {
+        addresses.push(state.bonus_vault);
+    }
+    addresses
+}
+
+/// PDA an integrating program signs `claim_via_cpi` with; derived under the
+/// caller's program id, not this one.
+pub fn caller_authority(caller_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CALLER_AUTHORITY_SEED], caller_program)
+}
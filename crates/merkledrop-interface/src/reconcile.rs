@@ -0,0 +1,122 @@
+//! Claimed-versus-allocated reconciliation.
+//!
+//! Finance teams need to know what a campaign paid against what it
+//! promised, and whether anything was paid that the allocation file doesn't
+//! account for. `Reconciliation` compares the file's `(index, amount)`
+//! entries with either the campaign's `Claimed` events or its `ClaimSet`,
+//! and `to_csv` writes the per-index result. Events carry the amount each
+//! claim was proven for, so only they can reveal a claim above its
+//! allocation; a `ClaimSet` assumes every recorded claim took its
+//! allocation in full.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::{ClaimSet, Claimed};
+
+/// Something paid that the allocation file doesn't account for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anomaly {
+    /// A claim for an index the file has no entry for
+    UnknownIndex { index: u64, claimed: u64 },
+    /// A claim for more than the index was allocated
+    ExceedsAllocation { index: u64, allocated: u64, claimed: u64 },
+    /// A second claim for an index; the program never pays one, so the
+    /// events come from two rounds or two campaigns
+    ClaimedTwice { index: u64 },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reconciliation {
+    pub claimed_count: u64,
+    pub claimed_amount: u64,
+    pub unclaimed_count: u64,
+    pub unclaimed_amount: u64,
+    pub anomalies: Vec<Anomaly>,
+    // index -> (allocated, claimed); claimed is None until a claim is seen
+    rows: BTreeMap<u64, (u64, Option<u64>)>,
+}
+
+impl Reconciliation {
+    /// Reconciles against `Claimed` events of one campaign and round.
+    pub fn from_events<'a>(
+        allocations: &[(u64, u64)],
+        claims: impl IntoIterator<Item = &'a Claimed>,
+    ) -> Self {
+        let mut reconciliation = Self::allocated(allocations);
+        for claim in claims {
+            let Some((allocated, claimed)) = reconciliation.rows.get_mut(&claim.index) else {
+                reconciliation.anomalies.push(Anomaly::UnknownIndex {
+                    index: claim.index,
+                    claimed: claim.amount,
+                });
+                continue;
+            };
+            if claimed.is_some() {
+                reconciliation.anomalies.push(Anomaly::ClaimedTwice { index: claim.index });
+                continue;
+            }
+            if claim.amount > *allocated {
+                reconciliation.anomalies.push(Anomaly::ExceedsAllocation {
+                    index: claim.index,
+                    allocated: *allocated,
+                    claimed: claim.amount,
+                });
+            }
+            *claimed = Some(claim.amount);
+        }
+        reconciliation.total();
+        reconciliation
+    }
+
+    /// Reconciles against the claims `State` records, taking each recorded
+    /// claim as its full allocation.
+    pub fn from_claim_set(allocations: &[(u64, u64)], claims: &ClaimSet) -> Self {
+        let mut reconciliation = Self::allocated(allocations);
+        for (&index, (allocated, claimed)) in &mut reconciliation.rows {
+            if claims.is_claimed(index) {
+                *claimed = Some(*allocated);
+            }
+        }
+        reconciliation.total();
+        reconciliation
+    }
+
+    /// `index,allocated,claimed,status` per allocation, in index order, with
+    /// an empty `claimed` for unclaimed indices.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("index,allocated,claimed,status\n");
+        for (index, (allocated, claimed)) in &self.rows {
+            let status = match claimed {
+                None => "unclaimed",
+                Some(claimed) if claimed > allocated => "exceeds_allocation",
+                Some(_) => "claimed",
+            };
+            let claimed = claimed.map(|claimed| claimed.to_string()).unwrap_or_default();
+            writeln!(csv, "{index},{allocated},{claimed},{status}").expect("writing to a String");
+        }
+        csv
+    }
+
+    fn allocated(allocations: &[(u64, u64)]) -> Self {
+        Self {
+            rows: allocations.iter().map(|&(index, amount)| (index, (amount, None))).collect(),
+            ..Self::default()
+        }
+    }
+
+    fn total(&mut self) {
+        for (allocated, claimed) in self.rows.values() {
+            match claimed {
+                Some(claimed) => {
+                    self.claimed_count += 1;
+                    self.claimed_amount += claimed;
+                }
+                None => {
+                    self.unclaimed_count += 1;
+                    self.unclaimed_amount += allocated;
+                }
+            }
+        }
+    }
+}
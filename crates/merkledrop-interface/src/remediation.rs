@@ -0,0 +1,232 @@
+//! What a claimant can do about a claim that would fail.
+//!
+//! Simulating `validate_claim` runs every check `claim` makes and returns
+//! `ClaimValidation`, whose `error_code` is the program error the claim
+//! would fail with. Account checks that fail before the handler runs come
+//! back as the simulation's custom error code instead. Either code goes
+//! through `ClaimError::from_code`, and `remediation` turns the error into
+//! guidance for the claimant, filled in from the decoded `State`.
+//!
+//! ```ignore
+//! let validation = ClaimValidation::try_from_slice(&return_data)?;
+//! if let Err(code) = validation.check() {
+//!     match ClaimError::from_code(code) {
+//!         Some(error) => show(error.remediation(&state)),
+//!         None => show_generic(code),
+//!     }
+//! }
+//! ```
+
+use anchor_lang::prelude::*;
+
+use crate::State;
+
+/// Return data of `validate_claim`; the amounts are only set when the claim
+/// would succeed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClaimValidation {
+    pub error_code: u32,
+    pub fee: u64,
+    pub penalty: u64,
+    pub net_amount: u64,
+}
+
+impl ClaimValidation {
+    /// The program error code the claim would fail with, if any.
+    pub fn check(&self) -> std::result::Result<(), u32> {
+        match self.error_code {
+            0 => Ok(()),
+            code => Err(code),
+        }
+    }
+}
+
+/// Errors a claim can fail with that the claimant can act on or should be
+/// told about, by their program error code. Codes never shift, so a mirror
+/// keeps naming them correctly for newer program versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ClaimError {
+    /// Anchor's `AccountNotInitialized`: the recipient token account doesn't exist
+    TokenAccountMissing = 3012,
+    AlreadyClaimed = 6001,
+    InvalidProof = 6004,
+    InvalidIndex = 6005,
+    ClaimClosed = 6006,
+    CapacityExceeded = 6010,
+    StateNotMigrated = 6014,
+    ProofTooLong = 6015,
+    TermsNotAccepted = 6022,
+    GateNotSatisfied = 6023,
+    AllocationExceeded = 6035,
+    VaultNeedsRefill = 6036,
+    RecipientAccountFrozen = 6037,
+    InvalidFeeAccount = 6040,
+    EscrowRequired = 6041,
+    IndexFrozen = 6045,
+    StatsAccountMissing = 6047,
+    CompressedLedgerRequired = 6056,
+    WindowNotStarted = 6063,
+    WindowEnded = 6064,
+    WrongMint = 6065,
+    CrossChainClaimRequired = 6068,
+    ClaimBadgeMissing = 6074,
+    WalletClaimsMissing = 6076,
+    WalletCapExceeded = 6077,
+    UsdPriceRequired = 6080,
+    StalePrice = 6082,
+    PriceTooUncertain = 6083,
+    NotFunded = 6089,
+    TicketRequired = 6091,
+    RevealTooEarly = 6094,
+    BelowDustThreshold = 6099,
+    JoinedSiblingCampaign = 6110,
+    FamilyClaimMissing = 6111,
+    LeafMismatch = 6113,
+    VestingHandoffRequired = 6117,
+    LeavesPastClaimBitmap = 6127,
+}
+
+impl ClaimError {
+    /// `None` for codes a claimant can't act on, such as the authority-only
+    /// errors, and for errors added after this release.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            3012 => Self::TokenAccountMissing,
+            6001 => Self::AlreadyClaimed,
+            6004 => Self::InvalidProof,
+            6005 => Self::InvalidIndex,
+            6006 => Self::ClaimClosed,
+            6010 => Self::CapacityExceeded,
+            6014 => Self::StateNotMigrated,
+            6015 => Self::ProofTooLong,
+            6022 => Self::TermsNotAccepted,
+            6023 => Self::GateNotSatisfied,
+            6035 => Self::AllocationExceeded,
+            6036 => Self::VaultNeedsRefill,
+            6037 => Self::RecipientAccountFrozen,
+            6040 => Self::InvalidFeeAccount,
+            6041 => Self::EscrowRequired,
+            6045 => Self::IndexFrozen,
+            6047 => Self::StatsAccountMissing,
+            6056 => Self::CompressedLedgerRequired,
+            6063 => Self::WindowNotStarted,
+            6064 => Self::WindowEnded,
+            6065 => Self::WrongMint,
+            6068 => Self::CrossChainClaimRequired,
+            6074 => Self::ClaimBadgeMissing,
+            6076 => Self::WalletClaimsMissing,
+            6077 => Self::WalletCapExceeded,
+            6080 => Self::UsdPriceRequired,
+            6082 => Self::StalePrice,
+            6083 => Self::PriceTooUncertain,
+            6089 => Self::NotFunded,
+            6091 => Self::TicketRequired,
+            6094 => Self::RevealTooEarly,
+            6099 => Self::BelowDustThreshold,
+            6110 => Self::JoinedSiblingCampaign,
+            6111 => Self::FamilyClaimMissing,
+            6113 => Self::LeafMismatch,
+            6117 => Self::VestingHandoffRequired,
+            6127 => Self::LeavesPastClaimBitmap,
+            _ => return None,
+        })
+    }
+
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    /// Guidance for this error in the campaign `state` describes. A cohort
+    /// claim's window is its cohort's, which `WindowNotStarted` doesn't see.
+    pub fn remediation(self, state: &State) -> Remediation {
+        match self {
+            Self::TokenAccountMissing | Self::WrongMint => {
+                Remediation::CreateTokenAccount { mint: state.mint }
+            }
+            Self::RecipientAccountFrozen => Remediation::UseAnotherTokenAccount,
+            Self::WindowNotStarted => Remediation::WaitForWindow { opens_at: state.claim_start_ts },
+            Self::TermsNotAccepted => Remediation::AcceptTerms { terms_hash: state.terms_hash },
+            Self::GateNotSatisfied => Remediation::HoldGateToken {
+                gate_key: state.gate_key,
+                min_amount: state.gate_min_amount,
+            },
+            Self::InvalidProof | Self::InvalidIndex | Self::ProofTooLong | Self::LeafMismatch => {
+                Remediation::RebuildProof
+            }
+            Self::VaultNeedsRefill | Self::StalePrice | Self::PriceTooUncertain | Self::RevealTooEarly => {
+                Remediation::RetryLater
+            }
+            Self::EscrowRequired => Remediation::UseInstruction("reserve_claim"),
+            Self::CompressedLedgerRequired => Remediation::UseInstruction("claim_compressed"),
+            Self::CrossChainClaimRequired => Remediation::UseInstruction("claim_from_vaa"),
+            Self::TicketRequired => Remediation::UseInstruction("commit_claim"),
+            Self::FamilyClaimMissing => Remediation::UseInstruction("join_family"),
+            Self::WalletClaimsMissing => Remediation::UseInstruction("init_wallet_claims"),
+            Self::VestingHandoffRequired => Remediation::UseInstruction("claim_to_vesting"),
+            Self::StatsAccountMissing => Remediation::PassAccount("stats"),
+            Self::ClaimBadgeMissing => Remediation::PassAccount("badge_account"),
+            Self::InvalidFeeAccount => Remediation::PassAccount("fee_account"),
+            Self::UsdPriceRequired => Remediation::PassAccount("price_update"),
+            Self::CapacityExceeded
+            | Self::StateNotMigrated
+            | Self::NotFunded
+            | Self::IndexFrozen
+            | Self::LeavesPastClaimBitmap => Remediation::AskAuthority,
+            Self::AlreadyClaimed
+            | Self::ClaimClosed
+            | Self::WindowEnded
+            | Self::AllocationExceeded
+            | Self::WalletCapExceeded
+            | Self::BelowDustThreshold
+            | Self::JoinedSiblingCampaign => Remediation::NotClaimable,
+        }
+    }
+}
+
+/// What to tell a claimant whose claim would fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Remediation {
+    /// Create the wallet's associated token account for the campaign mint
+    CreateTokenAccount { mint: Pubkey },
+    /// The mint's freeze authority froze the recipient account
+    UseAnotherTokenAccount,
+    WaitForWindow { opens_at: i64 },
+    /// Claim with the campaign's current `terms_hash` once the terms are accepted
+    AcceptTerms { terms_hash: [u8; 32] },
+    /// Hold at least `min_amount` of the gate mint, or an NFT of the gate collection
+    HoldGateToken { gate_key: Pubkey, min_amount: u64 },
+    /// Rebuild the proof for this wallet and amount from the published list
+    RebuildProof,
+    /// Temporary: the vault awaits a refill, or a price or ticket reveal isn't ready
+    RetryLater,
+    /// The campaign pays this claim through another instruction, or needs it run first
+    UseInstruction(&'static str),
+    /// The campaign requires this optional account on the claim
+    PassAccount(&'static str),
+    /// Only the campaign authority can unblock the claim
+    AskAuthority,
+    /// The leaf won't pay through a claim: it was claimed, the window is
+    /// over, or a cap, dust threshold or family pick rules it out
+    NotClaimable,
+}
+
+impl std::fmt::Display for Remediation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CreateTokenAccount { mint } => write!(f, "create a token account for mint {mint}"),
+            Self::UseAnotherTokenAccount => f.write_str("the token account is frozen; claim to another one"),
+            Self::WaitForWindow { opens_at } => write!(f, "claims open at unix time {opens_at}"),
+            Self::AcceptTerms { .. } => f.write_str("accept the campaign's current terms"),
+            Self::HoldGateToken { gate_key, min_amount } => {
+                write!(f, "hold at least {min_amount} of {gate_key} to claim")
+            }
+            Self::RebuildProof => f.write_str("proof does not match this wallet and amount; rebuild it"),
+            Self::RetryLater => f.write_str("temporarily unavailable; try again shortly"),
+            Self::UseInstruction(name) => write!(f, "this campaign needs {name}"),
+            Self::PassAccount(name) => write!(f, "pass the {name} account"),
+            Self::AskAuthority => f.write_str("the campaign authority must act before this can be claimed"),
+            Self::NotClaimable => f.write_str("this allocation can't be claimed"),
+        }
+    }
+}
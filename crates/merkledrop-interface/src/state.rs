@@ -0,0 +1,166 @@
+//! Mirror of the program's `State` account.
+
+use anchor_lang::prelude::*;
+
+use crate::InterfaceError;
+
+pub const MAX_URI_LEN: usize = 200;
+pub const MAX_FROZEN_INDICES: usize = 16;
+pub const MAX_EXTRA_VAULTS: usize = 4;
+pub const MAX_ROOT_APPENDS: usize = 4;
+pub const MAX_FAMILY_SIBLINGS: usize = 7;
+pub const MAX_COHORT_VAULTS: usize = 4;
+
+/// `State` version this mirror was cut from; `migrate_state` brings the
+/// layout from before `version` up to it.
+pub const STATE_VERSION: u8 = 1;
+
+/// Anchor account discriminator, `sha256("account:State")[..8]`.
+pub const STATE_DISCRIMINATOR: [u8; 8] = [216, 146, 107, 94, 104, 75, 182, 177];
+
+/// The campaign's singleton `["state"]` account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct State {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub snapshot_hash: [u8; 32],
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub claim_closed: bool,
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub claim_capacity: u64,
+    pub round: u32,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub mint_on_claim: bool,
+    pub crank_bounty: u64,
+    pub claimed_count: u64,
+    pub claimed_amount: u64,
+    // Commitment to the allocation file behind `merkle_root`. The URI is
+    // NUL-padded and fixed-width so later appended fields keep stable offsets.
+    pub allocations_hash: [u8; 32],
+    pub allocations_uri: [u8; MAX_URI_LEN],
+    pub terms_hash: [u8; 32], // zero = no terms acceptance required
+    pub gate_kind: GateKind,
+    pub gate_key: Pubkey,
+    pub gate_min_amount: u64,
+    pub total_allocation: u64, // sum of all leaf amounts committed at initialize/rollover
+    pub low_water_mark: u64, // zero = never pause
+    pub needs_refill: bool,
+    pub fee_bps: u16,
+    pub fee_account: Pubkey, // operator fee token account, same mint as the campaign
+    pub dispute_period: i64, // zero = claims pay out immediately
+    pub escrowed_amount: u64, // reserved but not yet withdrawn
+    pub frozen_indices: [u64; MAX_FROZEN_INDICES], // first `frozen_count` entries are live
+    pub frozen_count: u8,
+    pub stats_enabled: bool, // claims must then pass the ClaimStats PDA
+    pub fees_collected: u64, // token fees routed to `fee_account` so far
+    pub penalty_start_ts: i64,
+    pub penalty_ramp: i64, // seconds to reach `penalty_bps`; zero = step
+    pub penalty_bps: u16, // zero = no late penalty
+    pub penalties_collected: u64,
+    pub bonus_vault: Pubkey, // reserve token account; never the claim vault
+    pub bonus_end_ts: i64,
+    pub bonus_bps: u16, // zero = no early bonus
+    pub bonuses_paid: u64,
+    pub vesting_locked: u64, // committed to vesting schedules but not yet released
+    pub claim_ledger: Pubkey, // concurrent Merkle tree recording claims; zero = `claim_bitmap`
+    pub config_locked: bool, // root and window can no longer change; never cleared
+    pub extra_vaults: [Pubkey; MAX_EXTRA_VAULTS], // first `extra_vault_count` entries are live, in failover order
+    pub extra_vault_count: u8,
+    pub wormhole_emitter_chain: u16, // zero = claims are paid on Solana directly
+    pub wormhole_emitter: [u8; 32], // companion contract that locks claim rights on that chain
+    pub leaf_encoding: LeafEncoding,
+    pub badge_mint: Pubkey, // non-transferable claim receipt mint; zero = no badges
+    pub wallet_cap: u64, // most one wallet may claim per round over all its leaves; zero = no cap
+    pub relayer_fee: u64, // tokens of each relayed claim paid to its relayer
+    pub usd_price_feed: [u8; 32], // Pyth feed pricing micro-USD leaves; zero = leaves are token amounts
+    pub max_price_age: u64, // seconds a price update stays usable
+    pub max_price_conf_bps: u16, // widest confidence interval accepted, relative to the price
+    pub max_start_lead: i64, // furthest ahead of now a new window may start; zero = ten years
+    pub status: CampaignStatus,
+    pub reveal_delay: u64, // slots between `commit_claim` and `claim_ticketed`; zero = no tickets
+    pub root_version: u32, // roots the campaign has had before the current one, across rounds
+    pub close_grace: i64, // seconds `close_airdrop` lets an open window run on; zero = close at once
+    pub dust_threshold: u64, // leaves below this are paid only by `sweep_dust`; zero = no threshold
+    pub community_pool: Pubkey, // token account `sweep_dust` pays dust leaves into
+    pub prior_roots: [[u8; 32]; MAX_ROOT_APPENDS], // first `prior_root_count` entries are roots claims still verify against
+    pub prior_root_count: u8,
+    pub rounding: Rounding, // how fees, penalties, bonuses, vesting and USD payouts round
+    pub family_id: [u8; 32], // zero = not in a family
+    pub family_siblings: [Pubkey; MAX_FAMILY_SIBLINGS], // program ids of the other campaigns in the family
+    pub family_sibling_count: u8,
+    pub unclaimed_export_cursor: u64, // `emit_unclaimed` pages below this have earned their bounty this round
+    pub cohort_vaults: [CohortVault; MAX_COHORT_VAULTS], // first `cohort_vault_count` entries are live
+    pub cohort_vault_count: u8,
+    pub vesting_program: Pubkey, // claims open a lock here through `claim_to_vesting`; zero = paid directly
+    pub bonus_lottery: bool, // early bonus is paid through bonus tickets rather than with the claim
+    pub legacy_claims: bool, // claims from before migration past `claim_capacity` await `resize_state`
+    pub raffle_deadline: i64, // this round's raffle pays prizes until then; zero = none owed
+    pub scheduled_root_count: u8, // scheduled roots still waiting this round
+    pub cohorts_hash: [u8; 32], // chains every cohort update
+    pub relayers_hash: [u8; 32], // chains every relayer registration and revocation
+    pub claim_bitmap: Vec<u8>, // bit `index` set once claimed; `claim_capacity` bits
+}
+
+impl State {
+    /// Decodes raw account data. Accounts written before `STATE_VERSION`
+    /// lack fields this mirror expects and are rejected until
+    /// `migrate_state` has upgraded them.
+    pub fn decode(data: &[u8]) -> Result<Self, InterfaceError> {
+        let mut body = data
+            .strip_prefix(&STATE_DISCRIMINATOR)
+            .ok_or(InterfaceError::NotAState)?;
+        match body.first() {
+            Some(&version) if version >= STATE_VERSION => {}
+            Some(&version) => return Err(InterfaceError::OutdatedState(version)),
+            None => return Err(InterfaceError::NotAState),
+        }
+        Self::deserialize(&mut body).map_err(|_| InterfaceError::NotAState)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GateKind {
+    None,
+    Token,
+    Collection,
+}
+
+/// How allocation leaves are hashed; vesting leaves are always `Native`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LeafEncoding {
+    /// `keccak(index || wallet || amount)`, integers little-endian
+    Native,
+    /// `keccak256(abi.encodePacked(uint256 index, bytes32 wallet, uint256 amount))`,
+    /// matching a companion Solidity distributor
+    EvmPacked,
+}
+
+/// Where the current round is in its lifecycle.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CampaignStatus {
+    Draft,
+    Funded,
+    Live,
+    Ended,
+    Finalized,
+}
+
+/// Which way a derived amount rounds when its division leaves a remainder.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    /// To nearest, ties to even
+    HalfEven,
+}
+
+/// A cohort paid from its own vault rather than the campaign's.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct CohortVault {
+    pub cohort_id: u32,
+    pub vault: Pubkey,
+    pub clawback_ts: i64, // zero = never clawed back
+}
@@ -0,0 +1,55 @@
+//! Claim window timing from a decoded `State`.
+//!
+//! `claim` compares against the cluster's clock, which can run seconds off
+//! a user's machine, and a transaction signed just before the close can
+//! still land after it. `ClaimWindow` answers with the program's own
+//! bounds, given the cluster time (for instance the `getBlockTime` of a
+//! recent slot plus the time since) and how long confirmation usually takes.
+
+use crate::State;
+
+/// Where a claim window stands at some cluster time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowStatus {
+    NotStarted { opens_in: i64 },
+    Open { remaining: i64 },
+    /// Past its end, or closed early by `close_airdrop`
+    Ended,
+}
+
+/// The current round's claim window, inclusive at both ends as `claim`
+/// checks it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClaimWindow {
+    pub opens_at: i64,
+    pub closes_at: i64,
+    pub closed: bool,
+}
+
+impl ClaimWindow {
+    pub fn of(state: &State) -> Self {
+        Self {
+            opens_at: state.claim_start_ts,
+            closes_at: state.claim_start_ts.saturating_add(state.claim_duration),
+            closed: state.claim_closed,
+        }
+    }
+
+    pub fn status(&self, cluster_now: i64) -> WindowStatus {
+        if self.closed || cluster_now > self.closes_at {
+            WindowStatus::Ended
+        } else if cluster_now < self.opens_at {
+            WindowStatus::NotStarted { opens_in: self.opens_at - cluster_now }
+        } else {
+            WindowStatus::Open { remaining: self.closes_at - cluster_now }
+        }
+    }
+
+    /// Whether a claim sent at `cluster_now` and confirmed up to `latency`
+    /// seconds later executes inside the window.
+    pub fn lands_in_window(&self, cluster_now: i64, latency: i64) -> bool {
+        !self.closed
+            && cluster_now >= self.opens_at
+            && cluster_now.saturating_add(latency) <= self.closes_at
+    }
+}
@@ -0,0 +1,421 @@
+//! Bonus lottery: fair shares of an oversubscribed early-bonus reserve.
+//!
+//! Paid first-come-first-served, a bonus reserve smaller than the bonuses it
+//! could owe goes to whoever lands first, which turns the opening into a
+//! priority-fee war. With `set_bonus_lottery`, claims stop paying the bonus
+//! directly. Instead a claimant enters a `["bonus_ticket", snapshot_hash,
+//! index]` ticket for its claimed leaf before `bonus_end_ts`, usually in the
+//! claim's own transaction. The ticket's draw is the keccak of the entry
+//! slot's hash and the index. That hash only exists once the slot is over,
+//! so entrants can't grind for a good draw. Anyone can then `reveal` the
+//! ticket, which adds its bonus to one of `LOTTERY_BUCKETS` buckets by draw.
+//! A ticket left unrevealed past the `SlotHashes` history draws last. Once
+//! the window has ended and every ticket is revealed, `settle` walks the
+//! buckets from the lowest draw while the reserve lasts, in constant work
+//! however many tickets there are. Buckets below the cut-off win in full,
+//! the cut-off bucket shares what remains pro rata, and the rest get
+//! nothing. Anyone can then redeem any ticket into its wallet's token
+//! account, and the ticket's rent goes back to the wallet.
+
+use super::*;
+use anchor_lang::solana_program::{keccak, sysvar::slot_hashes};
+
+/// Enables or disables the lottery; either way, every ticket of the last
+/// one must have been redeemed.
+pub fn set_bonus_lottery(ctx: Context<SetBonusLottery>, enabled: bool) -> Result<()> {
+    track_config!(ctx, {
+        let lottery = &mut ctx.accounts.lottery;
+        require!(lottery.tickets == 0, ErrorCode::BonusLotteryActive);
+        let state = &mut ctx.accounts.state;
+        if enabled {
+            require!(state.bonus_vault != Pubkey::default(), ErrorCode::InvalidBonusVault);
+        }
+        state.bonus_lottery = enabled;
+        lottery.set_inner(BonusLottery {
+            state: state.key(),
+            buckets: [0; LOTTERY_BUCKETS],
+            tickets: 0,
+            unrevealed: 0,
+            requested: 0,
+            settled: false,
+            cutoff_bucket: 0,
+            cutoff_pool: 0,
+            bump: ctx.bumps.lottery,
+        });
+        emit_cpi!(BonusLotteryUpdated {
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+/// Enters the wallet's claimed leaf `index` for `bonus_bps` of `amount`.
+pub fn enter_bonus_lottery(
+    ctx: Context<EnterBonusLottery>,
+    index: u64,
+    amount: u64,
+    proof: [[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let state = &ctx.accounts.state;
+    let lottery = &mut ctx.accounts.lottery;
+    require!(
+        state.bonus_lottery
+            && !lottery.settled
+            && state.bonus_bps != 0
+            && clock.unix_timestamp < state.bonus_end_ts,
+        ErrorCode::BonusLotteryClosed
+    );
+    require!(state.usd_price_feed == [0; 32], ErrorCode::UsdPriceRequired);
+    require!(proof_len as usize <= MAX_PROOF_DEPTH, ErrorCode::ProofTooLong);
+    let wallet = ctx.accounts.wallet.key();
+    let leaf = keccak_leaf(state.leaf_encoding, index, &wallet, amount);
+    let root = merkle::root_from_proof(&leaf, &proof[..proof_len as usize]);
+    require!(is_campaign_root(state, &root), ErrorCode::InvalidProof);
+    require_claims_converted(state)?;
+    require!(is_claimed(state, index), ErrorCode::NotClaimed);
+    // A void sets the index's bit too, but nobody claimed it
+    require!(ctx.accounts.voided_index.data_is_empty(), ErrorCode::AllocationVoided);
+
+    let bonus = state.rounding.div(amount as u128 * state.bonus_bps as u128, 10_000) as u64;
+    let ticket = &mut ctx.accounts.ticket;
+    ticket.wallet = wallet;
+    ticket.index = index;
+    ticket.bonus = bonus;
+    ticket.entry_slot = clock.slot;
+    ticket.bucket = None;
+    ticket.bump = ctx.bumps.ticket;
+    lottery.tickets += 1;
+    lottery.unrevealed += 1;
+    emit_cpi!(BonusTicketEntered {
+        wallet,
+        index,
+        bonus,
+        entry_slot: clock.slot,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Permissionless: draws ticket `index` from its entry slot's hash.
+pub fn reveal_bonus_ticket(ctx: Context<RevealBonusTicket>, index: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let ticket = &mut ctx.accounts.ticket;
+    require!(
+        ticket.bucket.is_none() && clock.slot > ticket.entry_slot,
+        ErrorCode::InvalidBonusTicket
+    );
+    let bucket = match find_slot_hash(&ctx.accounts.slot_hashes, ticket.entry_slot)? {
+        Some(hash) => {
+            let draw = keccak::hashv(&[&hash, &index.to_le_bytes()]).to_bytes();
+            // Top bits of the draw; lower buckets are paid first
+            (draw[0] as usize * LOTTERY_BUCKETS / 256) as u8
+        }
+        None => (LOTTERY_BUCKETS - 1) as u8,
+    };
+    ticket.bucket = Some(bucket);
+    let lottery = &mut ctx.accounts.lottery;
+    lottery.buckets[bucket as usize] = lottery.buckets[bucket as usize]
+        .checked_add(ticket.bonus)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    lottery.requested = lottery.requested
+        .checked_add(ticket.bonus)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    lottery.unrevealed -= 1;
+    emit_cpi!(BonusTicketRevealed {
+        index,
+        bucket,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Permissionless: fixes which buckets the bonus reserve pays, once the
+/// bonus window has ended and every ticket is revealed.
+pub fn settle_bonus_lottery(ctx: Context<SettleBonusLottery>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let lottery = &mut ctx.accounts.lottery;
+    require!(
+        now >= ctx.accounts.state.bonus_end_ts && lottery.unrevealed == 0 && !lottery.settled,
+        ErrorCode::LotteryNotSettleable
+    );
+    let pool = ctx.accounts.bonus_vault.amount;
+    let mut covered = 0u64;
+    let mut cutoff = LOTTERY_BUCKETS;
+    for (bucket, &requested) in lottery.buckets.iter().enumerate() {
+        if covered + requested > pool {
+            cutoff = bucket;
+            break;
+        }
+        covered += requested;
+    }
+    lottery.settled = true;
+    lottery.cutoff_bucket = cutoff as u8;
+    lottery.cutoff_pool = pool - covered;
+    emit_cpi!(BonusLotterySettled {
+        pool,
+        requested: lottery.requested,
+        cutoff_bucket: lottery.cutoff_bucket,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+/// Permissionless: pays ticket `index` what its bucket won, if anything,
+/// and closes it to its wallet.
+pub fn redeem_bonus_ticket(ctx: Context<RedeemBonusTicket>, index: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let lottery = &ctx.accounts.lottery;
+    require!(lottery.settled, ErrorCode::LotteryNotSettled);
+    let ticket = &ctx.accounts.ticket;
+    let bucket = ticket.bucket.ok_or(ErrorCode::LotteryNotSettled)?;
+    let bonus = lottery_payout(lottery, bucket, ticket.bonus);
+    pay_early_bonus(
+        &ctx.accounts.state,
+        ctx.bumps.state,
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        Some(&ctx.accounts.bonus_vault),
+        &ctx.accounts.recipient,
+        bonus,
+    )?;
+
+    let state = &mut ctx.accounts.state;
+    state.bonuses_paid = state.bonuses_paid
+        .checked_add(bonus)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let lottery = &mut ctx.accounts.lottery;
+    lottery.tickets -= 1;
+    emit_cpi!(BonusTicketRedeemed {
+        wallet: ticket.wallet,
+        index,
+        bucket,
+        bonus,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+// A ticket's share of the reserve: all of `bonus` below the cut-off, a pro
+// rata share of what's left in the cut-off bucket, none above it
+fn lottery_payout(lottery: &BonusLottery, bucket: u8, bonus: u64) -> u64 {
+    match bucket.cmp(&lottery.cutoff_bucket) {
+        std::cmp::Ordering::Less => bonus,
+        std::cmp::Ordering::Equal => {
+            let requested = lottery.buckets[bucket as usize];
+            if requested == 0 {
+                0
+            } else {
+                (bonus as u128 * lottery.cutoff_pool as u128 / requested as u128) as u64
+            }
+        }
+        std::cmp::Ordering::Greater => 0,
+    }
+}
+
+// `slot`'s hash from the `SlotHashes` sysvar, if it is still in its
+// history; read in place, since deserializing all 512 entries won't fit
+fn find_slot_hash(slot_hashes: &AccountInfo, slot: u64) -> Result<Option<[u8; 32]>> {
+    let data = slot_hashes.try_borrow_data()?;
+    let len = data
+        .get(..8)
+        .map(|n| u64::from_le_bytes(n.try_into().unwrap()) as usize)
+        .ok_or(ErrorCode::InvalidBonusTicket)?;
+    // Entries are (slot, hash), newest first
+    for entry in data[8..].chunks_exact(40).take(len) {
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if entry_slot == slot {
+            return Ok(Some(entry[8..].try_into().unwrap()));
+        }
+        if entry_slot < slot {
+            break;
+        }
+    }
+    Ok(None)
+}
+
+/// Draw buckets of the current lottery; seeds = ["bonus_lottery", state].
+#[account]
+pub struct BonusLottery {
+    pub state: Pubkey,
+    pub buckets: [u64; LOTTERY_BUCKETS], // bonuses requested by revealed tickets, per draw bucket
+    pub tickets: u64, // entered and not yet redeemed
+    pub unrevealed: u64,
+    pub requested: u64,
+    pub settled: bool,
+    pub cutoff_bucket: u8, // `LOTTERY_BUCKETS` = every bucket is paid in full
+    pub cutoff_pool: u64, // what the cut-off bucket shares
+    pub bump: u8,
+}
+
+/// One claimed leaf's entry; seeds = ["bonus_ticket", snapshot_hash, index (u64 LE)].
+#[account]
+pub struct BonusTicket {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub bonus: u64, // `bonus_bps` of the leaf's amount, if the bucket wins in full
+    pub entry_slot: u64,
+    pub bucket: Option<u8>, // none until revealed
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetBonusLottery<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [BONUS_LOTTERY_SEED, state.key().as_ref()],
+        bump,
+        payer = authority,
+        space = BONUS_LOTTERY_SPACE
+    )]
+    pub lottery: Account<'info, BonusLottery>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct EnterBonusLottery<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(mut, seeds = [BONUS_LOTTERY_SEED, state.key().as_ref()], bump = lottery.bump)]
+    pub lottery: Account<'info, BonusLottery>,
+
+    /// Pays the ticket rent, refunded at redemption
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [BONUS_TICKET_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()],
+        bump,
+        payer = wallet,
+        space = BONUS_TICKET_SPACE
+    )]
+    pub ticket: Account<'info, BonusTicket>,
+
+    /// CHECK: the index's `VoidedIndex` record, which must not exist
+    #[account(seeds = [VOID_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()], bump)]
+    pub voided_index: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct RevealBonusTicket<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(mut, seeds = [BONUS_LOTTERY_SEED, state.key().as_ref()], bump = lottery.bump)]
+    pub lottery: Account<'info, BonusLottery>,
+
+    #[account(
+        mut,
+        seeds = [BONUS_TICKET_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()],
+        bump = ticket.bump
+    )]
+    pub ticket: Account<'info, BonusTicket>,
+
+    /// CHECK: the `SlotHashes` sysvar, read in place
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleBonusLottery<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(mut, seeds = [BONUS_LOTTERY_SEED, state.key().as_ref()], bump = lottery.bump)]
+    pub lottery: Account<'info, BonusLottery>,
+
+    #[account(address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct RedeemBonusTicket<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(mut, seeds = [BONUS_LOTTERY_SEED, state.key().as_ref()], bump = lottery.bump)]
+    pub lottery: Account<'info, BonusLottery>,
+
+    #[account(
+        mut,
+        seeds = [BONUS_TICKET_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()],
+        bump = ticket.bump,
+        close = wallet
+    )]
+    pub ticket: Account<'info, BonusTicket>,
+
+    /// Receives the ticket rent
+    #[account(mut, address = ticket.wallet)]
+    pub wallet: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = recipient.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub recipient: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct BonusLotteryUpdated {
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BonusTicketEntered {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub bonus: u64,
+    pub entry_slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BonusTicketRevealed {
+    pub index: u64,
+    pub bucket: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BonusLotterySettled {
+    pub pool: u64,
+    pub requested: u64,
+    pub cutoff_bucket: u8, // `LOTTERY_BUCKETS` = every bucket is paid in full
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BonusTicketRedeemed {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub bucket: u8,
+    pub bonus: u64, // zero for a losing bucket
+    pub timestamp: i64,
+}
@@ -0,0 +1,87 @@
+//! Verifiable-build metadata for the deployed program.
+//!
+//! After each deploy or upgrade, the upgrade authority runs
+//! `record_build_info` with the git commit and toolchain the bytecode was
+//! built from. The program adds its own crate version and the ProgramData
+//! deployment slot, and keeps all of it in one `["build_info"]` account.
+//! A claimant or auditor rebuilds that commit with that toolchain (for
+//! example with `anchor verify`) and compares the bytecode to the live
+//! program. A later upgrade moves the ProgramData slot, so a record that
+//! wasn't refreshed afterwards shows up as stale instead of vouching for
+//! code it doesn't describe. Every campaign on the program runs that code,
+//! so the record covers all of them.
+
+use super::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+const PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Rerun after every upgrade; the record is overwritten in place.
+pub fn record_build_info(ctx: Context<RecordBuildInfo>, git_commit: [u8; 20], toolchain: String) -> Result<()> {
+    require!(toolchain.len() <= MAX_TOOLCHAIN_LEN, ErrorCode::MetadataTooLong);
+    let now = Clock::get()?.unix_timestamp;
+    let build_info = &mut ctx.accounts.build_info;
+    build_info.program_version = [0; 16];
+    build_info.program_version[..PROGRAM_VERSION.len()].copy_from_slice(PROGRAM_VERSION.as_bytes());
+    build_info.git_commit = git_commit;
+    build_info.toolchain = [0; MAX_TOOLCHAIN_LEN];
+    build_info.toolchain[..toolchain.len()].copy_from_slice(toolchain.as_bytes());
+    build_info.deployed_slot = ctx.accounts.program_data.slot;
+    build_info.recorded_at = now;
+    build_info.bump = ctx.bumps.build_info;
+    emit_cpi!(BuildInfoRecorded {
+        program_version: PROGRAM_VERSION.to_string(),
+        git_commit,
+        toolchain,
+        deployed_slot: build_info.deployed_slot,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+/// What the live program was built from; seeds = ["build_info"].
+#[account]
+pub struct BuildInfo {
+    pub program_version: [u8; 16], // the program crate's version, zero-padded
+    pub git_commit: [u8; 20], // SHA-1 of the commit the bytecode was built from
+    pub toolchain: [u8; MAX_TOOLCHAIN_LEN], // e.g. "anchor 0.31.1, solana 2.1.21", zero-padded
+    pub deployed_slot: u64, // ProgramData slot when recorded; differs once the program is upgraded again
+    pub recorded_at: i64,
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordBuildInfo<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [BUILD_INFO_SEED],
+        bump,
+        payer = upgrade_authority,
+        space = BUILD_INFO_SPACE
+    )]
+    pub build_info: Account<'info, BuildInfo>,
+
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    /// This program's ProgramData; only its upgrade authority knows what was deployed
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct BuildInfoRecorded {
+    pub program_version: String,
+    pub git_commit: [u8; 20],
+    pub toolchain: String,
+    pub deployed_slot: u64,
+    pub timestamp: i64,
+}
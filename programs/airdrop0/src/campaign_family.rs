@@ -0,0 +1,144 @@
+//! Campaign families: one wallet, one campaign.
+//!
+//! A drop split by region runs as several deployments, one campaign each,
+//! and a wallet listed in more than one region must claim in only one of
+//! them. `set_family` links this campaign to its siblings by program id
+//! under a shared `family_id`. Before its first claim, a wallet signs
+//! `join_family`, which opens its `["family_claim", family_id, wallet]`
+//! account here, but only if no sibling holds the same account. The
+//! siblings' addresses go in `remaining_accounts`, and because the
+//! transaction locks them, two joins of one wallet in different campaigns
+//! can't both pass. Every claim path, epochs included, then requires the
+//! wallet's family claim, so a wallet claims, across any number of leaves,
+//! only in the campaign it joined.
+
+use super::*;
+
+/// Links this campaign to `siblings`, the program ids of the family's other
+/// campaigns; an empty list leaves the family. Only before the first claim.
+pub fn set_family(ctx: Context<SetFamily>, family_id: [u8; 32], siblings: Vec<Pubkey>) -> Result<()> {
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        require!(state.claimed_count == 0, ErrorCode::ClaimsAlreadyRecorded);
+        require!(siblings.len() <= MAX_FAMILY_SIBLINGS, ErrorCode::InvalidFamily);
+        require!(siblings.is_empty() || family_id != [0; 32], ErrorCode::InvalidFamily);
+        for (i, sibling) in siblings.iter().enumerate() {
+            require!(
+                *sibling != crate::ID && !siblings[..i].contains(sibling),
+                ErrorCode::InvalidFamily
+            );
+        }
+        state.family_id = if siblings.is_empty() { [0; 32] } else { family_id };
+        state.family_siblings = [Pubkey::default(); MAX_FAMILY_SIBLINGS];
+        state.family_siblings[..siblings.len()].copy_from_slice(&siblings);
+        state.family_sibling_count = siblings.len() as u8;
+        emit_cpi!(FamilyUpdated {
+            family_id: state.family_id,
+            siblings,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+/// Binds the signing wallet to this campaign within its family. Each
+/// sibling's `["family_claim", family_id, wallet]` address goes in
+/// `remaining_accounts`, in `family_siblings` order, and none may exist.
+pub fn join_family(ctx: Context<JoinFamily>) -> Result<()> {
+    let state = &ctx.accounts.state;
+    let wallet = ctx.accounts.wallet.key();
+    let siblings = &state.family_siblings[..state.family_sibling_count as usize];
+    require!(!siblings.is_empty(), ErrorCode::NotInFamily);
+    require!(
+        ctx.remaining_accounts.len() == siblings.len(),
+        ErrorCode::InvalidFamilySibling
+    );
+    for (sibling, account) in siblings.iter().zip(ctx.remaining_accounts) {
+        let (address, _) = Pubkey::find_program_address(
+            &[FAMILY_CLAIM_SEED, &state.family_id, wallet.as_ref()],
+            sibling,
+        );
+        require_keys_eq!(account.key(), address, ErrorCode::InvalidFamilySibling);
+        // Lamports sent to the address don't count; only the sibling can own it
+        require!(account.owner != sibling, ErrorCode::JoinedSiblingCampaign);
+    }
+    let family_claim = &mut ctx.accounts.family_claim;
+    family_claim.family_id = state.family_id;
+    family_claim.wallet = wallet;
+    family_claim.bump = ctx.bumps.family_claim;
+    emit_cpi!(FamilyJoined {
+        family_id: state.family_id,
+        wallet,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Fails unless a wallet in a family claims with its family claim for this
+// campaign
+pub(crate) fn check_family_claim(
+    state: &State,
+    family_claim: Option<&FamilyClaim>,
+    wallet: &Pubkey,
+) -> Result<()> {
+    if state.family_sibling_count == 0 {
+        return Ok(());
+    }
+    let family_claim = family_claim.ok_or(ErrorCode::FamilyClaimMissing)?;
+    require!(family_claim.family_id == state.family_id, ErrorCode::FamilyClaimMissing);
+    require_keys_eq!(family_claim.wallet, *wallet, ErrorCode::FamilyClaimMissing);
+    Ok(())
+}
+
+/// A wallet's pick of this campaign within its family; seeds =
+/// ["family_claim", family_id, wallet]. Never closed, so siblings keep
+/// seeing it.
+#[account]
+pub struct FamilyClaim {
+    pub family_id: [u8; 32],
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFamily<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct JoinFamily<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    /// Only the wallet picks its campaign, and pays for the record
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [FAMILY_CLAIM_SEED, state.family_id.as_ref(), wallet.key().as_ref()],
+        bump,
+        payer = wallet,
+        space = FAMILY_CLAIM_SPACE
+    )]
+    pub family_claim: Account<'info, FamilyClaim>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct FamilyUpdated {
+    pub family_id: [u8; 32], // zero once the campaign has left its family
+    pub siblings: Vec<Pubkey>, // program ids of the other campaigns
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FamilyJoined {
+    pub family_id: [u8; 32],
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
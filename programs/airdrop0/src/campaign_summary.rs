@@ -0,0 +1,93 @@
+//! Final per-round summary for auditors.
+//!
+//! Once a round has ended (closed, or past its window), anyone can run
+//! `finalize_airdrop`. It writes a small `["summary", snapshot_hash]`
+//! account with the round's outcome, emits `Finalized` and moves the round
+//! to `CampaignStatus::Finalized`. The account is created once and never
+//! closed, so each round leaves one permanent on-chain record, even after
+//! the campaign rolls over or its state is closed.
+
+use super::*;
+
+pub fn finalize_airdrop(ctx: Context<FinalizeAirdrop>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let state = &mut ctx.accounts.state;
+    require!(
+        !matches!(state.status, CampaignStatus::Draft | CampaignStatus::Finalized),
+        ErrorCode::InvalidStatus
+    );
+    require!(
+        state.claim_closed || now > claim_end_ts(state.claim_start_ts, state.claim_duration)?,
+        ErrorCode::CampaignStillActive
+    );
+    let changed = set_status(state, CampaignStatus::Finalized, now);
+    emit_cpi!(changed);
+    let summary = &mut ctx.accounts.summary;
+    summary.state = state.key();
+    summary.round = state.round;
+    summary.snapshot_hash = state.snapshot_hash;
+    summary.merkle_root = state.merkle_root;
+    summary.total_claims = state.total_claims;
+    summary.claimed_count = state.claimed_count;
+    summary.claimed_amount = state.claimed_amount;
+    summary.unclaimed_amount = state.total_allocation.saturating_sub(state.claimed_amount);
+    summary.finalized_ts = now;
+    summary.bump = ctx.bumps.summary;
+    emit_cpi!(Finalized {
+        round: summary.round,
+        snapshot_hash: summary.snapshot_hash,
+        merkle_root: summary.merkle_root,
+        claimed_count: summary.claimed_count,
+        claimed_amount: summary.claimed_amount,
+        unclaimed_amount: summary.unclaimed_amount,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+/// Outcome of one round; seeds = ["summary", snapshot_hash].
+#[account]
+pub struct Summary {
+    pub state: Pubkey,
+    pub round: u32,
+    pub snapshot_hash: [u8; 32],
+    pub merkle_root: [u8; 32], // the root claims were last checked against
+    pub total_claims: u64,
+    pub claimed_count: u64,
+    pub claimed_amount: u64, // gross allocations claimed, fees included
+    pub unclaimed_amount: u64, // `total_allocation` not claimed
+    pub finalized_ts: i64,
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeAirdrop<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    /// Pays the summary's rent, which is never returned
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [SUMMARY_SEED, state.snapshot_hash.as_ref()],
+        bump,
+        payer = payer,
+        space = SUMMARY_SPACE
+    )]
+    pub summary: Account<'info, Summary>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct Finalized {
+    pub round: u32,
+    pub snapshot_hash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub claimed_count: u64,
+    pub claimed_amount: u64,
+    pub unclaimed_amount: u64,
+    pub timestamp: i64,
+}
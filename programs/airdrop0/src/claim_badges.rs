@@ -0,0 +1,122 @@
+//! Soulbound claim receipts.
+//!
+//! Once enabled, every claim path also mints one "I claimed" badge to the
+//! claimant; an escrowed claim gets its badge on `withdraw`. The badge mint
+//! is a Token-2022 mint with the `NonTransferable` extension, so a badge
+//! stays in the wallet that claimed, and other programs can token-gate on it
+//! (governance boosts, community roles) without an indexer. The state PDA is its mint authority and it has no
+//! freeze authority.
+
+use super::*;
+use anchor_spl::token_2022::{self, spl_token_2022, InitializeMint2, Token2022};
+use anchor_spl::token_2022_extensions::non_transferable::{
+    non_transferable_mint_initialize, NonTransferableMintInitialize,
+};
+use anchor_spl::token_interface;
+use spl_token_2022::extension::ExtensionType;
+
+/// Creates the `["badge", state]` mint. Only before the first claim, so
+/// every claimant holds a badge.
+pub fn init_claim_badge(ctx: Context<InitClaimBadge>) -> Result<()> {
+    track_config!(ctx, {
+        let state = &ctx.accounts.state;
+        require!(state.claimed_count == 0, ErrorCode::ClaimBadgeTooLate);
+
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::NonTransferable,
+        ])?;
+        let state_key = state.key();
+        let badge_seeds = &[BADGE_MINT_SEED, state_key.as_ref(), &[ctx.bumps.badge_mint]];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.badge_mint.to_account_info(),
+                },
+                &[badge_seeds],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &Token2022::id(),
+        )?;
+        non_transferable_mint_initialize(CpiContext::new(
+            ctx.accounts.token_2022_program.to_account_info(),
+            NonTransferableMintInitialize {
+                token_program_id: ctx.accounts.token_2022_program.to_account_info(),
+                mint: ctx.accounts.badge_mint.to_account_info(),
+            },
+        ))?;
+        token_2022::initialize_mint2(
+            CpiContext::new(
+                ctx.accounts.token_2022_program.to_account_info(),
+                InitializeMint2 { mint: ctx.accounts.badge_mint.to_account_info() },
+            ),
+            0,
+            &state_key,
+            None,
+        )?;
+
+        let badge_mint = ctx.accounts.badge_mint.key();
+        ctx.accounts.state.badge_mint = badge_mint;
+        emit_cpi!(ClaimBadgeEnabled {
+            badge_mint,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+// Mints the claimant's badge, signed by the state PDA; a no-op until
+// `init_claim_badge` has run
+pub(crate) fn mint_claim_badge<'info>(
+    state: &Account<'info, State>,
+    state_bump: u8,
+    badge_mint: Option<&UncheckedAccount<'info>>,
+    badge_account: Option<&InterfaceAccount<'info, token_interface::TokenAccount>>,
+    token_2022_program: Option<&Program<'info, Token2022>>,
+) -> Result<()> {
+    if state.badge_mint == Pubkey::default() {
+        return Ok(());
+    }
+    let (Some(badge_mint), Some(badge_account), Some(token_2022_program)) =
+        (badge_mint, badge_account, token_2022_program)
+    else {
+        return err!(ErrorCode::ClaimBadgeMissing);
+    };
+    let state_seeds = &[STATE_SEED, &[state_bump]];
+    token_2022::mint_to(
+        CpiContext::new_with_signer(
+            token_2022_program.to_account_info(),
+            token_2022::MintTo {
+                mint: badge_mint.to_account_info(),
+                to: badge_account.to_account_info(),
+                authority: state.to_account_info(),
+            },
+            &[state_seeds],
+        ),
+        1,
+    )
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitClaimBadge<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: created and initialized here as a non-transferable Token-2022 mint
+    #[account(mut, seeds = [BADGE_MINT_SEED, state.key().as_ref()], bump)]
+    pub badge_mint: UncheckedAccount<'info>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct ClaimBadgeEnabled {
+    pub badge_mint: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,174 @@
+//! The steps every claim path shares.
+//!
+//! Claim paths differ in how they prove and record a leaf and in where the
+//! tokens go, but not in what surrounds that: the holding gate, the hourly
+//! stats, the wallet cap and the family check on the way in; the operator
+//! fee, late penalty and early bonus, the vault failover, the payout and the
+//! badge on the way out. `admit_claim` runs the first half around a path's
+//! own recording step and `settle_claim` the second, and `process_claim` runs
+//! both. Paths that settle on their own terms (vesting, epochs) still pay
+//! through `pay_out`, so no step can be left out of one path alone. Events
+//! stay with each path, which emits its own.
+
+use super::*;
+
+// Accounts a claim is admitted against
+pub(crate) struct ClaimChecks<'a, 'info> {
+    pub claimant: &'a Pubkey, // the gate, cap and family apply to it; a domain's owner
+    pub stats: Option<&'a mut Account<'info, ClaimStats>>,
+    pub wallet_claims: Option<&'a mut Account<'info, WalletClaims>>,
+    pub family_claim: Option<&'a FamilyClaim>,
+    pub gate_token_account: Option<&'a TokenAccount>,
+    pub gate_metadata: Option<&'a UncheckedAccount<'info>>,
+}
+
+// Accounts a claim is paid through
+pub(crate) struct ClaimPayout<'a, 'info> {
+    pub state_bump: u8,
+    pub vault_auth_bump: u8,
+    pub vault_auth: &'a SystemAccount<'info>,
+    pub vault: &'a Account<'info, TokenAccount>,
+    pub extra_vaults: &'info [AccountInfo<'info>], // registered extras, in order
+    pub mint: &'a Account<'info, Mint>,
+    pub token_program: &'a Program<'info, Token>,
+    pub recipient: &'a Account<'info, TokenAccount>, // gets the net amount and the bonus
+    pub fee_account: Option<&'a Account<'info, TokenAccount>>,
+    pub bonus_vault: Option<&'a Account<'info, TokenAccount>>,
+    pub relayer: Option<(&'a Account<'info, TokenAccount>, u64)>, // token account and cut
+    pub badge_mint: Option<&'a UncheckedAccount<'info>>,
+    pub badge_account: Option<&'a InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<&'a Program<'info, Token2022>>,
+}
+
+// How a settled claim was split and paid
+pub(crate) struct Settled<'info> {
+    pub amount: u64, // gross, in tokens
+    pub fee: u64,
+    pub penalty: u64,
+    pub bonus: u64,
+    pub net_amount: u64, // to the recipient, after any relayer's cut
+    pub vault: Account<'info, TokenAccount>, // the vault that paid
+    pub low_water: Option<u64>, // vault balance, if the claim tripped the low-water mark
+}
+
+// Gate, record, stats, wallet cap, family. `record` proves and records the
+// leaf and returns the claim's amount in tokens.
+pub(crate) fn admit_claim(
+    state: &mut Account<State>,
+    checks: ClaimChecks,
+    now: i64,
+    record: impl FnOnce(&mut State) -> Result<u64>,
+) -> Result<u64> {
+    check_claim_gate(
+        state,
+        checks.claimant,
+        checks.gate_token_account,
+        checks.gate_metadata,
+    )?;
+    let amount = record(&mut **state)?;
+    record_stats(state, checks.stats, now, amount)?;
+    record_wallet_claim(state, checks.wallet_claims, checks.claimant, amount)?;
+    check_family_claim(state, checks.family_claim, checks.claimant)?;
+    Ok(amount)
+}
+
+// Fee, penalty and bonus, judged at `claim_ts`, then the vault, payout,
+// badge and low-water mark
+pub(crate) fn settle_claim<'info>(
+    state: &mut Account<'info, State>,
+    payout: &ClaimPayout<'_, 'info>,
+    amount: u64,
+    claim_ts: i64,
+) -> Result<Settled<'info>> {
+    let fee = take_operator_fee(state, amount)?;
+    let penalty = take_late_penalty(state, amount, fee, claim_ts)?;
+    let relayer_fee = payout.relayer.map_or(0, |(_, relayer_fee)| relayer_fee);
+    let net_amount = (amount - fee - penalty)
+        .checked_sub(relayer_fee)
+        .ok_or(ErrorCode::RelayerFeeTooHigh)?;
+    let bonus = take_early_bonus(state, payout.bonus_vault, amount, claim_ts)?;
+    let vault = select_vault(state, payout.vault, payout.extra_vaults, amount)?;
+    if let Some((relayer_account, relayer_fee)) = payout.relayer.filter(|(_, cut)| *cut > 0) {
+        pay_claim(
+            state,
+            payout.vault_auth_bump,
+            payout.token_program,
+            payout.mint,
+            &vault,
+            payout.vault_auth,
+            relayer_account,
+            relayer_fee,
+        )?;
+    }
+    pay_out(state, payout, &vault, net_amount, bonus, fee + penalty)?;
+    let low_water = trip_low_water_mark(state, vault.amount, amount);
+    Ok(Settled {
+        amount,
+        fee,
+        penalty,
+        bonus,
+        net_amount,
+        vault,
+        low_water,
+    })
+}
+
+// `admit_claim`, then `settle_claim` at the time of the claim
+pub(crate) fn process_claim<'info>(
+    state: &mut Account<'info, State>,
+    checks: ClaimChecks<'_, 'info>,
+    payout: &ClaimPayout<'_, 'info>,
+    now: i64,
+    record: impl FnOnce(&mut State) -> Result<u64>,
+) -> Result<Settled<'info>> {
+    let amount = admit_claim(state, checks, now, record)?;
+    settle_claim(state, payout, amount, now)
+}
+
+// Pays `net_amount` and `bonus` to the recipient and `treasury_cut` to the
+// fee account out of `vault`, then mints the claimant's badge
+pub(crate) fn pay_out<'info>(
+    state: &Account<'info, State>,
+    payout: &ClaimPayout<'_, 'info>,
+    vault: &Account<'info, TokenAccount>,
+    net_amount: u64,
+    bonus: u64,
+    treasury_cut: u64,
+) -> Result<()> {
+    pay_claim(
+        state,
+        payout.vault_auth_bump,
+        payout.token_program,
+        payout.mint,
+        vault,
+        payout.vault_auth,
+        payout.recipient,
+        net_amount,
+    )?;
+    pay_early_bonus(
+        state,
+        payout.state_bump,
+        payout.token_program,
+        payout.mint,
+        payout.bonus_vault,
+        payout.recipient,
+        bonus,
+    )?;
+    pay_operator_fee(
+        state,
+        payout.vault_auth_bump,
+        payout.token_program,
+        payout.mint,
+        vault,
+        payout.vault_auth,
+        payout.fee_account,
+        treasury_cut,
+    )?;
+    mint_claim_badge(
+        state,
+        payout.state_bump,
+        payout.badge_mint,
+        payout.badge_account,
+        payout.token_2022_program,
+    )
+}
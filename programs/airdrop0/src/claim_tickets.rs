@@ -0,0 +1,277 @@
+//! Commit-reveal claim tickets for fair launches.
+//!
+//! Bots race to claim limited early-bonus pools in the window's opening
+//! block. With `reveal_delay` set, a claim takes two steps. First the
+//! claimant posts `merkle::claim_commitment(index, wallet, amount, salt)`
+//! with `commit_claim`, which costs only a small `["ticket", state, wallet]`
+//! account. At least `reveal_delay` slots later, `claim_ticketed` reveals
+//! the salt and claims. Tickets can be committed before the window opens,
+//! so a claimant who commits early is on equal footing with a bot at the
+//! opening slot. The ticket's rent goes back to the wallet on claim.
+//! Committing again replaces the ticket and restarts its delay. Every other
+//! claim path is refused while ticketing is on.
+
+use super::*;
+
+/// Zero turns ticketing off, and claims go through `claim` again.
+pub fn set_reveal_delay(ctx: Context<SetRevealDelay>, reveal_delay: u64) -> Result<()> {
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        require!(reveal_delay <= MAX_REVEAL_DELAY, ErrorCode::InvalidRevealDelay);
+        state.reveal_delay = reveal_delay;
+        emit_cpi!(RevealDelayUpdated {
+            reveal_delay,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+pub fn commit_claim(ctx: Context<CommitClaim>, commitment: [u8; 32]) -> Result<()> {
+    let clock = Clock::get()?;
+    let state = &ctx.accounts.state;
+    require!(state.reveal_delay > 0, ErrorCode::TicketingDisabled);
+    let ticket = &mut ctx.accounts.ticket;
+    ticket.state = state.key();
+    ticket.wallet = ctx.accounts.wallet.key();
+    ticket.round = state.round;
+    ticket.commitment = commitment;
+    ticket.committed_slot = clock.slot;
+    ticket.bump = ctx.bumps.ticket;
+    emit_cpi!(ClaimCommitted {
+        wallet: ticket.wallet,
+        commitment,
+        reveal_slot: clock.slot.saturating_add(state.reveal_delay),
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// `claim`, opening the wallet's ticket: `salt` must reproduce its
+/// commitment, and `reveal_delay` slots must have passed since it was made.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_ticketed<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimTicketed<'info>>,
+    index: u64,
+    amount: u64,
+    salt: [u8; 32],
+    proof: [[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let state = &ctx.accounts.state;
+    let ticket = &ctx.accounts.ticket;
+    require!(state.reveal_delay > 0, ErrorCode::TicketingDisabled);
+    require!(ticket.round == state.round, ErrorCode::InvalidTicket);
+    require!(
+        clock.slot >= ticket.committed_slot.saturating_add(state.reveal_delay),
+        ErrorCode::RevealTooEarly
+    );
+    let wallet = ctx.accounts.wallet.key();
+    require!(
+        ticket.commitment == merkle::claim_commitment(index, &wallet.to_bytes(), amount, &salt),
+        ErrorCode::InvalidTicket
+    );
+    require!(state.dispute_period == 0, ErrorCode::EscrowRequired);
+    let paid = process_claim(
+        &mut ctx.accounts.state,
+        ClaimChecks {
+            claimant: &wallet,
+            stats: ctx.accounts.stats.as_mut(),
+            wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+            family_claim: ctx.accounts.family_claim.as_deref(),
+            gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+            gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+        },
+        &ClaimPayout {
+            state_bump: ctx.bumps.state,
+            vault_auth_bump: ctx.bumps.vault_auth,
+            vault_auth: &ctx.accounts.vault_auth,
+            vault: &ctx.accounts.vault,
+            extra_vaults: ctx.remaining_accounts,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+            recipient: &ctx.accounts.user_ata,
+            fee_account: ctx.accounts.fee_account.as_ref(),
+            bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+            relayer: None,
+            badge_mint: ctx.accounts.badge_mint.as_ref(),
+            badge_account: ctx.accounts.badge_account.as_ref(),
+            token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+        },
+        now,
+        |state: &mut State| {
+            check_claim_route(state, ClaimRoute::Ticketed)?;
+            let leaf = keccak_leaf(state.leaf_encoding, index, &wallet, amount);
+            record_bitmap_claim(state, index, &leaf, amount, &proof, proof_len, terms_hash, now)?;
+            Ok(amount)
+        },
+    )?;
+    if let Some(vault_balance) = paid.low_water {
+        emit_cpi!(VaultBelowLowWaterMark {
+            vault_balance,
+            low_water_mark: ctx.accounts.state.low_water_mark,
+            timestamp: now,
+        });
+    }
+
+    emit_cpi!(Claimed {
+        wallet,
+        amount,
+        fee: paid.fee,
+        penalty: paid.penalty,
+        net_amount: paid.net_amount,
+        bonus: paid.bonus,
+        index,
+        terms_hash,
+        timestamp: now,
+        campaign: ctx.accounts.state.key(),
+        mint: ctx.accounts.state.mint,
+        root_version: ctx.accounts.state.root_version,
+        vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+    });
+    Ok(())
+}
+
+/// A wallet's pending claim commitment; seeds = ["ticket", state, wallet].
+#[account]
+pub struct ClaimTicket {
+    pub state: Pubkey,
+    pub wallet: Pubkey,
+    pub round: u32, // tickets from an earlier round don't open
+    pub commitment: [u8; 32], // `merkle::claim_commitment` of the claim
+    pub committed_slot: u64,
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRevealDelay<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CommitClaim<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    /// Pays the ticket's rent, refunded by `claim_ticketed`
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [TICKET_SEED, state.key().as_ref(), wallet.key().as_ref()],
+        bump,
+        payer = wallet,
+        space = TICKET_SPACE
+    )]
+    pub ticket: Account<'info, ClaimTicket>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimTicketed<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// Receives the ticket's rent back
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_SEED, state.key().as_ref(), wallet.key().as_ref()],
+        bump = ticket.bump,
+        close = wallet
+    )]
+    pub ticket: Account<'info, ClaimTicket>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    // Writable so mint-on-claim campaigns can mint_to
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    /// Wallet's holding of the gating mint (or of the collection NFT); only for gated campaigns
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event]
+pub struct RevealDelayUpdated {
+    pub reveal_delay: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimCommitted {
+    pub wallet: Pubkey,
+    pub commitment: [u8; 32],
+    pub reveal_slot: u64, // first slot `claim_ticketed` accepts the ticket
+    pub timestamp: i64,
+}
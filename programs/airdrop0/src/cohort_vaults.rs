@@ -0,0 +1,217 @@
+//! Per-cohort vaults: one campaign, separately funded categories.
+//!
+//! Cohorts already give team, investor and community leaves their own
+//! window and vesting, but by default they all draw on the campaign vault.
+//! `set_cohort_vault` gives a cohort a token account of its own, owned by
+//! `vault_auth`, and a `clawback_ts`. `claim_cohort` then pays the cohort's
+//! leaves from that account only: the unlocked share goes to the claimant
+//! and the operator fee, and the still-locked rest moves into the campaign
+//! vault, where `release_vested` pays it from as usual. Once the cohort's
+//! window has ended and its `clawback_ts` has passed, the authority can
+//! `clawback_cohort` whatever the cohort's vault still holds, without
+//! touching any other category's funds. A cohort vault's balance doesn't
+//! count toward the campaign's `Funded` check. Like the failover vaults,
+//! cohort vaults belong to the current `vault_auth`, so rotation and
+//! rollover wait until they have been clawed back.
+
+use super::*;
+
+/// A cohort with its own vault; first `cohort_vault_count` entries of
+/// `state.cohort_vaults` are live.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct CohortVault {
+    pub cohort_id: u32,
+    pub vault: Pubkey,
+    pub clawback_ts: i64, // zero = never clawed back
+}
+
+/// Gives cohort `cohort_id` its own vault, or replaces it. `clawback_ts`
+/// can't fall inside the cohort's current window.
+pub fn set_cohort_vault(
+    ctx: Context<SetCohortVault>,
+    cohort_id: u32,
+    clawback_ts: i64,
+) -> Result<()> {
+    track_config!(ctx, {
+        let vault = ctx.accounts.vault.key();
+        let cohort = &ctx.accounts.cohort;
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        // Minted claims have no balance to keep apart
+        require!(!state.mint_on_claim, ErrorCode::InvalidCohortVault);
+        require!(
+            clawback_ts == 0 || clawback_ts > claim_end_ts(cohort.claim_start_ts, cohort.claim_duration)?,
+            ErrorCode::InvalidCohortVault
+        );
+        let count = state.cohort_vault_count as usize;
+        let taken = vault == state.vault
+            || state.extra_vaults[..state.extra_vault_count as usize].contains(&vault)
+            || state.cohort_vaults[..count]
+                .iter()
+                .any(|entry| entry.vault == vault && entry.cohort_id != cohort_id);
+        require!(!taken, ErrorCode::InvalidVault);
+        let entry = CohortVault {
+            cohort_id,
+            vault,
+            clawback_ts,
+        };
+        match state.cohort_vaults[..count].iter().position(|e| e.cohort_id == cohort_id) {
+            Some(pos) => state.cohort_vaults[pos] = entry,
+            None => {
+                require!(count < MAX_COHORT_VAULTS, ErrorCode::VaultSetFull);
+                state.cohort_vaults[count] = entry;
+                state.cohort_vault_count += 1;
+            }
+        }
+        emit_cpi!(CohortVaultUpdated {
+            cohort_id,
+            vault,
+            clawback_ts,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+/// Sweeps cohort `cohort_id`'s vault into `destination` once its window has
+/// ended and its `clawback_ts` has passed; the cohort then has no vault of
+/// its own.
+pub fn clawback_cohort(ctx: Context<ClawbackCohort>, cohort_id: u32) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    track_config!(ctx, {
+        let state = &ctx.accounts.state;
+        let count = state.cohort_vault_count as usize;
+        let pos = state.cohort_vaults[..count]
+            .iter()
+            .position(|e| e.cohort_id == cohort_id)
+            .ok_or(ErrorCode::InvalidCohortVault)?;
+        let entry = state.cohort_vaults[pos];
+        require_keys_eq!(ctx.accounts.cohort_vault.key(), entry.vault, ErrorCode::InvalidVault);
+        let cohort = &ctx.accounts.cohort;
+        // The window is checked too, since `set_cohort` may have moved it since
+        require!(
+            entry.clawback_ts != 0
+                && now >= entry.clawback_ts
+                && now > claim_end_ts(cohort.claim_start_ts, cohort.claim_duration)?,
+            ErrorCode::ClawbackNotDue
+        );
+
+        let amount = ctx.accounts.cohort_vault.amount;
+        pay_claim(
+            &ctx.accounts.state,
+            ctx.bumps.vault_auth,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.cohort_vault,
+            &ctx.accounts.vault_auth,
+            &ctx.accounts.destination,
+            amount,
+        )?;
+
+        let state = &mut ctx.accounts.state;
+        state.cohort_vaults.copy_within(pos + 1..count, pos);
+        state.cohort_vaults[count - 1] = CohortVault::default();
+        state.cohort_vault_count -= 1;
+        emit_cpi!(CohortClawedBack {
+            cohort_id,
+            vault: entry.vault,
+            destination: ctx.accounts.destination.key(),
+            amount,
+            timestamp: now,
+        });
+    })
+}
+
+// The vault cohort `cohort_id` pays from: its own while it has one, which
+// must then be the one passed in, else the campaign vault
+pub(crate) fn cohort_source_vault<'a, 'info>(
+    state: &State,
+    cohort_id: u32,
+    vault: &'a Account<'info, TokenAccount>,
+    cohort_vault: Option<&'a Account<'info, TokenAccount>>,
+) -> Result<&'a Account<'info, TokenAccount>> {
+    let registered = state.cohort_vaults[..state.cohort_vault_count as usize]
+        .iter()
+        .find(|e| e.cohort_id == cohort_id);
+    match (registered, cohort_vault) {
+        (None, _) => Ok(vault),
+        (Some(entry), Some(source)) if source.key() == entry.vault => Ok(source),
+        _ => err!(ErrorCode::InvalidCohortVault),
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(cohort_id: u32)]
+pub struct SetCohortVault<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [COHORT_SEED, state.key().as_ref(), &cohort_id.to_le_bytes()],
+        bump = cohort.bump
+    )]
+    pub cohort: Account<'info, Cohort>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        constraint = vault.mint == state.mint @ ErrorCode::InvalidMint,
+        constraint = vault.owner == vault_auth.key() @ ErrorCode::InvalidVault
+    )]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(cohort_id: u32)]
+pub struct ClawbackCohort<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [COHORT_SEED, state.key().as_ref(), &cohort_id.to_le_bytes()],
+        bump = cohort.bump
+    )]
+    pub cohort: Account<'info, Cohort>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(mut, token::mint = mint, token::authority = vault_auth)]
+    pub cohort_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = destination.mint == mint.key() @ ErrorCode::WrongMint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct CohortVaultUpdated {
+    pub cohort_id: u32,
+    pub vault: Pubkey,
+    pub clawback_ts: i64, // zero = never clawed back
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CohortClawedBack {
+    pub cohort_id: u32,
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
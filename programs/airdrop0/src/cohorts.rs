@@ -0,0 +1,363 @@
+//! Cohorts: several claim windows and vesting schedules under one root.
+//!
+//! Legally distinct groups (team, investors, community) often need their own
+//! timing. Rather than a tree per group, a leaf can name a `cohort_id`. The
+//! authority describes each cohort in a `["cohort", state, cohort_id]`
+//! account with its own window and vesting schedule. `claim_cohort` checks
+//! the cohort's window instead of the campaign's and opens the same vesting
+//! account `claim_vested` does, on the cohort's schedule from the cohort's
+//! start. Campaign-wide switches (close, refill, terms, freeze, caps) still
+//! apply. Cohort leaves use the native encoding. A cohort can also have a
+//! vault of its own; see `cohort_vaults`.
+
+use super::*;
+
+/// Creates or replaces cohort `cohort_id`. Vesting accounts keep the
+/// schedule they were opened with, so a change only affects later claims.
+pub fn set_cohort(
+    ctx: Context<SetCohort>,
+    cohort_id: u32,
+    claim_start_ts: i64,
+    claim_duration: i64,
+    schedule: VestingSchedule,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(!ctx.accounts.state.config_locked, ErrorCode::ConfigLocked);
+    check_claim_window(claim_start_ts, claim_duration, now, max_start_lead(&ctx.accounts.state))?;
+    require!(
+        schedule.cliff >= 0 && schedule.duration >= 0 && schedule.tge_bps <= 10_000,
+        ErrorCode::InvalidVestingSchedule
+    );
+    track_config!(ctx, {
+        let cohort = &mut ctx.accounts.cohort;
+        cohort.state = ctx.accounts.state.key();
+        cohort.cohort_id = cohort_id;
+        cohort.claim_start_ts = claim_start_ts;
+        cohort.claim_duration = claim_duration;
+        cohort.schedule = schedule;
+        cohort.bump = ctx.bumps.cohort;
+        let updated = CohortUpdated {
+            cohort_id,
+            claim_start_ts,
+            claim_duration,
+            cliff: schedule.cliff,
+            duration: schedule.duration,
+            tge_bps: schedule.tge_bps,
+            timestamp: now,
+        };
+        chain_config(&mut ctx.accounts.state.cohorts_hash, &updated)?;
+        emit_cpi!(updated);
+    })
+}
+
+/// `claim_vested` for a leaf of cohort `cohort_id`: the cohort supplies the
+/// window and the schedule.
+pub fn claim_cohort<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimCohort<'info>>,
+    index: u64,
+    amount: u64,
+    cohort_id: u32,
+    proof: [[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+    let wallet = ctx.accounts.wallet.key;
+    let cohort = &ctx.accounts.cohort;
+    admit_claim(
+        &mut ctx.accounts.state,
+        ClaimChecks {
+            claimant: wallet,
+            stats: ctx.accounts.stats.as_mut(),
+            wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+            family_claim: ctx.accounts.family_claim.as_deref(),
+            gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+            gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+        },
+        now,
+        |state: &mut State| {
+            let leaf = merkle::cohort_leaf(index, &wallet.to_bytes(), amount, cohort_id);
+            record_cohort_claim(state, cohort, index, &leaf, amount, &proof, proof_len, terms_hash, now)?;
+            Ok(amount)
+        },
+    )?;
+    // As in `claim_vested`, the late haircut comes off the whole allocation
+    let penalty = take_late_penalty(&mut ctx.accounts.state, amount, 0, now)?;
+    let state = &mut ctx.accounts.state;
+    state.vesting_locked = state.vesting_locked
+        .checked_add(amount - penalty)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    let vesting = &mut ctx.accounts.vesting;
+    vesting.wallet = ctx.accounts.wallet.key();
+    vesting.index = index;
+    vesting.amount = amount - penalty;
+    vesting.released = 0;
+    vesting.start_ts = cohort.claim_start_ts;
+    vesting.schedule = cohort.schedule;
+    vesting.bump = ctx.bumps.vesting;
+
+    emit_cpi!(VestingStarted {
+        wallet: vesting.wallet,
+        index,
+        amount: vesting.amount,
+        penalty,
+        start_ts: vesting.start_ts,
+        cliff: vesting.schedule.cliff,
+        duration: vesting.schedule.duration,
+        tge_bps: vesting.schedule.tge_bps,
+        terms_hash,
+        timestamp: now,
+    });
+    emit_cpi!(CohortClaimed {
+        wallet: vesting.wallet,
+        index,
+        cohort_id,
+        timestamp: now,
+    });
+
+    let due = take_vested(&mut ctx.accounts.state, &mut ctx.accounts.vesting, now)?;
+    let fee = take_operator_fee(&mut ctx.accounts.state, due)?;
+    let cohort_source = cohort_source_vault(
+        &ctx.accounts.state,
+        cohort_id,
+        &ctx.accounts.vault,
+        ctx.accounts.cohort_vault.as_ref(),
+    )?;
+    // A cohort's own vault pays alone; the campaign vault fails over to extras
+    let from_cohort_vault = cohort_source.key() != ctx.accounts.vault.key();
+    let source = if from_cohort_vault {
+        cohort_source.clone()
+    } else {
+        select_vault(&ctx.accounts.state, &ctx.accounts.vault, ctx.remaining_accounts, due + penalty)?
+    };
+    pay_out(
+        &ctx.accounts.state,
+        &ClaimPayout {
+            state_bump: ctx.bumps.state,
+            vault_auth_bump: ctx.bumps.vault_auth,
+            vault_auth: &ctx.accounts.vault_auth,
+            vault: &ctx.accounts.vault,
+            extra_vaults: ctx.remaining_accounts,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+            recipient: &ctx.accounts.user_ata,
+            fee_account: ctx.accounts.fee_account.as_ref(),
+            bonus_vault: None,
+            relayer: None,
+            badge_mint: ctx.accounts.badge_mint.as_ref(),
+            badge_account: ctx.accounts.badge_account.as_ref(),
+            token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+        },
+        &source,
+        due - fee,
+        0,
+        fee + penalty,
+    )?;
+    if from_cohort_vault {
+        // `release_vested` pays the locked rest from the campaign vault
+        pay_claim(
+            &ctx.accounts.state,
+            ctx.bumps.vault_auth,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &source,
+            &ctx.accounts.vault_auth,
+            &ctx.accounts.vault,
+            amount - penalty - due,
+        )?;
+    } else if let Some(vault_balance) =
+        trip_low_water_mark(&mut ctx.accounts.state, source.amount, due + penalty)
+    {
+        emit_cpi!(VaultBelowLowWaterMark {
+            vault_balance,
+            low_water_mark: ctx.accounts.state.low_water_mark,
+            timestamp: now,
+        });
+    }
+
+    let vesting = &ctx.accounts.vesting;
+    emit_cpi!(VestedReleased {
+        wallet: vesting.wallet,
+        index,
+        amount: due,
+        fee,
+        released: vesting.released,
+        timestamp: now,
+        campaign: ctx.accounts.state.key(),
+        mint: ctx.accounts.state.mint,
+        root_version: ctx.accounts.state.root_version,
+        vault_balance: vault_balance_after(&ctx.accounts.state, &source, due + penalty),
+    });
+    if vesting.released == vesting.amount {
+        ctx.accounts.vesting.close(ctx.accounts.wallet.to_account_info())?;
+    }
+    Ok(())
+}
+
+// `record_leaf_claim` with the cohort's window in place of the campaign's
+#[allow(clippy::too_many_arguments)]
+fn record_cohort_claim(
+    state: &mut State,
+    cohort: &Cohort,
+    index: u64,
+    leaf: &[u8; 32],
+    amount: u64,
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<()> {
+    check_claim_route(state, ClaimRoute::Direct)?;
+    require!(state.claim_ledger == Pubkey::default(), ErrorCode::CompressedLedgerRequired);
+    let window = (cohort.claim_start_ts, cohort.claim_duration);
+    check_leaf_claim_in(state, window, index, leaf, proof, proof_len, terms_hash, now)?;
+    mark_claimed(state, index)?;
+    count_claim(state, amount)
+}
+
+/// One cohort's timing; seeds = ["cohort", state, cohort_id (u32 LE)].
+#[account]
+pub struct Cohort {
+    pub state: Pubkey,
+    pub cohort_id: u32,
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub schedule: VestingSchedule, // runs from `claim_start_ts`
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(cohort_id: u32)]
+pub struct SetCohort<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [COHORT_SEED, state.key().as_ref(), &cohort_id.to_le_bytes()],
+        bump,
+        payer = authority,
+        space = COHORT_SPACE
+    )]
+    pub cohort: Account<'info, Cohort>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64, amount: u64, cohort_id: u32)]
+pub struct ClaimCohort<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(
+        seeds = [COHORT_SEED, state.key().as_ref(), &cohort_id.to_le_bytes()],
+        bump = cohort.bump
+    )]
+    pub cohort: Account<'info, Cohort>,
+
+    /// Pays the vesting account rent, refunded once fully released
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [VESTING_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()],
+        bump,
+        payer = wallet,
+        space = VESTING_SPACE
+    )]
+    pub vesting: Account<'info, VestingAccount>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// The cohort's own vault; required while it has one
+    #[account(mut)]
+    pub cohort_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event]
+pub struct CohortUpdated {
+    pub cohort_id: u32,
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub cliff: i64,
+    pub duration: i64,
+    pub tge_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CohortClaimed {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub cohort_id: u32,
+    pub timestamp: i64,
+}
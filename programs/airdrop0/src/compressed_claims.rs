@@ -0,0 +1,318 @@
+//! Compressed claim ledger for campaigns of many millions of entries.
+//!
+//! Instead of the claim bitmap, each claim replaces the empty leaf at its
+//! index in an spl-account-compression concurrent Merkle tree, and the
+//! replacement fails if the leaf was already claimed. The tree's rent depends
+//! only on its depth, buffer and canopy, not on the number of claims; in
+//! exchange, claimants fetch the ledger proof from an indexer that replays the
+//! noop logs.
+
+use super::*;
+use spl_account_compression::{
+    cpi::{
+        accounts::{Initialize, Modify, VerifyLeaf},
+        init_empty_merkle_tree, replace_leaf, verify_leaf,
+    },
+    program::SplAccountCompression,
+    Noop,
+};
+
+// Leaf written over the empty (all-zero) leaf at `index` once it is claimed
+fn claimed_leaf(index: u64, beneficiary: &Pubkey, amount: u64) -> [u8; 32] {
+    merkle::hashv(&[
+        b"claimed".as_ref(),
+        &index.to_le_bytes(),
+        beneficiary.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+}
+
+pub fn init_compressed_ledger(
+    ctx: Context<InitCompressedLedger>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    track_config!(ctx, {
+        let state = &ctx.accounts.state;
+        require!(state.claimed_count == 0, ErrorCode::ClaimsAlreadyRecorded);
+        require!(
+            max_depth < 64 && state.total_claims <= 1u64 << max_depth,
+            ErrorCode::CapacityExceeded
+        );
+
+        let state_key = state.key();
+        let ledger_seeds = &[LEDGER_AUTHORITY_SEED, state_key.as_ref(), &[ctx.bumps.ledger_authority]];
+        let signer_seeds: &[&[&[u8]]] = &[ledger_seeds];
+        init_empty_merkle_tree(
+            CpiContext::new_with_signer(
+                ctx.accounts.compression_program.to_account_info(),
+                Initialize {
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    authority:   ctx.accounts.ledger_authority.to_account_info(),
+                    noop:        ctx.accounts.noop_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            max_depth,
+            max_buffer_size,
+        )?;
+
+        ctx.accounts.state.claim_ledger = ctx.accounts.merkle_tree.key();
+        emit_cpi!(CompressedLedgerInitialized {
+            merkle_tree: ctx.accounts.merkle_tree.key(),
+            max_depth,
+            max_buffer_size,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+/// `claim` against the compressed ledger. `ledger_root` is a recent root of
+/// the tree and the proof of the (still empty) leaf at `index` is passed as
+/// `remaining_accounts`, minus the levels covered by the canopy, after every
+/// registered extra vault.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_compressed<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimCompressed<'info>>,
+    index: u64,
+    amount: u64,
+    proof: [[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    ledger_root: [u8; 32],
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+    let wallet = ctx.accounts.wallet.key;
+    let (extra_vaults, ledger_proof) = split_extra_vaults(&ctx.accounts.state, ctx.remaining_accounts)?;
+    let state_key = ctx.accounts.state.key();
+    let ledger_seeds = &[LEDGER_AUTHORITY_SEED, state_key.as_ref(), &[ctx.bumps.ledger_authority]];
+    let signer_seeds: &[&[&[u8]]] = &[ledger_seeds];
+    let paid = process_claim(
+        &mut ctx.accounts.state,
+        ClaimChecks {
+            claimant: wallet,
+            stats: ctx.accounts.stats.as_mut(),
+            wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+            family_claim: ctx.accounts.family_claim.as_deref(),
+            gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+            gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+        },
+        &ClaimPayout {
+            state_bump: ctx.bumps.state,
+            vault_auth_bump: ctx.bumps.vault_auth,
+            vault_auth: &ctx.accounts.vault_auth,
+            vault: &ctx.accounts.vault,
+            extra_vaults,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+            recipient: &ctx.accounts.user_ata,
+            fee_account: ctx.accounts.fee_account.as_ref(),
+            bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+            relayer: None,
+            badge_mint: ctx.accounts.badge_mint.as_ref(),
+            badge_account: ctx.accounts.badge_account.as_ref(),
+            token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+        },
+        now,
+        |state: &mut State| {
+            check_claim_route(state, ClaimRoute::Direct)?;
+            let leaf = keccak_leaf(state.leaf_encoding, index, wallet, amount);
+            check_leaf_claim(state, index, &leaf, &proof, proof_len, terms_hash, now)?;
+
+            // Replacing the empty leaf fails if `index` was already claimed
+            replace_leaf(
+                CpiContext::new_with_signer(
+                    ctx.accounts.compression_program.to_account_info(),
+                    Modify {
+                        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                        authority:   ctx.accounts.ledger_authority.to_account_info(),
+                        noop:        ctx.accounts.noop_program.to_account_info(),
+                    },
+                    signer_seeds,
+                )
+                .with_remaining_accounts(ledger_proof.to_vec()),
+                ledger_root,
+                [0; 32],
+                claimed_leaf(index, wallet, amount),
+                u32::try_from(index).map_err(|_| ErrorCode::InvalidIndex)?,
+            )?;
+            count_claim(state, amount)?;
+            Ok(amount)
+        },
+    )?;
+    if let Some(vault_balance) = paid.low_water {
+        emit_cpi!(VaultBelowLowWaterMark {
+            vault_balance,
+            low_water_mark: ctx.accounts.state.low_water_mark,
+            timestamp: now,
+        });
+    }
+
+    emit_cpi!(Claimed {
+        wallet: *ctx.accounts.wallet.key,
+        amount,
+        fee: paid.fee,
+        penalty: paid.penalty,
+        net_amount: paid.net_amount,
+        bonus: paid.bonus,
+        index,
+        terms_hash,
+        timestamp: now,
+        campaign: ctx.accounts.state.key(),
+        mint: ctx.accounts.state.mint,
+        root_version: ctx.accounts.state.root_version,
+        vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+    });
+    Ok(())
+}
+
+/// Succeeds only if `index` has been claimed by `wallet` for `amount`; the
+/// ledger proof is passed as `remaining_accounts`.
+pub fn verify_compressed_claim<'info>(
+    ctx: Context<'_, '_, 'info, 'info, VerifyCompressedClaim<'info>>,
+    index: u64,
+    wallet: Pubkey,
+    amount: u64,
+    ledger_root: [u8; 32],
+) -> Result<()> {
+    verify_leaf(
+        CpiContext::new(
+            ctx.accounts.compression_program.to_account_info(),
+            VerifyLeaf {
+                merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        ledger_root,
+        claimed_leaf(index, &wallet, amount),
+        u32::try_from(index).map_err(|_| ErrorCode::InvalidIndex)?,
+    )
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitCompressedLedger<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+
+    /// CHECK: zeroed tree account allocated by the authority and owned by the
+    /// compression program, which validates and initializes it
+    #[account(mut, owner = compression_program.key())]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that is the tree authority; signs the compression CPIs
+    #[account(seeds = [LEDGER_AUTHORITY_SEED, state.key().as_ref()], bump)]
+    pub ledger_authority: UncheckedAccount<'info>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub noop_program: Program<'info, Noop>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimCompressed<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated,
+        constraint = state.claim_ledger != Pubkey::default() @ ErrorCode::CompressedLedgerDisabled
+    )]
+    pub state: Account<'info, State>,
+
+    pub wallet: Signer<'info>,
+
+    /// CHECK: the campaign's concurrent Merkle tree; validated by the compression program
+    #[account(mut, address = state.claim_ledger @ ErrorCode::InvalidClaimLedger)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that is the tree authority; signs the compression CPIs
+    #[account(seeds = [LEDGER_AUTHORITY_SEED, state.key().as_ref()], bump)]
+    pub ledger_authority: UncheckedAccount<'info>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub noop_program: Program<'info, Noop>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyCompressedClaim<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    /// CHECK: the campaign's concurrent Merkle tree; validated by the compression program
+    #[account(address = state.claim_ledger @ ErrorCode::InvalidClaimLedger)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+}
+
+#[event]
+pub struct CompressedLedgerInitialized {
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub timestamp: i64,
+}
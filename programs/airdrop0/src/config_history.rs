@@ -0,0 +1,153 @@
+//! Tamper-evident history of campaign settings.
+//!
+//! Each admin instruction that can change the campaign's `State` settings
+//! takes a `ConfigSnapshot` before it runs. Afterwards it emits
+//! `ConfigChanged`, which lists every setting that changed with its old and
+//! new value; `track_config!` wraps a handler's body to do both. Replaying
+//! those events from `initialize` rebuilds the settings at any point in
+//! time. Values are Borsh-encoded the way `State` stores them, so a decoder
+//! only needs the field's type from the IDL. Counters, the claim bitmap and
+//! `status` are left out because claims and cranks move them, and they have
+//! events of their own. Cohorts and relayers live in accounts of their own;
+//! `State` keeps a hash chaining every change to them, and that hash's old
+//! and new value stand in for the change.
+
+use super::*;
+
+pub(crate) struct ConfigSnapshot {
+    authority: Pubkey,
+    fields: Vec<(&'static str, Vec<u8>)>,
+}
+
+impl ConfigSnapshot {
+    pub(crate) fn of(state: &State) -> Result<Self> {
+        Ok(Self {
+            authority: state.authority,
+            fields: config_fields(state)?,
+        })
+    }
+
+    /// `ConfigChanged` for the settings that differ from the snapshot; None
+    /// when the instruction left them all as they were.
+    pub(crate) fn changes(self, state: &State) -> Result<Option<ConfigChanged>> {
+        let changes: Vec<FieldChange> = self
+            .fields
+            .into_iter()
+            .zip(config_fields(state)?)
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((field, old), (_, new))| FieldChange {
+                field: field.to_string(),
+                old,
+                new,
+            })
+            .collect();
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(ConfigChanged {
+            authority: self.authority,
+            changes,
+            timestamp: Clock::get()?.unix_timestamp,
+        }))
+    }
+}
+
+// Every admin-settable `State` field, by name, Borsh-encoded
+fn config_fields(state: &State) -> Result<Vec<(&'static str, Vec<u8>)>> {
+    macro_rules! encode {
+        ($($field:ident),* $(,)?) => {
+            vec![$(
+                (stringify!($field), state.$field.try_to_vec().map_err(ProgramError::from)?),
+            )*]
+        };
+    }
+    Ok(encode!(
+        authority,
+        snapshot_hash,
+        claim_start_ts,
+        claim_duration,
+        claim_closed,
+        merkle_root,
+        total_claims,
+        claim_capacity,
+        mint,
+        vault,
+        mint_on_claim,
+        crank_bounty,
+        allocations_hash,
+        allocations_uri,
+        terms_hash,
+        gate_kind,
+        gate_key,
+        gate_min_amount,
+        total_allocation,
+        low_water_mark,
+        needs_refill,
+        fee_bps,
+        fee_account,
+        dispute_period,
+        frozen_indices,
+        frozen_count,
+        stats_enabled,
+        penalty_start_ts,
+        penalty_ramp,
+        penalty_bps,
+        bonus_vault,
+        bonus_end_ts,
+        bonus_bps,
+        claim_ledger,
+        config_locked,
+        extra_vaults,
+        extra_vault_count,
+        wormhole_emitter_chain,
+        wormhole_emitter,
+        leaf_encoding,
+        badge_mint,
+        wallet_cap,
+        relayer_fee,
+        usd_price_feed,
+        max_price_age,
+        max_price_conf_bps,
+        max_start_lead,
+        reveal_delay,
+        root_version,
+        close_grace,
+        dust_threshold,
+        community_pool,
+        prior_roots,
+        prior_root_count,
+        rounding,
+        family_id,
+        family_siblings,
+        family_sibling_count,
+        cohort_vaults,
+        cohort_vault_count,
+        vesting_program,
+        bonus_lottery,
+        scheduled_root_count,
+        cohorts_hash,
+        relayers_hash,
+    ))
+}
+
+// Folds `change` into `chain`, one of the `State` hashes standing in for
+// settings kept outside it
+pub(crate) fn chain_config(chain: &mut [u8; 32], change: &impl AnchorSerialize) -> Result<()> {
+    let data = change.try_to_vec().map_err(ProgramError::from)?;
+    *chain = anchor_lang::solana_program::keccak::hashv(&[&chain[..], &data]).to_bytes();
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String, // the `State` field's name
+    pub old: Vec<u8>, // Borsh encoding of the value before the instruction
+    pub new: Vec<u8>,
+}
+
+#[event]
+pub struct ConfigChanged {
+    pub authority: Pubkey, // the authority that made the change, as it was before it
+    pub changes: Vec<FieldChange>,
+    pub timestamp: i64,
+}
@@ -0,0 +1,70 @@
+//! Lamport bounties for permissionless cranks.
+//!
+//! Campaign hygiene shouldn't depend on the operator running keepers. The
+//! state account's lamports above its rent-exempt floor are a bounty pool:
+//! `initialize` seeds it with `crank_pool` lamports from the creator, and
+//! anyone can top it up with a plain transfer. Cranks that do one-off work
+//! pay their caller `crank_bounty` lamports from the pool, or whatever is
+//! left of it: `crank_close` for ending an elapsed window, and
+//! `emit_unclaimed` for exporting the next page of an ended round's
+//! unclaimed indices. Export pages are paid in order and once per round, so
+//! replaying a page earns nothing. `close_state` returns what's left of the
+//! pool with the rest of the state's rent.
+
+use super::*;
+
+/// Moves `lamports` from `payer` into the state account's bounty pool.
+pub(crate) fn fund_crank_pool<'info>(
+    payer: &Signer<'info>,
+    state: &Account<'info, State>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<()> {
+    if lamports == 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: state.to_account_info(),
+            },
+        ),
+        lamports,
+    )
+}
+
+// Pays `cranker` the campaign's bounty, or what the pool has left of it;
+// returns the lamports paid
+pub(crate) fn pay_crank_bounty<'info>(
+    state: &Account<'info, State>,
+    cranker: &AccountInfo<'info>,
+) -> Result<u64> {
+    // Bounty only ever comes out of lamports above the rent-exempt floor
+    let state_info = state.to_account_info();
+    let rent_floor = Rent::get()?.minimum_balance(state_info.data_len());
+    let bounty = state.crank_bounty.min(state_info.lamports().saturating_sub(rent_floor));
+    if bounty > 0 {
+        state_info.sub_lamports(bounty)?;
+        cranker.add_lamports(bounty)?;
+    }
+    Ok(bounty)
+}
+
+// Whether `[range_start, range_end)` is the next full page of an ended
+// round's export; if so, the export cursor moves past it
+pub(crate) fn take_export_page(
+    state: &mut State,
+    range_start: u64,
+    range_end: u64,
+    now: i64,
+) -> Result<bool> {
+    let ended = state.claim_closed || now > claim_end_ts(state.claim_start_ts, state.claim_duration)?;
+    let page_end = state.total_claims.min(range_start.saturating_add(MAX_UNCLAIMED_PAGE));
+    if !ended || range_start != state.unclaimed_export_cursor || range_end != page_end {
+        return Ok(false);
+    }
+    state.unclaimed_export_cursor = range_end;
+    Ok(true)
+}
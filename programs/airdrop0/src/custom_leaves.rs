@@ -0,0 +1,134 @@
+//! Claims against custom leaf formats.
+//!
+//! Some drops carry more in a leaf than `(index, wallet, amount)`: a lockup
+//! tier, a destination chain, a category. Rather than a new claim path per
+//! format, `claim_with_leaf` takes the leaf hash and a `LeafSchema`, which
+//! rebuilds the leaf from the claim's wallet and amount plus the extra
+//! fields. The two must agree, so the leaf can't be paid to another wallet
+//! or for another amount, and from there the claim takes `claim`'s own
+//! verification and payout. The program doesn't interpret the fields; it
+//! echoes them in `LeafClaimed` for off-chain consumers. Each schema is
+//! hashed so that no plain, vesting or cohort leaf rebuilds as one, which
+//! keeps those leaves to their own claim paths.
+
+use super::*;
+
+/// How `claim_with_leaf` rebuilds a leaf from the claim and fields the
+/// program doesn't interpret.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum LeafSchema {
+    /// `keccak("merkledrop-leaf" || index || wallet || amount || fields)`,
+    /// integers little-endian
+    Tagged { fields: Vec<u8> },
+    /// `keccak256(abi.encodePacked(uint256 index, bytes32 wallet, uint256 amount, fields))`,
+    /// matching a companion Solidity distributor; `fields` can't be empty
+    EvmPacked { fields: Vec<u8> },
+}
+
+impl LeafSchema {
+    fn leaf(&self, index: u64, wallet: &Pubkey, amount: u64) -> Result<[u8; 32]> {
+        match self {
+            LeafSchema::Tagged { fields } => {
+                require!(fields.len() <= MAX_LEAF_FIELDS_LEN, ErrorCode::InvalidLeafFields);
+                Ok(merkle::tagged_leaf(index, &wallet.to_bytes(), amount, fields))
+            }
+            LeafSchema::EvmPacked { fields } => {
+                require!(
+                    !fields.is_empty() && fields.len() <= MAX_LEAF_FIELDS_LEN,
+                    ErrorCode::InvalidLeafFields
+                );
+                Ok(merkle::evm_tagged_leaf(index, wallet.as_ref(), amount, fields))
+            }
+        }
+    }
+}
+
+/// `claim` for a leaf in a custom format: `schema` must rebuild `leaf`
+/// from the wallet and `amount`. USD-priced campaigns aren't supported.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_with_leaf<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+    index: u64,
+    leaf: [u8; 32],
+    amount: u64,
+    schema: LeafSchema,
+    proof: [[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let wallet = ctx.accounts.wallet.key();
+    require!(schema.leaf(index, &wallet, amount)? == leaf, ErrorCode::LeafMismatch);
+    require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+    let paid = process_claim(
+        &mut ctx.accounts.state,
+        ClaimChecks {
+            claimant: &wallet,
+            stats: ctx.accounts.stats.as_mut(),
+            wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+            family_claim: ctx.accounts.family_claim.as_deref(),
+            gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+            gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+        },
+        &ClaimPayout {
+            state_bump: ctx.bumps.state,
+            vault_auth_bump: ctx.bumps.vault_auth,
+            vault_auth: &ctx.accounts.vault_auth,
+            vault: &ctx.accounts.vault,
+            extra_vaults: ctx.remaining_accounts,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+            recipient: &ctx.accounts.user_ata,
+            fee_account: ctx.accounts.fee_account.as_ref(),
+            bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+            relayer: None,
+            badge_mint: ctx.accounts.badge_mint.as_ref(),
+            badge_account: ctx.accounts.badge_account.as_ref(),
+            token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+        },
+        now,
+        |state: &mut State| {
+            record_leaf_claim(state, index, &leaf, amount, &proof, proof_len, terms_hash, now)?;
+            Ok(amount)
+        },
+    )?;
+    if let Some(vault_balance) = paid.low_water {
+        emit_cpi!(VaultBelowLowWaterMark {
+            vault_balance,
+            low_water_mark: ctx.accounts.state.low_water_mark,
+            timestamp: now,
+        });
+    }
+
+    emit_cpi!(LeafClaimed {
+        wallet,
+        index,
+        leaf,
+        schema,
+    });
+    emit_cpi!(Claimed {
+        wallet,
+        amount,
+        fee: paid.fee,
+        penalty: paid.penalty,
+        net_amount: paid.net_amount,
+        bonus: paid.bonus,
+        index,
+        terms_hash,
+        timestamp: now,
+        campaign: ctx.accounts.state.key(),
+        mint: ctx.accounts.state.mint,
+        root_version: ctx.accounts.state.root_version,
+        vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+    });
+    Ok(())
+}
+
+/// The custom leaf behind a `Claimed` with the same index.
+#[event]
+pub struct LeafClaimed {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub leaf: [u8; 32],
+    pub schema: LeafSchema, // carries the leaf's extra fields
+}
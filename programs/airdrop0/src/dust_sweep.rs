@@ -0,0 +1,158 @@
+//! Dust threshold and aggregate sweeps to a community pool.
+//!
+//! Long-tail snapshots are full of leaves worth a fraction of a cent. A
+//! claim transaction for each one costs more than the leaf is worth, both
+//! in fees and in block space. With `dust_threshold` set, claims for less
+//! than it are refused, and `sweep_dust` settles such leaves in batches
+//! instead. Anyone can crank it. It proves each leaf against the root,
+//! records it in the claim bitmap and counters like any other claim, and
+//! pays the batch total to the campaign's `community_pool` in one transfer.
+//! Swept leaves therefore stay accounted for: they read as claimed, count
+//! toward `claimed_amount`, and can't be swept or claimed twice.
+
+use super::*;
+
+/// Zero turns the threshold off, and claims of any size go through `claim`
+/// again.
+pub fn set_dust_threshold(
+    ctx: Context<SetDustThreshold>,
+    dust_threshold: u64,
+    community_pool: Pubkey,
+) -> Result<()> {
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        // Dust with nowhere to go could never be paid out
+        require!(
+            dust_threshold == 0 || community_pool != Pubkey::default(),
+            ErrorCode::InvalidCommunityPool
+        );
+        state.dust_threshold = dust_threshold;
+        state.community_pool = community_pool;
+        emit_cpi!(DustThresholdUpdated {
+            dust_threshold,
+            community_pool,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+pub fn sweep_dust(ctx: Context<SweepDust>, leaves: Vec<DustLeaf>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !leaves.is_empty() && leaves.len() <= MAX_DUST_SWEEP,
+        ErrorCode::InvalidBatch
+    );
+    let state: &mut State = &mut ctx.accounts.state;
+    require!(state.dust_threshold > 0, ErrorCode::DustSweepDisabled);
+    check_claim_route(state, ClaimRoute::DustSweep)?;
+    require!(state.claim_ledger == Pubkey::default(), ErrorCode::CompressedLedgerRequired);
+
+    let mut indices = Vec::with_capacity(leaves.len());
+    let mut total_amount: u64 = 0;
+    for leaf in &leaves {
+        require!(leaf.amount < state.dust_threshold, ErrorCode::NotDust);
+        require!(leaf.proof.len() <= MAX_PROOF_DEPTH, ErrorCode::ProofTooLong);
+        let mut proof = [[0u8; 32]; MAX_PROOF_DEPTH];
+        proof[..leaf.proof.len()].copy_from_slice(&leaf.proof);
+        let hash = keccak_leaf(state.leaf_encoding, leaf.index, &leaf.wallet, leaf.amount);
+        let terms_hash = state.terms_hash;
+        check_leaf_claim(state, leaf.index, &hash, &proof, leaf.proof.len() as u8, terms_hash, now)?;
+        mark_claimed(state, leaf.index)?;
+        add_claimed(state, leaf.amount)?;
+        total_amount = total_amount
+            .checked_add(leaf.amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        indices.push(leaf.index);
+    }
+
+    pay_claim(
+        &ctx.accounts.state,
+        ctx.bumps.vault_auth,
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        &ctx.accounts.vault,
+        &ctx.accounts.vault_auth,
+        &ctx.accounts.community_pool,
+        total_amount,
+    )?;
+    emit_cpi!(DustSwept {
+        indices,
+        total_amount,
+        community_pool: ctx.accounts.community_pool.key(),
+        timestamp: now,
+    });
+    Ok(())
+}
+
+/// One leaf below the dust threshold, with its proof (at most
+/// `MAX_PROOF_LEN` nodes, unpadded so a batch fits in a transaction).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DustLeaf {
+    pub index: u64,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetDustThreshold<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = state.community_pool @ ErrorCode::InvalidCommunityPool,
+        token::mint = mint
+    )]
+    pub community_pool: Account<'info, TokenAccount>,
+
+    // Writable so mint-on-claim campaigns can mint_to
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct DustThresholdUpdated {
+    pub dust_threshold: u64,
+    pub community_pool: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DustSwept {
+    pub indices: Vec<u64>,
+    pub total_amount: u64,
+    pub community_pool: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,73 @@
+//! Claims recorded before `State::version`.
+//!
+//! The first layout recorded a claim as three residues of its index, modulo
+//! 971, 311 and 601, and read an index as claimed when all three were set.
+//! `migrate_state` rebuilds that as bitmap bits, but neither the bitmap for
+//! a large round nor the compute to fill it fits one instruction. The
+//! migrated bitmap covers the first `MAX_LEGACY_CONVERSION` leaves, and the
+//! residues move into a `["legacy_claims", state]` account. Each
+//! `resize_state` then sets the bits for the leaves it adds, and the account
+//! closes once the bitmap covers every leaf. Until then, claims past the
+//! bitmap fail and claim-status reports wait. Residues also match indices
+//! that never claimed, so the rebuilt bitmap blocks exactly the indices the
+//! old layout blocked.
+
+use super::*;
+
+// Residue moduli of the first layout, one per residue array
+const LEGACY_MODULI: [u64; 3] = [971, 311, 601];
+
+/// Leaves one instruction converts while a `LegacyClaims` account is open;
+/// a few dozen compute units each, within the default budget.
+pub const MAX_LEGACY_CONVERSION: u64 = 4_096;
+
+pub(crate) const LEGACY_CLAIMS_SPACE: usize = 8 + 122 + 39 + 76;
+
+/// Residues of the claims a migrated round recorded before `State::version`;
+/// seeds = ["legacy_claims", state].
+#[account]
+pub struct LegacyClaims {
+    pub claim_residues0: [u8; 122], // 971 bits
+    pub claim_residues1: [u8; 39],  // 311 bits
+    pub claim_residues2: [u8; 76],  // 601 bits
+}
+
+impl LegacyClaims {
+    pub(crate) fn of(legacy: &LegacyState) -> Self {
+        LegacyClaims {
+            claim_residues0: legacy.claim_residues0,
+            claim_residues1: legacy.claim_residues1,
+            claim_residues2: legacy.claim_residues2,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.residues().iter().all(|residues| residues.iter().all(|byte| *byte == 0))
+    }
+
+    fn residues(&self) -> [&[u8]; 3] {
+        [&self.claim_residues0, &self.claim_residues1, &self.claim_residues2]
+    }
+
+    // Sets the bit of every index in `from..to` whose three residues are
+    // set, stepping the residues along rather than dividing per index
+    pub(crate) fn convert(&self, bitmap: &mut [u8], from: u64, to: u64) {
+        let residues = self.residues();
+        let mut at = LEGACY_MODULI.map(|modulus| from % modulus);
+        for index in from..to {
+            if residues.iter().zip(at).all(|(bits, residue)| bit_set(bits, residue)) {
+                bitmap[(index / 8) as usize] |= 1 << (index % 8);
+            }
+            for (residue, modulus) in at.iter_mut().zip(LEGACY_MODULI) {
+                *residue = if *residue + 1 == modulus { 0 } else { *residue + 1 };
+            }
+        }
+    }
+}
+
+// Claim status past `claim_capacity` isn't known while a `LegacyClaims`
+// account still holds claims from before migration
+pub(crate) fn require_claims_converted(state: &State) -> Result<()> {
+    require!(!state.legacy_claims, ErrorCode::LeavesPastClaimBitmap);
+    Ok(())
+}
@@ -1,29 +1,132 @@
 /*!
     ─────────────────────────────────────────────────────────────
-    🌀 Arthimium Lab: RNS-Optimized Solana Airdrop Contract 🌀
+    🌀 Arthimium Lab: Merkle Bitmap Solana Airdrop Contract 🌀
     ─────────────────────────────────────────────────────────────
 
     ## Ultra-Light, Cost-Efficient, and Feature-Complete
 
-    This contract implements a scalable, Merkle-based airdrop with Residue Number System (RNS) claim tracking:
-      - Uses three compact residue arrays to uniquely map up to **1,000,000 claims** with near-zero collision risk.
-      - On-chain state footprint: a fraction of traditional bitmap solutions—**saving over 90% in rent costs** even at Solana scale.
+    This contract implements a scalable, Merkle-based airdrop with exact claim tracking:
+      - Records each claim as one bit of a bitmap in `State`, sized at initialize from the campaign's claim capacity (up to 65,536 claims) and grown with `resize_state` (up to 2^24).
+      - Campaigns can record claims in a compressed Merkle tree instead, whose rent doesn't grow with the number of claims.
 
     ## Feature Set
 
     - **Merkle Airdrop Core:**  
       Secure, privacy-friendly Merkle proof verification for each claim.
-    - **RNS-Based Double-Claim Prevention:**  
-      Compact mathematical residue tracking (Chinese Remainder Theorem style) replaces bitmaps, slashing cost and storage.
+    - **Bitmap Double-Claim Prevention:**  
+      One bit per index: a claim pays only if its bit is clear, and no other index shares it.
     - **One-PDA-Per-Claim Enforcement:**  
       Each claim spawns a unique record, blocking runtime double-inits.
+    - **Mint-on-Claim Mode (optional):**  
+      With `vault_auth` as mint authority, `claim` mints each allocation instead of drawing down a pre-funded vault.
+    - **Domain Claims:**  
+      `claim_domain` pays allocations keyed to a name-service domain to whoever owns the domain at claim time.
+    - **Leaf-Encoded Vesting:**  
+      `claim_vested` accepts leaves that commit to a `(cliff, duration, tge_bps)` schedule; `release_vested` pays the rest as it vests, and `get_vested_amount` reports what it would pay and when more unlocks.
+    - **Cohorts (optional):**  
+      Leaves naming a `cohort_id` claim through `claim_cohort` under that `Cohort` account's own window and vesting schedule, so one root serves several cohorts. With `set_cohort_vault`, a cohort is funded and clawed back apart from the others.
+    - **Vesting Program Handoff (optional):**  
+      With `set_vesting_handoff`, `claim_to_vesting` opens a stream or lock for the wallet in an external vesting program (Streamflow and the like) instead of paying it out.
+    - **Bonus Lottery (optional):**  
+      With `set_bonus_lottery`, an early-claim bonus reserve too small for every claimant is shared by a draw from each entry's slot hash, settled once the window ends, instead of going to whoever lands first.
+    - **wSOL Unwrap-on-Claim:**  
+      For wrapped-SOL campaigns, `claim_unwrapped` delivers native SOL instead of wSOL.
+    - **PDA Claimants:**  
+      Claimants need not be keypairs; a program can claim for its PDA by invoking `claim` with `invoke_signed`.
+    - **Compressed Claim Ledger (optional):**  
+      `init_compressed_ledger` records claims in an spl-account-compression tree (`claim_compressed`) instead of the bitmap, for campaigns whose bitmap rent would outweigh a tree's.
+    - **Cross-Chain Claims (optional):**  
+      With a Wormhole emitter set, a campaign is homed on another chain and `claim_from_vaa` pays allocations whose claim right was locked there.
+    - **EVM-Parity Leaves (optional):**  
+      `set_leaf_encoding` switches to `abi.encodePacked` leaves, so one tree serves a Solana deployment and a companion Solidity distributor.
+    - **Per-Wallet Claim Cap (optional):**  
+      `set_wallet_cap` bounds what one wallet claims per round across all of its leaves, tracked in a `["wallet_claims", state, wallet]` PDA.
+    - **Custom Leaf Formats:**  
+      `claim_with_leaf` takes a leaf hash and a `LeafSchema` that rebuilds it from the wallet, amount and extra fields, so leaves can carry lockups, chains or categories.
+    - **Campaign Families (optional):**  
+      `set_family` links sibling deployments; a wallet signs `join_family` to pick one, and its `["family_claim", family_id, wallet]` PDA bars it from the rest.
+    - **Soulbound Claim Badges (optional):**  
+      After `init_claim_badge`, every claim, escrow withdrawals and epoch claims included, also mints a non-transferable Token-2022 "I claimed" badge that other programs can token-gate on.
+    - **USD-Denominated Leaves (optional):**  
+      After `set_usd_pricing`, leaf amounts are micro-USD and `claim` pays them in tokens at a fresh, tight Pyth price.
+    - **Gasless Relayed Claims (optional):**  
+      `claim_relayed` lets a registered relayer pay for a claimant's transaction and take a fixed `relayer_fee` out of the allocation.
+    - **Commit-Reveal Tickets (optional):**  
+      With `set_reveal_delay`, claimants first post a salted commitment with `commit_claim` and claim with `claim_ticketed` some slots later, so bots gain nothing by racing the opening block.
+    - **Dust Sweeps (optional):**  
+      With `set_dust_threshold`, leaves below a minimum amount can't be claimed one by one; anyone can `sweep_dust` them to a community pool in batches, and they still count as claimed.
+    - **Attributed CPI Claims:**  
+      Quest and launchpad programs embed claiming with `claim_via_cpi`, which records the calling program in `CpiClaimed`.
+    - **Allocation Transparency:**  
+      Every root is committed alongside the URI and content hash of the allocation file it was built from.
+    - **Lifecycle Status:**  
+      Each round moves Draft → Funded → Live → Ended → Finalized. Claims need a funded round, `close_state` a finalized one, and every transition emits `StatusChanged`; `advance_status` is the permissionless crank.
+    - **Round Summaries:**  
+      After the window, anyone can run `finalize_airdrop` to keep the round's claims, distributed and unclaimed amounts and final root in a permanent `Summary` account.
+    - **Settings History:**  
+      Every admin change to the campaign's settings emits `ConfigChanged` with the old and new value of each field it touched.
+    - **Simulation-Friendly View:**  
+      `get_airdrop_info` returns root, window, counters, vault balance and flags as return data.
+    - **Snapshot Commitment Check:**  
+      `assert_snapshot_hash` fails unless `snapshot_hash` was derived from the root, leaf count and total allocation, tying the two commitments together.
+    - **Unclaimed Index Export:**  
+      `emit_unclaimed` pages through the round and emits the unclaimed indices of each range as a compact bitmap event.
+    - **Claim Status CPI:**  
+      `assert_claimed` / `assert_not_claimed` let other programs gate on an index's claim status through a CPI that simply succeeds or fails.
+    - **Claim Pre-Flight:**  
+      `validate_claim` runs every check of `claim` without moving tokens and returns the error code it would fail with.
+    - **Protocol Config (optional):**  
+      A program-wide `Config`, created by the upgrade authority with `init_config`, names a protocol admin and fee treasury and charges a lamport fee per campaign at `initialize`.
+    - **Verifiable Builds:**  
+      The upgrade authority records the git commit and toolchain of each deploy with `record_build_info`, so anyone can rebuild the exact source behind a live campaign.
     - **Admin Controls:**  
-      - `update_claim_window`: Adjust airdrop start and duration.
-      - `update_merkle_root`: Instantly update the Merkle root for new allocations.
-      - `close_airdrop`: Immediately halt new claims if needed.
+      - `update_claim_window`: Adjust airdrop start and duration (not in `immutable` builds).
+      - `set_max_start_lead`: Narrow how far ahead a window may start, catching millisecond timestamps.
+      - `set_campaign_metadata`: Publish name, symbol and URIs for explorers and claim UIs.
+      - `set_terms_hash`: Require claimants to accept a specific terms-of-service document.
+      - `set_dispute_period`: Switch to two-phase claims (`reserve_claim`, then `withdraw` after a dispute period).
+      - `set_late_penalty`: Haircut claims made after a cut-off (step or linear ramp), paid to the treasury.
+      - `init_bonus_vault` / `set_early_bonus`: Top up claims made before a cut-off from a separately funded bonus reserve.
+      - `set_bonus_lottery`: Share the bonus reserve by slot-hash draw instead of first come, first served.
+      - `init_treasury` / `withdraw_fees`: Program-owned treasury that collects fees; the authority sweeps it.
+      - `set_operator_fee`: Take a basis-point fee in kind from every claim into the treasury.
+      - `init_claim_badge`: Mint a soulbound receipt to every claimant from the first claim on.
+      - `init_claim_stats`: Track hourly claim counts and amounts on-chain for dashboards.
+      - `freeze_index` / `unfreeze_index`: Block a single allocation's claim or withdrawal during an incident.
+      - `set_dust_threshold`: Refuse claims below a minimum amount; `sweep_dust` pays such leaves to a community pool in batches.
+      - `void_index`: Permanently retire an allocation that should never have been in the snapshot, with a reason hash.
+      - `register_relayer` / `revoke_relayer` / `set_relayer_fee`: Run gasless claims through vetted relayers reimbursed in the airdropped token.
+      - `set_reveal_delay`: Require claims to be committed a number of slots before they are revealed.
+      - `set_wallet_cap`: Cap what one wallet can claim per round across all of its leaves.
+      - `set_low_water_mark` / `resume_claims`: Pause claims when the vault runs low and re-open them after a top-up.
+      - `add_funding_vault` / `remove_funding_vault`: Register failover vaults that `claim` draws from when the main vault runs short.
+      - `set_claim_gate`: Restrict claims to holders of a token or of an NFT from a verified collection.
+      - `update_merkle_root`: Replace the Merkle root for new allocations while claims are closed or paused (not in `immutable` builds).
+      - `append_allocations`: Add late-discovered wallets after the last leaf; published proofs stay valid (not in `immutable` builds).
+      - `schedule_root` / `clear_root_schedule`: Queue roots for later waves; the permissionless `activate_scheduled_root` switches to each on time (not in `immutable` builds).
+      - `set_rounding`: Choose floor, ceil or half-even rounding for every derived amount.
+      - `set_family`: Link regional campaigns so each wallet claims in only one of them.
+      - `set_vesting_handoff`: Route every claim into a lock in an external vesting program, from an instruction template.
+      - `set_leaf_encoding`: Choose native or EVM-parity leaf hashing for the current root (not in `immutable` builds).
+      - `set_cohort`: Give a cohort of leaves its own claim window and vesting schedule.
+      - `set_cohort_vault` / `clawback_cohort`: Fund a cohort from its own vault and reclaim what it leaves unclaimed after its clawback time.
+      - `set_usd_pricing`: Denominate leaves in USD against a Pyth feed, with staleness and confidence bounds (not in `immutable` builds).
+      - `lock_config`: Irreversibly freeze the Merkle root and claim window.
+      - `set_wormhole_emitter`: Home the campaign on another chain's distributor contract.
+      - `renounce_authority`: Give up every admin control for a provably hands-off campaign.
+      - `resize_state`: Grow or shrink the claim capacity (and rent) of the state account.
+      - `migrate_state`: Upgrade a state account written by an older program version in place.
+      - `close_airdrop`: Halt new claims, at once or after the `set_close_grace` period.
+      - `set_close_grace`: Let claims already in flight land for a few seconds after `close_airdrop`.
+      - `set_crank_bounty`: Lamports paid from the crank pool (state's excess balance) to whoever cranks the close or the unclaimed export.
+      - `distribute_batch`: Push tokens straight from the vault to a list of recipients (small drops), within the allocation and until `lock_config`.
       - `close_state`: Recover rent by closing the state post-drop.
+      - `finalize_and_rollover`: Archive a finished round and re-drop its unclaimed supply (not in `immutable` builds).
+      - `rotate_vault_authority`: Move the running round's vault (and mint authority) under a new snapshot hash.
+      - `create_epoch`: Open a recurring (weekly/monthly) distribution period claimed with `claim_epoch`.
+      - `request_raffle`: Commit to a Switchboard randomness account to raffle the unclaimed supply among claimants.
     - **Security-First:**  
-      Custom errors and strict on-chain validation. All math/proof logic has been reviewed for safety.
+      Custom errors and strict on-chain validation. Once the window elapses, anyone can `crank_close` the campaign. All math/proof logic has been reviewed for safety.
 
     ## Why This Matters
 
@@ -43,7 +146,91 @@
 */
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint, TransferChecked};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, CloseAccount, Token, TokenAccount, Mint, MintTo, SetAuthority, TransferChecked};
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface;
+use mpl_token_metadata::accounts::Metadata;
+use switchboard_on_demand::RandomnessAccountData;
+
+// `msg!` for devnet debugging, kept only in `verbose-logs` builds; elsewhere
+// the branch is dead and neither the formatting nor its compute is paid for
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "verbose-logs") {
+            msg!($($arg)*);
+        }
+    };
+}
+
+// An admin handler's body, followed by `ConfigChanged` for the `State`
+// settings it changed. `emit_cpi!` reads a local named `ctx`, which the
+// macro has to bind itself to be seen
+macro_rules! track_config {
+    ($ctx:ident, $body:block) => {{
+        let config_before = ConfigSnapshot::of(&$ctx.accounts.state)?;
+        $body
+        let ctx = &$ctx;
+        if let Some(changed) = config_before.changes(&ctx.accounts.state)? {
+            emit_cpi!(changed);
+        }
+        Ok(())
+    }};
+}
+
+mod compressed_claims;
+pub use compressed_claims::*;
+mod wormhole_claims;
+pub use wormhole_claims::*;
+mod claim_badges;
+pub use claim_badges::*;
+mod claim_pipeline;
+pub use claim_pipeline::*;
+mod relayed_claims;
+pub use relayed_claims::*;
+mod usd_pricing;
+pub use usd_pricing::*;
+mod cohorts;
+pub use cohorts::*;
+mod campaign_summary;
+pub use campaign_summary::*;
+mod lifecycle;
+pub use lifecycle::*;
+mod claim_tickets;
+pub use claim_tickets::*;
+mod protocol_config;
+pub use protocol_config::*;
+mod config_history;
+pub use config_history::*;
+mod voided_indices;
+pub use voided_indices::*;
+mod dust_sweep;
+pub use dust_sweep::*;
+mod build_info;
+pub use build_info::*;
+mod root_appends;
+pub use root_appends::*;
+mod vesting_view;
+pub use vesting_view::*;
+mod root_schedule;
+pub use root_schedule::*;
+mod rounding;
+pub use rounding::*;
+mod campaign_family;
+pub use campaign_family::*;
+mod crank_pool;
+pub use crank_pool::*;
+mod custom_leaves;
+pub use custom_leaves::*;
+mod cohort_vaults;
+pub use cohort_vaults::*;
+mod vesting_handoff;
+pub use vesting_handoff::*;
+mod bonus_lottery;
+pub use bonus_lottery::*;
+mod legacy_claims;
+pub use legacy_claims::*;
 
 // Conditional compilation for security.txt
 #[cfg(not(feature = "no-entrypoint"))]          
@@ -68,10 +255,147 @@ security_txt! {
 }
 /*───────────────────────────────────────────────────────────────*/
 
+// Published in the IDL, and mirrored by `merkledrop-interface`, so clients
+// derive addresses and encode leaves without copying literals
+#[constant]
+pub const STATE_SEED: &[u8] = b"state";
+#[constant]
+pub const VAULT_SEED: &[u8] = b"vault";
+#[constant]
+pub const WALLET_CLAIMS_SEED: &[u8] = b"wallet_claims";
+#[constant]
+pub const RELAYER_SEED: &[u8] = b"relayer";
+#[constant]
+pub const METADATA_SEED: &[u8] = b"metadata";
+#[constant]
+pub const STATS_SEED: &[u8] = b"stats";
+#[constant]
+pub const TREASURY_SEED: &[u8] = b"treasury";
+#[constant]
+pub const TREASURY_TOKENS_SEED: &[u8] = b"treasury_tokens";
+#[constant]
+pub const BONUS_VAULT_SEED: &[u8] = b"bonus_vault";
+#[constant]
+pub const BADGE_MINT_SEED: &[u8] = b"badge";
+#[constant]
+pub const LEDGER_AUTHORITY_SEED: &[u8] = b"ledger";
+#[constant]
+pub const COHORT_SEED: &[u8] = b"cohort";
+#[constant]
+pub const ESCROW_SEED: &[u8] = b"escrow";
+#[constant]
+pub const VESTING_SEED: &[u8] = b"vesting";
+#[constant]
+pub const SUMMARY_SEED: &[u8] = b"summary";
+#[constant]
+pub const ROLLOVER_SEED: &[u8] = b"rollover";
+#[constant]
+pub const EPOCH_SEED: &[u8] = b"epoch";
+#[constant]
+pub const RAFFLE_SEED: &[u8] = b"raffle";
+#[constant]
+pub const RAFFLE_PRIZE_SEED: &[u8] = b"raffle_prize";
+#[constant]
+pub const UNWRAP_SEED: &[u8] = b"unwrap";
+#[constant]
+pub const CONFIG_SEED: &[u8] = b"config";
+#[constant]
+pub const TICKET_SEED: &[u8] = b"ticket";
+#[constant]
+pub const VOID_SEED: &[u8] = b"void";
+#[constant]
+pub const BUILD_INFO_SEED: &[u8] = b"build_info";
+#[constant]
+pub const ROOT_SCHEDULE_SEED: &[u8] = b"root_schedule";
+#[constant]
+pub const FAMILY_CLAIM_SEED: &[u8] = b"family_claim";
+#[constant]
+pub const VESTING_HANDOFF_SEED: &[u8] = b"vesting_handoff";
+#[constant]
+pub const VESTING_SENDER_SEED: &[u8] = b"vesting_sender";
+#[constant]
+pub const BONUS_LOTTERY_SEED: &[u8] = b"bonus_lottery";
+#[constant]
+pub const BONUS_TICKET_SEED: &[u8] = b"bonus_ticket";
+#[constant]
+pub const LEGACY_CLAIMS_SEED: &[u8] = b"legacy_claims";
+#[constant]
+pub const CALLER_AUTHORITY_SEED: &[u8] = b"merkledrop_caller"; // PDA an integrating program signs `claim_via_cpi` with
+#[constant]
+pub const LEAF_FORMAT_VERSION: u8 = 1; // bumped if any `merkle` leaf encoding changes
+#[constant]
+pub const MAX_CLAIM_CAPACITY: u64 = 1 << 24; // a leaf per bit for the deepest tree a proof covers; `resize_state` grows the bitmap up to it
+#[constant]
+pub const MAX_INIT_CLAIM_CAPACITY: u64 = 1 << 16; // an 8KiB bitmap, within the 10KiB an account can be created with
+#[constant]
+pub const MAX_PROOF_LEN: u8 = MAX_PROOF_DEPTH as u8; // `proof` is always padded to this many nodes
+#[constant]
+pub const MAX_UNCLAIMED_PAGE: u64 = 2_048; // indices per `emit_unclaimed` call; a 256-byte bitmap within default compute
+
 // Configuration
-const MAX_CLAIMS: usize = 1_000_000;
-const MODULI: [usize; 3] = [971, 311, 601]; // Coprime moduli
-const STATE_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 1 + 32 + 8 + 122 + 39 + 76;
+const MAX_PROOF_DEPTH: usize = 24; // 2^24 leaves; proof is passed inline, no heap
+const MAX_BATCH_RECIPIENTS: usize = 20;
+const MAX_DUST_SWEEP: usize = 8; // leaves per `sweep_dust`, each with its proof inline
+const MAX_LEAF_FIELDS_LEN: usize = 256; // extra fields of a `claim_with_leaf` leaf, passed inline
+const MAX_HANDOFF_DATA_LEN: usize = 512; // instruction data of a vesting handoff template
+const MAX_HANDOFF_ACCOUNTS: usize = 24; // enough for a Streamflow `create` and its fee accounts
+const LOTTERY_BUCKETS: usize = 64; // draw resolution of the bonus lottery; settling walks every bucket
+const MAX_FEE_BPS: u16 = 1_000; // operators can take at most 10% of a claim
+const MAX_PENALTY_BPS: u16 = 5_000; // a late claim keeps at least half
+const MAX_BONUS_BPS: u16 = 10_000; // an early claim gets at most double
+const MAX_FROZEN_INDICES: usize = 16; // incident response, not a blocklist
+const MAX_EXTRA_VAULTS: usize = 4; // failover vaults behind `state.vault`
+const MAX_ROOT_APPENDS: usize = 4; // prior roots claims still verify against
+const MAX_SCHEDULED_ROOTS: usize = 4; // waves queued ahead in a `RootSchedule`
+const MAX_FAMILY_SIBLINGS: usize = 7; // a family is at most eight campaigns
+const MAX_COHORT_VAULTS: usize = 4; // separately funded categories, e.g. team, investors, community
+const MAX_CLOSE_GRACE: i64 = 3_600; // in-flight claims need a couple of minutes at most
+const MAX_REVEAL_DELAY: u64 = 150; // about a minute of slots; enough to outlast the opening block
+const MAX_WINDOW_DRIFT: i64 = 10 * 365 * 86_400; // a window may start at most ten years either side of now
+const MAX_RAFFLE_WINNERS: usize = 32; // every winning index is kept on the `Raffle`
+const MAX_RAFFLE_DRAWS: u64 = 256; // tickets one `settle_raffle` call draws; sparse rounds take several calls
+const RAFFLE_CLAIM_PERIOD: i64 = 30 * 86_400; // after this, unpaid prizes stay unclaimed supply
+const STATE_VERSION: u8 = 1;
+const STATE_BASE_SPACE: usize = 8 + 1 + 32 + 32 + 8 + 8 + 1 + 32 + 8 + 8 + 4 + 32 + 32 + 1 + 8 + 8 + 8
+    + 32 + MAX_URI_LEN + 32 + 1 + 32 + 8 + 8 + 8 + 1 + 2 + 32 + 8 + 8 + 8 * MAX_FROZEN_INDICES + 1
+    + 1 + 8 + 8 + 8 + 2 + 8 + 32 + 8 + 2 + 8 + 8 + 32 + 1 + 32 * MAX_EXTRA_VAULTS + 1 + 2 + 32 + 1
+    + 32 + 8 + 8 + 32 + 8 + 2 + 8 + 1 + 8 + 4 + 8 + 8 + 32 + 32 * MAX_ROOT_APPENDS + 1 + 1
+    + 32 + 32 * MAX_FAMILY_SIBLINGS + 1 + 8 + (4 + 32 + 8) * MAX_COHORT_VAULTS + 1 + 32 + 1 + 1 + 8 + 4 + 1 + 32 + 32;
+
+// Pre-versioning layout (fixed residue arrays over moduli 971/311/601)
+const LEGACY_STATE_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 1 + 32 + 8 + 122 + 39 + 76;
+const MAX_NAME_LEN: usize = 32;
+const MAX_SYMBOL_LEN: usize = 10;
+const MAX_URI_LEN: usize = 200;
+const MAX_TOOLCHAIN_LEN: usize = 64;
+const CAMPAIGN_METADATA_SPACE: usize =
+    8 + 32 + (4 + MAX_NAME_LEN) + (4 + MAX_SYMBOL_LEN) + 2 * (4 + MAX_URI_LEN) + 8;
+const ROLLOVER_SUMMARY_SPACE: usize = 8 + 4 + 32 + 32 + 8 + 8 + 8 + 32;
+const NAME_SERVICE_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+const NAME_RECORD_HEADER_LEN: usize = 96; // parent_name | owner | class
+const EPOCH_ROOT_BASE_SPACE: usize = 8 + 32 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 4 + 1;
+const STATS_BUCKETS: usize = 72; // three days of hourly buckets
+const CLAIM_STATS_SPACE: usize = 8 + 32 + 1 + STATS_BUCKETS * (8 + 4 + 8);
+const TREASURY_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 1;
+const CLAIM_ESCROW_SPACE: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1;
+const VESTING_SPACE: usize = 8 + 32 + 8 + 8 + 8 + 8 + (8 + 8 + 2) + 1;
+const RAFFLE_SPACE: usize =
+    8 + 32 + 32 + 8 + 4 + 8 + 8 + 8 + 32 + 1 + 8 + 8 * MAX_RAFFLE_WINNERS + 4 + 4 + 1 + 1;
+const RAFFLE_PRIZE_SPACE: usize = 8 + 8 + 32;
+const WALLET_CLAIMS_SPACE: usize = 8 + 32 + 32 + 4 + 8 + 1;
+const RELAYER_SPACE: usize = 8 + 32 + 32 + 1 + 8 + 8 + 1;
+const COHORT_SPACE: usize = 8 + 32 + 4 + 8 + 8 + (8 + 8 + 2) + 1;
+const SUMMARY_SPACE: usize = 8 + 32 + 4 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+const TICKET_SPACE: usize = 8 + 32 + 32 + 4 + 32 + 8 + 1;
+const CONFIG_SPACE: usize = 8 + 32 + 32 + 8 + 1;
+const VOIDED_INDEX_SPACE: usize = 8 + 8 + 32 + 8 + 1;
+const BUILD_INFO_SPACE: usize = 8 + 16 + 20 + MAX_TOOLCHAIN_LEN + 8 + 8 + 1;
+const ROOT_SCHEDULE_SPACE: usize = 8 + 32 + 4 + MAX_SCHEDULED_ROOTS * (32 + 8 + 8 + 8 + 32 + MAX_URI_LEN) + 1 + 1;
+const FAMILY_CLAIM_SPACE: usize = 8 + 32 + 32 + 1;
+const VESTING_HANDOFF_SPACE: usize =
+    8 + 32 + (4 + MAX_HANDOFF_DATA_LEN) + 2 + (4 + (1 + 32 + 1) * MAX_HANDOFF_ACCOUNTS) + 1;
+const BONUS_LOTTERY_SPACE: usize = 8 + 32 + 8 * LOTTERY_BUCKETS + 8 + 8 + 8 + 1 + 1 + 8 + 1;
+const BONUS_TICKET_SPACE: usize = 8 + 32 + 8 + 8 + 8 + (1 + 1) + 1;
 
 #[program]
 pub mod merkledrop_rns {
@@ -84,13 +408,34 @@ pub mod merkledrop_rns {
         claim_duration: i64,
         merkle_root: [u8; 32],
         total_claims: u64,
+        claim_capacity: u64,
+        mint_on_claim: bool,
+        allocations_hash: [u8; 32],
+        allocations_uri: String,
+        total_allocation: u64,
+        max_creation_fee: u64,
+        crank_pool: u64,
         ) 
         -> Result<()> {
-        require!(claim_duration > 0, ErrorCode::InvalidDuration);
-        require!(total_claims as usize <= MAX_CLAIMS, ErrorCode::InvalidIndex);
+        check_claim_window(claim_start_ts, claim_duration, Clock::get()?.unix_timestamp, MAX_WINDOW_DRIFT)?;
+        require!(total_allocation > 0, ErrorCode::InvalidTotalAllocation);
+        require!(
+            claim_capacity > 0 && claim_capacity <= MAX_INIT_CLAIM_CAPACITY,
+            ErrorCode::InvalidCapacity
+        );
+        // `total_claims` may exceed the bitmap; indices past it can't be
+        // claimed until `resize_state` grows it or the campaign moves to the
+        // compressed ledger
+        if mint_on_claim {
+            require!(
+                ctx.accounts.mint.mint_authority == Some(ctx.accounts.vault_auth.key()).into(),
+                ErrorCode::MintAuthorityMismatch
+            );
+        }
 
         let 
         state = &mut ctx.accounts.state;
+        state.version = STATE_VERSION;
         state.authority = *ctx.accounts.authority.key;
         state.snapshot_hash = snapshot_hash;
         state.claim_start_ts = claim_start_ts;
@@ -98,314 +443,5185 @@ pub mod merkledrop_rns {
         state.claim_closed = false;
         state.merkle_root = merkle_root;
         state.total_claims = total_claims;
+        state.claim_capacity = claim_capacity;
+        state.round = 0;
+        state.mint = ctx.accounts.mint.key();
+        state.vault = ctx.accounts.vault.key();
+        state.mint_on_claim = mint_on_claim;
+        state.allocations_hash = allocations_hash;
+        state.allocations_uri = pad_uri(&allocations_uri)?;
+        state.total_allocation = total_allocation;
         
-        // Initialize residue arrays
-        state.claim_residues0 = [0; 122];
-        state.claim_residues1 = [0; 39];
-        state.claim_residues2 = [0; 76];
+        state.claim_bitmap = vec![0; bitmap_bytes(claim_capacity)];
 
-        emit!(AirdropInitialized {
+        emit_cpi!(AirdropInitialized {
             authority: state.authority,
             snapshot_hash,
             claim_start_ts,
             claim_duration,
+            allocations_hash,
+            allocations_uri,
+            total_allocation,
         });
+        // Pre-funded vaults (and minting campaigns) skip Draft
+        if is_funded(state, ctx.accounts.vault.amount) {
+            let changed = set_status(state, CampaignStatus::Funded, Clock::get()?.unix_timestamp);
+            emit_cpi!(changed);
+        }
+
+        let fee = charge_creation_fee(
+            &ctx.accounts.config,
+            ctx.accounts.fee_treasury.as_ref(),
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            max_creation_fee,
+        )?;
+        if fee > 0 {
+            emit_cpi!(CreationFeePaid {
+                authority: ctx.accounts.authority.key(),
+                fee_treasury: ctx.accounts.fee_treasury.as_ref().unwrap().key(),
+                fee,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        fund_crank_pool(
+            &ctx.accounts.authority,
+            &ctx.accounts.state,
+            &ctx.accounts.system_program,
+            crank_pool,
+        )?;
         Ok(())
     }
 
-    pub fn claim(
-        ctx: Context<Claim>,
+    /// Creates the program-wide `Config`; only the program's upgrade authority may.
+    pub fn init_config(
+        ctx: Context<InitConfig>,
+        admin: Pubkey,
+        fee_treasury: Pubkey,
+        creation_fee: u64,
+    ) -> Result<()> {
+        protocol_config::init_config(ctx, admin, fee_treasury, creation_fee)
+    }
+
+    /// Records the commit and toolchain the deployed bytecode was built
+    /// from; only the program's upgrade authority may.
+    pub fn record_build_info(
+        ctx: Context<RecordBuildInfo>,
+        git_commit: [u8; 20],
+        toolchain: String,
+    ) -> Result<()> {
+        build_info::record_build_info(ctx, git_commit, toolchain)
+    }
+
+    pub fn set_config(
+        ctx: Context<SetConfig>,
+        admin: Pubkey,
+        fee_treasury: Pubkey,
+        creation_fee: u64,
+    ) -> Result<()> {
+        protocol_config::set_config(ctx, admin, fee_treasury, creation_fee)
+    }
+
+    /// Draws from `vault`, or failing that from the first registered extra
+    /// vault that covers the claim; extras go in `remaining_accounts`, in order.
+    /// Under USD pricing, `amount` is the leaf's micro-USD value, paid in
+    /// tokens at the price in `price_update`.
+    pub fn claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
         index: u64,
         amount: u64,
-        proof: Vec<[u8; 32]>,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
     ) -> Result<()> {
-        let state = &mut ctx.accounts.state;
         let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+        let wallet = ctx.accounts.wallet.key;
+        let price_update = ctx.accounts.price_update.as_ref();
+        let decimals = ctx.accounts.mint.decimals;
+        let mut priced = None;
+        let paid = process_claim(
+            &mut ctx.accounts.state,
+            ClaimChecks {
+                claimant: wallet,
+                stats: ctx.accounts.stats.as_mut(),
+                wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+                family_claim: ctx.accounts.family_claim.as_deref(),
+                gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+                gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+            },
+            &ClaimPayout {
+                state_bump: ctx.bumps.state,
+                vault_auth_bump: ctx.bumps.vault_auth,
+                vault_auth: &ctx.accounts.vault_auth,
+                vault: &ctx.accounts.vault,
+                extra_vaults: ctx.remaining_accounts,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                recipient: &ctx.accounts.user_ata,
+                fee_account: ctx.accounts.fee_account.as_ref(),
+                bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+                relayer: None,
+                badge_mint: ctx.accounts.badge_mint.as_ref(),
+                badge_account: ctx.accounts.badge_account.as_ref(),
+                token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+            },
+            now,
+            |state: &mut State| {
+                if state.usd_price_feed == [0; 32] {
+                    record_claim(state, index, wallet, amount, &proof, proof_len, terms_hash, now)?;
+                    return Ok(amount);
+                }
+                let (tokens, price, exponent) = record_usd_claim(
+                    state,
+                    price_update,
+                    decimals,
+                    index,
+                    wallet,
+                    amount,
+                    &proof,
+                    proof_len,
+                    terms_hash,
+                    now,
+                )?;
+                priced = Some((price, exponent));
+                Ok(tokens)
+            },
+        )?;
+        if let Some((price, exponent)) = priced {
+            emit_cpi!(UsdClaimPriced {
+                wallet: *wallet,
+                index,
+                usd_amount: amount,
+                price,
+                exponent,
+                amount: paid.amount,
+                timestamp: now,
+            });
+        }
+        if let Some(vault_balance) = paid.low_water {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
 
-        // Validate claim conditions
-        require!(!state.claim_closed, ErrorCode::ClaimClosed);
-        require!(
-            now >= state.claim_start_ts &&
-            now <= state.claim_start_ts + state.claim_duration,
-            ErrorCode::ClaimWindowClosed
-        );
-        require!(index < state.total_claims, ErrorCode::InvalidIndex);
-
-        // Verify Merkle proof
-        let leaf = keccak_leaf(index, ctx.accounts.wallet.key, amount);
-        require!(
-            verify_merkle_proof(&leaf, &proof, &state.merkle_root),
-            ErrorCode::InvalidProof
-        );
-
-        // Calculate residues
-        let residue0 = (index % MODULI[0] as u64) as usize;
-        let residue1 = (index % MODULI[1] as u64) as usize;
-        let residue2 = (index % MODULI[2] as u64) as usize;
+        // Emit claim event
+        emit_cpi!(Claimed {
+            wallet: *wallet,
+            amount: paid.amount,
+            fee: paid.fee,
+            penalty: paid.penalty,
+            net_amount: paid.net_amount,
+            bonus: paid.bonus,
+            index,
+            terms_hash,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, paid.amount),
+        });
+        Ok(())
+    }
 
-        // Check for duplicates using RNS
-        if check_residue_set(&state.claim_residues0, residue0) ||
-           check_residue_set(&state.claim_residues1, residue1) ||
-           check_residue_set(&state.claim_residues2, residue2) 
-        {
-            return Err(ErrorCode::AlreadyClaimed.into());
-        }
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_with_leaf<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+        index: u64,
+        leaf: [u8; 32],
+        amount: u64,
+        schema: LeafSchema,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        custom_leaves::claim_with_leaf(ctx, index, leaf, amount, schema, proof, proof_len, terms_hash)
+    }
 
-        // Mark as claimed
-        set_residue(&mut state.claim_residues0, residue0);
-        set_residue(&mut state.claim_residues1, residue1);
-        set_residue(&mut state.claim_residues2, residue2);
+    pub fn claim_to_vesting<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimToVesting<'info>>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        vesting_handoff::claim_to_vesting(ctx, index, amount, proof, proof_len, terms_hash)
+    }
 
-        // Transfer tokens
-        let bump = ctx.bumps.vault_auth;
-        let vault_seeds = &[
-            b"vault".as_ref(),
-            state.snapshot_hash.as_ref(),
-            &[bump],
-        ];
-        let signer_seeds: &[&[&[u8]]] = &[vault_seeds];
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from:      ctx.accounts.vault.to_account_info(),
-                to:        ctx.accounts.user_ata.to_account_info(),
-                authority: ctx.accounts.vault_auth.to_account_info(),
-                mint:      ctx.accounts.mint.to_account_info(),
+    /// `claim` for integrating programs (quests, onboarding flows): the
+    /// caller signs with its `["merkledrop_caller"]` PDA, which proves which
+    /// program invoked the claim, and the event attributes the claim to it.
+    pub fn claim_via_cpi<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimViaCpi<'info>>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+        let wallet = ctx.accounts.wallet.key;
+        let paid = process_claim(
+            &mut ctx.accounts.state,
+            ClaimChecks {
+                claimant: wallet,
+                stats: ctx.accounts.stats.as_mut(),
+                wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+                family_claim: ctx.accounts.family_claim.as_deref(),
+                gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+                gate_metadata: ctx.accounts.gate_metadata.as_ref(),
             },
-            signer_seeds,
-        );
-        token::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+            &ClaimPayout {
+                state_bump: ctx.bumps.state,
+                vault_auth_bump: ctx.bumps.vault_auth,
+                vault_auth: &ctx.accounts.vault_auth,
+                vault: &ctx.accounts.vault,
+                extra_vaults: ctx.remaining_accounts,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                recipient: &ctx.accounts.user_ata,
+                fee_account: ctx.accounts.fee_account.as_ref(),
+                bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+                relayer: None,
+                badge_mint: ctx.accounts.badge_mint.as_ref(),
+                badge_account: ctx.accounts.badge_account.as_ref(),
+                token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+            },
+            now,
+            |state: &mut State| {
+                record_claim(state, index, wallet, amount, &proof, proof_len, terms_hash, now)?;
+                Ok(amount)
+            },
+        )?;
+        if let Some(vault_balance) = paid.low_water {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
 
-        // Emit claim event
-        emit!(Claimed {
-            wallet: *ctx.accounts.wallet.key,
+        emit_cpi!(CpiClaimed {
+            caller_program: ctx.accounts.caller_program.key(),
+            wallet: *wallet,
             amount,
+            fee: paid.fee,
+            penalty: paid.penalty,
+            net_amount: paid.net_amount,
+            bonus: paid.bonus,
             index,
+            terms_hash,
             timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
         });
         Ok(())
     }
 
-    pub fn close_airdrop(ctx: Context<CloseAirdrop>) -> Result<()> {
-        let state = &mut ctx.accounts.state;
-        require!(
-            ctx.accounts.authority.key() == state.authority,
-            ErrorCode::Unauthorized
-        );
-        state.claim_closed = true;
-        emit!(AirdropClosed {
-            authority: state.authority,
-            timestamp: Clock::get()?.unix_timestamp,
-        });
-        Ok(())
+    /// Switches a campaign with no claims yet to the compressed ledger: claims
+    /// are then recorded in `merkle_tree`, a concurrent Merkle tree allocated
+    /// by the authority, and only `claim_compressed` is accepted.
+    pub fn init_compressed_ledger(
+        ctx: Context<InitCompressedLedger>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        compressed_claims::init_compressed_ledger(ctx, max_depth, max_buffer_size)
     }
 
-    pub fn update_claim_window(
-        ctx: Context<UpdateClaimWindow>,
-        new_start_ts: i64,
-        new_duration: i64,
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_compressed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimCompressed<'info>>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+        ledger_root: [u8; 32],
     ) -> Result<()> {
-        let state = &mut ctx.accounts.state;
-        require!(
-            ctx.accounts.authority.key() == state.authority,
-            ErrorCode::Unauthorized
-        );
-        require!(new_duration > 0, ErrorCode::InvalidDuration);
-        state.claim_closed = false;
-        state.claim_start_ts = new_start_ts;
-        state.claim_duration = new_duration;
-        emit!(ClaimWindowUpdated {
-            new_start_ts,
-            new_duration,
-            timestamp: Clock::get()?.unix_timestamp,
-        });
-        Ok(())
+        compressed_claims::claim_compressed(ctx, index, amount, proof, proof_len, terms_hash, ledger_root)
     }
 
-    pub fn update_merkle_root(
-        ctx: Context<UpdateMerkleRoot>,
-        new_root: [u8; 32],
-        new_total_claims: u64,
+    pub fn verify_compressed_claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyCompressedClaim<'info>>,
+        index: u64,
+        wallet: Pubkey,
+        amount: u64,
+        ledger_root: [u8; 32],
     ) -> Result<()> {
-        require!(
-            new_total_claims as usize <= MAX_CLAIMS,
-            ErrorCode::InvalidIndex
-        );
+        compressed_claims::verify_compressed_claim(ctx, index, wallet, amount, ledger_root)
+    }
+
+    pub fn init_claim_badge(ctx: Context<InitClaimBadge>) -> Result<()> {
+        claim_badges::init_claim_badge(ctx)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_relayed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimRelayed<'info>>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+        max_relayer_fee: u64,
+    ) -> Result<()> {
+        relayed_claims::claim_relayed(ctx, index, amount, proof, proof_len, terms_hash, max_relayer_fee)
+    }
+
+    /// Turns commit-reveal tickets on (`reveal_delay` slots) or off (zero).
+    pub fn set_reveal_delay(ctx: Context<SetRevealDelay>, reveal_delay: u64) -> Result<()> {
+        claim_tickets::set_reveal_delay(ctx, reveal_delay)
+    }
+
+    /// Posts or replaces the wallet's ticket for a later `claim_ticketed`.
+    pub fn commit_claim(ctx: Context<CommitClaim>, commitment: [u8; 32]) -> Result<()> {
+        claim_tickets::commit_claim(ctx, commitment)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_ticketed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimTicketed<'info>>,
+        index: u64,
+        amount: u64,
+        salt: [u8; 32],
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        claim_tickets::claim_ticketed(ctx, index, amount, salt, proof, proof_len, terms_hash)
+    }
+
+    /// Tokens of each allocation paid to the relayer of a `claim_relayed`.
+    pub fn set_relayer_fee(ctx: Context<SetRelayerFee>, relayer_fee: u64) -> Result<()> {
+        relayed_claims::set_relayer_fee(ctx, relayer_fee)
+    }
+
+    /// Revoking keeps the relayer's `Relayer` account and counters, so
+    /// registering it again picks up where it left off.
+    pub fn register_relayer(ctx: Context<RegisterRelayer>, relayer: Pubkey) -> Result<()> {
+        relayed_claims::register_relayer(ctx, relayer)
+    }
+
+    pub fn revoke_relayer(ctx: Context<RevokeRelayer>, relayer: Pubkey) -> Result<()> {
+        relayed_claims::revoke_relayer(ctx, relayer)
+    }
+
+    pub fn set_wormhole_emitter(
+        ctx: Context<SetWormholeEmitter>,
+        emitter_chain: u16,
+        emitter: [u8; 32],
+    ) -> Result<()> {
+        wormhole_claims::set_wormhole_emitter(ctx, emitter_chain, emitter)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_from_vaa<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimFromVaa<'info>>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        wormhole_claims::claim_from_vaa(ctx, index, amount, proof, proof_len, terms_hash)
+    }
+
+    /// Escrow mode, phase one: verifies and records the claim like `claim`,
+    /// but parks the entitlement in a per-index escrow PDA instead of paying.
+    pub fn reserve_claim(
+        ctx: Context<ReserveClaim>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.state.dispute_period > 0, ErrorCode::EscrowDisabled);
+        let wallet = ctx.accounts.wallet.key;
+        admit_claim(
+            &mut ctx.accounts.state,
+            ClaimChecks {
+                claimant: wallet,
+                stats: ctx.accounts.stats.as_mut(),
+                wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+                family_claim: ctx.accounts.family_claim.as_deref(),
+                gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+                gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+            },
+            now,
+            |state: &mut State| {
+                record_claim(state, index, wallet, amount, &proof, proof_len, terms_hash, now)?;
+                Ok(amount)
+            },
+        )?;
         let state = &mut ctx.accounts.state;
-        require!(
-            ctx.accounts.authority.key() == state.authority,
-            ErrorCode::Unauthorized
-        );
-        state.merkle_root = new_root;
-        state.total_claims = new_total_claims;
-        emit!(MerkleRootUpdated {
-            new_root,
-            new_total_claims,
-            timestamp: Clock::get()?.unix_timestamp,
+        state.escrowed_amount = state.escrowed_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.wallet = ctx.accounts.wallet.key();
+        escrow.index = index;
+        escrow.amount = amount;
+        escrow.reserved_ts = now;
+        escrow.release_ts = now
+            .checked_add(state.dispute_period)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        escrow.bump = ctx.bumps.escrow;
+
+        emit_cpi!(ClaimReserved {
+            wallet: escrow.wallet,
+            index,
+            amount,
+            release_ts: escrow.release_ts,
+            terms_hash,
+            timestamp: now,
         });
         Ok(())
     }
-    pub fn close_state(ctx: Context<CloseState>) -> Result<()> {
+
+    /// Escrow mode, phase two: pays a reservation once its dispute period has
+    /// passed and returns the escrow rent to the wallet.
+    pub fn withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.escrow.amount;
+        require!(now >= ctx.accounts.escrow.release_ts, ErrorCode::DisputePeriodActive);
+        require!(!is_frozen(&ctx.accounts.state, ctx.accounts.escrow.index), ErrorCode::IndexFrozen);
+
+        // No longer held back from the vault for this reservation
         let state = &mut ctx.accounts.state;
-        require!(
-            ctx.accounts.authority.key() == state.authority,
-            ErrorCode::Unauthorized
-        );
-    
-        // By default, Anchor's `#[account(close = recipient)]` will transfer 
-        // the lamports of `state` to the `recipient` account 
-        // and mark `state` as closed (so no more rent).
+        state.escrowed_amount = state.escrowed_amount.saturating_sub(amount);
+        // Lateness (and earliness) is judged at reservation, not at the end of the dispute period
+        let paid = settle_claim(
+            &mut ctx.accounts.state,
+            &ClaimPayout {
+                state_bump: ctx.bumps.state,
+                vault_auth_bump: ctx.bumps.vault_auth,
+                vault_auth: &ctx.accounts.vault_auth,
+                vault: &ctx.accounts.vault,
+                extra_vaults: ctx.remaining_accounts,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                recipient: &ctx.accounts.user_ata,
+                fee_account: ctx.accounts.fee_account.as_ref(),
+                bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+                relayer: None,
+                badge_mint: ctx.accounts.badge_mint.as_ref(),
+                badge_account: ctx.accounts.badge_account.as_ref(),
+                token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+            },
+            amount,
+            ctx.accounts.escrow.reserved_ts,
+        )?;
+        if let Some(vault_balance) = paid.low_water {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
+
+        emit_cpi!(EscrowWithdrawn {
+            wallet: ctx.accounts.wallet.key(),
+            index: ctx.accounts.escrow.index,
+            amount,
+            fee: paid.fee,
+            penalty: paid.penalty,
+            net_amount: paid.net_amount,
+            bonus: paid.bonus,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+        });
         Ok(())
     }
-}
 
-// Helper functions for residue tracking
-fn check_residue_set(residues: &[u8], residue: usize) -> bool {
-    let byte_index = residue / 8;
-    let bit_index = residue % 8;
-    residues.get(byte_index)
-        .map(|byte| (byte & (1 << bit_index)) != 0)
-        .unwrap_or(false)
-}
+    /// Which way fees, penalties, bonuses, vesting unlocks and USD payouts
+    /// round; `Floor` unless set.
+    pub fn set_rounding(ctx: Context<SetRounding>, rounding: Rounding) -> Result<()> {
+        rounding::set_rounding(ctx, rounding)
+    }
 
-fn set_residue(residues: &mut [u8], residue: usize) {
-    let byte_index = residue / 8;
-    let bit_index = residue % 8;
-    if let Some(byte) = residues.get_mut(byte_index) {
-        *byte |= 1 << bit_index;
+    pub fn set_family(
+        ctx: Context<SetFamily>,
+        family_id: [u8; 32],
+        siblings: Vec<Pubkey>,
+    ) -> Result<()> {
+        campaign_family::set_family(ctx, family_id, siblings)
     }
-}
 
-// Utility functions
-fn keccak_leaf(index: u64, wallet: &Pubkey, amount: u64) -> [u8; 32] {
-    use anchor_lang::solana_program::keccak;
-    keccak::hashv(&[
-        &index.to_le_bytes(),
-        wallet.as_ref(),
-        &amount.to_le_bytes(),
-    ])
-    .to_bytes()
-}
+    /// Once per wallet and family, ahead of or with the wallet's first claim.
+    pub fn join_family(ctx: Context<JoinFamily>) -> Result<()> {
+        campaign_family::join_family(ctx)
+    }
 
-fn verify_merkle_proof(
-    leaf: &[u8; 32],
-    proof: &Vec<[u8; 32]>,
-    root: &[u8; 32],
-) -> bool {
-    use anchor_lang::solana_program::keccak;
-    let mut hash = *leaf;
-    let mut buf = [0u8; 64];
-    for p in proof.iter() {
-        if hash <= *p {
-            buf[..32].copy_from_slice(&hash);
-            buf[32..].copy_from_slice(p);
-        } else {
-            buf[..32].copy_from_slice(p);
-            buf[32..].copy_from_slice(&hash);
-        }
-        hash = keccak::hash(&buf).to_bytes();
+    /// `data` holds the net amount as a little-endian u64 at `amount_offset`.
+    pub fn set_vesting_handoff(
+        ctx: Context<SetVestingHandoff>,
+        program: Pubkey,
+        data: Vec<u8>,
+        amount_offset: u16,
+        accounts: Vec<HandoffAccount>,
+    ) -> Result<()> {
+        vesting_handoff::set_vesting_handoff(ctx, program, data, amount_offset, accounts)
     }
-    &hash == root
-}
 
-// Account Structs
-#[account]
-pub struct State {
-    pub authority: Pubkey,
-    pub snapshot_hash: [u8; 32],
-    pub claim_start_ts: i64,
-    pub claim_duration: i64,
-    pub claim_closed: bool,
-    pub merkle_root: [u8; 32],
-    pub total_claims: u64,
-    pub claim_residues0: [u8; 122], // 971 bits
-    pub claim_residues1: [u8; 39],  // 311 bits
-    pub claim_residues2: [u8; 76],  // 601 bits
-}
+    /// Queues a root to replace the current one at `activation_ts`.
+    #[cfg(not(feature = "immutable"))]
+    pub fn schedule_root(
+        ctx: Context<ScheduleRoot>,
+        merkle_root: [u8; 32],
+        total_claims: u64,
+        total_allocation: u64,
+        activation_ts: i64,
+        allocations_hash: [u8; 32],
+        allocations_uri: String,
+    ) -> Result<()> {
+        root_schedule::schedule_root(
+            ctx,
+            merkle_root,
+            total_claims,
+            total_allocation,
+            activation_ts,
+            allocations_hash,
+            allocations_uri,
+        )
+    }
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        seeds = [b"state".as_ref()],
-        bump,
-        payer = authority,
-        space = STATE_SPACE
-    )]
-    pub state: Account<'info, State>,
+    #[cfg(not(feature = "immutable"))]
+    pub fn clear_root_schedule(ctx: Context<ClearRootSchedule>) -> Result<()> {
+        root_schedule::clear_root_schedule(ctx)
+    }
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// Permissionless: activates the latest scheduled root that is due.
+    #[cfg(not(feature = "immutable"))]
+    pub fn activate_scheduled_root(ctx: Context<ActivateScheduledRoot>) -> Result<()> {
+        root_schedule::activate_scheduled_root(ctx)
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    /// How much of `index`'s vesting claim has unlocked at `at` (default:
+    /// now) and what `release_vested` would pay; call via simulation and
+    /// decode the return data as `VestedAmount`.
+    pub fn get_vested_amount(
+        ctx: Context<GetVestedAmount>,
+        index: u64,
+        at: Option<i64>,
+    ) -> Result<VestedAmount> {
+        vesting_view::get_vested_amount(ctx, index, at)
+    }
 
-#[derive(Accounts)]
-#[instruction(index: u64)]
-pub struct Claim<'info> {
-    #[account(mut, seeds = [b"state".as_ref()], bump)]
-    pub state: Account<'info, State>,
+    /// Vesting claim: the leaf also commits to a `(cliff, duration, tge_bps)`
+    /// schedule, so cohorts in one tree can vest differently. Records the
+    /// claim, opens a per-index vesting account and pays what has vested so far.
+    pub fn claim_vested<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimVested<'info>>,
+        index: u64,
+        amount: u64,
+        schedule: VestingSchedule,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            schedule.cliff >= 0 && schedule.duration >= 0 && schedule.tge_bps <= 10_000,
+            ErrorCode::InvalidVestingSchedule
+        );
+        require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+        let wallet = ctx.accounts.wallet.key;
+        admit_claim(
+            &mut ctx.accounts.state,
+            ClaimChecks {
+                claimant: wallet,
+                stats: ctx.accounts.stats.as_mut(),
+                wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+                family_claim: ctx.accounts.family_claim.as_deref(),
+                gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+                gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+            },
+            now,
+            |state: &mut State| {
+                let leaf = keccak_vesting_leaf(index, wallet, amount, &schedule);
+                record_leaf_claim(state, index, &leaf, amount, &proof, proof_len, terms_hash, now)?;
+                Ok(amount)
+            },
+        )?;
+        // The late haircut comes off the whole allocation at the claim; only
+        // the rest vests
+        let penalty = take_late_penalty(&mut ctx.accounts.state, amount, 0, now)?;
+        let state = &mut ctx.accounts.state;
+        state.vesting_locked = state.vesting_locked
+            .checked_add(amount - penalty)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
 
-    #[account(mut)]
-    pub wallet: Signer<'info>,
+        // The schedule runs from the campaign start, not from the claim, so
+        // claiming late doesn't push the unlock back
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.wallet = ctx.accounts.wallet.key();
+        vesting.index = index;
+        vesting.amount = amount - penalty;
+        vesting.released = 0;
+        vesting.start_ts = state.claim_start_ts;
+        vesting.schedule = schedule;
+        vesting.bump = ctx.bumps.vesting;
 
-    /// CHECK: PDA authority
-    #[account(
-        seeds = [b"vault".as_ref(), state.snapshot_hash.as_ref()],
-        bump
-    )]
-    pub vault_auth: AccountInfo<'info>,
+        emit_cpi!(VestingStarted {
+            wallet: vesting.wallet,
+            index,
+            amount: vesting.amount,
+            penalty,
+            start_ts: vesting.start_ts,
+            cliff: schedule.cliff,
+            duration: schedule.duration,
+            tge_bps: schedule.tge_bps,
+            terms_hash,
+            timestamp: now,
+        });
 
-    #[account(
-        mut,
-        token::mint = mint,
-        token::authority = vault_auth
-    )]
-    pub vault: Account<'info, TokenAccount>,
+        let due = take_vested(&mut ctx.accounts.state, &mut ctx.accounts.vesting, now)?;
+        let fee = take_operator_fee(&mut ctx.accounts.state, due)?;
+        let vault = select_vault(&ctx.accounts.state, &ctx.accounts.vault, ctx.remaining_accounts, due + penalty)?;
+        pay_out(
+            &ctx.accounts.state,
+            &ClaimPayout {
+                state_bump: ctx.bumps.state,
+                vault_auth_bump: ctx.bumps.vault_auth,
+                vault_auth: &ctx.accounts.vault_auth,
+                vault: &ctx.accounts.vault,
+                extra_vaults: ctx.remaining_accounts,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                recipient: &ctx.accounts.user_ata,
+                fee_account: ctx.accounts.fee_account.as_ref(),
+                bonus_vault: None,
+                relayer: None,
+                badge_mint: ctx.accounts.badge_mint.as_ref(),
+                badge_account: ctx.accounts.badge_account.as_ref(),
+                token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+            },
+            &vault,
+            due - fee,
+            0,
+            fee + penalty,
+        )?;
+        if let Some(vault_balance) = trip_low_water_mark(&mut ctx.accounts.state, vault.amount, due + penalty) {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
 
-    #[account(
-        mut,
-        token::mint = mint,
-        token::authority = wallet
-    )]
+        let vesting = &ctx.accounts.vesting;
+        emit_cpi!(VestedReleased {
+            wallet: vesting.wallet,
+            index,
+            amount: due,
+            fee,
+            released: vesting.released,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &vault, due + penalty),
+        });
+        if vesting.released == vesting.amount {
+            ctx.accounts.vesting.close(ctx.accounts.wallet.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    /// Pays whatever has vested since the last release; the vesting account
+    /// is closed to the wallet once the full amount is out.
+    pub fn release_vested<'info>(ctx: Context<'_, '_, 'info, 'info, ReleaseVested<'info>>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let index = ctx.accounts.vesting.index;
+        require!(!is_frozen(&ctx.accounts.state, index), ErrorCode::IndexFrozen);
+
+        let due = take_vested(&mut ctx.accounts.state, &mut ctx.accounts.vesting, now)?;
+        require!(due > 0, ErrorCode::NothingVested);
+        let fee = take_operator_fee(&mut ctx.accounts.state, due)?;
+        let vault = select_vault(&ctx.accounts.state, &ctx.accounts.vault, ctx.remaining_accounts, due)?;
+        pay_claim(
+            &ctx.accounts.state,
+            ctx.bumps.vault_auth,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &vault,
+            &ctx.accounts.vault_auth,
+            &ctx.accounts.user_ata,
+            due - fee,
+        )?;
+        pay_operator_fee(
+            &ctx.accounts.state,
+            ctx.bumps.vault_auth,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &vault,
+            &ctx.accounts.vault_auth,
+            ctx.accounts.fee_account.as_ref(),
+            fee,
+        )?;
+        if let Some(vault_balance) = trip_low_water_mark(&mut ctx.accounts.state, vault.amount, due) {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
+
+        let vesting = &ctx.accounts.vesting;
+        emit_cpi!(VestedReleased {
+            wallet: vesting.wallet,
+            index,
+            amount: due,
+            fee,
+            released: vesting.released,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &vault, due),
+        });
+        if vesting.released == vesting.amount {
+            ctx.accounts.vesting.close(ctx.accounts.wallet.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    pub fn claim_cohort<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimCohort<'info>>,
+        index: u64,
+        amount: u64,
+        cohort_id: u32,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        cohorts::claim_cohort(ctx, index, amount, cohort_id, proof, proof_len, terms_hash)
+    }
+
+    /// Wrapped-SOL campaigns: pays the allocation into a temporary wSOL
+    /// account and closes it to the wallet in the same instruction, so the
+    /// claimant receives native SOL (the temporary account's rent included).
+    pub fn claim_unwrapped<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimUnwrapped<'info>>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+        let wallet = ctx.accounts.wallet.key;
+        let paid = process_claim(
+            &mut ctx.accounts.state,
+            ClaimChecks {
+                claimant: wallet,
+                stats: ctx.accounts.stats.as_mut(),
+                wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+                family_claim: ctx.accounts.family_claim.as_deref(),
+                gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+                gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+            },
+            &ClaimPayout {
+                state_bump: ctx.bumps.state,
+                vault_auth_bump: ctx.bumps.vault_auth,
+                vault_auth: &ctx.accounts.vault_auth,
+                vault: &ctx.accounts.vault,
+                extra_vaults: ctx.remaining_accounts,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                recipient: &ctx.accounts.temp_wsol,
+                fee_account: ctx.accounts.fee_account.as_ref(),
+                bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+                relayer: None,
+                badge_mint: ctx.accounts.badge_mint.as_ref(),
+                badge_account: ctx.accounts.badge_account.as_ref(),
+                token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+            },
+            now,
+            |state: &mut State| {
+                record_claim(state, index, wallet, amount, &proof, proof_len, terms_hash, now)?;
+                Ok(amount)
+            },
+        )?;
+        if let Some(vault_balance) = paid.low_water {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
+
+        // A native token account's lamports track its amount, so closing it
+        // hands the wallet the claimed SOL without a separate sync_native.
+        let bump = ctx.bumps.vault_auth;
+        let vault_seeds = &[
+            VAULT_SEED,
+            ctx.accounts.state.snapshot_hash.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[vault_seeds];
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account:     ctx.accounts.temp_wsol.to_account_info(),
+                destination: ctx.accounts.wallet.to_account_info(),
+                authority:   ctx.accounts.vault_auth.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        emit_cpi!(Claimed {
+            wallet: *ctx.accounts.wallet.key,
+            amount,
+            fee: paid.fee,
+            penalty: paid.penalty,
+            net_amount: paid.net_amount,
+            bonus: paid.bonus,
+            index,
+            terms_hash,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+        });
+        Ok(())
+    }
+
+    /// Domain-mode claim: the leaf commits to a name-service domain account
+    /// instead of a wallet, and whoever owns the domain at claim time is paid.
+    pub fn claim_domain<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimDomain<'info>>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let domain_owner = name_record_owner(&ctx.accounts.domain)?;
+        require_keys_eq!(domain_owner, ctx.accounts.owner.key(), ErrorCode::NotDomainOwner);
+
+        require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+        let claimant = ctx.accounts.owner.key;
+        let domain = ctx.accounts.domain.key;
+        let paid = process_claim(
+            &mut ctx.accounts.state,
+            ClaimChecks {
+                claimant,
+                stats: ctx.accounts.stats.as_mut(),
+                wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+                family_claim: ctx.accounts.family_claim.as_deref(),
+                gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+                gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+            },
+            &ClaimPayout {
+                state_bump: ctx.bumps.state,
+                vault_auth_bump: ctx.bumps.vault_auth,
+                vault_auth: &ctx.accounts.vault_auth,
+                vault: &ctx.accounts.vault,
+                extra_vaults: ctx.remaining_accounts,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                recipient: &ctx.accounts.user_ata,
+                fee_account: ctx.accounts.fee_account.as_ref(),
+                bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+                relayer: None,
+                badge_mint: ctx.accounts.badge_mint.as_ref(),
+                badge_account: ctx.accounts.badge_account.as_ref(),
+                token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+            },
+            now,
+            |state: &mut State| {
+                record_claim(state, index, domain, amount, &proof, proof_len, terms_hash, now)?;
+                Ok(amount)
+            },
+        )?;
+        if let Some(vault_balance) = paid.low_water {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
+
+        emit_cpi!(DomainClaimed {
+            domain: ctx.accounts.domain.key(),
+            owner: domain_owner,
+            amount,
+            fee: paid.fee,
+            penalty: paid.penalty,
+            net_amount: paid.net_amount,
+            bonus: paid.bonus,
+            index,
+            terms_hash,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+        });
+        Ok(())
+    }
+
+    /// Push-mode distribution: recipients' token accounts are passed as
+    /// `remaining_accounts`, one per entry in `amounts`. Each recipient
+    /// counts as a claim against `total_allocation` and the low-water mark,
+    /// and the batch can't touch tokens owed to reserved or vesting claimants.
+    pub fn distribute_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeBatch<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        require!(
+            !amounts.is_empty()
+                && amounts.len() <= MAX_BATCH_RECIPIENTS
+                && amounts.len() == ctx.remaining_accounts.len(),
+            ErrorCode::InvalidBatch
+        );
+        require!(!ctx.accounts.state.config_locked, ErrorCode::ConfigLocked);
+        let total_amount = amounts
+            .iter()
+            .try_fold(0u64, |total, amount| total.checked_add(*amount))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(
+            total_amount <= unreserved_balance(&ctx.accounts.state, &ctx.accounts.vault),
+            ErrorCode::BatchExceedsUnreserved
+        );
+        let state = &mut ctx.accounts.state;
+        for amount in &amounts {
+            add_claimed(state, *amount)?;
+        }
+        let low_water = trip_low_water_mark(state, ctx.accounts.vault.amount, total_amount);
+
+        let state = &ctx.accounts.state;
+        let bump = ctx.bumps.vault_auth;
+        let vault_seeds = &[
+            VAULT_SEED,
+            state.snapshot_hash.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[vault_seeds];
+
+        let mut recipients = Vec::with_capacity(amounts.len());
+        for (recipient, amount) in ctx.remaining_accounts.iter().zip(amounts.iter()) {
+            let recipient_ata = Account::<TokenAccount>::try_from(recipient)?;
+            require_keys_eq!(recipient_ata.mint, state.mint, ErrorCode::InvalidMint);
+            require!(!recipient_ata.is_frozen(), ErrorCode::RecipientAccountFrozen);
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from:      ctx.accounts.vault.to_account_info(),
+                    to:        recipient.clone(),
+                    authority: ctx.accounts.vault_auth.to_account_info(),
+                    mint:      ctx.accounts.mint.to_account_info(),
+                },
+                signer_seeds,
+            );
+            trace!("batch: transfer {} to {}", amount, recipient.key());
+            token::transfer_checked(cpi_ctx, *amount, ctx.accounts.mint.decimals)?;
+            recipients.push(recipient.key());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(vault_balance) = low_water {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
+        emit_cpi!(BatchDistributed {
+            recipients,
+            amounts,
+            total_amount,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    pub fn close_airdrop(ctx: Context<CloseAirdrop>) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            require!(
+                ctx.accounts.authority.key() == state.authority,
+                ErrorCode::Unauthorized
+            );
+            require!(state.status != CampaignStatus::Finalized, ErrorCode::InvalidStatus);
+            let now = Clock::get()?.unix_timestamp;
+            let end = claim_end_ts(state.claim_start_ts, state.claim_duration)?;
+            // Under a grace period an open window just ends early, so claims
+            // already in flight still land; the round ends with the window. A
+            // locked window can't be moved, so it closes at once
+            let closes_at = if state.close_grace > 0
+                && !state.config_locked
+                && !state.claim_closed
+                && (state.claim_start_ts..=end).contains(&now)
+            {
+                let cutoff = now.saturating_add(state.close_grace).min(end);
+                state.claim_duration = cutoff - state.claim_start_ts;
+                cutoff
+            } else {
+                state.claim_closed = true;
+                if state.status != CampaignStatus::Ended {
+                    let changed = set_status(state, CampaignStatus::Ended, now);
+                    emit_cpi!(changed);
+                }
+                now
+            };
+            emit_cpi!(AirdropClosed {
+                authority: state.authority,
+                timestamp: now,
+                campaign: state.key(),
+                mint: state.mint,
+                root_version: state.root_version,
+                vault_balance: ctx.accounts.vault.amount,
+                closes_at,
+            });
+        })
+    }
+
+    /// Read-only snapshot of the campaign for frontends; call via simulation
+    /// and decode the return data as `AirdropInfo`.
+    pub fn get_airdrop_info(ctx: Context<GetAirdropInfo>) -> Result<AirdropInfo> {
+        let state = &ctx.accounts.state;
+        let now = Clock::get()?.unix_timestamp;
+        let end = claim_end_ts(state.claim_start_ts, state.claim_duration)?;
+        Ok(AirdropInfo {
+            version: state.version,
+            authority: state.authority,
+            mint: state.mint,
+            vault: state.vault,
+            snapshot_hash: state.snapshot_hash,
+            merkle_root: state.merkle_root,
+            round: state.round,
+            claim_start_ts: state.claim_start_ts,
+            claim_end_ts: end,
+            claim_closed: state.claim_closed,
+            needs_refill: state.needs_refill,
+            window_open: !state.claim_closed
+                && now >= state.claim_start_ts
+                && now <= end,
+            mint_on_claim: state.mint_on_claim,
+            total_claims: state.total_claims,
+            claim_capacity: state.claim_capacity,
+            claimed_count: state.claimed_count,
+            claimed_amount: state.claimed_amount,
+            total_allocation: state.total_allocation,
+            vault_balance: ctx.accounts.vault.amount,
+            allocations_hash: state.allocations_hash,
+            allocations_uri: String::from_utf8_lossy(&state.allocations_uri)
+                .trim_end_matches('\0')
+                .to_string(),
+            now,
+            status: state.status,
+        })
+    }
+
+    /// Dry run of `claim` for `wallet`: the same eligibility checks, run
+    /// against a scratch copy of the state, so nothing moves or changes. Call
+    /// via simulation and decode the return data as `ClaimValidation`.
+    pub fn validate_claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ValidateClaim<'info>>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<ClaimValidation> {
+        let now = Clock::get()?.unix_timestamp;
+        let checked = dry_run_claim(ctx.accounts, index, amount, &proof, proof_len, terms_hash, now)
+            .and_then(|(amount, fee, penalty)| {
+                // Fails as the claim would where only reserved or vesting
+                // tokens are left to cover it
+                let vault = select_vault(&ctx.accounts.state, &ctx.accounts.vault, ctx.remaining_accounts, amount)?;
+                require!(
+                    ctx.accounts.state.mint_on_claim || vault.amount >= amount,
+                    ErrorCode::VaultNeedsRefill
+                );
+                Ok((amount, fee, penalty))
+            });
+
+        Ok(match checked {
+            Ok((amount, fee, penalty)) => ClaimValidation {
+                error_code: 0,
+                fee,
+                penalty,
+                net_amount: amount - fee - penalty,
+            },
+            Err(Error::AnchorError(err)) => ClaimValidation {
+                error_code: err.error_code_number,
+                ..Default::default()
+            },
+            Err(err) => return Err(err),
+        })
+    }
+
+    /// Succeeds only if `snapshot_hash` is `merkle::snapshot_hash` of the
+    /// current root, `total_claims` and `total_allocation`. Append it to the
+    /// `initialize` (or rollover) transaction to refuse a snapshot and root
+    /// that came from different allocation files.
+    pub fn assert_snapshot_hash(ctx: Context<AssertSnapshotHash>) -> Result<()> {
+        let state = &ctx.accounts.state;
+        let expected = merkle::snapshot_hash(&state.merkle_root, state.total_claims, state.total_allocation);
+        require!(state.snapshot_hash == expected, ErrorCode::SnapshotMismatch);
+        Ok(())
+    }
+
+    /// Succeeds only if `index` is recorded as claimed in the current round.
+    /// Meant for CPI, so other programs can gate on claim status without
+    /// reading `State`, whose layout changes between versions.
+    pub fn assert_claimed(ctx: Context<ClaimStatus>, index: u64) -> Result<()> {
+        let state = &ctx.accounts.state;
+        check_status_index(state, index)?;
+        require!(is_claimed(state, index), ErrorCode::NotClaimed);
+        Ok(())
+    }
+
+    /// Succeeds only if `index` is not recorded as claimed in the current
+    /// round; the exact negation of `assert_claimed`.
+    pub fn assert_not_claimed(ctx: Context<ClaimStatus>, index: u64) -> Result<()> {
+        let state = &ctx.accounts.state;
+        check_status_index(state, index)?;
+        require!(!is_claimed(state, index), ErrorCode::AlreadyClaimed);
+        Ok(())
+    }
+
+    /// Permissionless: emits `UnclaimedIndices` for `[range_start, range_end)`,
+    /// a bitmap of the indices not recorded as claimed, so clawback and
+    /// rollover tooling can page through the round without decoding the
+    /// bitmap. Once the round has ended, a `cranker` exporting
+    /// the next page in order collects the crank bounty.
+    pub fn emit_unclaimed(ctx: Context<EmitUnclaimed>, range_start: u64, range_end: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &ctx.accounts.state;
+        require!(state.claim_ledger == Pubkey::default(), ErrorCode::CompressedLedgerRequired);
+        require_claims_converted(state)?;
+        require!(
+            range_start < range_end
+                && range_end <= state.total_claims
+                && range_end - range_start <= MAX_UNCLAIMED_PAGE,
+            ErrorCode::InvalidRange
+        );
+        let mut unclaimed = vec![0u8; (range_end - range_start).div_ceil(8) as usize];
+        let mut unclaimed_count = 0u32;
+        for index in range_start..range_end {
+            if !is_claimed(state, index) {
+                let offset = (index - range_start) as usize;
+                unclaimed[offset / 8] |= 1 << (offset % 8);
+                unclaimed_count += 1;
+            }
+        }
+        emit_cpi!(UnclaimedIndices {
+            round: state.round,
+            range_start,
+            range_end,
+            unclaimed_count,
+            unclaimed,
+        });
+        if let Some(cranker) = &ctx.accounts.cranker {
+            if take_export_page(&mut ctx.accounts.state, range_start, range_end, now)? {
+                let bounty = pay_crank_bounty(&ctx.accounts.state, &cranker.to_account_info())?;
+                if bounty > 0 {
+                    emit_cpi!(CrankBountyPaid {
+                        cranker: cranker.key(),
+                        lamports: bounty,
+                        timestamp: now,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Permissionless: closes the campaign once its window has elapsed, so
+    /// end-of-window behaviour doesn't depend on the authority showing up.
+    pub fn crank_close(ctx: Context<CrankClose>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.state;
+        require!(!state.claim_closed, ErrorCode::ClaimClosed);
+        require!(
+            now > claim_end_ts(state.claim_start_ts, state.claim_duration)?,
+            ErrorCode::CampaignStillActive
+        );
+        state.claim_closed = true;
+        let changed = set_status(state, CampaignStatus::Ended, now);
+        emit_cpi!(changed);
+        let authority = state.authority;
+        let bounty = pay_crank_bounty(&ctx.accounts.state, &ctx.accounts.cranker.to_account_info())?;
+        if bounty > 0 {
+            emit_cpi!(CrankBountyPaid {
+                cranker: ctx.accounts.cranker.key(),
+                lamports: bounty,
+                timestamp: now,
+            });
+        }
+
+        emit_cpi!(AirdropClosed {
+            authority,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: ctx.accounts.vault.amount,
+            closes_at: now,
+        });
+        Ok(())
+    }
+
+    /// Irreversibly disables `update_merkle_root` and `update_claim_window`,
+    /// so the committed allocations and window can't be changed after launch.
+    pub fn lock_config(ctx: Context<LockConfig>) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            require!(!state.config_locked, ErrorCode::ConfigLocked);
+            state.config_locked = true;
+            emit_cpi!(ConfigLocked {
+                merkle_root: state.merkle_root,
+                claim_start_ts: state.claim_start_ts,
+                claim_duration: state.claim_duration,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Hands the campaign over to its rules: the authority becomes the
+    /// default pubkey, which can't sign, so every `has_one = authority`
+    /// instruction is unreachable from then on. Claims, `crank_close` and
+    /// other permissionless paths keep working; treasury fees and the bonus
+    /// reserve can no longer be withdrawn.
+    pub fn renounce_authority(ctx: Context<RenounceAuthority>) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            let previous_authority = state.authority;
+            state.authority = Pubkey::default();
+            emit_cpi!(AuthorityRenounced {
+                previous_authority,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Registers a token account owned by the current `vault_auth` as a
+    /// failover vault; `claim` draws from extras in registration order.
+    pub fn add_funding_vault(ctx: Context<AddFundingVault>) -> Result<()> {
+        track_config!(ctx, {
+            let vault = ctx.accounts.vault.key();
+            let state = &mut ctx.accounts.state;
+            let count = state.extra_vault_count as usize;
+            require!(
+                vault != state.vault && !state.extra_vaults[..count].contains(&vault),
+                ErrorCode::InvalidVault
+            );
+            require!(count < MAX_EXTRA_VAULTS, ErrorCode::VaultSetFull);
+            state.extra_vaults[count] = vault;
+            state.extra_vault_count += 1;
+            emit_cpi!(FundingVaultUpdated {
+                vault,
+                registered: true,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Unregisters a failover vault; its balance stays with `vault_auth` and
+    /// it can be registered again.
+    pub fn remove_funding_vault(ctx: Context<RemoveFundingVault>, vault: Pubkey) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            let count = state.extra_vault_count as usize;
+            let pos = state.extra_vaults[..count]
+                .iter()
+                .position(|v| *v == vault)
+                .ok_or(ErrorCode::InvalidVault)?;
+            // Shift down rather than swap, so failover order is kept
+            state.extra_vaults.copy_within(pos + 1..count, pos);
+            state.extra_vaults[count - 1] = Pubkey::default();
+            state.extra_vault_count -= 1;
+            emit_cpi!(FundingVaultUpdated {
+                vault,
+                registered: false,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    pub fn set_crank_bounty(ctx: Context<SetCrankBounty>, lamports: u64) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            state.crank_bounty = lamports;
+            emit_cpi!(CrankBountyUpdated {
+                lamports,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    pub fn set_campaign_metadata(
+        ctx: Context<SetCampaignMetadata>,
+        name: String,
+        symbol: String,
+        description_uri: String,
+        image_uri: String,
+    ) -> Result<()> {
+        require!(
+            name.len() <= MAX_NAME_LEN
+                && symbol.len() <= MAX_SYMBOL_LEN
+                && description_uri.len() <= MAX_URI_LEN
+                && image_uri.len() <= MAX_URI_LEN,
+            ErrorCode::MetadataTooLong
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.state = ctx.accounts.state.key();
+        metadata.name = name;
+        metadata.symbol = symbol;
+        metadata.description_uri = description_uri;
+        metadata.image_uri = image_uri;
+        metadata.updated_ts = now;
+
+        emit_cpi!(CampaignMetadataUpdated {
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Zero hash disables the requirement.
+    pub fn set_terms_hash(ctx: Context<SetTermsHash>, terms_hash: [u8; 32]) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            state.terms_hash = terms_hash;
+            emit_cpi!(TermsHashUpdated {
+                terms_hash,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Non-zero switches the campaign to two-phase claims: `reserve_claim`
+    /// records the entitlement and `withdraw` pays it `dispute_period` later.
+    pub fn set_dispute_period(ctx: Context<SetDisputePeriod>, dispute_period: i64) -> Result<()> {
+        track_config!(ctx, {
+            require!(dispute_period >= 0, ErrorCode::InvalidDuration);
+            let state = &mut ctx.accounts.state;
+            state.dispute_period = dispute_period;
+            emit_cpi!(DisputePeriodUpdated {
+                dispute_period,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Basis-point cut of every claim paid to the treasury's token account;
+    /// zero disables it.
+    pub fn set_operator_fee(ctx: Context<SetOperatorFee>, fee_bps: u16) -> Result<()> {
+        track_config!(ctx, {
+            require!(fee_bps <= MAX_FEE_BPS, ErrorCode::FeeTooHigh);
+            let state = &mut ctx.accounts.state;
+            state.fee_bps = fee_bps;
+            state.fee_account = ctx.accounts.fee_account.key();
+            emit_cpi!(OperatorFeeUpdated {
+                fee_bps,
+                fee_account: state.fee_account,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Claims from `start_ts` on forfeit up to `penalty_bps` to the treasury,
+    /// reached linearly over `ramp` seconds; `penalty_bps` zero disables it.
+    /// Penalties go to the fee account `set_operator_fee` configured, which
+    /// this never changes.
+    pub fn set_late_penalty(
+        ctx: Context<SetLatePenalty>,
+        start_ts: i64,
+        ramp: i64,
+        penalty_bps: u16,
+    ) -> Result<()> {
+        track_config!(ctx, {
+            require!(ramp >= 0, ErrorCode::InvalidDuration);
+            require!(penalty_bps <= MAX_PENALTY_BPS, ErrorCode::PenaltyTooHigh);
+            let state = &mut ctx.accounts.state;
+            state.penalty_start_ts = start_ts;
+            state.penalty_ramp = ramp;
+            state.penalty_bps = penalty_bps;
+            emit_cpi!(LatePenaltyUpdated {
+                start_ts,
+                ramp,
+                penalty_bps,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Claims made before `end_ts` are topped up by `bonus_bps` of their
+    /// amount from the bonus reserve, while it lasts; `bonus_bps` zero
+    /// disables it. The reserve must exist (`init_bonus_vault`).
+    pub fn set_early_bonus(ctx: Context<SetEarlyBonus>, bonus_bps: u16, end_ts: i64) -> Result<()> {
+        track_config!(ctx, {
+            require!(bonus_bps <= MAX_BONUS_BPS, ErrorCode::BonusTooHigh);
+            let state = &mut ctx.accounts.state;
+            require!(state.bonus_vault != Pubkey::default(), ErrorCode::InvalidBonusVault);
+            state.bonus_bps = bonus_bps;
+            state.bonus_end_ts = end_ts;
+            emit_cpi!(EarlyBonusUpdated {
+                bonus_bps,
+                end_ts,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Creates the program-owned treasury that collects fees in kind (and any
+    /// lamports sent to it) for the operator to withdraw.
+    pub fn init_treasury(ctx: Context<InitTreasury>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.state = ctx.accounts.state.key();
+        treasury.token_account = ctx.accounts.treasury_tokens.key();
+        treasury.tokens_withdrawn = 0;
+        treasury.lamports_withdrawn = 0;
+        treasury.bump = ctx.bumps.treasury;
+        Ok(())
+    }
+
+    /// Sweeps accumulated fees: `token_amount` from the treasury's token
+    /// account and `lamports` above the treasury's rent-exempt floor.
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, token_amount: u64, lamports: u64) -> Result<()> {
+        let state_key = ctx.accounts.state.key();
+        let bump = ctx.accounts.treasury.bump;
+        let treasury_seeds = &[TREASURY_SEED, state_key.as_ref(), &[bump]];
+        let signer_seeds: &[&[&[u8]]] = &[treasury_seeds];
+
+        if token_amount > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from:      ctx.accounts.treasury_tokens.to_account_info(),
+                    to:        ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.treasury.to_account_info(),
+                    mint:      ctx.accounts.mint.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_ctx, token_amount, ctx.accounts.mint.decimals)?;
+        }
+
+        if lamports > 0 {
+            let treasury_info = ctx.accounts.treasury.to_account_info();
+            let rent_floor = Rent::get()?.minimum_balance(treasury_info.data_len());
+            require!(
+                treasury_info.lamports().saturating_sub(rent_floor) >= lamports,
+                ErrorCode::InsufficientTreasury
+            );
+            treasury_info.sub_lamports(lamports)?;
+            ctx.accounts.authority.add_lamports(lamports)?;
+        }
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.tokens_withdrawn = treasury.tokens_withdrawn
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        treasury.lamports_withdrawn = treasury.lamports_withdrawn
+            .checked_add(lamports)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit_cpi!(FeesWithdrawn {
+            destination: ctx.accounts.destination.key(),
+            token_amount,
+            lamports,
+            tokens_withdrawn: treasury.tokens_withdrawn,
+            lamports_withdrawn: treasury.lamports_withdrawn,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Creates the early-claim bonus reserve, a token account owned by the
+    /// state PDA. It is funded with a plain token transfer and is kept apart
+    /// from the claim vault so bonuses can never eat into base entitlements.
+    pub fn init_bonus_vault(ctx: Context<InitBonusVault>) -> Result<()> {
+        track_config!(ctx, {
+            ctx.accounts.state.bonus_vault = ctx.accounts.bonus_vault.key();
+        })
+    }
+
+    /// Returns unspent bonus reserve to the authority once the bonus window
+    /// has ended (or the bonus is disabled).
+    pub fn withdraw_bonus_reserve(ctx: Context<WithdrawBonusReserve>, amount: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &ctx.accounts.state;
+        require!(
+            state.bonus_bps == 0 || now >= state.bonus_end_ts,
+            ErrorCode::BonusWindowActive
+        );
+        // Lottery winners are paid from the reserve until it is switched off
+        require!(!state.bonus_lottery, ErrorCode::BonusLotteryActive);
+        pay_early_bonus(
+            state,
+            ctx.bumps.state,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            Some(&ctx.accounts.bonus_vault),
+            &ctx.accounts.destination,
+            amount,
+        )?;
+        emit_cpi!(BonusReserveWithdrawn {
+            destination: ctx.accounts.destination.key(),
+            amount,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Claims stop paying the early bonus directly and enter tickets for it
+    /// instead. Only between lotteries: every ticket must be redeemed.
+    pub fn set_bonus_lottery(ctx: Context<SetBonusLottery>, enabled: bool) -> Result<()> {
+        bonus_lottery::set_bonus_lottery(ctx, enabled)
+    }
+
+    /// Signed by the claimant, usually right after its claim.
+    pub fn enter_bonus_lottery(
+        ctx: Context<EnterBonusLottery>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+    ) -> Result<()> {
+        bonus_lottery::enter_bonus_lottery(ctx, index, amount, proof, proof_len)
+    }
+
+    /// Any slot after the ticket's, within the `SlotHashes` history.
+    pub fn reveal_bonus_ticket(ctx: Context<RevealBonusTicket>, index: u64) -> Result<()> {
+        bonus_lottery::reveal_bonus_ticket(ctx, index)
+    }
+
+    pub fn settle_bonus_lottery(ctx: Context<SettleBonusLottery>) -> Result<()> {
+        bonus_lottery::settle_bonus_lottery(ctx)
+    }
+
+    pub fn redeem_bonus_ticket(ctx: Context<RedeemBonusTicket>, index: u64) -> Result<()> {
+        bonus_lottery::redeem_bonus_ticket(ctx, index)
+    }
+
+    /// Creates the hourly stats ring buffer; from then on every claim path
+    /// must pass it so the counters can't be skipped.
+    pub fn init_claim_stats(ctx: Context<InitClaimStats>) -> Result<()> {
+        track_config!(ctx, {
+            let stats = &mut ctx.accounts.stats;
+            stats.state = ctx.accounts.state.key();
+            stats.bump = ctx.bumps.stats;
+            stats.buckets = [HourlyStats::default(); STATS_BUCKETS];
+            ctx.accounts.state.stats_enabled = true;
+        })
+    }
+
+    /// Stops one allocation (pending claim or escrowed reservation) without
+    /// pausing the campaign.
+    pub fn freeze_index(ctx: Context<FreezeIndex>, index: u64) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            if !is_frozen(state, index) {
+                let count = state.frozen_count as usize;
+                require!(count < MAX_FROZEN_INDICES, ErrorCode::FrozenSetFull);
+                state.frozen_indices[count] = index;
+                state.frozen_count += 1;
+            }
+            emit_cpi!(IndexFreezeUpdated {
+                index,
+                frozen: true,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Sets the smallest claimable amount and the pool that dust below it
+    /// is swept to.
+    pub fn set_dust_threshold(
+        ctx: Context<SetDustThreshold>,
+        dust_threshold: u64,
+        community_pool: Pubkey,
+    ) -> Result<()> {
+        dust_sweep::set_dust_threshold(ctx, dust_threshold, community_pool)
+    }
+
+    /// Pays a batch of dust leaves to the community pool; anyone can crank it.
+    pub fn sweep_dust(ctx: Context<SweepDust>, leaves: Vec<DustLeaf>) -> Result<()> {
+        dust_sweep::sweep_dust(ctx, leaves)
+    }
+
+    /// Retires an allocation for good; its tokens stay in the unclaimed supply.
+    pub fn void_index(ctx: Context<VoidIndex>, index: u64, reason_hash: [u8; 32]) -> Result<()> {
+        voided_indices::void_index(ctx, index, reason_hash)
+    }
+
+    pub fn unfreeze_index(ctx: Context<UnfreezeIndex>, index: u64) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            let count = state.frozen_count as usize;
+            if let Some(pos) = state.frozen_indices[..count].iter().position(|i| *i == index) {
+                // Swap-remove keeps the live entries contiguous
+                state.frozen_indices[pos] = state.frozen_indices[count - 1];
+                state.frozen_indices[count - 1] = 0;
+                state.frozen_count -= 1;
+            }
+            emit_cpi!(IndexFreezeUpdated {
+                index,
+                frozen: false,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Zero disables the threshold.
+    pub fn set_low_water_mark(ctx: Context<SetLowWaterMark>, low_water_mark: u64) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            state.low_water_mark = low_water_mark;
+            emit_cpi!(LowWaterMarkUpdated {
+                low_water_mark,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Narrows how far ahead `update_claim_window`, `finalize_and_rollover`,
+    /// `create_epoch` and `set_cohort` accept a window start. Zero restores
+    /// the ten-year default.
+    pub fn set_max_start_lead(ctx: Context<SetMaxStartLead>, max_start_lead: i64) -> Result<()> {
+        track_config!(ctx, {
+            require!(
+                (0..=MAX_WINDOW_DRIFT).contains(&max_start_lead),
+                ErrorCode::InvalidStartLead
+            );
+            let state = &mut ctx.accounts.state;
+            require!(!state.config_locked, ErrorCode::ConfigLocked);
+            state.max_start_lead = max_start_lead;
+            emit_cpi!(MaxStartLeadUpdated {
+                max_start_lead,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Seconds `close_airdrop` keeps an open window running before claims
+    /// stop, so transactions already in flight don't fail. Zero closes at once,
+    /// as does a campaign under `lock_config`.
+    pub fn set_close_grace(ctx: Context<SetCloseGrace>, close_grace: i64) -> Result<()> {
+        track_config!(ctx, {
+            require!((0..=MAX_CLOSE_GRACE).contains(&close_grace), ErrorCode::InvalidCloseGrace);
+            let state = &mut ctx.accounts.state;
+            state.close_grace = close_grace;
+            emit_cpi!(CloseGraceUpdated {
+                close_grace,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Caps what one wallet can claim per round, summed over all its leaves.
+    /// Zero lifts the cap; claims then only keep the running totals.
+    pub fn set_wallet_cap(ctx: Context<SetWalletCap>, wallet_cap: u64) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            state.wallet_cap = wallet_cap;
+            emit_cpi!(WalletCapUpdated {
+                wallet_cap,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Permissionless: opens `wallet`'s running total. Claimants (or a
+    /// frontend) create it ahead of, or in the same transaction as, their
+    /// first claim under a cap.
+    pub fn init_wallet_claims(ctx: Context<InitWalletClaims>, wallet: Pubkey) -> Result<()> {
+        let wallet_claims = &mut ctx.accounts.wallet_claims;
+        wallet_claims.state = ctx.accounts.state.key();
+        wallet_claims.wallet = wallet;
+        wallet_claims.round = ctx.accounts.state.round;
+        wallet_claims.claimed = 0;
+        wallet_claims.bump = ctx.bumps.wallet_claims;
+        Ok(())
+    }
+
+    /// Re-opens claims paused by the low-water mark once the vault is topped up.
+    pub fn resume_claims(ctx: Context<ResumeClaims>) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            let vault_balance = ctx.accounts.vault.amount;
+            require!(vault_balance >= state.low_water_mark, ErrorCode::VaultNeedsRefill);
+            state.needs_refill = false;
+            emit_cpi!(ClaimsResumed {
+                vault_balance,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// `GateKind::Token`: `gate_key` is the gating mint. `GateKind::Collection`:
+    /// `gate_key` is the verified collection mint. `GateKind::None` lifts the gate.
+    pub fn set_claim_gate(
+        ctx: Context<SetClaimGate>,
+        kind: GateKind,
+        gate_key: Pubkey,
+        min_amount: u64,
+    ) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            state.gate_kind = kind;
+            state.gate_key = gate_key;
+            state.gate_min_amount = min_amount;
+            emit_cpi!(ClaimGateUpdated {
+                kind,
+                gate_key,
+                min_amount,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    // Compiled out of `immutable` builds, like `update_merkle_root`
+    #[cfg(not(feature = "immutable"))]
+    pub fn update_claim_window(
+        ctx: Context<UpdateClaimWindow>,
+        new_start_ts: i64,
+        new_duration: i64,
+    ) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            require!(
+                ctx.accounts.authority.key() == state.authority,
+                ErrorCode::Unauthorized
+            );
+            require!(!state.config_locked, ErrorCode::ConfigLocked);
+            require!(state.status != CampaignStatus::Finalized, ErrorCode::InvalidStatus);
+            let now = Clock::get()?.unix_timestamp;
+            check_claim_window(new_start_ts, new_duration, now, max_start_lead(state))?;
+            state.claim_closed = false;
+            state.claim_start_ts = new_start_ts;
+            state.claim_duration = new_duration;
+            // Reopening an ended round makes it live again
+            if state.status == CampaignStatus::Ended {
+                let changed = set_status(state, CampaignStatus::Live, now);
+                emit_cpi!(changed);
+            }
+            emit_cpi!(ClaimWindowUpdated {
+                new_start_ts,
+                new_duration,
+                timestamp: now,
+            });
+        })
+    }
+
+    #[cfg(not(feature = "immutable"))]
+    pub fn update_merkle_root(
+        ctx: Context<UpdateMerkleRoot>,
+        new_root: [u8; 32],
+        new_total_claims: u64,
+        new_allocations_hash: [u8; 32],
+        new_allocations_uri: String,
+    ) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            require!(
+                ctx.accounts.authority.key() == state.authority,
+                ErrorCode::Unauthorized
+            );
+            require!(!state.config_locked, ErrorCode::ConfigLocked);
+            // Swapping the root under in-flight claims races them against the new
+            // allocations; claims have to be stopped first
+            require!(state.claim_closed || state.needs_refill, ErrorCode::ClaimsNotPaused);
+            require!(
+                new_total_claims <= state.claim_capacity,
+                ErrorCode::CapacityExceeded
+            );
+            let old_root = state.merkle_root;
+            state.merkle_root = new_root;
+            state.root_version += 1;
+            // A replaced tree shares no prefix with the old one
+            state.prior_roots = [[0; 32]; MAX_ROOT_APPENDS];
+            state.prior_root_count = 0;
+            state.total_claims = new_total_claims;
+            state.allocations_hash = new_allocations_hash;
+            state.allocations_uri = pad_uri(&new_allocations_uri)?;
+            emit_cpi!(MerkleRootUpdated {
+                old_root,
+                new_root,
+                new_total_claims,
+                new_allocations_hash,
+                new_allocations_uri,
+                timestamp: Clock::get()?.unix_timestamp,
+                campaign: state.key(),
+                mint: state.mint,
+                root_version: state.root_version,
+                vault_balance: ctx.accounts.vault.amount,
+            });
+        })
+    }
+
+    /// Adds leaves after the last one without invalidating published proofs.
+    #[cfg(not(feature = "immutable"))]
+    pub fn append_allocations(
+        ctx: Context<AppendAllocations>,
+        segment_root: [u8; 32],
+        segment_claims: u64,
+        segment_amount: u64,
+        new_allocations_hash: [u8; 32],
+        new_allocations_uri: String,
+    ) -> Result<()> {
+        root_appends::append_allocations(
+            ctx,
+            segment_root,
+            segment_claims,
+            segment_amount,
+            new_allocations_hash,
+            new_allocations_uri,
+        )
+    }
+
+    /// Leaves are hashed one way for the whole campaign, so the encoding
+    /// changes under the same conditions as the root it describes, and is
+    /// compiled out of `immutable` builds with it.
+    #[cfg(not(feature = "immutable"))]
+    pub fn set_leaf_encoding(ctx: Context<SetLeafEncoding>, encoding: LeafEncoding) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            require!(!state.config_locked, ErrorCode::ConfigLocked);
+            require!(state.claim_closed || state.needs_refill, ErrorCode::ClaimsNotPaused);
+            state.leaf_encoding = encoding;
+            emit_cpi!(LeafEncodingUpdated {
+                encoding,
+                merkle_root: state.merkle_root,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    #[cfg(not(feature = "immutable"))]
+    pub fn set_usd_pricing(
+        ctx: Context<SetUsdPricing>,
+        price_feed: [u8; 32],
+        max_price_age: u64,
+        max_price_conf_bps: u16,
+    ) -> Result<()> {
+        usd_pricing::set_usd_pricing(ctx, price_feed, max_price_age, max_price_conf_bps)
+    }
+
+    pub fn set_cohort(
+        ctx: Context<SetCohort>,
+        cohort_id: u32,
+        claim_start_ts: i64,
+        claim_duration: i64,
+        schedule: VestingSchedule,
+    ) -> Result<()> {
+        cohorts::set_cohort(ctx, cohort_id, claim_start_ts, claim_duration, schedule)
+    }
+
+    /// Pays cohort `cohort_id` from its own vault, clawed back with
+    /// `clawback_cohort` after `clawback_ts` (zero = never).
+    pub fn set_cohort_vault(
+        ctx: Context<SetCohortVault>,
+        cohort_id: u32,
+        clawback_ts: i64,
+    ) -> Result<()> {
+        cohort_vaults::set_cohort_vault(ctx, cohort_id, clawback_ts)
+    }
+
+    pub fn clawback_cohort(ctx: Context<ClawbackCohort>, cohort_id: u32) -> Result<()> {
+        cohort_vaults::clawback_cohort(ctx, cohort_id)
+    }
+
+    /// Sets the claim bitmap to `new_capacity` bits. An account grows by at
+    /// most 10KiB per instruction, so a bitmap grows by at most 81,920 bits
+    /// per call, and by `MAX_LEGACY_CONVERSION` leaves while a migration's
+    /// `LegacyClaims` are being converted.
+    pub fn resize_state(ctx: Context<ResizeState>, new_capacity: u64) -> Result<()> {
+        track_config!(ctx, {
+            let state = &mut ctx.accounts.state;
+            let old_capacity = state.claim_capacity;
+            require!(
+                new_capacity > 0 && new_capacity <= MAX_CLAIM_CAPACITY,
+                ErrorCode::InvalidCapacity
+            );
+            // Growing may take several calls; shrinking stops at the round's leaves
+            require!(
+                new_capacity > old_capacity || new_capacity >= state.total_claims,
+                ErrorCode::InvalidCapacity
+            );
+            // The tree, not the bitmap, records a compressed campaign's claims
+            require!(state.claim_ledger == Pubkey::default(), ErrorCode::CompressedLedgerRequired);
+            // Bitmap bits are positional, so recorded claims carry over
+            state.claim_bitmap.resize(bitmap_bytes(new_capacity), 0);
+            state.claim_capacity = new_capacity;
+
+            // Pending claims from before migration cover the leaves just added
+            if state.legacy_claims {
+                let legacy_claims = ctx
+                    .accounts
+                    .legacy_claims
+                    .as_ref()
+                    .ok_or(ErrorCode::LeavesPastClaimBitmap)?;
+                let converted_to = new_capacity.min(state.total_claims);
+                require!(
+                    converted_to - old_capacity <= MAX_LEGACY_CONVERSION,
+                    ErrorCode::InvalidCapacity
+                );
+                legacy_claims.convert(&mut state.claim_bitmap, old_capacity, converted_to);
+                if converted_to == state.total_claims {
+                    state.legacy_claims = false;
+                    legacy_claims.close(ctx.accounts.authority.to_account_info())?;
+                }
+            }
+
+            emit_cpi!(StateResized {
+                old_capacity,
+                new_capacity,
+                space: state_space(new_capacity) as u64,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        })
+    }
+
+    /// Moves a campaign from the layout before `State::version` to the
+    /// current one. A round with claims on leaves past the new bitmap passes
+    /// `legacy_claims` to keep them until `resize_state` converts them.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let info = ctx.accounts.state.to_account_info();
+        let data_len = info.data_len();
+
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 9 && &data[..8] == State::DISCRIMINATOR,
+                ErrorCode::UnknownStateLayout
+            );
+            if data_len != LEGACY_STATE_SPACE {
+                require!(data[8] != STATE_VERSION, ErrorCode::AlreadyMigrated);
+                return err!(ErrorCode::UnknownStateLayout);
+            }
+            LegacyState::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(
+            legacy.authority,
+            ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        // The old layout read an index as claimed when its three residues
+        // were; the bitmap starts from that for the leaves it covers
+        let legacy_claims = LegacyClaims::of(&legacy);
+        let first_capacity = if legacy_claims.is_empty() {
+            MAX_INIT_CLAIM_CAPACITY
+        } else {
+            MAX_LEGACY_CONVERSION
+        };
+        let claim_capacity = legacy.total_claims.min(first_capacity);
+        let mut migrated = State {
+            version: STATE_VERSION,
+            authority: legacy.authority,
+            snapshot_hash: legacy.snapshot_hash,
+            claim_start_ts: legacy.claim_start_ts,
+            claim_duration: legacy.claim_duration,
+            claim_closed: legacy.claim_closed,
+            merkle_root: legacy.merkle_root,
+            total_claims: legacy.total_claims,
+            claim_capacity,
+            status: if legacy.claim_closed { CampaignStatus::Ended } else { CampaignStatus::Live },
+            claim_bitmap: vec![0; bitmap_bytes(claim_capacity)],
+            ..Default::default()
+        };
+        legacy_claims.convert(&mut migrated.claim_bitmap, 0, claim_capacity);
+        let pending = claim_capacity < legacy.total_claims && !legacy_claims.is_empty();
+        require!(
+            ctx.accounts.legacy_claims.is_some() == pending,
+            ErrorCode::LeavesPastClaimBitmap
+        );
+        if let Some(account) = ctx.accounts.legacy_claims.as_mut() {
+            account.set_inner(legacy_claims);
+            migrated.legacy_claims = true;
+        }
+
+        // The old layout didn't pin the vault; take the canonical ATA of the
+        // current snapshot's vault authority
+        let (vault_auth, _) = Pubkey::find_program_address(
+            &[VAULT_SEED, migrated.snapshot_hash.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            ctx.accounts.vault.key(),
+            get_associated_token_address(&vault_auth, &ctx.accounts.vault.mint),
+            ErrorCode::InvalidVault
+        );
+        migrated.mint = ctx.accounts.vault.mint;
+        migrated.vault = ctx.accounts.vault.key();
+
+        let new_len = state_space(claim_capacity);
+        let rent_due = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(info.lamports());
+        if rent_due > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to:   info.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        info.realloc(new_len, true)?;
+        migrated.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit_cpi!(StateMigrated {
+            from_len: data_len as u64,
+            to_len: new_len as u64,
+            version: STATE_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn close_state(ctx: Context<CloseState>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(
+            ctx.accounts.authority.key() == state.authority,
+            ErrorCode::Unauthorized
+        );
+        // Only a finalized round, or one that was never funded, can go
+        require!(
+            matches!(state.status, CampaignStatus::Draft | CampaignStatus::Finalized),
+            ErrorCode::InvalidStatus
+        );
+    
+        // By default, Anchor's `#[account(close = recipient)]` will transfer 
+        // the lamports of `state` to the `recipient` account 
+        // and mark `state` as closed (so no more rent).
+        Ok(())
+    }
+
+    /// Permissionless: takes the next lifecycle transition the campaign's
+    /// funding and window allow.
+    pub fn advance_status(ctx: Context<AdvanceStatus>) -> Result<()> {
+        lifecycle::advance_status(ctx)
+    }
+
+    /// Permissionless once the round has ended: records its outcome in a
+    /// permanent `Summary` account and marks it Finalized.
+    pub fn finalize_airdrop(ctx: Context<FinalizeAirdrop>) -> Result<()> {
+        campaign_summary::finalize_airdrop(ctx)
+    }
+
+    // Swaps in a new root, so compiled out of `immutable` builds
+    #[cfg(not(feature = "immutable"))]
+    pub fn finalize_and_rollover(
+        ctx: Context<FinalizeAndRollover>,
+        new_snapshot_hash: [u8; 32],
+        new_merkle_root: [u8; 32],
+        new_total_claims: u64,
+        new_start_ts: i64,
+        new_duration: i64,
+        new_allocations_hash: [u8; 32],
+        new_allocations_uri: String,
+        new_total_allocation: u64,
+    ) -> Result<()> {
+        track_config!(ctx, {
+            let now = Clock::get()?.unix_timestamp;
+            check_claim_window(new_start_ts, new_duration, now, max_start_lead(&ctx.accounts.state))?;
+            require!(new_total_allocation > 0, ErrorCode::InvalidTotalAllocation);
+
+            let state = &mut ctx.accounts.state;
+            require!(
+                new_total_claims <= state.claim_capacity,
+                ErrorCode::CapacityExceeded
+            );
+            require!(
+                state.claim_closed || now > claim_end_ts(state.claim_start_ts, state.claim_duration)?,
+                ErrorCode::CampaignStillActive
+            );
+            require!(
+                new_snapshot_hash != state.snapshot_hash,
+                ErrorCode::SnapshotReused
+            );
+            // The mint authority is this round's vault_auth; a new snapshot would
+            // derive a vault_auth that can't mint.
+            require!(!state.mint_on_claim, ErrorCode::MintAuthorityMismatch);
+
+            // Archive the finished round. The bitmap can outgrow what an account
+            // can be created with, so the summary commits to it by hash; the
+            // bitmap itself is in the state's history and `emit_unclaimed` pages.
+            require_claims_converted(state)?;
+            require!(state.escrowed_amount == 0, ErrorCode::PendingEscrows);
+            require!(state.vesting_locked == 0, ErrorCode::PendingVesting);
+            require!(state.extra_vault_count == 0, ErrorCode::ExtraVaultsRegistered);
+            require!(state.cohort_vault_count == 0, ErrorCode::CohortVaultsRegistered);
+            require!(!state.bonus_lottery, ErrorCode::BonusLotteryActive);
+            // Prizes are paid from this round's vault
+            require!(!raffle_open(state, now), ErrorCode::RaffleOpen);
+            // The bitmap is zeroed below, so its unclaimed indices must have been
+            // paged out first; a compressed round's ledger account keeps them
+            require!(
+                state.claim_ledger != Pubkey::default()
+                    || state.unclaimed_export_cursor >= state.total_claims,
+                ErrorCode::ExportIncomplete
+            );
+            let unclaimed_amount = ctx.accounts.vault.amount;
+            let summary = &mut ctx.accounts.summary;
+            summary.round = state.round;
+            summary.snapshot_hash = state.snapshot_hash;
+            summary.merkle_root = state.merkle_root;
+            summary.total_claims = state.total_claims;
+            summary.unclaimed_amount = unclaimed_amount;
+            summary.finalized_ts = now;
+            summary.claim_bitmap_hash =
+                anchor_lang::solana_program::keccak::hash(&state.claim_bitmap).to_bytes();
+
+            // Move the leftover supply under the next round's vault authority
+            if unclaimed_amount > 0 {
+                let bump = ctx.bumps.vault_auth;
+                let vault_seeds = &[
+                    VAULT_SEED,
+                    state.snapshot_hash.as_ref(),
+                    &[bump],
+                ];
+                let signer_seeds: &[&[&[u8]]] = &[vault_seeds];
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from:      ctx.accounts.vault.to_account_info(),
+                        to:        ctx.accounts.new_vault.to_account_info(),
+                        authority: ctx.accounts.vault_auth.to_account_info(),
+                        mint:      ctx.accounts.mint.to_account_info(),
+                    },
+                    signer_seeds,
+                );
+                token::transfer_checked(cpi_ctx, unclaimed_amount, ctx.accounts.mint.decimals)?;
+            }
+
+            // Seed the follow-up round in place
+            let previous_round = state.round;
+            state.round = previous_round + 1;
+            state.snapshot_hash = new_snapshot_hash;
+            state.merkle_root = new_merkle_root;
+            state.root_version += 1;
+            state.prior_roots = [[0; 32]; MAX_ROOT_APPENDS];
+            state.prior_root_count = 0;
+            state.total_claims = new_total_claims;
+            state.allocations_hash = new_allocations_hash;
+            state.allocations_uri = pad_uri(&new_allocations_uri)?;
+            state.claim_start_ts = new_start_ts;
+            state.claim_duration = new_duration;
+            state.claim_closed = false;
+            state.vault = ctx.accounts.new_vault.key();
+            state.claimed_count = 0;
+            state.claimed_amount = 0;
+            state.unclaimed_export_cursor = 0;
+            state.total_allocation = new_total_allocation;
+            state.needs_refill = false;
+            state.frozen_indices = [0; MAX_FROZEN_INDICES];
+            state.frozen_count = 0;
+            state.raffle_deadline = 0;
+            state.scheduled_root_count = 0;
+            // The tree's leaves are keyed by this round's indices; a new round
+            // starts on the bitmap until `init_compressed_ledger` runs again
+            if state.claim_ledger != Pubkey::default() {
+                state.claim_ledger = Pubkey::default();
+                state.claim_capacity = state.claim_bitmap.len() as u64 * 8;
+                // Checked above against the ledger's capacity, which can be larger
+                require!(
+                    state.total_claims <= state.claim_capacity,
+                    ErrorCode::CapacityExceeded
+                );
+            }
+            state.claim_bitmap.fill(0);
+            // The new vault only holds what was just rolled into it
+            let funded = is_funded(state, ctx.accounts.new_vault.amount.saturating_add(unclaimed_amount));
+            let status = if funded { CampaignStatus::Funded } else { CampaignStatus::Draft };
+            let changed = set_status(state, status, now);
+            emit_cpi!(changed);
+
+            emit_cpi!(CampaignRolledOver {
+                previous_round,
+                previous_snapshot_hash: summary.snapshot_hash,
+                new_snapshot_hash,
+                new_merkle_root,
+                rolled_amount: unclaimed_amount,
+                timestamp: now,
+            });
+        })
+    }
+
+    /// Re-keys the running round under a new snapshot hash: the vault balance
+    /// moves to the ATA of the new `vault_auth` (and, for mint-on-claim
+    /// campaigns, mint authority follows), and the emptied vault is closed.
+    /// The claim ledger lives on the state and is kept. Not after `lock_config`.
+    pub fn rotate_vault_authority(
+        ctx: Context<RotateVaultAuthority>,
+        new_snapshot_hash: [u8; 32],
+    ) -> Result<()> {
+        track_config!(ctx, {
+            let now = Clock::get()?.unix_timestamp;
+            let state = &ctx.accounts.state;
+            require!(
+                new_snapshot_hash != state.snapshot_hash,
+                ErrorCode::SnapshotReused
+            );
+            // A locked campaign's snapshot and vault are as committed as its root
+            require!(!state.config_locked, ErrorCode::ConfigLocked);
+            // Past the window, `finalize_and_rollover` is the way to a new snapshot
+            require!(
+                now <= claim_end_ts(state.claim_start_ts, state.claim_duration)?,
+                ErrorCode::WindowEnded
+            );
+            // Escrow and vesting PDAs are keyed by the snapshot hash
+            require!(state.escrowed_amount == 0, ErrorCode::PendingEscrows);
+            require!(state.vesting_locked == 0, ErrorCode::PendingVesting);
+            // Extra and cohort vaults belong to the current vault_auth and wouldn't move
+            require!(state.extra_vault_count == 0, ErrorCode::ExtraVaultsRegistered);
+            require!(state.cohort_vault_count == 0, ErrorCode::CohortVaultsRegistered);
+            require!(!state.bonus_lottery, ErrorCode::BonusLotteryActive);
+            // The raffle is keyed by the snapshot hash and pays from this vault
+            require!(!raffle_open(state, now), ErrorCode::RaffleOpen);
+
+            let vault_seeds = &[
+                VAULT_SEED,
+                state.snapshot_hash.as_ref(),
+                &[ctx.bumps.vault_auth],
+            ];
+            let signer_seeds: &[&[&[u8]]] = &[vault_seeds];
+            let balance = ctx.accounts.vault.amount;
+            if balance > 0 {
+                token::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        TransferChecked {
+                            from:      ctx.accounts.vault.to_account_info(),
+                            to:        ctx.accounts.new_vault.to_account_info(),
+                            authority: ctx.accounts.vault_auth.to_account_info(),
+                            mint:      ctx.accounts.mint.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    balance,
+                    ctx.accounts.mint.decimals,
+                )?;
+            }
+            token::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account:     ctx.accounts.vault.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority:   ctx.accounts.vault_auth.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+            if state.mint_on_claim {
+                token::set_authority(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        SetAuthority {
+                            current_authority: ctx.accounts.vault_auth.to_account_info(),
+                            account_or_mint:   ctx.accounts.mint.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    AuthorityType::MintTokens,
+                    Some(ctx.accounts.new_vault_auth.key()),
+                )?;
+            }
+
+            let state = &mut ctx.accounts.state;
+            let old_snapshot_hash = state.snapshot_hash;
+            state.snapshot_hash = new_snapshot_hash;
+            state.vault = ctx.accounts.new_vault.key();
+            state.raffle_deadline = 0;
+            emit_cpi!(VaultAuthorityRotated {
+                old_snapshot_hash,
+                new_snapshot_hash,
+                old_vault_auth: ctx.accounts.vault_auth.key(),
+                new_vault_auth: ctx.accounts.new_vault_auth.key(),
+                new_vault: state.vault,
+                moved_amount: balance,
+                timestamp: now,
+            });
+        })
+    }
+
+    /// Opens a recurring distribution period with its own root, window and
+    /// claim ledger, paid from the campaign vault.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_epoch(
+        ctx: Context<CreateEpoch>,
+        epoch: u64,
+        merkle_root: [u8; 32],
+        total_claims: u64,
+        claim_start_ts: i64,
+        claim_duration: i64,
+        total_allocation: u64,
+    ) -> Result<()> {
+        let max_lead = max_start_lead(&ctx.accounts.state);
+        check_claim_window(claim_start_ts, claim_duration, Clock::get()?.unix_timestamp, max_lead)?;
+        require!(
+            total_claims > 0 && total_claims <= MAX_INIT_CLAIM_CAPACITY,
+            ErrorCode::InvalidCapacity
+        );
+        require!(total_allocation > 0, ErrorCode::InvalidTotalAllocation);
+
+        let epoch_root = &mut ctx.accounts.epoch_root;
+        epoch_root.state = ctx.accounts.state.key();
+        epoch_root.epoch = epoch;
+        epoch_root.merkle_root = merkle_root;
+        epoch_root.total_claims = total_claims;
+        epoch_root.claim_start_ts = claim_start_ts;
+        epoch_root.claim_duration = claim_duration;
+        epoch_root.total_allocation = total_allocation;
+        epoch_root.claimed_amount = 0;
+        epoch_root.claimed_count = 0;
+        epoch_root.claim_bitmap = vec![0; bitmap_bytes(total_claims)];
+        epoch_root.bump = ctx.bumps.epoch_root;
+
+        emit_cpi!(EpochCreated {
+            epoch,
+            merkle_root,
+            total_claims,
+            claim_start_ts,
+            claim_duration,
+            total_allocation,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn claim_epoch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimEpoch<'info>>,
+        epoch: u64,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.state.dispute_period == 0, ErrorCode::EscrowRequired);
+        let wallet = ctx.accounts.wallet.key;
+        admit_claim(
+            &mut ctx.accounts.state,
+            ClaimChecks {
+                claimant: wallet,
+                stats: ctx.accounts.stats.as_mut(),
+                wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+                family_claim: ctx.accounts.family_claim.as_deref(),
+                gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+                gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+            },
+            now,
+            |state: &mut State| {
+                record_epoch_claim(
+                    state,
+                    &mut ctx.accounts.epoch_root,
+                    index,
+                    wallet,
+                    amount,
+                    &proof,
+                    proof_len,
+                    terms_hash,
+                    now,
+                )?;
+                Ok(amount)
+            },
+        )?;
+        // Epoch rounds carry the operator fee but no late penalty or early bonus
+        let fee = take_operator_fee(&mut ctx.accounts.state, amount)?;
+        let vault = select_vault(&ctx.accounts.state, &ctx.accounts.vault, ctx.remaining_accounts, amount)?;
+        pay_out(
+            &ctx.accounts.state,
+            &ClaimPayout {
+                state_bump: ctx.bumps.state,
+                vault_auth_bump: ctx.bumps.vault_auth,
+                vault_auth: &ctx.accounts.vault_auth,
+                vault: &ctx.accounts.vault,
+                extra_vaults: ctx.remaining_accounts,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                recipient: &ctx.accounts.user_ata,
+                fee_account: ctx.accounts.fee_account.as_ref(),
+                bonus_vault: None,
+                relayer: None,
+                badge_mint: ctx.accounts.badge_mint.as_ref(),
+                badge_account: ctx.accounts.badge_account.as_ref(),
+                token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+            },
+            &vault,
+            amount - fee,
+            0,
+            fee,
+        )?;
+        if let Some(vault_balance) = trip_low_water_mark(&mut ctx.accounts.state, vault.amount, amount) {
+            emit_cpi!(VaultBelowLowWaterMark {
+                vault_balance,
+                low_water_mark: ctx.accounts.state.low_water_mark,
+                timestamp: now,
+            });
+        }
+
+        emit_cpi!(EpochClaimed {
+            epoch,
+            wallet: *ctx.accounts.wallet.key,
+            amount,
+            fee,
+            index,
+            terms_hash,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &vault, amount),
+        });
+        Ok(())
+    }
+
+    /// Commits to a freshly created Switchboard randomness account once the
+    /// window is over. The whole vault balance becomes the prize pool, split
+    /// evenly across `winner_count` prizes that stay claimable for
+    /// `RAFFLE_CLAIM_PERIOD`.
+    pub fn request_raffle(ctx: Context<RequestRaffle>, winner_count: u32) -> Result<()> {
+        let state = &ctx.accounts.state;
+        let clock = Clock::get()?;
+        require!(!state.mint_on_claim, ErrorCode::RaffleUnavailable);
+        require!(
+            state.claim_closed
+                || clock.unix_timestamp > claim_end_ts(state.claim_start_ts, state.claim_duration)?,
+            ErrorCode::CampaignStillActive
+        );
+        // Winners are drawn from the claim bitmap
+        require!(state.claim_ledger == Pubkey::default(), ErrorCode::CompressedLedgerRequired);
+        require_claims_converted(state)?;
+        require!(
+            winner_count > 0
+                && winner_count as usize <= MAX_RAFFLE_WINNERS
+                && (winner_count as u64) <= state.claimed_count,
+            ErrorCode::InvalidWinnerCount
+        );
+
+        // Randomness must be committed in the previous slot and not yet revealed
+        let randomness = RandomnessAccountData::parse(ctx.accounts.randomness_account.data.borrow())
+            .map_err(|_| ErrorCode::InvalidRandomness)?;
+        require!(
+            randomness.seed_slot == clock.slot.saturating_sub(1),
+            ErrorCode::RandomnessAlreadyRevealed
+        );
+
+        // Reserved and vesting allocations aren't unclaimed
+        let pool_amount = unreserved_balance(state, &ctx.accounts.vault);
+        let raffle = &mut ctx.accounts.raffle;
+        raffle.snapshot_hash = state.snapshot_hash;
+        raffle.randomness_account = ctx.accounts.randomness_account.key();
+        raffle.commit_slot = randomness.seed_slot;
+        raffle.winner_count = winner_count;
+        raffle.pool_amount = pool_amount;
+        raffle.prize_amount = pool_amount / winner_count as u64;
+        raffle.paid_amount = 0;
+        raffle.randomness = [0; 32];
+        raffle.revealed = false;
+        raffle.draws = 0;
+        raffle.winners = [0; MAX_RAFFLE_WINNERS];
+        raffle.drawn = 0;
+        raffle.paid_count = 0;
+        raffle.settled = false;
+        raffle.bump = ctx.bumps.raffle;
+        ctx.accounts.state.raffle_deadline = clock.unix_timestamp.saturating_add(RAFFLE_CLAIM_PERIOD);
+
+        emit_cpi!(RaffleRequested {
+            snapshot_hash: raffle.snapshot_hash,
+            randomness_account: raffle.randomness_account,
+            commit_slot: raffle.commit_slot,
+            winner_count,
+            pool_amount,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Permissionless: reads the revealed value from the committed randomness
+    /// account, then draws winners from it until `winner_count` distinct
+    /// claimed indices have won. Ticket `n` lands on index
+    /// `raffle_ticket(randomness, n) % total_claims`; tickets landing on an
+    /// unclaimed index, a voided one or an earlier winner are redrawn. Each
+    /// call draws up to `MAX_RAFFLE_DRAWS` tickets, so a sparse round takes
+    /// several. Remaining accounts are the `VoidedIndex` addresses of the
+    /// claimed indices the call's tickets land on, in draw order; the call
+    /// stops early when they run out.
+    pub fn settle_raffle(ctx: Context<SettleRaffle>) -> Result<()> {
+        let clock = Clock::get()?;
+        let state = &ctx.accounts.state;
+        let raffle = &mut ctx.accounts.raffle;
+        require!(!raffle.settled, ErrorCode::RaffleAlreadySettled);
+        require!(raffle_open(state, clock.unix_timestamp), ErrorCode::RaffleExpired);
+
+        if !raffle.revealed {
+            let randomness = RandomnessAccountData::parse(ctx.accounts.randomness_account.data.borrow())
+                .map_err(|_| ErrorCode::InvalidRandomness)?;
+            require!(randomness.seed_slot == raffle.commit_slot, ErrorCode::InvalidRandomness);
+            raffle.randomness = randomness
+                .get_value(&clock)
+                .map_err(|_| ErrorCode::RandomnessNotResolved)?;
+            raffle.revealed = true;
+        }
+
+        let mut void_records = ctx.remaining_accounts.iter();
+        let last_draw = raffle.draws.saturating_add(MAX_RAFFLE_DRAWS);
+        while raffle.drawn < raffle.winner_count && raffle.draws < last_draw {
+            let index = raffle_ticket(&raffle.randomness, raffle.draws) % state.total_claims;
+            let drawn = raffle.drawn as usize;
+            if is_claimed(state, index) && !raffle.winners[..drawn].contains(&index) {
+                let Some(record) = void_records.next() else {
+                    break;
+                };
+                require_keys_eq!(
+                    record.key(),
+                    voided_index_address(&state.snapshot_hash, index),
+                    ErrorCode::InvalidVoidRecord
+                );
+                if record.data_is_empty() {
+                    raffle.winners[drawn] = index;
+                    raffle.drawn += 1;
+                }
+            }
+            raffle.draws += 1;
+        }
+        if raffle.drawn < raffle.winner_count {
+            return Ok(());
+        }
+        raffle.settled = true;
+
+        emit_cpi!(RaffleSettled {
+            snapshot_hash: raffle.snapshot_hash,
+            randomness: raffle.randomness,
+            winners: raffle.winners[..raffle.drawn as usize].to_vec(),
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// The wallet of a winning index collects its prize, once, before the
+    /// raffle's claim period ends.
+    pub fn claim_raffle_prize(
+        ctx: Context<ClaimRafflePrize>,
+        index: u64,
+        amount: u64,
+        proof: [[u8; 32]; MAX_PROOF_DEPTH],
+        proof_len: u8,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &ctx.accounts.state;
+        let raffle = &ctx.accounts.raffle;
+        require!(raffle.settled, ErrorCode::RandomnessNotResolved);
+        require!(raffle_open(state, now), ErrorCode::RaffleExpired);
+        require!(
+            proof_len as usize <= MAX_PROOF_DEPTH,
+            ErrorCode::ProofTooLong
+        );
+        let leaf = keccak_leaf(state.leaf_encoding, index, ctx.accounts.wallet.key, amount);
+        require!(
+            is_campaign_root(state, &merkle::root_from_proof(&leaf, &proof[..proof_len as usize])),
+            ErrorCode::InvalidProof
+        );
+        require!(
+            raffle.winners[..raffle.drawn as usize].contains(&index),
+            ErrorCode::NotRaffleWinner
+        );
+
+        // `winner_count` prizes of `prize_amount` never exceed the pool
+        let prize = raffle.prize_amount;
+        require!(prize > 0, ErrorCode::RafflePoolExhausted);
+
+        pay_claim(
+            state,
+            ctx.bumps.vault_auth,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_auth,
+            &ctx.accounts.user_ata,
+            prize,
+        )?;
+
+        let raffle = &mut ctx.accounts.raffle;
+        raffle.paid_amount += prize;
+        raffle.paid_count += 1;
+        if raffle.paid_count == raffle.winner_count {
+            ctx.accounts.state.raffle_deadline = 0;
+        }
+        let receipt = &mut ctx.accounts.prize;
+        receipt.index = index;
+        receipt.wallet = ctx.accounts.wallet.key();
+
+        emit_cpi!(RafflePrizeClaimed {
+            wallet: receipt.wallet,
+            index,
+            prize,
+            timestamp: now,
+            campaign: ctx.accounts.state.key(),
+            mint: ctx.accounts.state.mint,
+            root_version: ctx.accounts.state.root_version,
+            vault_balance: vault_balance_after(&ctx.accounts.state, &ctx.accounts.vault, prize),
+        });
+        Ok(())
+    }
+}
+
+// Helper functions for claim tracking
+
+fn bitmap_bytes(capacity: u64) -> usize {
+    capacity.div_ceil(8) as usize
+}
+
+// Last second of a claim window
+fn claim_end_ts(start_ts: i64, duration: i64) -> Result<i64> {
+    Ok(start_ts
+        .checked_add(duration)
+        .ok_or(ErrorCode::WindowOverflow)?)
+}
+
+// A window must be non-empty, start no more than MAX_WINDOW_DRIFT before
+// `now` or `max_lead` after it, and end without overflowing i64. Both bounds
+// catch timestamps passed in milliseconds or left at zero.
+fn check_claim_window(start_ts: i64, duration: i64, now: i64, max_lead: i64) -> Result<()> {
+    require!(duration > 0, ErrorCode::InvalidDuration);
+    require!(
+        start_ts >= now.saturating_sub(MAX_WINDOW_DRIFT) && start_ts <= now.saturating_add(max_lead),
+        ErrorCode::ClaimStartOutOfRange
+    );
+    claim_end_ts(start_ts, duration)?;
+    Ok(())
+}
+
+// How far ahead of now the campaign lets a window start
+fn max_start_lead(state: &State) -> i64 {
+    if state.max_start_lead == 0 {
+        MAX_WINDOW_DRIFT
+    } else {
+        state.max_start_lead
+    }
+}
+
+fn pad_uri(uri: &str) -> Result<[u8; MAX_URI_LEN]> {
+    require!(uri.len() <= MAX_URI_LEN, ErrorCode::MetadataTooLong);
+    let mut padded = [0u8; MAX_URI_LEN];
+    padded[..uri.len()].copy_from_slice(uri.as_bytes());
+    Ok(padded)
+}
+
+fn state_space(capacity: u64) -> usize {
+    STATE_BASE_SPACE + bitmap_bytes(capacity)
+}
+
+fn epoch_root_space(capacity: u64) -> usize {
+    EPOCH_ROOT_BASE_SPACE + bitmap_bytes(capacity)
+}
+
+// Rejects `index` if it is already recorded as claimed, then records it; an
+// index past `claim_capacity` waits for `resize_state`
+fn mark_claimed(state: &mut State, index: u64) -> Result<()> {
+    require!(index < state.claim_capacity, ErrorCode::CapacityExceeded);
+    mark_bit(&mut state.claim_bitmap, index)
+}
+
+// Sets bit `index`, failing if it is already set or past the bitmap
+fn mark_bit(bitmap: &mut [u8], index: u64) -> Result<()> {
+    let byte = bitmap
+        .get_mut((index / 8) as usize)
+        .ok_or(ErrorCode::CapacityExceeded)?;
+    let bit = 1 << (index % 8);
+    require!(*byte & bit == 0, ErrorCode::AlreadyClaimed);
+    *byte |= bit;
+    trace!("bitmap: index {} marked", index);
+    Ok(())
+}
+
+fn bit_set(bitmap: &[u8], index: u64) -> bool {
+    bitmap
+        .get((index / 8) as usize)
+        .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+}
+
+// `claim`'s checks against a scratch copy of the state, bar the vault
+// balance; returns (amount, fee, penalty), the amount in tokens
+fn dry_run_claim(
+    accounts: &ValidateClaim,
+    index: u64,
+    amount: u64,
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<(u64, u64, u64)> {
+    let wallet = accounts.wallet.key;
+    let mut scratch = (*accounts.state).clone();
+    require!(scratch.dispute_period == 0, ErrorCode::EscrowRequired);
+    check_claim_gate(
+        &scratch,
+        wallet,
+        accounts.gate_token_account.as_deref(),
+        accounts.gate_metadata.as_ref(),
+    )?;
+    let amount = if scratch.usd_price_feed == [0; 32] {
+        record_claim(&mut scratch, index, wallet, amount, proof, proof_len, terms_hash, now)?;
+        amount
+    } else {
+        let mint = accounts.mint.as_ref().ok_or(ErrorCode::InvalidMint)?;
+        let (amount, _, _) = record_usd_claim(
+            &mut scratch,
+            accounts.price_update.as_ref(),
+            mint.decimals,
+            index,
+            wallet,
+            amount,
+            proof,
+            proof_len,
+            terms_hash,
+            now,
+        )?;
+        amount
+    };
+    wallet_claimed_total(&scratch, accounts.wallet_claims.as_deref(), wallet, amount)?;
+    check_family_claim(&scratch, accounts.family_claim.as_deref(), wallet)?;
+    let fee = take_operator_fee(&mut scratch, amount)?;
+    let penalty = take_late_penalty(&mut scratch, amount, fee, now)?;
+    Ok((amount, fee, penalty))
+}
+
+// Shared claim path: validates the claim against the window, terms and
+// Merkle root, then records it in the claim bitmap and counters.
+#[allow(clippy::too_many_arguments)]
+fn record_claim(
+    state: &mut State,
+    index: u64,
+    beneficiary: &Pubkey,
+    amount: u64,
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<()> {
+    let leaf = keccak_leaf(state.leaf_encoding, index, beneficiary, amount);
+    record_leaf_claim(state, index, &leaf, amount, proof, proof_len, terms_hash, now)
+}
+
+// The instruction a leaf is being paid through. Each campaign switch
+// reserves its leaves for one route, checked in `check_claim_route`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ClaimRoute {
+    Direct,
+    Vaa,
+    Ticketed,
+    UsdPriced,
+    VestingHandoff,
+    DustSweep,
+}
+
+// Fails unless every switch set on the campaign allows `route`; every path
+// that records a claim runs it, so a new switch is added here alone
+pub(crate) fn check_claim_route(state: &State, route: ClaimRoute) -> Result<()> {
+    // Campaigns homed on another chain pay out only against a VAA
+    require!(
+        state.wormhole_emitter_chain == 0 || route == ClaimRoute::Vaa,
+        ErrorCode::CrossChainClaimRequired
+    );
+    // USD leaves pay out only through `claim`, which prices them
+    require!(
+        state.usd_price_feed == [0; 32] || route == ClaimRoute::UsdPriced,
+        ErrorCode::UsdPriceRequired
+    );
+    // Ticketed campaigns pay out only through `claim_ticketed`; a dust sweep
+    // pays the pool, so there is no claimant to front-run
+    require!(
+        state.reveal_delay == 0 || matches!(route, ClaimRoute::Ticketed | ClaimRoute::DustSweep),
+        ErrorCode::TicketRequired
+    );
+    // Handoff campaigns pay out only through `claim_to_vesting`; dust goes to
+    // the pool unlocked
+    require!(
+        state.vesting_program == Pubkey::default()
+            || matches!(route, ClaimRoute::VestingHandoff | ClaimRoute::DustSweep),
+        ErrorCode::VestingHandoffRequired
+    );
+    Ok(())
+}
+
+// `record_claim` for a leaf hashed by the caller (e.g. one committing to a
+// vesting schedule as well)
+#[allow(clippy::too_many_arguments)]
+fn record_leaf_claim(
+    state: &mut State,
+    index: u64,
+    leaf: &[u8; 32],
+    amount: u64,
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<()> {
+    check_claim_route(state, ClaimRoute::Direct)?;
+    record_bitmap_claim(state, index, leaf, amount, proof, proof_len, terms_hash, now)
+}
+
+// `record_leaf_claim` minus its route check, for paths that check their own
+// route first
+#[allow(clippy::too_many_arguments)]
+fn record_bitmap_claim(
+    state: &mut State,
+    index: u64,
+    leaf: &[u8; 32],
+    amount: u64,
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<()> {
+    // Compressed campaigns record claims in their tree, never in the bitmap
+    require!(state.claim_ledger == Pubkey::default(), ErrorCode::CompressedLedgerRequired);
+    check_leaf_claim(state, index, leaf, proof, proof_len, terms_hash, now)?;
+    mark_claimed(state, index)?;
+    count_claim(state, amount)
+}
+
+// Claim validation shared by every ledger: switches, window, index, freeze,
+// terms and the allocation proof
+fn check_leaf_claim(
+    state: &State,
+    index: u64,
+    leaf: &[u8; 32],
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<()> {
+    let window = (state.claim_start_ts, state.claim_duration);
+    check_leaf_claim_in(state, window, index, leaf, proof, proof_len, terms_hash, now)
+}
+
+// `check_leaf_claim` against a `(start, duration)` window other than the
+// campaign's, such as a cohort's
+#[allow(clippy::too_many_arguments)]
+fn check_leaf_claim_in(
+    state: &State,
+    (claim_start_ts, claim_duration): (i64, i64),
+    index: u64,
+    leaf: &[u8; 32],
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<()> {
+    // Validate claim conditions
+    require!(!state.claim_closed, ErrorCode::ClaimClosed);
+    require!(!state.needs_refill, ErrorCode::VaultNeedsRefill);
+    check_claimable_status(state)?;
+    require!(now >= claim_start_ts, ErrorCode::WindowNotStarted);
+    require!(
+        now <= claim_end_ts(claim_start_ts, claim_duration)?,
+        ErrorCode::WindowEnded
+    );
+    require!(index < state.total_claims, ErrorCode::InvalidIndex);
+    require!(!is_frozen(state, index), ErrorCode::IndexFrozen);
+    require!(
+        state.terms_hash == [0; 32] || terms_hash == state.terms_hash,
+        ErrorCode::TermsNotAccepted
+    );
+
+    // Verify Merkle proof
+    require!(
+        proof_len as usize <= MAX_PROOF_DEPTH,
+        ErrorCode::ProofTooLong
+    );
+    let computed = merkle::root_from_proof(leaf, &proof[..proof_len as usize]);
+    trace!(
+        "proof: index {} leaf {:?} depth {} root {:?} expected {:?}",
+        index,
+        leaf,
+        proof_len,
+        computed,
+        state.merkle_root
+    );
+    require!(is_campaign_root(state, &computed), ErrorCode::InvalidProof);
+    Ok(())
+}
+
+// Bumps the campaign counters for a recorded claim and enforces the allocation cap
+fn count_claim(state: &mut State, amount: u64) -> Result<()> {
+    // Dust leaves are paid out only in aggregate, by `sweep_dust`
+    require!(amount >= state.dust_threshold, ErrorCode::BelowDustThreshold);
+    add_claimed(state, amount)
+}
+
+// `count_claim` without the dust threshold
+fn add_claimed(state: &mut State, amount: u64) -> Result<()> {
+    state.claimed_count = state.claimed_count
+        .checked_add(1)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    state.claimed_amount = state.claimed_amount
+        .checked_add(amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    // States migrated from before the cap existed carry zero and stay uncapped
+    require!(
+        state.total_allocation == 0 || state.claimed_amount <= state.total_allocation,
+        ErrorCode::AllocationExceeded
+    );
+    Ok(())
+}
+
+// Epoch counterpart of `record_claim`: the window, root and ledger come from
+// the epoch account; campaign-wide switches (close, refill, routes, freezes,
+// terms, dust) still apply.
+#[allow(clippy::too_many_arguments)]
+fn record_epoch_claim(
+    state: &State,
+    epoch_root: &mut EpochRoot,
+    index: u64,
+    beneficiary: &Pubkey,
+    amount: u64,
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<()> {
+    require!(!state.claim_closed, ErrorCode::ClaimClosed);
+    require!(!state.needs_refill, ErrorCode::VaultNeedsRefill);
+    check_claimable_status(state)?;
+    check_claim_route(state, ClaimRoute::Direct)?;
+    require!(now >= epoch_root.claim_start_ts, ErrorCode::WindowNotStarted);
+    require!(
+        now <= claim_end_ts(epoch_root.claim_start_ts, epoch_root.claim_duration)?,
+        ErrorCode::WindowEnded
+    );
+    require!(index < epoch_root.total_claims, ErrorCode::InvalidIndex);
+    require!(!is_frozen(state, index), ErrorCode::IndexFrozen);
+    require!(amount >= state.dust_threshold, ErrorCode::BelowDustThreshold);
+    require!(
+        state.terms_hash == [0; 32] || terms_hash == state.terms_hash,
+        ErrorCode::TermsNotAccepted
+    );
+
+    require!(
+        proof_len as usize <= MAX_PROOF_DEPTH,
+        ErrorCode::ProofTooLong
+    );
+    let leaf = keccak_leaf(state.leaf_encoding, index, beneficiary, amount);
+    require!(
+        merkle::verify(&leaf, &proof[..proof_len as usize], &epoch_root.merkle_root),
+        ErrorCode::InvalidProof
+    );
+
+    mark_bit(&mut epoch_root.claim_bitmap, index)?;
+    epoch_root.claimed_count = epoch_root.claimed_count
+        .checked_add(1)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    epoch_root.claimed_amount = epoch_root.claimed_amount
+        .checked_add(amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    require!(
+        epoch_root.claimed_amount <= epoch_root.total_allocation,
+        ErrorCode::AllocationExceeded
+    );
+    Ok(())
+}
+
+// Adds a claim to its hour's bucket, recycling the slot of the hour that last
+// occupied it.
+fn record_stats(
+    state: &State,
+    stats: Option<&mut Account<ClaimStats>>,
+    now: i64,
+    amount: u64,
+) -> Result<()> {
+    let Some(stats) = stats else {
+        require!(!state.stats_enabled, ErrorCode::StatsAccountMissing);
+        return Ok(());
+    };
+    let hour = now.div_euclid(3_600);
+    let bucket = &mut stats.buckets[hour.rem_euclid(STATS_BUCKETS as i64) as usize];
+    if bucket.hour != hour {
+        *bucket = HourlyStats { hour, claims: 0, amount: 0 };
+    }
+    bucket.claims = bucket.claims.saturating_add(1);
+    bucket.amount = bucket.amount.saturating_add(amount);
+    Ok(())
+}
+
+// `wallet`'s round total once `amount` is added; fails if that breaks the cap
+fn wallet_claimed_total(
+    state: &State,
+    wallet_claims: Option<&WalletClaims>,
+    wallet: &Pubkey,
+    amount: u64,
+) -> Result<u64> {
+    let Some(wallet_claims) = wallet_claims else {
+        require!(state.wallet_cap == 0, ErrorCode::WalletClaimsMissing);
+        return Ok(amount);
+    };
+    require_keys_eq!(wallet_claims.wallet, *wallet, ErrorCode::WalletClaimsMissing);
+    // A total from an earlier round starts over
+    let claimed = if wallet_claims.round == state.round { wallet_claims.claimed } else { 0 };
+    let total = claimed.checked_add(amount).ok_or(ErrorCode::WalletCapExceeded)?;
+    require!(
+        state.wallet_cap == 0 || total <= state.wallet_cap,
+        ErrorCode::WalletCapExceeded
+    );
+    Ok(total)
+}
+
+fn record_wallet_claim(
+    state: &State,
+    wallet_claims: Option<&mut Account<WalletClaims>>,
+    wallet: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let total = wallet_claimed_total(state, wallet_claims.as_deref().map(|w| &**w), wallet, amount)?;
+    if let Some(wallet_claims) = wallet_claims {
+        wallet_claims.round = state.round;
+        wallet_claims.claimed = total;
+    }
+    Ok(())
+}
+
+// The vault a claim of `amount` draws from: `vault` if it covers it on top of
+// the reserves it holds, else the first registered extra vault (passed in
+// order) that does. With none, `vault` is returned and the transfer fails on
+// its balance, unless only the reserves would cover it.
+fn select_vault<'info>(
+    state: &State,
+    vault: &Account<'info, TokenAccount>,
+    extra_vaults: &'info [AccountInfo<'info>],
+    amount: u64,
+) -> Result<Account<'info, TokenAccount>> {
+    if state.mint_on_claim || unreserved_balance(state, vault) >= amount {
+        return Ok(vault.clone());
+    }
+    let registered = &state.extra_vaults[..state.extra_vault_count as usize];
+    for (info, key) in extra_vaults.iter().zip(registered) {
+        require_keys_eq!(info.key(), *key, ErrorCode::InvalidVault);
+        let extra = Account::<TokenAccount>::try_from(info)?;
+        // Same mint and vault authority as `vault`, so the same signer pays
+        require_keys_eq!(extra.mint, vault.mint, ErrorCode::InvalidMint);
+        require_keys_eq!(extra.owner, vault.owner, ErrorCode::InvalidVault);
+        if extra.amount >= amount {
+            return Ok(extra);
+        }
+    }
+    require!(
+        state.escrowed_amount == 0 && state.vesting_locked == 0,
+        ErrorCode::VaultNeedsRefill
+    );
+    Ok(vault.clone())
+}
+
+// What the main vault holds beyond what reserved claims still waiting out
+// their dispute period, and allocations still vesting, are owed
+fn unreserved_balance(state: &State, vault: &TokenAccount) -> u64 {
+    vault.amount
+        .saturating_sub(state.escrowed_amount)
+        .saturating_sub(state.vesting_locked)
+}
+
+// Splits `remaining_accounts` into the registered extra vaults, which come
+// first where an instruction also passes other accounts there, and the rest
+fn split_extra_vaults<'info>(
+    state: &State,
+    remaining: &'info [AccountInfo<'info>],
+) -> Result<(&'info [AccountInfo<'info>], &'info [AccountInfo<'info>])> {
+    let count = state.extra_vault_count as usize;
+    require!(remaining.len() >= count, ErrorCode::InvalidVault);
+    Ok(remaining.split_at(count))
+}
+
+// What `vault` holds once it has paid a claim of `amount`; minting
+// campaigns don't draw on it
+fn vault_balance_after(state: &State, vault: &TokenAccount, amount: u64) -> u64 {
+    if state.mint_on_claim {
+        vault.amount
+    } else {
+        vault.amount.saturating_sub(amount)
+    }
+}
+
+// Flags the campaign for a refill when a vault-funded claim leaves the vault
+// under the low-water mark; returns the remaining balance if it tripped.
+fn trip_low_water_mark(state: &mut State, vault_balance: u64, amount: u64) -> Option<u64> {
+    let remaining = vault_balance.saturating_sub(amount);
+    if state.mint_on_claim || state.low_water_mark == 0 || remaining >= state.low_water_mark {
+        return None;
+    }
+    state.needs_refill = true;
+    Some(remaining)
+}
+
+// Operator's cut of a claim, added to the campaign's fee accounting
+fn take_operator_fee(state: &mut State, amount: u64) -> Result<u64> {
+    let fee = state.rounding.div(amount as u128 * state.fee_bps as u128, 10_000) as u64;
+    state.fees_collected = state.fees_collected
+        .checked_add(fee)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    Ok(fee)
+}
+
+// Late-claim haircut: zero before `penalty_start_ts`, then ramping linearly
+// to `penalty_bps` over `penalty_ramp` seconds (a step when the ramp is zero).
+// Capped at what `fee` left of `amount`, which rounding up both can exceed.
+fn take_late_penalty(state: &mut State, amount: u64, fee: u64, claim_ts: i64) -> Result<u64> {
+    if state.penalty_bps == 0 || claim_ts < state.penalty_start_ts {
+        return Ok(0);
+    }
+    let elapsed = (claim_ts - state.penalty_start_ts) as u128;
+    let bps = if state.penalty_ramp == 0 || elapsed >= state.penalty_ramp as u128 {
+        state.penalty_bps as u128
+    } else {
+        state.penalty_bps as u128 * elapsed / state.penalty_ramp as u128
+    };
+    let penalty = (state.rounding.div(amount as u128 * bps, 10_000) as u64).min(amount - fee);
+    state.penalties_collected = state.penalties_collected
+        .checked_add(penalty)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    Ok(penalty)
+}
+
+// Early-claim top-up of `amount`, capped at what is left in the bonus reserve
+// so late bonuses shrink instead of failing and the claim vault is never drawn
+fn take_early_bonus(
+    state: &mut State,
+    bonus_vault: Option<&Account<TokenAccount>>,
+    amount: u64,
+    claim_ts: i64,
+) -> Result<u64> {
+    // A lottery pays the bonus through tickets instead
+    if state.bonus_bps == 0 || claim_ts >= state.bonus_end_ts || state.bonus_lottery {
+        return Ok(0);
+    }
+    let bonus_vault = bonus_vault.ok_or(ErrorCode::InvalidBonusVault)?;
+    let bonus = state.rounding.div(amount as u128 * state.bonus_bps as u128, 10_000) as u64;
+    let bonus = bonus.min(bonus_vault.amount);
+    state.bonuses_paid = state.bonuses_paid
+        .checked_add(bonus)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    Ok(bonus)
+}
+
+// Pays the treasury's cut (fee plus any penalty) out of the same vault (or
+// mint) as the claim
+#[allow(clippy::too_many_arguments)]
+fn pay_operator_fee<'info>(
+    state: &State,
+    vault_bump: u8,
+    token_program: &Program<'info, Token>,
+    mint: &Account<'info, Mint>,
+    vault: &Account<'info, TokenAccount>,
+    vault_auth: &SystemAccount<'info>,
+    fee_account: Option<&Account<'info, TokenAccount>>,
+    fee: u64,
+) -> Result<()> {
+    if fee == 0 {
+        return Ok(());
+    }
+    let fee_account = fee_account.ok_or(ErrorCode::InvalidFeeAccount)?;
+    require_keys_eq!(fee_account.key(), state.fee_account, ErrorCode::InvalidFeeAccount);
+    pay_claim(state, vault_bump, token_program, mint, vault, vault_auth, fee_account, fee)
+}
+
+// Transfer out of the bonus reserve, signed by the state PDA that owns it
+fn pay_early_bonus<'info>(
+    state: &Account<'info, State>,
+    state_bump: u8,
+    token_program: &Program<'info, Token>,
+    mint: &Account<'info, Mint>,
+    bonus_vault: Option<&Account<'info, TokenAccount>>,
+    recipient: &Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    let Some(bonus_vault) = bonus_vault else {
+        return Ok(());
+    };
+    if amount == 0 {
+        return Ok(());
+    }
+    trace!("bonus: transfer {} to {}", amount, recipient.key());
+    let state_seeds = &[STATE_SEED, &[state_bump]];
+    let signer_seeds: &[&[&[u8]]] = &[state_seeds];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from:      bonus_vault.to_account_info(),
+            to:        recipient.to_account_info(),
+            authority: state.to_account_info(),
+            mint:      mint.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+// Transfer (or mint) a recorded claim to the recipient's token account
+#[allow(clippy::too_many_arguments)]
+fn pay_claim<'info>(
+    state: &State,
+    vault_bump: u8,
+    token_program: &Program<'info, Token>,
+    mint: &Account<'info, Mint>,
+    vault: &Account<'info, TokenAccount>,
+    vault_auth: &SystemAccount<'info>,
+    recipient: &Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    // Surface a frozen destination before the token program rejects the CPI
+    require!(!recipient.is_frozen(), ErrorCode::RecipientAccountFrozen);
+    let vault_seeds = &[
+        VAULT_SEED,
+        state.snapshot_hash.as_ref(),
+        &[vault_bump],
+    ];
+    let signer_seeds: &[&[&[u8]]] = &[vault_seeds];
+    trace!(
+        "pay: {} {} to {}",
+        if state.mint_on_claim { "mint" } else { "transfer" },
+        amount,
+        recipient.key()
+    );
+    if state.mint_on_claim {
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint:      mint.to_account_info(),
+                to:        recipient.to_account_info(),
+                authority: vault_auth.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(cpi_ctx, amount)
+    } else {
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from:      vault.to_account_info(),
+                to:        recipient.to_account_info(),
+                authority: vault_auth.to_account_info(),
+                mint:      mint.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer_checked(cpi_ctx, amount, mint.decimals)
+    }
+}
+
+// The current root, or one `append_allocations` grew it from
+fn is_campaign_root(state: &State, root: &[u8; 32]) -> bool {
+    *root == state.merkle_root || state.prior_roots[..state.prior_root_count as usize].contains(root)
+}
+
+fn is_frozen(state: &State, index: u64) -> bool {
+    state.frozen_indices[..state.frozen_count as usize].contains(&index)
+}
+
+// Whether `claim` would reject `index` as already claimed
+fn is_claimed(state: &State, index: u64) -> bool {
+    bit_set(&state.claim_bitmap, index)
+}
+
+// Claim status is only defined for bitmap-tracked indices of the current
+// root, once the bitmap holds every claim from before migration
+fn check_status_index(state: &State, index: u64) -> Result<()> {
+    require!(state.claim_ledger == Pubkey::default(), ErrorCode::CompressedLedgerRequired);
+    require_claims_converted(state)?;
+    require!(index < state.total_claims, ErrorCode::InvalidIndex);
+    Ok(())
+}
+
+fn raffle_ticket(randomness: &[u8; 32], draw: u64) -> u64 {
+    use anchor_lang::solana_program::keccak;
+    let hash = keccak::hashv(&[randomness, &draw.to_le_bytes()]).to_bytes();
+    u64::from_le_bytes(hash[..8].try_into().unwrap())
+}
+
+// Whether this round's raffle is still drawing or owes a prize
+fn raffle_open(state: &State, now: i64) -> bool {
+    state.raffle_deadline != 0 && now <= state.raffle_deadline
+}
+
+// Reads the current owner out of an SPL Name Service record header
+// (parent_name | owner | class, 32 bytes each).
+fn name_record_owner(domain: &UncheckedAccount) -> Result<Pubkey> {
+    require_keys_eq!(*domain.owner, NAME_SERVICE_PROGRAM_ID, ErrorCode::InvalidDomain);
+    let data = domain.try_borrow_data()?;
+    require!(data.len() >= NAME_RECORD_HEADER_LEN, ErrorCode::InvalidDomain);
+    Ok(Pubkey::new_from_array(data[32..64].try_into().unwrap()))
+}
+
+// Claim gating
+fn check_claim_gate(
+    state: &State,
+    wallet: &Pubkey,
+    holding: Option<&TokenAccount>,
+    metadata: Option<&UncheckedAccount>,
+) -> Result<()> {
+    if state.gate_kind == GateKind::None {
+        return Ok(());
+    }
+    let holding = holding.ok_or(ErrorCode::GateNotSatisfied)?;
+    require_keys_eq!(holding.owner, *wallet, ErrorCode::GateNotSatisfied);
+    require!(
+        holding.amount >= state.gate_min_amount.max(1),
+        ErrorCode::GateNotSatisfied
+    );
+
+    match state.gate_kind {
+        GateKind::Token => {
+            require_keys_eq!(holding.mint, state.gate_key, ErrorCode::GateNotSatisfied);
+        }
+        GateKind::Collection => {
+            let metadata = metadata.ok_or(ErrorCode::GateNotSatisfied)?;
+            require_keys_eq!(
+                *metadata.owner,
+                mpl_token_metadata::ID,
+                ErrorCode::GateNotSatisfied
+            );
+            let metadata = Metadata::safe_deserialize(&metadata.try_borrow_data()?)
+                .map_err(|_| ErrorCode::GateNotSatisfied)?;
+            require_keys_eq!(metadata.mint, holding.mint, ErrorCode::GateNotSatisfied);
+            require!(
+                metadata.collection.is_some_and(|c| c.verified && c.key == state.gate_key),
+                ErrorCode::GateNotSatisfied
+            );
+        }
+        GateKind::None => {}
+    }
+    Ok(())
+}
+
+// Utility functions
+fn keccak_leaf(encoding: LeafEncoding, index: u64, wallet: &Pubkey, amount: u64) -> [u8; 32] {
+    match encoding {
+        LeafEncoding::Native => merkle::leaf(index, &wallet.to_bytes(), amount),
+        LeafEncoding::EvmPacked => merkle::evm_leaf(index, wallet.as_ref(), amount),
+    }
+}
+
+fn keccak_vesting_leaf(index: u64, wallet: &Pubkey, amount: u64, schedule: &VestingSchedule) -> [u8; 32] {
+    merkle::vesting_leaf(
+        index,
+        &wallet.to_bytes(),
+        amount,
+        schedule.cliff,
+        schedule.duration,
+        schedule.tge_bps,
+    )
+}
+
+// Amount unlocked by `now`: the TGE share at start, nothing more until the
+// cliff, then linear up to the full amount at `duration`
+fn vested_amount(
+    amount: u64,
+    schedule: &VestingSchedule,
+    start_ts: i64,
+    now: i64,
+    rounding: Rounding,
+) -> u64 {
+    let elapsed = now.saturating_sub(start_ts);
+    if elapsed < 0 {
+        return 0;
+    }
+    if schedule.duration == 0 || elapsed >= schedule.duration {
+        return amount;
+    }
+    let tge = rounding.div(amount as u128 * schedule.tge_bps as u128, 10_000) as u64;
+    if elapsed < schedule.cliff {
+        return tge;
+    }
+    tge + rounding.div((amount - tge) as u128 * elapsed as u128, schedule.duration as u128) as u64
+}
+
+// Marks everything vested so far as released and returns the newly due part
+fn take_vested(state: &mut State, vesting: &mut VestingAccount, now: i64) -> Result<u64> {
+    let rounding = state.rounding;
+    let vested = vested_amount(vesting.amount, &vesting.schedule, vesting.start_ts, now, rounding);
+    let due = vested.saturating_sub(vesting.released);
+    vesting.released = vested.max(vesting.released);
+    state.vesting_locked = state.vesting_locked.saturating_sub(due);
+    Ok(due)
+}
+
+
+// Account Structs
+#[account]
+pub struct State {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub snapshot_hash: [u8; 32],
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub claim_closed: bool,
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub claim_capacity: u64,
+    pub round: u32,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub mint_on_claim: bool,
+    pub crank_bounty: u64,
+    pub claimed_count: u64,
+    pub claimed_amount: u64,
+    // Commitment to the allocation file behind `merkle_root`. The URI is
+    // NUL-padded and fixed-width so later appended fields keep stable offsets.
+    pub allocations_hash: [u8; 32],
+    pub allocations_uri: [u8; MAX_URI_LEN],
+    pub terms_hash: [u8; 32], // zero = no terms acceptance required
+    pub gate_kind: GateKind,
+    pub gate_key: Pubkey,
+    pub gate_min_amount: u64,
+    pub total_allocation: u64, // sum of all leaf amounts committed at initialize/rollover
+    pub low_water_mark: u64, // zero = never pause
+    pub needs_refill: bool,
+    pub fee_bps: u16,
+    pub fee_account: Pubkey, // operator fee token account, same mint as the campaign
+    pub dispute_period: i64, // zero = claims pay out immediately
+    pub escrowed_amount: u64, // reserved but not yet withdrawn
+    pub frozen_indices: [u64; MAX_FROZEN_INDICES], // first `frozen_count` entries are live
+    pub frozen_count: u8,
+    pub stats_enabled: bool, // claims must then pass the ClaimStats PDA
+    pub fees_collected: u64, // token fees routed to `fee_account` so far
+    pub penalty_start_ts: i64,
+    pub penalty_ramp: i64, // seconds to reach `penalty_bps`; zero = step
+    pub penalty_bps: u16, // zero = no late penalty
+    pub penalties_collected: u64,
+    pub bonus_vault: Pubkey, // reserve token account; never the claim vault
+    pub bonus_end_ts: i64,
+    pub bonus_bps: u16, // zero = no early bonus
+    pub bonuses_paid: u64,
+    pub vesting_locked: u64, // committed to vesting schedules but not yet released
+    pub claim_ledger: Pubkey, // concurrent Merkle tree recording claims; zero = `claim_bitmap`
+    pub config_locked: bool, // root and window can no longer change; never cleared
+    pub extra_vaults: [Pubkey; MAX_EXTRA_VAULTS], // first `extra_vault_count` entries are live, in failover order
+    pub extra_vault_count: u8,
+    pub wormhole_emitter_chain: u16, // zero = claims are paid on Solana directly
+    pub wormhole_emitter: [u8; 32], // companion contract that locks claim rights on that chain
+    pub leaf_encoding: LeafEncoding,
+    pub badge_mint: Pubkey, // non-transferable claim receipt mint; zero = no badges
+    pub wallet_cap: u64, // most one wallet may claim per round over all its leaves; zero = no cap
+    pub relayer_fee: u64, // tokens of each relayed claim paid to its relayer
+    pub usd_price_feed: [u8; 32], // Pyth feed pricing micro-USD leaves; zero = leaves are token amounts
+    pub max_price_age: u64, // seconds a price update stays usable
+    pub max_price_conf_bps: u16, // widest confidence interval accepted, relative to the price
+    pub max_start_lead: i64, // furthest ahead of now a new window may start; zero = MAX_WINDOW_DRIFT
+    pub status: CampaignStatus,
+    pub reveal_delay: u64, // slots between `commit_claim` and `claim_ticketed`; zero = no tickets
+    pub root_version: u32, // roots the campaign has had before the current one, across rounds
+    pub close_grace: i64, // seconds `close_airdrop` lets an open window run on; zero = close at once
+    pub dust_threshold: u64, // leaves below this are paid only by `sweep_dust`; zero = no threshold
+    pub community_pool: Pubkey, // token account `sweep_dust` pays dust leaves into
+    pub prior_roots: [[u8; 32]; MAX_ROOT_APPENDS], // roots `append_allocations` grew the current one from, oldest first
+    pub prior_root_count: u8,
+    pub rounding: Rounding, // how fees, penalties, bonuses, vesting and USD payouts round
+    pub family_id: [u8; 32], // zero = not in a family
+    pub family_siblings: [Pubkey; MAX_FAMILY_SIBLINGS], // program ids of the other campaigns in the family
+    pub family_sibling_count: u8,
+    pub unclaimed_export_cursor: u64, // `emit_unclaimed` pages below this have earned their bounty this round
+    pub cohort_vaults: [CohortVault; MAX_COHORT_VAULTS], // first `cohort_vault_count` entries are live
+    pub cohort_vault_count: u8,
+    pub vesting_program: Pubkey, // claims open a lock here through `claim_to_vesting`; zero = paid directly
+    pub bonus_lottery: bool, // early bonus is paid through `BonusTicket`s rather than with the claim
+    pub legacy_claims: bool, // a `LegacyClaims` account holds claims from before migration past `claim_capacity`
+    pub raffle_deadline: i64, // this round's `Raffle` pays prizes until then; zero = none owed
+    pub scheduled_root_count: u8, // `RootSchedule` entries still waiting this round
+    pub cohorts_hash: [u8; 32], // chains every `CohortUpdated`
+    pub relayers_hash: [u8; 32], // chains every `RelayerUpdated`
+    pub claim_bitmap: Vec<u8>, // bit `index` set once claimed; `claim_capacity` bits
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GateKind {
+    None,
+    Token,
+    Collection,
+}
+
+/// How allocation leaves are hashed; vesting leaves are always `Native`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LeafEncoding {
+    /// `keccak(index || wallet || amount)`, integers little-endian
+    Native,
+    /// `keccak256(abi.encodePacked(uint256 index, bytes32 wallet, uint256 amount))`,
+    /// matching a companion Solidity distributor
+    EvmPacked,
+}
+
+/// Return data of `get_airdrop_info`; a stable view decoupled from `State`'s layout.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AirdropInfo {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub snapshot_hash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub round: u32,
+    pub claim_start_ts: i64,
+    pub claim_end_ts: i64,
+    pub claim_closed: bool,
+    pub needs_refill: bool,
+    pub window_open: bool,
+    pub mint_on_claim: bool,
+    pub total_claims: u64,
+    pub claim_capacity: u64,
+    pub claimed_count: u64,
+    pub claimed_amount: u64,
+    pub total_allocation: u64,
+    pub vault_balance: u64,
+    pub allocations_hash: [u8; 32],
+    pub allocations_uri: String,
+    pub now: i64,
+    pub status: CampaignStatus,
+}
+
+/// Return data of `validate_claim`. `error_code` is the error `claim` would
+/// fail with (zero when it would succeed); the amounts are only set on success.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct ClaimValidation {
+    pub error_code: u32,
+    pub fee: u64,
+    pub penalty: u64,
+    pub net_amount: u64,
+}
+
+impl Default for State {
+    // All-zero bytes decode as the zero value of every field, so this never
+    // needs updating when fields are appended.
+    fn default() -> Self {
+        State::deserialize(&mut &vec![0u8; STATE_BASE_SPACE][..])
+            .expect("zeroed buffer covers every fixed-size field")
+    }
+}
+
+/// Layout of `State` before `version` was introduced; read-only, used by `migrate_state`.
+#[derive(AnchorDeserialize)]
+pub struct LegacyState {
+    pub authority: Pubkey,
+    pub snapshot_hash: [u8; 32],
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub claim_closed: bool,
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub claim_residues0: [u8; 122],
+    pub claim_residues1: [u8; 39],
+    pub claim_residues2: [u8; 76],
+}
+
+/// One recurring distribution period; seeds = ["epoch", state, epoch (u64 LE)].
+#[account]
+pub struct EpochRoot {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub total_allocation: u64,
+    pub claimed_amount: u64,
+    pub claimed_count: u64,
+    pub claim_bitmap: Vec<u8>, // bit `index` set once claimed
+    pub bump: u8,
+}
+
+/// Fee treasury; seeds = ["treasury", state]. Holds lamports itself and owns
+/// `token_account` (seeds = ["treasury_tokens", state]) for fees in kind.
+#[account]
+pub struct Treasury {
+    pub state: Pubkey,
+    pub token_account: Pubkey,
+    pub tokens_withdrawn: u64,
+    pub lamports_withdrawn: u64,
+    pub bump: u8,
+}
+
+/// Ring buffer of hourly claim counts; seeds = ["stats", state].
+#[account]
+pub struct ClaimStats {
+    pub state: Pubkey,
+    pub bump: u8,
+    pub buckets: [HourlyStats; STATS_BUCKETS], // slot = hour % STATS_BUCKETS
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct HourlyStats {
+    pub hour: i64, // unix_timestamp / 3600
+    pub claims: u32,
+    pub amount: u64,
+}
+
+/// Reserved claim awaiting its dispute period; seeds = ["escrow", snapshot_hash, index (u64 LE)].
+#[account]
+pub struct ClaimEscrow {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub reserved_ts: i64,
+    pub release_ts: i64,
+    pub bump: u8,
+}
+
+/// Per-claimant vesting parameters, committed to in the leaf.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct VestingSchedule {
+    pub cliff: i64, // seconds after the campaign start before linear unlocking begins
+    pub duration: i64, // seconds until fully vested; zero = fully vested at once
+    pub tge_bps: u16, // share unlocked immediately at the campaign start
+}
+
+/// A vesting claim's release ledger; seeds = ["vesting", snapshot_hash, index (u64 LE)].
+#[account]
+pub struct VestingAccount {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub released: u64,
+    pub start_ts: i64,
+    pub schedule: VestingSchedule,
+    pub bump: u8,
+}
+
+/// Unclaimed-supply raffle for one round; seeds = ["raffle", snapshot_hash].
+#[account]
+pub struct Raffle {
+    pub snapshot_hash: [u8; 32],
+    pub randomness_account: Pubkey,
+    pub commit_slot: u64,
+    pub winner_count: u32,
+    pub pool_amount: u64,
+    pub prize_amount: u64,
+    pub paid_amount: u64,
+    pub randomness: [u8; 32],
+    pub revealed: bool, // `randomness` holds the committed account's value
+    pub draws: u64, // tickets drawn so far, winning or not
+    pub winners: [u64; MAX_RAFFLE_WINNERS], // first `drawn` entries are winning indices, in draw order
+    pub drawn: u32,
+    pub paid_count: u32,
+    pub settled: bool, // all `winner_count` winners drawn
+    pub bump: u8,
+}
+
+/// Receipt blocking a second payout for the same ticket; seeds = ["raffle_prize", raffle, index].
+#[account]
+pub struct RafflePrize {
+    pub index: u64,
+    pub wallet: Pubkey,
+}
+
+/// Per-round running total of one wallet's claims, checked against
+/// `state.wallet_cap`; seeds = ["wallet_claims", state, wallet].
+#[account]
+pub struct WalletClaims {
+    pub state: Pubkey,
+    pub wallet: Pubkey,
+    pub round: u32, // `claimed` is reset when the campaign rolls over
+    pub claimed: u64, // gross allocation claimed this round
+    pub bump: u8,
+}
+
+/// Display metadata for explorers and claim UIs; seeds = ["metadata", state].
+#[account]
+pub struct CampaignMetadata {
+    pub state: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub description_uri: String,
+    pub image_uri: String,
+    pub updated_ts: i64,
+}
+
+#[account]
+pub struct RolloverSummary {
+    pub round: u32,
+    pub snapshot_hash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub unclaimed_amount: u64,
+    pub finalized_ts: i64,
+    pub claim_bitmap_hash: [u8; 32], // keccak of the round's final claim bitmap
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(
+    snapshot_hash: [u8; 32],
+    claim_start_ts: i64,
+    claim_duration: i64,
+    merkle_root: [u8; 32],
+    total_claims: u64,
+    claim_capacity: u64
+)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        seeds = [STATE_SEED],
+        bump,
+        payer = authority,
+        space = state_space(claim_capacity)
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    // Canonical ATA only, so there is exactly one fundable vault per campaign
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: the protocol `Config`, if `init_config` has run; read in `charge_creation_fee`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: receives the creation fee; must be `config.fee_treasury` while a fee is set
+    #[account(mut)]
+    pub fee_treasury: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct Claim<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// Leaf wallet. Read-only so program-owned PDAs (multisig vaults,
+    /// protocol treasuries) can claim by signing the CPI with `invoke_signed`.
+    pub wallet: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    // Writable so mint-on-claim campaigns can mint_to
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    /// Wallet's holding of the gating mint (or of the collection NFT); only for gated campaigns
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+
+    /// CHECK: Pyth `PriceUpdateV2`; owner and contents checked in `record_usd_claim`.
+    /// Required while leaves are USD-denominated
+    pub price_update: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct ClaimViaCpi<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// Leaf wallet. Read-only so program-owned PDAs (multisig vaults,
+    /// protocol treasuries) can claim by signing the CPI with `invoke_signed`.
+    pub wallet: Signer<'info>,
+
+    /// CHECK: program credited in `CpiClaimed`; proven by `caller_authority`
+    #[account(executable)]
+    pub caller_program: UncheckedAccount<'info>,
+
+    /// Only `caller_program` can sign for its caller PDA
+    #[account(seeds = [CALLER_AUTHORITY_SEED], bump, seeds::program = caller_program.key())]
+    pub caller_authority: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    // Writable so mint-on-claim campaigns can mint_to
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    /// Wallet's holding of the gating mint (or of the collection NFT); only for gated campaigns
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct ReserveClaim<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// Pays the escrow rent, refunded on withdraw
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [ESCROW_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()],
+        bump,
+        payer = wallet,
+        space = CLAIM_ESCROW_SPACE
+    )]
+    pub escrow: Account<'info, ClaimEscrow>,
+    pub system_program: Program<'info, System>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, state.snapshot_hash.as_ref(), &escrow.index.to_le_bytes()],
+        bump = escrow.bump,
+        has_one = wallet,
+        close = wallet
+    )]
+    pub escrow: Account<'info, ClaimEscrow>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct ClaimVested<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// Pays the vesting account rent, refunded once fully released
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [VESTING_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()],
+        bump,
+        payer = wallet,
+        space = VESTING_SPACE
+    )]
+    pub vesting: Account<'info, VestingAccount>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReleaseVested<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(
+        mut,
+        seeds = [VESTING_SEED, state.snapshot_hash.as_ref(), &vesting.index.to_le_bytes()],
+        bump = vesting.bump,
+        has_one = wallet
+    )]
+    pub vesting: Account<'info, VestingAccount>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimUnwrapped<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated,
+        constraint = !state.mint_on_claim @ ErrorCode::NotWrappedSol
+    )]
+    pub state: Account<'info, State>,
+
+    /// Pays the temporary account's rent and receives it back with the SOL
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Opened and closed within the instruction
+    #[account(
+        init,
+        seeds = [UNWRAP_SEED, wallet.key().as_ref()],
+        bump,
+        payer = wallet,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub temp_wsol: Account<'info, TokenAccount>,
+
+    #[account(
+        address = state.mint @ ErrorCode::InvalidMint,
+        constraint = mint.key() == token::spl_token::native_mint::ID @ ErrorCode::NotWrappedSol
+    )]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimDomain<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// CHECK: SPL Name Service record committed to by the leaf; owner read in `name_record_owner`
+    pub domain: UncheckedAccount<'info>,
+
+    /// Current domain owner; read-only for the same reason as `Claim::wallet`
+    pub owner: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = owner
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Owner's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == owner.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DistributeBatch<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(mut, address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseAirdrop<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateClaim<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+    /// CHECK: the leaf wallet being checked; only its key is read and it need not sign
+    pub wallet: UncheckedAccount<'info>,
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+    /// CHECK: Pyth `PriceUpdateV2`; owner and contents checked in `record_usd_claim`.
+    /// Required, with `mint`, while leaves are USD-denominated
+    pub price_update: Option<UncheckedAccount<'info>>,
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Option<Account<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStatus<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EmitUnclaimed<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    /// Collects the crank bounty for the next page of an ended round
+    #[account(mut)]
+    pub cranker: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct AssertSnapshotHash<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+}
+
+#[derive(Accounts)]
+pub struct GetAirdropInfo<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CrankClose<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct LockConfig<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RenounceAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddFundingVault<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        constraint = vault.mint == state.mint @ ErrorCode::InvalidMint,
+        constraint = vault.owner == vault_auth.key() @ ErrorCode::InvalidVault
+    )]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveFundingVault<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCrankBounty<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCampaignMetadata<'info> {
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [METADATA_SEED, state.key().as_ref()],
+        bump,
+        payer = authority,
+        space = CAMPAIGN_METADATA_SPACE
+    )]
+    pub metadata: Account<'info, CampaignMetadata>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetDisputePeriod<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetOperatorFee<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [TREASURY_SEED, state.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(address = treasury.token_account @ ErrorCode::InvalidFeeAccount)]
+    pub fee_account: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetLatePenalty<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [TREASURY_SEED, state.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// Must already be the campaign's fee account; see `set_operator_fee`
+    #[account(
+        address = treasury.token_account @ ErrorCode::InvalidFeeAccount,
+        constraint = fee_account.key() == state.fee_account @ ErrorCode::InvalidFeeAccount
+    )]
+    pub fee_account: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetEarlyBonus<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitBonusVault<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [BONUS_VAULT_SEED, state.key().as_ref()],
+        bump,
+        payer = authority,
+        token::mint = mint,
+        token::authority = state
+    )]
+    pub bonus_vault: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawBonusReserve<'info> {
+    #[account(seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitTreasury<'info> {
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [TREASURY_SEED, state.key().as_ref()],
+        bump,
+        payer = authority,
+        space = TREASURY_SPACE
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        seeds = [TREASURY_TOKENS_SEED, state.key().as_ref()],
+        bump,
+        payer = authority,
+        token::mint = mint,
+        token::authority = treasury
+    )]
+    pub treasury_tokens: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, state.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut, address = treasury.token_account @ ErrorCode::InvalidFeeAccount)]
+    pub treasury_tokens: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitClaimStats<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [STATS_SEED, state.key().as_ref()],
+        bump,
+        payer = authority,
+        space = CLAIM_STATS_SPACE
+    )]
+    pub stats: Account<'info, ClaimStats>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCloseGrace<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetWalletCap<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct InitWalletClaims<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [WALLET_CLAIMS_SEED, state.key().as_ref(), wallet.as_ref()],
+        bump,
+        payer = payer,
+        space = WALLET_CLAIMS_SPACE
+    )]
+    pub wallet_claims: Account<'info, WalletClaims>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FreezeIndex<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnfreezeIndex<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetLowWaterMark<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResumeClaims<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetTermsHash<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetClaimGate<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetLeafEncoding<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateClaimWindow<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetMaxStartLead<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateMerkleRoot<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+}
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_capacity: u64)]
+pub struct ResizeState<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        has_one = authority,
+        realloc = state_space(new_capacity),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required while `state.legacy_claims` is set; closed to the authority
+    /// once the bitmap covers every leaf
+    #[account(mut, seeds = [LEGACY_CLAIMS_SEED, state.key().as_ref()], bump)]
+    pub legacy_claims: Option<Account<'info, LegacyClaims>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: Raw access since older layouts don't deserialize as `State`;
+    /// discriminator and authority are verified in the handler.
+    #[account(mut, seeds = [STATE_SEED], bump, owner = crate::ID)]
+    pub state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The canonical vault, which the old layout didn't record
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Required exactly when claims recorded before migration lie past the
+    /// new bitmap; `resize_state` converts and closes it
+    #[account(
+        init,
+        payer = authority,
+        space = LEGACY_CLAIMS_SPACE,
+        seeds = [LEGACY_CLAIMS_SEED, state.key().as_ref()],
+        bump
+    )]
+    pub legacy_claims: Option<Account<'info, LegacyClaims>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseState<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        close = recipient
+    )]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    /// Receives the rent back; must be a plain system-owned wallet
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_snapshot_hash: [u8; 32])]
+pub struct FinalizeAndRollover<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [ROLLOVER_SEED, state.snapshot_hash.as_ref()],
+        bump,
+        payer = authority,
+        space = ROLLOVER_SUMMARY_SPACE
+    )]
+    pub summary: Account<'info, RolloverSummary>,
+
+    /// PDA authority of the finished round
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// PDA authority of the next round
+    #[account(
+        seeds = [VAULT_SEED, new_snapshot_hash.as_ref()],
+        bump
+    )]
+    pub new_vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = new_vault_auth
+    )]
+    pub new_vault: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_snapshot_hash: [u8; 32])]
+pub struct RotateVaultAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        has_one = authority,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// Receives the closed vault's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Current PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// PDA authority under the new snapshot hash
+    #[account(
+        seeds = [VAULT_SEED, new_snapshot_hash.as_ref()],
+        bump
+    )]
+    pub new_vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = new_vault_auth
+    )]
+    pub new_vault: Account<'info, TokenAccount>,
+
+    // Writable so mint-on-claim campaigns can hand over mint authority
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(epoch: u64, merkle_root: [u8; 32], total_claims: u64)]
+pub struct CreateEpoch<'info> {
+    #[account(seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [EPOCH_SEED, state.key().as_ref(), &epoch.to_le_bytes()],
+        bump,
+        payer = authority,
+        space = epoch_root_space(total_claims)
+    )]
+    pub epoch_root: Account<'info, EpochRoot>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct ClaimEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(
+        mut,
+        seeds = [EPOCH_SEED, state.key().as_ref(), &epoch.to_le_bytes()],
+        bump = epoch_root.bump
+    )]
+    pub epoch_root: Account<'info, EpochRoot>,
+
+    pub wallet: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
     pub user_ata: Account<'info, TokenAccount>,
 
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct CloseAirdrop<'info> {
-    #[account(mut, has_one = authority)]
+pub struct RequestRaffle<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
     pub state: Account<'info, State>,
+
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [RAFFLE_SEED, state.snapshot_hash.as_ref()],
+        bump,
+        payer = authority,
+        space = RAFFLE_SPACE
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    /// CHECK: Switchboard randomness account; owner checked, contents parsed in handler
+    #[account(
+        owner = switchboard_on_demand::ON_DEMAND_MAINNET_PID @ ErrorCode::InvalidRandomness
+    )]
+    pub randomness_account: UncheckedAccount<'info>,
+
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct UpdateClaimWindow<'info> {
-    #[account(mut, has_one = authority)]
+pub struct SettleRaffle<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
     pub state: Account<'info, State>,
-    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [RAFFLE_SEED, state.snapshot_hash.as_ref()],
+        bump = raffle.bump,
+        has_one = randomness_account @ ErrorCode::InvalidRandomness
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    /// CHECK: pinned to the account committed at request time
+    pub randomness_account: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct UpdateMerkleRoot<'info> {
-    #[account(mut, has_one = authority)]
+#[instruction(index: u64)]
+pub struct ClaimRafflePrize<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
     pub state: Account<'info, State>,
-    pub authority: Signer<'info>,
-}
-#[derive(Accounts)]
-pub struct CloseState<'info> {
+
     #[account(
         mut,
-        has_one = authority,
-        close = recipient
+        seeds = [RAFFLE_SEED, state.snapshot_hash.as_ref()],
+        bump = raffle.bump
     )]
-    pub state: Account<'info, State>,
-    pub authority: Signer<'info>,
-    /// CHECK: The recipient to receive rent back.
+    pub raffle: Account<'info, Raffle>,
+
+    #[account(
+        init,
+        seeds = [RAFFLE_PRIZE_SEED, raffle.key().as_ref(), &index.to_le_bytes()],
+        bump,
+        payer = wallet,
+        space = RAFFLE_PRIZE_SPACE
+    )]
+    pub prize: Account<'info, RafflePrize>,
+
     #[account(mut)]
-    pub recipient: AccountInfo<'info>,
+    pub wallet: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 // Events & Errors
 #[event]
@@ -414,13 +5630,69 @@ pub struct AirdropInitialized {
     pub snapshot_hash: [u8; 32],
     pub claim_start_ts: i64,
     pub claim_duration: i64,
+    pub allocations_hash: [u8; 32],
+    pub allocations_uri: String,
+    pub total_allocation: u64,
 }
 
 #[event]
 pub struct Claimed {
     pub wallet: Pubkey,
     pub amount: u64,
+    pub fee: u64, // operator's cut of `amount`; the recipient got the rest
+    pub penalty: u64, // late-claim haircut, paid to the treasury
+    pub net_amount: u64,
+    pub bonus: u64, // early-claim top-up from the bonus reserve, on top of `net_amount`
+    pub index: u64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+    pub campaign: Pubkey, // the campaign's `State` account
+    pub mint: Pubkey,
+    pub root_version: u32, // which of the campaign's roots the claim was proven against
+    pub vault_balance: u64, // left in the vault that paid, after the claim
+}
+
+#[event]
+pub struct CpiClaimed {
+    pub caller_program: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub fee: u64, // operator's cut of `amount`; the recipient got the rest
+    pub penalty: u64, // late-claim haircut, paid to the treasury
+    pub net_amount: u64,
+    pub bonus: u64, // early-claim top-up from the bonus reserve, on top of `net_amount`
+    pub index: u64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct DomainClaimed {
+    pub domain: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub fee: u64, // operator's cut of `amount`; the recipient got the rest
+    pub penalty: u64, // late-claim haircut, paid to the treasury
+    pub net_amount: u64,
+    pub bonus: u64, // early-claim top-up from the bonus reserve, on top of `net_amount`
     pub index: u64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct BatchDistributed {
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub total_amount: u64,
     pub timestamp: i64,
 }
 
@@ -428,6 +5700,277 @@ pub struct Claimed {
 pub struct AirdropClosed {
     pub authority: Pubkey,
     pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+    pub closes_at: i64, // last second claims are accepted; `timestamp` unless a close grace applied
+}
+
+#[event]
+pub struct CrankBountyPaid {
+    pub cranker: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigLocked {
+    pub merkle_root: [u8; 32],
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityRenounced {
+    pub previous_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultAuthorityRotated {
+    pub old_snapshot_hash: [u8; 32],
+    pub new_snapshot_hash: [u8; 32],
+    pub old_vault_auth: Pubkey,
+    pub new_vault_auth: Pubkey,
+    pub new_vault: Pubkey,
+    pub moved_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundingVaultUpdated {
+    pub vault: Pubkey,
+    pub registered: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CrankBountyUpdated {
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CampaignMetadataUpdated {
+    pub name: String,
+    pub symbol: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TermsHashUpdated {
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimGateUpdated {
+    pub kind: GateKind,
+    pub gate_key: Pubkey,
+    pub min_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LeafEncodingUpdated {
+    pub encoding: LeafEncoding,
+    pub merkle_root: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EpochCreated {
+    pub epoch: u64,
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub claim_start_ts: i64,
+    pub claim_duration: i64,
+    pub total_allocation: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EpochClaimed {
+    pub epoch: u64,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub index: u64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct RaffleRequested {
+    pub snapshot_hash: [u8; 32],
+    pub randomness_account: Pubkey,
+    pub commit_slot: u64,
+    pub winner_count: u32,
+    pub pool_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RaffleSettled {
+    pub snapshot_hash: [u8; 32],
+    pub randomness: [u8; 32],
+    pub winners: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RafflePrizeClaimed {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub prize: u64,
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct VaultBelowLowWaterMark {
+    pub vault_balance: u64,
+    pub low_water_mark: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimReserved {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub release_ts: i64,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingStarted {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub penalty: u64, // late-claim haircut paid to the treasury at the claim; `amount` is what vests
+    pub start_ts: i64,
+    pub cliff: i64,
+    pub duration: i64,
+    pub tge_bps: u16,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestedReleased {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64, // newly vested; `fee` of it went to the treasury
+    pub fee: u64,
+    pub released: u64, // running total, `amount` included
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct EscrowWithdrawn {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub penalty: u64, // late-claim haircut, paid to the treasury
+    pub net_amount: u64,
+    pub bonus: u64, // early-claim top-up from the bonus reserve, on top of `net_amount`
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct DisputePeriodUpdated {
+    pub dispute_period: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LatePenaltyUpdated {
+    pub start_ts: i64,
+    pub ramp: i64,
+    pub penalty_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EarlyBonusUpdated {
+    pub bonus_bps: u16,
+    pub end_ts: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BonusReserveWithdrawn {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub destination: Pubkey,
+    pub token_amount: u64,
+    pub lamports: u64,
+    pub tokens_withdrawn: u64,
+    pub lamports_withdrawn: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperatorFeeUpdated {
+    pub fee_bps: u16,
+    pub fee_account: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IndexFreezeUpdated {
+    pub index: u64,
+    pub frozen: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LowWaterMarkUpdated {
+    pub low_water_mark: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WalletCapUpdated {
+    pub wallet_cap: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CloseGraceUpdated {
+    pub close_grace: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimsResumed {
+    pub vault_balance: u64,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -437,15 +5980,64 @@ pub struct ClaimWindowUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct MaxStartLeadUpdated {
+    pub max_start_lead: i64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct MerkleRootUpdated {
+    pub old_root: [u8; 32],
     pub new_root: [u8; 32],
     pub new_total_claims: u64,
+    pub new_allocations_hash: [u8; 32],
+    pub new_allocations_uri: String,
+    pub timestamp: i64,
+    pub campaign: Pubkey,
+    pub mint: Pubkey,
+    pub root_version: u32, // of `new_root`
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct StateResized {
+    pub old_capacity: u64,
+    pub new_capacity: u64,
+    pub space: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StateMigrated {
+    pub from_len: u64,
+    pub to_len: u64,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CampaignRolledOver {
+    pub previous_round: u32,
+    pub previous_snapshot_hash: [u8; 32],
+    pub new_snapshot_hash: [u8; 32],
+    pub new_merkle_root: [u8; 32],
+    pub rolled_amount: u64,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct UnclaimedIndices {
+    pub round: u32,
+    pub range_start: u64,
+    pub range_end: u64, // exclusive
+    pub unclaimed_count: u32,
+    pub unclaimed: Vec<u8>, // bit i (LSB first) set = `range_start + i` is unclaimed
+}
+
 #[error_code]
 pub enum ErrorCode {
+    // Superseded by WindowNotStarted / WindowEnded; kept so later codes don't shift
     #[msg("Airdrop claim window is not open.")]
     ClaimWindowClosed,
     #[msg("Airdrop already claimed.")]
@@ -459,4 +6051,263 @@ pub enum ErrorCode {
     #[msg("Invalid index.")]
     InvalidIndex,
     #[msg("Airdrop is closed.")]
-    ClaimClosed,}
+    ClaimClosed,
+    #[msg("Campaign is still accepting claims.")]
+    CampaignStillActive,
+    #[msg("Snapshot hash already used by this campaign.")]
+    SnapshotReused,
+    #[msg("Claim capacity must be 1 to 2^16 at creation, grow to at most 2^24, and not shrink below total claims.")]
+    InvalidCapacity,
+    #[msg("Index or total claims exceed the state's claim capacity; call resize_state first.")]
+    CapacityExceeded,
+    // Unused now that resize_state converts claims from before migration;
+    // kept so later codes don't shift
+    #[msg("Claims recorded as residues before migration pin the capacity until the round rolls over.")]
+    ResizeAfterClaims,
+    #[msg("State account layout is not recognized.")]
+    UnknownStateLayout,
+    #[msg("State account is already at the current version.")]
+    AlreadyMigrated,
+    #[msg("State account must be migrated with migrate_state first.")]
+    StateNotMigrated,
+    #[msg("Proof is deeper than the supported maximum.")]
+    ProofTooLong,
+    #[msg("Vault does not match the campaign vault.")]
+    InvalidVault,
+    #[msg("Mint does not match the campaign mint.")]
+    InvalidMint,
+    #[msg("Batch must list 1-20 recipients, one amount per recipient account.")]
+    InvalidBatch,
+    #[msg("Arithmetic overflow.")]
+    ArithmeticOverflow,
+    #[msg("Mint-on-claim requires vault_auth to be the mint authority.")]
+    MintAuthorityMismatch,
+    #[msg("Metadata field exceeds its maximum length.")]
+    MetadataTooLong,
+    #[msg("Claim must accept the campaign's current terms hash.")]
+    TermsNotAccepted,
+    #[msg("Wallet does not hold the token or NFT required by the claim gate.")]
+    GateNotSatisfied,
+    #[msg("Domain account is not an SPL Name Service record.")]
+    InvalidDomain,
+    #[msg("Signer does not currently own the domain.")]
+    NotDomainOwner,
+    #[msg("Raffles are not available for mint-on-claim campaigns.")]
+    RaffleUnavailable,
+    #[msg("Winner count must be non-zero and no larger than the number of claimants or MAX_RAFFLE_WINNERS.")]
+    InvalidWinnerCount,
+    #[msg("Randomness account is invalid or does not match the raffle.")]
+    InvalidRandomness,
+    #[msg("Randomness was already revealed when the raffle was requested.")]
+    RandomnessAlreadyRevealed,
+    #[msg("Randomness has not been revealed yet.")]
+    RandomnessNotResolved,
+    #[msg("Raffle has already been settled.")]
+    RaffleAlreadySettled,
+    #[msg("Claim did not win the raffle.")]
+    NotRaffleWinner,
+    #[msg("Raffle prize pool has been paid out.")]
+    RafflePoolExhausted,
+    #[msg("Total allocation must be non-zero.")]
+    InvalidTotalAllocation,
+    #[msg("Claim would exceed the campaign's committed total allocation.")]
+    AllocationExceeded,
+    #[msg("Vault is below its low-water mark; claims resume after a refill.")]
+    VaultNeedsRefill,
+    #[msg("Recipient token account is frozen by the mint's freeze authority.")]
+    RecipientAccountFrozen,
+    #[msg("Unwrapped claims are only available for vault-funded wrapped SOL campaigns.")]
+    NotWrappedSol,
+    #[msg("Operator fee is capped at 1000 bps.")]
+    FeeTooHigh,
+    #[msg("Fee account is missing or differs from the configured one.")]
+    InvalidFeeAccount,
+    #[msg("Campaign is in escrow mode; use reserve_claim and withdraw.")]
+    EscrowRequired,
+    #[msg("Escrow mode is not enabled for this campaign.")]
+    EscrowDisabled,
+    #[msg("Reservation is still within its dispute period.")]
+    DisputePeriodActive,
+    #[msg("Reserved claims must be withdrawn before the vault can move.")]
+    PendingEscrows,
+    #[msg("This allocation index is frozen by the authority.")]
+    IndexFrozen,
+    #[msg("Frozen index set is full; unfreeze an index or close the campaign.")]
+    FrozenSetFull,
+    #[msg("Claim stats are enabled; pass the stats account.")]
+    StatsAccountMissing,
+    #[msg("Treasury holds fewer withdrawable lamports than requested.")]
+    InsufficientTreasury,
+    #[msg("Late penalty is capped at 5000 bps.")]
+    PenaltyTooHigh,
+    #[msg("Early bonus is capped at 10000 bps.")]
+    BonusTooHigh,
+    #[msg("Bonus vault missing or not the campaign's bonus reserve.")]
+    InvalidBonusVault,
+    #[msg("Bonus reserve can't be withdrawn while the bonus window is open.")]
+    BonusWindowActive,
+    #[msg("Vesting schedule has a negative cliff or duration, or a TGE share above 10000 bps.")]
+    InvalidVestingSchedule,
+    #[msg("Nothing has vested since the last release.")]
+    NothingVested,
+    #[msg("Vesting allocations must be fully released before the vault can move.")]
+    PendingVesting,
+    #[msg("This campaign records claims in a compressed ledger; use claim_compressed.")]
+    CompressedLedgerRequired,
+    #[msg("This campaign has no compressed claim ledger.")]
+    CompressedLedgerDisabled,
+    #[msg("Claim ledger can only change before the first claim.")]
+    ClaimsAlreadyRecorded,
+    #[msg("Claim ledger is not the campaign's concurrent Merkle tree.")]
+    InvalidClaimLedger,
+    #[msg("Claim window end overflows.")]
+    WindowOverflow,
+    #[msg("Campaign configuration is locked.")]
+    ConfigLocked,
+    #[msg("Claims must be closed or paused before the Merkle root can change.")]
+    ClaimsNotPaused,
+    #[msg("Claim window has not started yet.")]
+    WindowNotStarted,
+    #[msg("Claim window has ended.")]
+    WindowEnded,
+    #[msg("Token account is not for the campaign mint.")]
+    WrongMint,
+    #[msg("Funding vault set is full.")]
+    VaultSetFull,
+    #[msg("Unregister the extra funding vaults first.")]
+    ExtraVaultsRegistered,
+    #[msg("This campaign pays claims only against a Wormhole VAA from its home chain.")]
+    CrossChainClaimRequired,
+    #[msg("Cross-chain claims are not enabled for this campaign.")]
+    CrossChainDisabled,
+    #[msg("VAA is not a posted Wormhole message from the campaign's emitter.")]
+    InvalidVaa,
+    #[msg("VAA payload does not match the claim.")]
+    VaaPayloadMismatch,
+    #[msg("Index has not been claimed.")]
+    NotClaimed,
+    #[msg("Claim badges can only be enabled before the first claim.")]
+    ClaimBadgeTooLate,
+    #[msg("Claim badges are enabled; pass the badge mint, badge token account and Token-2022 program.")]
+    ClaimBadgeMissing,
+    #[msg("Badge mint or token account does not belong to this claim.")]
+    InvalidBadgeAccount,
+    #[msg("A wallet cap is set; pass the claimant's wallet_claims account.")]
+    WalletClaimsMissing,
+    #[msg("Claim would take the wallet past its per-round cap.")]
+    WalletCapExceeded,
+    #[msg("Relayer is not registered.")]
+    UnknownRelayer,
+    #[msg("Relayer fee exceeds the signed maximum or the claim's net amount.")]
+    RelayerFeeTooHigh,
+    #[msg("Leaves are USD-denominated; claim with `claim` and a Pyth price update.")]
+    UsdPriceRequired,
+    #[msg("Price update is not a fully verified Pyth update for the campaign's feed.")]
+    InvalidPriceUpdate,
+    #[msg("Price update is older than the campaign allows.")]
+    StalePrice,
+    #[msg("Price confidence interval is wider than the campaign allows.")]
+    PriceTooUncertain,
+    #[msg("USD pricing needs a positive max age and a confidence bound of at most 10000 bps.")]
+    InvalidPricingConfig,
+    #[msg("Snapshot hash was not derived from this root and allocation.")]
+    SnapshotMismatch,
+    #[msg("Index range must be non-empty, within total_claims and at most 2048 long.")]
+    InvalidRange,
+    #[msg("Claim start is too far from the current time; timestamps are Unix seconds.")]
+    ClaimStartOutOfRange,
+    #[msg("Start lead must be between zero and ten years.")]
+    InvalidStartLead,
+    #[msg("Campaign is not funded yet; fund the vault and run advance_status.")]
+    NotFunded,
+    #[msg("Not allowed in the campaign's current lifecycle status.")]
+    InvalidStatus,
+    #[msg("Claims need a ticket; commit with commit_claim, then claim with claim_ticketed.")]
+    TicketRequired,
+    #[msg("Claim tickets are not enabled for this campaign.")]
+    TicketingDisabled,
+    #[msg("Ticket does not open for this claim or round.")]
+    InvalidTicket,
+    #[msg("Ticket's reveal delay has not passed yet.")]
+    RevealTooEarly,
+    #[msg("Reveal delay must be at most 150 slots.")]
+    InvalidRevealDelay,
+    #[msg("Creation fee exceeds the signed maximum.")]
+    CreationFeeTooHigh,
+    #[msg("Fee treasury does not match the protocol config.")]
+    InvalidFeeTreasury,
+    #[msg("Close grace must be between 0 and 3600 seconds.")]
+    InvalidCloseGrace,
+    #[msg("Amount is below the dust threshold; dust leaves are paid to the community pool by sweep_dust.")]
+    BelowDustThreshold,
+    #[msg("Community pool must be set while a dust threshold is, and match the campaign's.")]
+    InvalidCommunityPool,
+    #[msg("No dust threshold is set for this campaign.")]
+    DustSweepDisabled,
+    #[msg("Leaf is not below the dust threshold; claim it with claim.")]
+    NotDust,
+    #[msg("An append must add at least one claim.")]
+    InvalidAppend,
+    #[msg("Root history is full; replace the root with update_merkle_root.")]
+    RootHistoryFull,
+    #[msg("Activation time must be in the future and after every root already scheduled.")]
+    InvalidActivationTime,
+    #[msg("Root schedule is full.")]
+    RootScheduleFull,
+    #[msg("A family needs a non-zero id and at most 7 distinct siblings other than this program.")]
+    InvalidFamily,
+    #[msg("This campaign is not in a family.")]
+    NotInFamily,
+    #[msg("Pass each sibling's family_claim address, in family order.")]
+    InvalidFamilySibling,
+    #[msg("Wallet already joined a sibling campaign of this family.")]
+    JoinedSiblingCampaign,
+    #[msg("Campaign is in a family; pass the wallet's family_claim, opened with join_family.")]
+    FamilyClaimMissing,
+    #[msg("Leaf fields must be at most 256 bytes, and not empty for EVM-packed leaves.")]
+    InvalidLeafFields,
+    #[msg("Leaf does not match the wallet, amount and fields under its schema.")]
+    LeafMismatch,
+    #[msg("Cohort vault is missing, unregistered, or not allowed with these settings.")]
+    InvalidCohortVault,
+    #[msg("Cohort's window and clawback time must both have passed.")]
+    ClawbackNotDue,
+    #[msg("Claw back the cohort vaults first.")]
+    CohortVaultsRegistered,
+    #[msg("Claims in this campaign open a lock in its vesting program; use claim_to_vesting.")]
+    VestingHandoffRequired,
+    #[msg("Vesting handoff is not set, or its template is invalid.")]
+    InvalidVestingHandoff,
+    #[msg("Accounts passed do not fill the vesting handoff template.")]
+    HandoffAccountMismatch,
+    #[msg("Vesting program did not take the whole claim.")]
+    HandoffIncomplete,
+    #[msg("Bonus lottery is on; switch it off once every ticket is redeemed.")]
+    BonusLotteryActive,
+    #[msg("Bonus lottery is off, settled, or past the bonus window.")]
+    BonusLotteryClosed,
+    #[msg("Bonus ticket was already drawn, or its slot hash isn't available yet.")]
+    InvalidBonusTicket,
+    #[msg("Bonus window is still open or tickets are still unrevealed.")]
+    LotteryNotSettleable,
+    #[msg("Bonus lottery has not been settled yet.")]
+    LotteryNotSettled,
+    // Superseded by LeavesPastClaimBitmap; kept so later codes don't shift
+    #[msg("This round has claims recorded as residues before migration; roll over for exact claim status.")]
+    InexactClaimRecord,
+    #[msg("Claims from before migration past the claim bitmap are held in legacy_claims until resize_state converts them.")]
+    LeavesPastClaimBitmap,
+    #[msg("Batch exceeds what the vault holds beyond reserved and vesting allocations.")]
+    BatchExceedsUnreserved,
+    #[msg("emit_unclaimed has not exported every page of this round.")]
+    ExportIncomplete,
+    #[msg("This round's raffle is still drawing winners or paying prizes.")]
+    RaffleOpen,
+    #[msg("Raffle claim period has ended.")]
+    RaffleExpired,
+    #[msg("Allocation was voided by the authority.")]
+    AllocationVoided,
+    #[msg("Account is not the VoidedIndex record of the drawn index.")]
+    InvalidVoidRecord,
+    #[msg("Vault doesn't cover the scheduled root's allocation beyond what has been claimed.")]
+    ScheduledRootUnderfunded,}
@@ -0,0 +1,94 @@
+//! Campaign lifecycle: Draft → Funded → Live → Ended → Finalized.
+//!
+//! `state.status` records where a round is, and instructions check it
+//! instead of inferring it from the clock and switches. Nothing can be
+//! claimed before the vault covers the allocation (Funded), and the state
+//! can only be closed once the round is Finalized (or was never funded).
+//! `close_airdrop` and `crank_close` end a round, `update_claim_window`
+//! can bring an ended round back to Live, `finalize_airdrop` finalizes it
+//! and `finalize_and_rollover` starts the next round as Draft or Funded.
+//! The transitions that only depend on facts (funding arriving, the window
+//! opening or passing) are taken by the permissionless `advance_status`
+//! crank. Every transition emits `StatusChanged`.
+
+use super::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CampaignStatus {
+    /// Initialized, but the vault doesn't yet cover the allocation
+    Draft,
+    /// Fully funded; claims open with the window
+    Funded,
+    /// The window has opened
+    Live,
+    /// Closed, or past the window
+    Ended,
+    /// Outcome recorded by `finalize_airdrop`; the state may be closed
+    Finalized,
+}
+
+/// Permissionless: takes the next transition the campaign's facts allow.
+/// Draft becomes Funded once the vault covers what is left to claim, Funded
+/// becomes Live once the window opens, and Live becomes Ended once it has
+/// passed.
+pub fn advance_status(ctx: Context<AdvanceStatus>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let state = &mut ctx.accounts.state;
+    let next = match state.status {
+        CampaignStatus::Draft if is_funded(state, ctx.accounts.vault.amount) => CampaignStatus::Funded,
+        CampaignStatus::Draft => return err!(ErrorCode::NotFunded),
+        CampaignStatus::Funded if now >= state.claim_start_ts => CampaignStatus::Live,
+        CampaignStatus::Live if now > claim_end_ts(state.claim_start_ts, state.claim_duration)? => {
+            state.claim_closed = true;
+            CampaignStatus::Ended
+        }
+        _ => return err!(ErrorCode::InvalidStatus),
+    };
+    let changed = set_status(state, next, now);
+    emit_cpi!(changed);
+    Ok(())
+}
+
+// Whether `vault_balance` covers what the round still owes
+pub(crate) fn is_funded(state: &State, vault_balance: u64) -> bool {
+    state.mint_on_claim || vault_balance >= state.total_allocation.saturating_sub(state.claimed_amount)
+}
+
+// Claims are paid from Funded or Live; Draft isn't funded yet
+pub(crate) fn check_claimable_status(state: &State) -> Result<()> {
+    match state.status {
+        CampaignStatus::Funded | CampaignStatus::Live => Ok(()),
+        CampaignStatus::Draft => err!(ErrorCode::NotFunded),
+        _ => err!(ErrorCode::InvalidStatus),
+    }
+}
+
+// Moves the round to `to`, returning the event to emit
+pub(crate) fn set_status(state: &mut State, to: CampaignStatus, now: i64) -> StatusChanged {
+    let from = state.status;
+    state.status = to;
+    StatusChanged {
+        round: state.round,
+        from,
+        to,
+        timestamp: now,
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AdvanceStatus<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[event]
+pub struct StatusChanged {
+    pub round: u32,
+    pub from: CampaignStatus,
+    pub to: CampaignStatus,
+    pub timestamp: i64,
+}
@@ -0,0 +1,152 @@
+//! Program-wide protocol configuration.
+//!
+//! A deployment run as shared infrastructure has one `["config"]` account,
+//! created by the program's upgrade authority with `init_config`. It names
+//! the protocol admin, the fee schedule and the `fee_treasury` that fees
+//! are paid to. Today the schedule is a lamport `creation_fee`, which
+//! `initialize` charges the campaign's creator. The creator signs a
+//! `max_creation_fee`, so a fee raised after signing makes `initialize`
+//! fail instead of charging more. Deployments that never run `init_config`
+//! charge nothing.
+
+use super::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+pub fn init_config(
+    ctx: Context<InitConfig>,
+    admin: Pubkey,
+    fee_treasury: Pubkey,
+    creation_fee: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.bump = ctx.bumps.config;
+    write_config(config, admin, fee_treasury, creation_fee)?;
+    emit_cpi!(ConfigUpdated {
+        admin,
+        fee_treasury,
+        creation_fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Replaces the whole schedule; passing another `admin` hands the config over.
+pub fn set_config(
+    ctx: Context<SetConfig>,
+    admin: Pubkey,
+    fee_treasury: Pubkey,
+    creation_fee: u64,
+) -> Result<()> {
+    write_config(&mut ctx.accounts.config, admin, fee_treasury, creation_fee)?;
+    emit_cpi!(ConfigUpdated {
+        admin,
+        fee_treasury,
+        creation_fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn write_config(config: &mut Config, admin: Pubkey, fee_treasury: Pubkey, creation_fee: u64) -> Result<()> {
+    require!(admin != Pubkey::default(), ErrorCode::Unauthorized);
+    require!(
+        creation_fee == 0 || fee_treasury != Pubkey::default(),
+        ErrorCode::InvalidFeeTreasury
+    );
+    config.admin = admin;
+    config.fee_treasury = fee_treasury;
+    config.creation_fee = creation_fee;
+    Ok(())
+}
+
+// Charges the configured creation fee to `payer`; returns what was charged,
+// zero when `init_config` never ran
+pub(crate) fn charge_creation_fee<'info>(
+    config: &UncheckedAccount<'info>,
+    fee_treasury: Option<&UncheckedAccount<'info>>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    max_creation_fee: u64,
+) -> Result<u64> {
+    if config.owner != &crate::ID {
+        return Ok(0);
+    }
+    let config = Config::try_deserialize(&mut &config.data.borrow()[..])?;
+    if config.creation_fee == 0 {
+        return Ok(0);
+    }
+    require!(config.creation_fee <= max_creation_fee, ErrorCode::CreationFeeTooHigh);
+    let fee_treasury = fee_treasury.ok_or(ErrorCode::InvalidFeeTreasury)?;
+    require_keys_eq!(fee_treasury.key(), config.fee_treasury, ErrorCode::InvalidFeeTreasury);
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: fee_treasury.to_account_info(),
+            },
+        ),
+        config.creation_fee,
+    )?;
+    Ok(config.creation_fee)
+}
+
+/// Program-wide settings; seeds = ["config"].
+#[account]
+pub struct Config {
+    pub admin: Pubkey, // may change the schedule with `set_config`
+    pub fee_treasury: Pubkey, // receives protocol fees
+    pub creation_fee: u64, // lamports `initialize` charges per campaign
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(
+        init,
+        seeds = [CONFIG_SEED],
+        bump,
+        payer = upgrade_authority,
+        space = CONFIG_SPACE
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    /// This program's ProgramData, whose upgrade authority alone may create the config
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetConfig<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub fee_treasury: Pubkey,
+    pub creation_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreationFeePaid {
+    pub authority: Pubkey,
+    pub fee_treasury: Pubkey,
+    pub fee: u64,
+    pub timestamp: i64,
+}
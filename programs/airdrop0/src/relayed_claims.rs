@@ -0,0 +1,326 @@
+//! Gasless claims through registered relayers.
+//!
+//! The claimant signs a `claim_relayed` transaction, and a relayer the
+//! authority registered pays its fee and submits it, so the claimant needs
+//! no SOL. The relayer is reimbursed a fixed token amount, `relayer_fee`,
+//! out of the claimant's allocation. The authority sets that amount, and
+//! every relayed claim reports it in `RelayedClaim`. The claimant signs a
+//! ceiling on it, so a fee raised after signing can't be charged.
+//!
+//! Each relayer has a `["relayer", state, relayer]` account, which both
+//! allowlists it and counts the claims it submitted and the fees it earned,
+//! so abuse can be attributed. Revoking only deactivates that account.
+
+use super::*;
+
+pub fn set_relayer_fee(ctx: Context<SetRelayerFee>, relayer_fee: u64) -> Result<()> {
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        state.relayer_fee = relayer_fee;
+        emit_cpi!(RelayerFeeUpdated {
+            relayer_fee,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+pub fn register_relayer(ctx: Context<RegisterRelayer>, relayer: Pubkey) -> Result<()> {
+    require!(relayer != Pubkey::default(), ErrorCode::UnknownRelayer);
+    track_config!(ctx, {
+        let relayer_account = &mut ctx.accounts.relayer_account;
+        relayer_account.state = ctx.accounts.state.key();
+        relayer_account.relayer = relayer;
+        relayer_account.active = true;
+        relayer_account.bump = ctx.bumps.relayer_account;
+        let updated = RelayerUpdated {
+            relayer,
+            registered: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        chain_config(&mut ctx.accounts.state.relayers_hash, &updated)?;
+        emit_cpi!(updated);
+    })
+}
+
+pub fn revoke_relayer(ctx: Context<RevokeRelayer>, relayer: Pubkey) -> Result<()> {
+    require!(ctx.accounts.relayer_account.active, ErrorCode::UnknownRelayer);
+    track_config!(ctx, {
+        ctx.accounts.relayer_account.active = false;
+        let updated = RelayerUpdated {
+            relayer,
+            registered: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        chain_config(&mut ctx.accounts.state.relayers_hash, &updated)?;
+        emit_cpi!(updated);
+    })
+}
+
+/// `claim`, submitted and paid for by a registered relayer, who receives
+/// `relayer_fee` of the allocation. Fails if the fee exceeds
+/// `max_relayer_fee` or what is left after the operator fee and penalty.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_relayed<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimRelayed<'info>>,
+    index: u64,
+    amount: u64,
+    proof: [[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    max_relayer_fee: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let state = &ctx.accounts.state;
+    let relayer = ctx.accounts.relayer.key();
+    let relayer_fee = state.relayer_fee;
+    require!(relayer_fee <= max_relayer_fee, ErrorCode::RelayerFeeTooHigh);
+    require!(state.dispute_period == 0, ErrorCode::EscrowRequired);
+    let wallet = ctx.accounts.wallet.key;
+    let paid = process_claim(
+        &mut ctx.accounts.state,
+        ClaimChecks {
+            claimant: wallet,
+            stats: ctx.accounts.stats.as_mut(),
+            wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+            family_claim: ctx.accounts.family_claim.as_deref(),
+            gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+            gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+        },
+        &ClaimPayout {
+            state_bump: ctx.bumps.state,
+            vault_auth_bump: ctx.bumps.vault_auth,
+            vault_auth: &ctx.accounts.vault_auth,
+            vault: &ctx.accounts.vault,
+            extra_vaults: ctx.remaining_accounts,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+            recipient: &ctx.accounts.user_ata,
+            fee_account: ctx.accounts.fee_account.as_ref(),
+            bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+            relayer: Some((&ctx.accounts.relayer_token_account, relayer_fee)),
+            badge_mint: ctx.accounts.badge_mint.as_ref(),
+            badge_account: ctx.accounts.badge_account.as_ref(),
+            token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+        },
+        now,
+        |state: &mut State| {
+            record_claim(state, index, wallet, amount, &proof, proof_len, terms_hash, now)?;
+            Ok(amount)
+        },
+    )?;
+    if let Some(vault_balance) = paid.low_water {
+        emit_cpi!(VaultBelowLowWaterMark {
+            vault_balance,
+            low_water_mark: ctx.accounts.state.low_water_mark,
+            timestamp: now,
+        });
+    }
+    let relayer_account = &mut ctx.accounts.relayer_account;
+    relayer_account.claims += 1;
+    relayer_account.fees_earned += relayer_fee;
+    let relayer_claims = relayer_account.claims;
+
+    emit_cpi!(Claimed {
+        wallet: *ctx.accounts.wallet.key,
+        amount,
+        fee: paid.fee,
+        penalty: paid.penalty,
+        net_amount: paid.net_amount,
+        bonus: paid.bonus,
+        index,
+        terms_hash,
+        timestamp: now,
+        campaign: ctx.accounts.state.key(),
+        mint: ctx.accounts.state.mint,
+        root_version: ctx.accounts.state.root_version,
+        vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+    });
+    emit_cpi!(RelayedClaim {
+        wallet: *ctx.accounts.wallet.key,
+        relayer,
+        index,
+        relayer_fee,
+        relayer_claims,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRelayerFee<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+/// A relayer's allowlist entry and running tally; seeds = ["relayer", state, relayer].
+#[account]
+pub struct Relayer {
+    pub state: Pubkey,
+    pub relayer: Pubkey,
+    pub active: bool, // may submit `claim_relayed`; cleared by `revoke_relayer`
+    pub claims: u64, // relayed claims submitted, across rounds and revocations
+    pub fees_earned: u64, // tokens paid to the relayer as `relayer_fee`
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(relayer: Pubkey)]
+pub struct RegisterRelayer<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [RELAYER_SEED, state.key().as_ref(), relayer.as_ref()],
+        bump,
+        payer = authority,
+        space = RELAYER_SPACE
+    )]
+    pub relayer_account: Account<'info, Relayer>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(relayer: Pubkey)]
+pub struct RevokeRelayer<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [RELAYER_SEED, state.key().as_ref(), relayer.as_ref()],
+        bump = relayer_account.bump
+    )]
+    pub relayer_account: Account<'info, Relayer>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRelayed<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    /// Leaf wallet; signs, but needs no SOL
+    pub wallet: Signer<'info>,
+
+    /// Registered relayer, the transaction's fee payer
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// Relayer's reimbursement destination
+    #[account(
+        mut,
+        constraint = relayer_token_account.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = relayer
+    )]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+
+    /// The relayer's allowlist entry; its counters are bumped here
+    #[account(
+        mut,
+        seeds = [RELAYER_SEED, state.key().as_ref(), relayer.key().as_ref()],
+        bump = relayer_account.bump,
+        constraint = relayer_account.active @ ErrorCode::UnknownRelayer
+    )]
+    pub relayer_account: Account<'info, Relayer>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    // Writable so mint-on-claim campaigns can mint_to
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    /// Wallet's holding of the gating mint (or of the collection NFT); only for gated campaigns
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event]
+pub struct RelayerFeeUpdated {
+    pub relayer_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayerUpdated {
+    pub relayer: Pubkey,
+    pub registered: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayedClaim {
+    pub wallet: Pubkey,
+    pub relayer: Pubkey,
+    pub index: u64,
+    pub relayer_fee: u64, // tokens of the allocation paid to the relayer; `Claimed.net_amount` excludes them
+    pub relayer_claims: u64, // the relayer's running claim count, including this one
+    pub timestamp: i64,
+}
@@ -0,0 +1,92 @@
+//! Append-only allocation updates.
+//!
+//! `update_merkle_root` replaces the tree, so every published proof goes
+//! stale and claims have to be paused while it happens. `append_allocations`
+//! grows the tree to the right instead. The added leaves form a segment
+//! tree, indexed on from `total_claims`, and the new root is
+//! `merkle::append_root(current root, segment root)`. The roots it grew
+//! from are kept in `prior_roots`, and claims verify against any of them
+//! as well as against the current root. Each prior root commits to a
+//! prefix of the current allocations, so a proof published before an
+//! append keeps working. Only the new leaves' proofs need publishing, and
+//! `merkle::SegmentedTree` builds them. Claims don't have to pause. After
+//! `MAX_ROOT_APPENDS` appends, or to change a leaf that's already out, the
+//! campaign goes back to `update_merkle_root`, which clears the history.
+
+use super::*;
+
+// Like `update_merkle_root`, compiled out of `immutable` builds
+#[cfg(not(feature = "immutable"))]
+pub fn append_allocations(
+    ctx: Context<AppendAllocations>,
+    segment_root: [u8; 32],
+    segment_claims: u64,
+    segment_amount: u64,
+    new_allocations_hash: [u8; 32],
+    new_allocations_uri: String,
+) -> Result<()> {
+    track_config!(ctx, {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        require!(state.status != CampaignStatus::Finalized, ErrorCode::InvalidStatus);
+        require!(segment_claims > 0, ErrorCode::InvalidAppend);
+        let count = state.prior_root_count as usize;
+        require!(count < MAX_ROOT_APPENDS, ErrorCode::RootHistoryFull);
+        let first_index = state.total_claims;
+        let new_total_claims = first_index
+            .checked_add(segment_claims)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(new_total_claims <= state.claim_capacity, ErrorCode::CapacityExceeded);
+        // States migrated from before the cap existed carry zero and stay uncapped
+        if state.total_allocation != 0 {
+            state.total_allocation = state.total_allocation
+                .checked_add(segment_amount)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+
+        let old_root = state.merkle_root;
+        state.prior_roots[count] = old_root;
+        state.prior_root_count += 1;
+        state.merkle_root = merkle::append_root(&old_root, &segment_root);
+        state.root_version += 1;
+        state.total_claims = new_total_claims;
+        state.allocations_hash = new_allocations_hash;
+        state.allocations_uri = pad_uri(&new_allocations_uri)?;
+        emit_cpi!(AllocationsAppended {
+            old_root,
+            segment_root,
+            new_root: state.merkle_root,
+            first_index,
+            segment_claims,
+            segment_amount,
+            new_allocations_hash,
+            new_allocations_uri,
+            root_version: state.root_version,
+            timestamp: now,
+        });
+    })
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AppendAllocations<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct AllocationsAppended {
+    pub old_root: [u8; 32],
+    pub segment_root: [u8; 32], // root of the added leaves alone
+    pub new_root: [u8; 32],
+    pub first_index: u64, // index of the segment's first leaf
+    pub segment_claims: u64,
+    pub segment_amount: u64,
+    pub new_allocations_hash: [u8; 32],
+    pub new_allocations_uri: String,
+    pub root_version: u32,
+    pub timestamp: i64,
+}
@@ -0,0 +1,251 @@
+//! Scheduled root rotations.
+//!
+//! A multi-wave drop knows its roots and their go-live times in advance.
+//! The authority queues them with `schedule_root` in a
+//! `["root_schedule", state]` account, and once a root's `activation_ts`
+//! has passed, anyone can run `activate_scheduled_root` to make it the
+//! campaign's root, just as `update_merkle_root` would. The crank succeeds
+//! without changes when nothing is due, so a claim transaction can always
+//! start with it: the first claim after a wave opens moves the campaign to
+//! that wave's root, and no one has to be awake to press the button.
+//!
+//! Unlike `update_merkle_root`, activation doesn't need claims paused: the
+//! switch happens at a time published in advance, so clients can refetch
+//! proofs for the new root instead of racing it. Each entry also carries
+//! the wave's `total_allocation`, which the vault must cover when it's
+//! queued and which becomes the campaign's cap when it activates. When
+//! several roots are due, the latest one wins. Locking the config stops
+//! activations along with every other root change, and entries scheduled
+//! in an earlier round are never activated. None of this exists in
+//! `immutable` builds.
+
+use super::*;
+
+/// Queues `merkle_root` to take over at `activation_ts`, after every root
+/// already queued.
+#[cfg(not(feature = "immutable"))]
+pub fn schedule_root(
+    ctx: Context<ScheduleRoot>,
+    merkle_root: [u8; 32],
+    total_claims: u64,
+    total_allocation: u64,
+    activation_ts: i64,
+    allocations_hash: [u8; 32],
+    allocations_uri: String,
+) -> Result<()> {
+    track_config!(ctx, {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        require!(total_claims <= state.claim_capacity, ErrorCode::CapacityExceeded);
+        require!(total_allocation > 0, ErrorCode::InvalidTotalAllocation);
+        // The new tree keeps the bitmap, so what's been claimed counts against it
+        require!(total_allocation >= state.claimed_amount, ErrorCode::AllocationExceeded);
+        require!(
+            state.mint_on_claim || ctx.accounts.vault.amount >= total_allocation - state.claimed_amount,
+            ErrorCode::ScheduledRootUnderfunded
+        );
+        let schedule = &mut ctx.accounts.root_schedule;
+        if schedule.round != state.round {
+            schedule.entries = Default::default();
+            schedule.count = 0;
+        }
+        let count = schedule.count as usize;
+        let earliest = match count {
+            0 => now,
+            _ => schedule.entries[count - 1].activation_ts.max(now),
+        };
+        require!(activation_ts > earliest, ErrorCode::InvalidActivationTime);
+        require!(count < MAX_SCHEDULED_ROOTS, ErrorCode::RootScheduleFull);
+        schedule.state = state.key();
+        schedule.round = state.round;
+        schedule.entries[count] = ScheduledRoot {
+            merkle_root,
+            total_claims,
+            total_allocation,
+            activation_ts,
+            allocations_hash,
+            allocations_uri: pad_uri(&allocations_uri)?,
+        };
+        schedule.count += 1;
+        schedule.bump = ctx.bumps.root_schedule;
+        ctx.accounts.state.scheduled_root_count = schedule.count;
+        emit_cpi!(RootScheduled {
+            merkle_root,
+            total_claims,
+            total_allocation,
+            activation_ts,
+            allocations_hash,
+            allocations_uri,
+            timestamp: now,
+        });
+    })
+}
+
+/// Drops every root still waiting; already activated roots stay.
+#[cfg(not(feature = "immutable"))]
+pub fn clear_root_schedule(ctx: Context<ClearRootSchedule>) -> Result<()> {
+    track_config!(ctx, {
+        let schedule = &mut ctx.accounts.root_schedule;
+        let dropped = if schedule.round == ctx.accounts.state.round { schedule.count } else { 0 };
+        schedule.entries = Default::default();
+        schedule.count = 0;
+        ctx.accounts.state.scheduled_root_count = 0;
+        emit_cpi!(RootScheduleCleared {
+            dropped,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+/// Permissionless: makes the latest due root the campaign's root, or does
+/// nothing when none is due.
+#[cfg(not(feature = "immutable"))]
+pub fn activate_scheduled_root(ctx: Context<ActivateScheduledRoot>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let schedule = &mut ctx.accounts.root_schedule;
+    // A no-op rather than an error, so claims bundled with the crank keep working
+    if schedule.round != ctx.accounts.state.round || ctx.accounts.state.config_locked {
+        return Ok(());
+    }
+    let count = schedule.count as usize;
+    let due = schedule.entries[..count]
+        .iter()
+        .take_while(|entry| entry.activation_ts <= now)
+        .count();
+    if due == 0 {
+        return Ok(());
+    }
+    let entry = schedule.entries[due - 1].clone();
+    schedule.entries.rotate_left(due);
+    for stale in &mut schedule.entries[count - due..] {
+        *stale = ScheduledRoot::default();
+    }
+    schedule.count -= due as u8;
+
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        let old_root = state.merkle_root;
+        state.merkle_root = entry.merkle_root;
+        state.root_version += 1;
+        state.prior_roots = [[0; 32]; MAX_ROOT_APPENDS];
+        state.prior_root_count = 0;
+        state.total_claims = entry.total_claims;
+        // Claims made between scheduling and activation already count against it
+        state.total_allocation = entry.total_allocation.max(state.claimed_amount);
+        state.scheduled_root_count = schedule.count;
+        state.allocations_hash = entry.allocations_hash;
+        state.allocations_uri = entry.allocations_uri;
+        emit_cpi!(MerkleRootUpdated {
+            old_root,
+            new_root: entry.merkle_root,
+            new_total_claims: entry.total_claims,
+            new_allocations_hash: entry.allocations_hash,
+            new_allocations_uri: String::from_utf8_lossy(&entry.allocations_uri)
+                .trim_end_matches('\0')
+                .to_string(),
+            timestamp: now,
+            campaign: state.key(),
+            mint: state.mint,
+            root_version: state.root_version,
+            vault_balance: ctx.accounts.vault.amount,
+        });
+    })
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ScheduledRoot {
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub total_allocation: u64,
+    pub activation_ts: i64,
+    pub allocations_hash: [u8; 32],
+    pub allocations_uri: [u8; MAX_URI_LEN],
+}
+
+impl Default for ScheduledRoot {
+    fn default() -> Self {
+        Self {
+            merkle_root: [0; 32],
+            total_claims: 0,
+            total_allocation: 0,
+            activation_ts: 0,
+            allocations_hash: [0; 32],
+            allocations_uri: [0; MAX_URI_LEN],
+        }
+    }
+}
+
+/// Roots waiting to take over, in activation order; seeds = ["root_schedule", state].
+#[account]
+pub struct RootSchedule {
+    pub state: Pubkey,
+    pub round: u32, // entries from an earlier round are never activated
+    pub entries: [ScheduledRoot; MAX_SCHEDULED_ROOTS], // first `count` entries are pending
+    pub count: u8,
+    pub bump: u8,
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ScheduleRoot<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        seeds = [ROOT_SCHEDULE_SEED, state.key().as_ref()],
+        bump,
+        payer = authority,
+        space = ROOT_SCHEDULE_SPACE
+    )]
+    pub root_schedule: Account<'info, RootSchedule>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClearRootSchedule<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    #[account(mut, seeds = [ROOT_SCHEDULE_SEED, state.key().as_ref()], bump = root_schedule.bump)]
+    pub root_schedule: Account<'info, RootSchedule>,
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ActivateScheduledRoot<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut, seeds = [ROOT_SCHEDULE_SEED, state.key().as_ref()], bump = root_schedule.bump)]
+    pub root_schedule: Account<'info, RootSchedule>,
+    #[account(address = state.vault @ ErrorCode::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[event]
+pub struct RootScheduled {
+    pub merkle_root: [u8; 32],
+    pub total_claims: u64,
+    pub total_allocation: u64,
+    pub activation_ts: i64,
+    pub allocations_hash: [u8; 32],
+    pub allocations_uri: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RootScheduleCleared {
+    pub dropped: u8, // pending roots removed
+    pub timestamp: i64,
+}
@@ -0,0 +1,66 @@
+//! Rounding of derived amounts.
+//!
+//! Fees, late penalties, early bonuses, vesting unlocks and USD-priced
+//! payouts are all fractions of a leaf amount, so each one ends in a
+//! division. Every such share is computed once from the gross amount and
+//! the other side gets `gross - share`, so no unit is ever lost, and the
+//! campaign's `rounding` only decides which side a fractional unit lands
+//! on. `Floor`, the default, leaves it with the claimant, `Ceil` sends it
+//! to the treasury (fees and penalties) or pays it early (bonuses, vesting,
+//! USD), and `HalfEven` rounds to nearest with ties to even, for auditors
+//! who want no systematic bias either way. It matters for tokens with few
+//! decimals, where one base unit is real money.
+
+use super::*;
+
+/// Which way a derived amount rounds when its division leaves a remainder.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    /// To nearest, ties to even
+    HalfEven,
+}
+
+impl Rounding {
+    /// `numerator / denominator` rounded this way; `denominator` is never zero.
+    pub fn div(self, numerator: u128, denominator: u128) -> u128 {
+        let (quotient, remainder) = (numerator / denominator, numerator % denominator);
+        let round_up = match self {
+            Rounding::Floor => false,
+            Rounding::Ceil => remainder > 0,
+            Rounding::HalfEven => {
+                // `remainder` vs half of `denominator`, without overflowing
+                let rest = denominator - remainder;
+                remainder > rest || (remainder == rest && quotient % 2 == 1)
+            }
+        };
+        quotient + round_up as u128
+    }
+}
+
+pub fn set_rounding(ctx: Context<SetRounding>, rounding: Rounding) -> Result<()> {
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        state.rounding = rounding;
+        emit_cpi!(RoundingUpdated {
+            rounding,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRounding<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct RoundingUpdated {
+    pub rounding: Rounding,
+    pub timestamp: i64,
+}
@@ -0,0 +1,172 @@
+//! USD-denominated allocations, priced by a Pyth pull oracle.
+//!
+//! Rebate-style drops promise a dollar value rather than a token count.
+//! Once the authority names a Pyth price feed, leaf amounts are read as
+//! micro-USD (six decimals), and `claim` converts each one to tokens at the
+//! price in a `PriceUpdateV2` account the claimant posts alongside. The
+//! update must be fully verified, for the configured feed, no older than
+//! `max_price_age` and with a confidence interval within
+//! `max_price_conf_bps` of the price. The conversion rounds down, and the
+//! rest of the claim (fees, caps, stats, `Claimed`) sees the token amount.
+//! Other claim paths take the leaf amount as tokens, so they refuse these
+//! campaigns.
+
+use super::*;
+
+/// Pyth Solana receiver; owner of `PriceUpdateV2` accounts.
+pub const PYTH_RECEIVER: Pubkey =
+    anchor_lang::solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Decimals of a USD-denominated leaf amount.
+pub const USD_DECIMALS: u32 = 6;
+
+// sha256("account:PriceUpdateV2")[..8]
+const PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+// `VerificationLevel::Full`; partially verified updates are refused
+const VERIFICATION_FULL: u8 = 1;
+
+// The parts of a `PriceUpdateV2` a claim needs
+struct PythPrice {
+    feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+}
+
+// Layout: discriminator [u8; 8] | write_authority Pubkey | verification_level
+// u8 (1 = Full) | feed_id [u8; 32] | price i64 | conf u64 | exponent i32 |
+// publish_time i64 | ..., little-endian
+fn parse_price_update(price_update: &UncheckedAccount) -> Result<PythPrice> {
+    require_keys_eq!(*price_update.owner, PYTH_RECEIVER, ErrorCode::InvalidPriceUpdate);
+    let data = price_update.try_borrow_data()?;
+    require!(data.starts_with(&PRICE_UPDATE_DISCRIMINATOR), ErrorCode::InvalidPriceUpdate);
+    let mut body = data.get(8 + 32..).ok_or(ErrorCode::InvalidPriceUpdate)?;
+    require!(u8::deserialize(&mut body)? == VERIFICATION_FULL, ErrorCode::InvalidPriceUpdate);
+    let feed_id = <[u8; 32]>::deserialize(&mut body)?;
+    let price = i64::deserialize(&mut body)?;
+    let conf = u64::deserialize(&mut body)?;
+    let exponent = i32::deserialize(&mut body)?;
+    let publish_time = i64::deserialize(&mut body)?;
+    Ok(PythPrice { feed_id, price, conf, exponent, publish_time })
+}
+
+// `usd_amount` micro-USD in base units of a `decimals` mint
+fn usd_to_tokens(
+    usd_amount: u64,
+    price: &PythPrice,
+    decimals: u8,
+    rounding: Rounding,
+) -> Result<u64> {
+    let scale = |exp: u32| 10u128.checked_pow(exp).ok_or(ErrorCode::ArithmeticOverflow);
+    let mut numerator = (usd_amount as u128)
+        .checked_mul(scale(decimals as u32)?)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let mut denominator = (price.price as u128)
+        .checked_mul(scale(USD_DECIMALS)?)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    if price.exponent < 0 {
+        numerator = numerator
+            .checked_mul(scale(price.exponent.unsigned_abs())?)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    } else {
+        denominator = denominator
+            .checked_mul(scale(price.exponent as u32)?)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    }
+    u64::try_from(rounding.div(numerator, denominator))
+        .map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+}
+
+/// `price_feed` zero goes back to token-denominated leaves. The feed changes
+/// what every leaf amount means, so only while the root itself could change,
+/// and never in `immutable` builds.
+#[cfg(not(feature = "immutable"))]
+pub fn set_usd_pricing(
+    ctx: Context<SetUsdPricing>,
+    price_feed: [u8; 32],
+    max_price_age: u64,
+    max_price_conf_bps: u16,
+) -> Result<()> {
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        require!(state.claim_closed || state.needs_refill, ErrorCode::ClaimsNotPaused);
+        require!(
+            price_feed == [0; 32] || (max_price_age > 0 && max_price_conf_bps <= 10_000),
+            ErrorCode::InvalidPricingConfig
+        );
+        state.usd_price_feed = price_feed;
+        state.max_price_age = max_price_age;
+        state.max_price_conf_bps = max_price_conf_bps;
+        emit_cpi!(UsdPricingUpdated {
+            price_feed,
+            max_price_age,
+            max_price_conf_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+// `record_claim` for a leaf of `usd_amount`: checks the posted price, counts
+// the claim in tokens and returns the token amount with the price used
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn record_usd_claim(
+    state: &mut State,
+    price_update: Option<&UncheckedAccount>,
+    decimals: u8,
+    index: u64,
+    wallet: &Pubkey,
+    usd_amount: u64,
+    proof: &[[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+    now: i64,
+) -> Result<(u64, i64, i32)> {
+    let price_update = price_update.ok_or(ErrorCode::UsdPriceRequired)?;
+    let price = parse_price_update(price_update)?;
+    require!(price.feed_id == state.usd_price_feed, ErrorCode::InvalidPriceUpdate);
+    require!(price.price > 0, ErrorCode::InvalidPriceUpdate);
+    require!(
+        now.saturating_sub(price.publish_time) <= state.max_price_age as i64,
+        ErrorCode::StalePrice
+    );
+    require!(
+        (price.conf as u128) * 10_000 <= (price.price as u128) * state.max_price_conf_bps as u128,
+        ErrorCode::PriceTooUncertain
+    );
+    let amount = usd_to_tokens(usd_amount, &price, decimals, state.rounding)?;
+
+    check_claim_route(state, ClaimRoute::UsdPriced)?;
+    let leaf = keccak_leaf(state.leaf_encoding, index, wallet, usd_amount);
+    record_bitmap_claim(state, index, &leaf, amount, proof, proof_len, terms_hash, now)?;
+    Ok((amount, price.price, price.exponent))
+}
+
+#[cfg(not(feature = "immutable"))]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetUsdPricing<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct UsdPricingUpdated {
+    pub price_feed: [u8; 32],
+    pub max_price_age: u64,
+    pub max_price_conf_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UsdClaimPriced {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub usd_amount: u64, // leaf amount, micro-USD
+    pub price: i64, // Pyth price, scaled by 10^exponent
+    pub exponent: i32,
+    pub amount: u64, // tokens the leaf converted to; `Claimed.amount`
+    pub timestamp: i64,
+}
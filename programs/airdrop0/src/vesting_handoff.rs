@@ -0,0 +1,360 @@
+//! Handing claims off to an external vesting program.
+//!
+//! Teams that already lock allocations with a vesting provider (Streamflow
+//! and the like) can keep the Merkle drop as the gate and let the provider
+//! hold the tokens. `set_vesting_handoff` names the program and a template
+//! of the instruction that opens a stream or lock: its data, with the byte
+//! offset the claim's amount is written at, and a role for each account.
+//! While a handoff is set, claims go through `claim_to_vesting` only. It
+//! verifies the leaf and takes the fee, late penalty and early bonus as
+//! `claim` does. It moves the net amount and bonus into the
+//! `["vesting_sender", state]` PDA's token account and invokes the template
+//! with that PDA signing as the sender and the claiming wallet in the
+//! recipient's place. The terms of the lock are the template's, so
+//! claimants can't shorten them; besides the template's fixed accounts,
+//! they choose only its free slots, such as a fresh stream account. The
+//! vesting program must take the whole amount, or the claim fails. Any
+//! lamports the provider charges the sender come from the sender PDA,
+//! which the operator keeps topped up with plain transfers.
+
+use super::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+/// Which account fills a slot of the handoff instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum HandoffAccount {
+    /// This account, passed by the claimant: the mint, a program, the
+    /// provider's treasury
+    Fixed { key: Pubkey, is_writable: bool },
+    /// The `["vesting_sender", state]` PDA, signing and writable
+    Sender,
+    /// The sender PDA's token account, holding the claim; writable
+    SenderTokens,
+    /// The claiming wallet
+    Recipient { is_writable: bool },
+    /// The next account the claimant passes, with these flags
+    Free { is_writable: bool, is_signer: bool },
+}
+
+/// Makes every claim a handoff to `program`, or, with the default key,
+/// pays claims out directly again. Fixed once claims have been recorded.
+pub fn set_vesting_handoff(
+    ctx: Context<SetVestingHandoff>,
+    program: Pubkey,
+    data: Vec<u8>,
+    amount_offset: u16,
+    accounts: Vec<HandoffAccount>,
+) -> Result<()> {
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        require!(!state.config_locked, ErrorCode::ConfigLocked);
+        require!(state.claimed_count == 0, ErrorCode::ClaimsAlreadyRecorded);
+        if program != Pubkey::default() {
+            require!(
+                program != crate::ID
+                    && data.len() <= MAX_HANDOFF_DATA_LEN
+                    && amount_offset as usize + 8 <= data.len()
+                    && accounts.len() <= MAX_HANDOFF_ACCOUNTS
+                    && accounts.contains(&HandoffAccount::Sender)
+                    && accounts.iter().any(|a| matches!(a, HandoffAccount::Recipient { .. })),
+                ErrorCode::InvalidVestingHandoff
+            );
+        }
+        state.vesting_program = program;
+        let handoff = &mut ctx.accounts.handoff;
+        handoff.state = state.key();
+        handoff.data = data;
+        handoff.amount_offset = amount_offset;
+        handoff.accounts = accounts;
+        handoff.bump = ctx.bumps.handoff;
+        emit_cpi!(VestingHandoffUpdated {
+            program,
+            data: handoff.data.clone(),
+            amount_offset,
+            accounts: handoff.accounts.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+/// `claim` for a campaign with a vesting handoff: the claim opens a lock in
+/// the vesting program for the wallet instead of paying it. Remaining
+/// accounts are every registered extra vault, then the template's fixed and
+/// free slots, in order.
+pub fn claim_to_vesting<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimToVesting<'info>>,
+    index: u64,
+    amount: u64,
+    proof: [[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let wallet = ctx.accounts.wallet.key();
+    let state = &ctx.accounts.state;
+    require!(state.vesting_program != Pubkey::default(), ErrorCode::InvalidVestingHandoff);
+    require!(state.dispute_period == 0, ErrorCode::EscrowRequired);
+    let (extra_vaults, handoff_accounts) = split_extra_vaults(state, ctx.remaining_accounts)?;
+    let sender_balance = ctx.accounts.sender_tokens.amount;
+    // The bonus goes into the lock with the claim
+    let paid = process_claim(
+        &mut ctx.accounts.state,
+        ClaimChecks {
+            claimant: &wallet,
+            stats: ctx.accounts.stats.as_mut(),
+            wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+            family_claim: ctx.accounts.family_claim.as_deref(),
+            gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+            gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+        },
+        &ClaimPayout {
+            state_bump: ctx.bumps.state,
+            vault_auth_bump: ctx.bumps.vault_auth,
+            vault_auth: &ctx.accounts.vault_auth,
+            vault: &ctx.accounts.vault,
+            extra_vaults,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+            recipient: &ctx.accounts.sender_tokens,
+            fee_account: ctx.accounts.fee_account.as_ref(),
+            bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+            relayer: None,
+            badge_mint: ctx.accounts.badge_mint.as_ref(),
+            badge_account: ctx.accounts.badge_account.as_ref(),
+            token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+        },
+        now,
+        |state: &mut State| {
+            check_claim_route(state, ClaimRoute::VestingHandoff)?;
+            let leaf = keccak_leaf(state.leaf_encoding, index, &wallet, amount);
+            record_bitmap_claim(state, index, &leaf, amount, &proof, proof_len, terms_hash, now)?;
+            Ok(amount)
+        },
+    )?;
+    let locked = paid.net_amount + paid.bonus;
+    let (ix, infos) = handoff_instruction(&ctx, handoff_accounts, locked)?;
+    let state_key = ctx.accounts.state.key();
+    let sender_seeds: &[&[u8]] = &[VESTING_SENDER_SEED, state_key.as_ref(), &[ctx.bumps.sender]];
+    invoke_signed(&ix, &infos, &[sender_seeds])?;
+    // Whatever the lock's terms, the wallet's tokens must all be in it
+    ctx.accounts.sender_tokens.reload()?;
+    require!(ctx.accounts.sender_tokens.amount <= sender_balance, ErrorCode::HandoffIncomplete);
+    if let Some(vault_balance) = paid.low_water {
+        emit_cpi!(VaultBelowLowWaterMark {
+            vault_balance,
+            low_water_mark: ctx.accounts.state.low_water_mark,
+            timestamp: now,
+        });
+    }
+
+    emit_cpi!(VestingHandedOff {
+        wallet,
+        index,
+        program: ctx.accounts.state.vesting_program,
+        amount: locked,
+        timestamp: now,
+    });
+    emit_cpi!(Claimed {
+        wallet,
+        amount,
+        fee: paid.fee,
+        penalty: paid.penalty,
+        net_amount: paid.net_amount,
+        bonus: paid.bonus,
+        index,
+        terms_hash,
+        timestamp: now,
+        campaign: ctx.accounts.state.key(),
+        mint: ctx.accounts.state.mint,
+        root_version: ctx.accounts.state.root_version,
+        vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+    });
+    Ok(())
+}
+
+// The template with `amount` written in, and the accounts behind its slots
+fn handoff_instruction<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, ClaimToVesting<'info>>,
+    passed: &'info [AccountInfo<'info>],
+    amount: u64,
+) -> Result<(Instruction, Vec<AccountInfo<'info>>)> {
+    let handoff = &ctx.accounts.handoff;
+    let mut data = handoff.data.clone();
+    let offset = handoff.amount_offset as usize;
+    data[offset..offset + 8].copy_from_slice(&amount.to_le_bytes());
+
+    let mut passed = passed.iter();
+    let mut metas = Vec::with_capacity(handoff.accounts.len());
+    let mut infos = vec![ctx.accounts.vesting_program.to_account_info()];
+    for slot in &handoff.accounts {
+        let (info, is_writable, is_signer) = match slot {
+            HandoffAccount::Fixed { key, is_writable } => {
+                let info = passed.next().ok_or(ErrorCode::HandoffAccountMismatch)?;
+                require_keys_eq!(*info.key, *key, ErrorCode::HandoffAccountMismatch);
+                (info.clone(), *is_writable, false)
+            }
+            HandoffAccount::Sender => (ctx.accounts.sender.to_account_info(), true, true),
+            HandoffAccount::SenderTokens => {
+                (ctx.accounts.sender_tokens.to_account_info(), true, false)
+            }
+            HandoffAccount::Recipient { is_writable } => {
+                (ctx.accounts.wallet.to_account_info(), *is_writable, false)
+            }
+            HandoffAccount::Free { is_writable, is_signer } => {
+                let info = passed.next().ok_or(ErrorCode::HandoffAccountMismatch)?;
+                require!(
+                    (!is_writable || info.is_writable) && (!is_signer || info.is_signer),
+                    ErrorCode::HandoffAccountMismatch
+                );
+                (info.clone(), *is_writable, *is_signer)
+            }
+        };
+        metas.push(if is_writable {
+            AccountMeta::new(*info.key, is_signer)
+        } else {
+            AccountMeta::new_readonly(*info.key, is_signer)
+        });
+        infos.push(info);
+    }
+    let ix = Instruction {
+        program_id: ctx.accounts.vesting_program.key(),
+        accounts: metas,
+        data,
+    };
+    Ok((ix, infos))
+}
+
+/// Template of the instruction `claim_to_vesting` invokes;
+/// seeds = ["vesting_handoff", state].
+#[account]
+pub struct VestingHandoff {
+    pub state: Pubkey,
+    pub data: Vec<u8>,
+    pub amount_offset: u16, // where the claim's net amount goes, little-endian u64
+    pub accounts: Vec<HandoffAccount>,
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetVestingHandoff<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [VESTING_HANDOFF_SEED, state.key().as_ref()],
+        bump,
+        payer = authority,
+        space = VESTING_HANDOFF_SPACE
+    )]
+    pub handoff: Account<'info, VestingHandoff>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimToVesting<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(seeds = [VESTING_HANDOFF_SEED, state.key().as_ref()], bump = handoff.bump)]
+    pub handoff: Account<'info, VestingHandoff>,
+
+    /// CHECK: the program named by `set_vesting_handoff`
+    #[account(address = state.vesting_program @ ErrorCode::InvalidVestingHandoff, executable)]
+    pub vesting_program: UncheckedAccount<'info>,
+
+    /// Signs the handoff as the lock's sender
+    #[account(mut, seeds = [VESTING_SENDER_SEED, state.key().as_ref()], bump)]
+    pub sender: SystemAccount<'info>,
+
+    #[account(mut, token::mint = mint, token::authority = sender)]
+    pub sender_tokens: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event]
+pub struct VestingHandoffUpdated {
+    pub program: Pubkey, // default = claims pay out directly
+    pub data: Vec<u8>,
+    pub amount_offset: u16,
+    pub accounts: Vec<HandoffAccount>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingHandedOff {
+    pub wallet: Pubkey,
+    pub index: u64,
+    pub program: Pubkey,
+    pub amount: u64, // net of the operator fee and late penalty, early bonus included
+    pub timestamp: i64,
+}
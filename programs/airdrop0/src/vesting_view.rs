@@ -0,0 +1,84 @@
+//! Read-only vesting queries.
+//!
+//! `release_vested` pays whatever has unlocked, but a wallet can't see the
+//! amount beforehand without redoing the schedule math. `get_vested_amount`
+//! does that math with the program's own `vested_amount`, at the cluster
+//! clock or at any given time, and adds when the next unlock happens. It
+//! is meant for simulation: decode the return data as `VestedAmount`. A
+//! vesting account is closed by the release that pays it out, so once
+//! everything has been paid there's nothing left to query.
+
+use super::*;
+
+pub fn get_vested_amount(
+    ctx: Context<GetVestedAmount>,
+    index: u64,
+    at: Option<i64>,
+) -> Result<VestedAmount> {
+    let vesting = &ctx.accounts.vesting;
+    let at = match at {
+        Some(at) => at,
+        None => Clock::get()?.unix_timestamp,
+    };
+    let rounding = ctx.accounts.state.rounding;
+    let vested = vested_amount(vesting.amount, &vesting.schedule, vesting.start_ts, at, rounding);
+    Ok(VestedAmount {
+        index,
+        amount: vesting.amount,
+        vested,
+        released: vesting.released,
+        withdrawable: vested.saturating_sub(vesting.released),
+        next_unlock_ts: next_unlock_ts(vesting, at, rounding),
+        fully_vested_ts: vesting.start_ts.saturating_add(vesting.schedule.duration),
+        at,
+    })
+}
+
+// First time after `at` at which more has vested than at `at`, or zero once
+// everything has. `vested_amount` never decreases over time, whichever way
+// it rounds, so this is a binary search up to the end of the schedule.
+fn next_unlock_ts(vesting: &VestingAccount, at: i64, rounding: Rounding) -> i64 {
+    let vested_at =
+        |ts: i64| vested_amount(vesting.amount, &vesting.schedule, vesting.start_ts, ts, rounding);
+    let vested = vested_at(at);
+    if vested >= vesting.amount {
+        return 0;
+    }
+    // Fully vested from here on, so more has vested by then
+    let end = vesting.start_ts.saturating_add(vesting.schedule.duration);
+    let (mut low, mut high) = (at, end.max(at.saturating_add(1)));
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if vested_at(mid) > vested {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    high
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct GetVestedAmount<'info> {
+    #[account(seeds = [STATE_SEED], bump)]
+    pub state: Account<'info, State>,
+    #[account(
+        seeds = [VESTING_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()],
+        bump = vesting.bump
+    )]
+    pub vesting: Account<'info, VestingAccount>,
+}
+
+/// Return data of `get_vested_amount`, as of `at`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestedAmount {
+    pub index: u64,
+    pub amount: u64,
+    pub vested: u64,
+    pub released: u64,
+    pub withdrawable: u64, // what `release_vested` would pay at `at`
+    pub next_unlock_ts: i64, // when `vested` next grows; zero once fully vested
+    pub fully_vested_ts: i64,
+    pub at: i64,
+}
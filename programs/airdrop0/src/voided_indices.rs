@@ -0,0 +1,79 @@
+//! Voiding allocations that should never have been in the snapshot.
+//!
+//! Snapshots sometimes include exchange hot wallets or contract addresses
+//! that can't or mustn't claim. `void_index` records such an index in the
+//! claim bitmap, so no proof for it ever pays out. The index is not
+//! counted as claimed, so its allocation stays in the round's unclaimed
+//! supply, which `finalize_and_rollover` carries into the next round. A
+//! frozen index can be unfrozen, but a void can't be undone. It also
+//! targets one allocation, where a wallet cap targets a wallet. A
+//! `["void", snapshot_hash, index]` record keeps the authority's
+//! `reason_hash`, for example of a published incident note, so explorers
+//! can tell a voided index from a claimed one. Bonus lottery entries and
+//! raffle draws check for the record, since the bitmap can't tell either.
+
+use super::*;
+
+pub fn void_index(ctx: Context<VoidIndex>, index: u64, reason_hash: [u8; 32]) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let state: &mut State = &mut ctx.accounts.state;
+    // Locked allocations stay exactly as committed
+    require!(!state.config_locked, ErrorCode::ConfigLocked);
+    // The raffle's winners were drawn as claimants
+    require!(!raffle_open(state, now), ErrorCode::RaffleOpen);
+    check_status_index(state, index)?;
+    mark_claimed(state, index)?;
+    let voided = &mut ctx.accounts.voided_index;
+    voided.index = index;
+    voided.reason_hash = reason_hash;
+    voided.voided_at = now;
+    voided.bump = ctx.bumps.voided_index;
+    emit_cpi!(IndexVoided {
+        index,
+        reason_hash,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+// Address of `index`'s `VoidedIndex` record in the round of `snapshot_hash`
+pub(crate) fn voided_index_address(snapshot_hash: &[u8; 32], index: u64) -> Pubkey {
+    Pubkey::find_program_address(&[VOID_SEED, snapshot_hash, &index.to_le_bytes()], &crate::ID).0
+}
+
+/// An allocation the authority voided; seeds = ["void", snapshot_hash, index (u64 LE)].
+#[account]
+pub struct VoidedIndex {
+    pub index: u64,
+    pub reason_hash: [u8; 32], // commits to why; the authority publishes the preimage
+    pub voided_at: i64,
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct VoidIndex<'info> {
+    #[account(mut, seeds = [STATE_SEED], bump, has_one = authority)]
+    pub state: Account<'info, State>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [VOID_SEED, state.snapshot_hash.as_ref(), &index.to_le_bytes()],
+        bump,
+        payer = authority,
+        space = VOIDED_INDEX_SPACE
+    )]
+    pub voided_index: Account<'info, VoidedIndex>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct IndexVoided {
+    pub index: u64,
+    pub reason_hash: [u8; 32],
+    pub timestamp: i64,
+}
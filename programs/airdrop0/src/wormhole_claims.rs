@@ -0,0 +1,272 @@
+//! Cross-chain claims through Wormhole.
+//!
+//! A campaign can be homed on another chain, where a companion distributor
+//! holds the same allocation list. There, a claimant either claims directly
+//! or locks the claim right for Solana. Locking marks the index claimed on
+//! that chain and publishes a Wormhole message. Once guardians sign it and
+//! the VAA is posted to the core bridge, `claim_from_vaa` pays the
+//! allocation here. Direct claims on Solana are disabled for such a campaign,
+//! so each index pays on exactly one chain. The bitmap stops the same VAA,
+//! or another VAA for the same index, from paying twice.
+
+use super::*;
+
+/// Wormhole core bridge (mainnet); owner of posted VAA accounts.
+pub const WORMHOLE_CORE_BRIDGE: Pubkey =
+    anchor_lang::solana_program::pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+
+/// Payload id of a claim-right lock message.
+pub const CLAIM_LOCK_PAYLOAD_ID: u8 = 1;
+
+const POSTED_VAA_MAGIC: &[u8] = b"vaa";
+
+// The parts of a core bridge `PostedVAA` account a claim needs
+struct PostedVaa {
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+    sequence: u64,
+    payload: Vec<u8>,
+}
+
+// Layout: "vaa" | version u8 | consistency u8 | vaa_time u32 | signature_set
+// Pubkey | submission_time u32 | nonce u32 | sequence u64 | emitter_chain u16
+// | emitter_address [u8; 32] | payload (u32 length-prefixed), little-endian
+fn parse_posted_vaa(posted_vaa: &UncheckedAccount) -> Result<PostedVaa> {
+    require_keys_eq!(*posted_vaa.owner, WORMHOLE_CORE_BRIDGE, ErrorCode::InvalidVaa);
+    let data = posted_vaa.try_borrow_data()?;
+    require!(data.starts_with(POSTED_VAA_MAGIC), ErrorCode::InvalidVaa);
+    let mut body = data
+        .get(POSTED_VAA_MAGIC.len() + 1 + 1 + 4 + 32 + 4 + 4..)
+        .ok_or(ErrorCode::InvalidVaa)?;
+    let sequence = u64::deserialize(&mut body)?;
+    let emitter_chain = u16::deserialize(&mut body)?;
+    let emitter_address = <[u8; 32]>::deserialize(&mut body)?;
+    let payload = Vec::<u8>::deserialize(&mut body)?;
+    Ok(PostedVaa { emitter_chain, emitter_address, sequence, payload })
+}
+
+// Claim-lock payload, big-endian as the EVM side encodes it:
+// payload_id u8 | index u64 | wallet [u8; 32] | amount u64
+fn claim_lock_payload(index: u64, wallet: &Pubkey, amount: u64) -> [u8; 49] {
+    let mut payload = [0u8; 49];
+    payload[0] = CLAIM_LOCK_PAYLOAD_ID;
+    payload[1..9].copy_from_slice(&index.to_be_bytes());
+    payload[9..41].copy_from_slice(wallet.as_ref());
+    payload[41..].copy_from_slice(&amount.to_be_bytes());
+    payload
+}
+
+/// `emitter_chain` zero homes the campaign on Solana again. Only before the
+/// first claim, so no index can become claimable on two chains.
+pub fn set_wormhole_emitter(
+    ctx: Context<SetWormholeEmitter>,
+    emitter_chain: u16,
+    emitter: [u8; 32],
+) -> Result<()> {
+    track_config!(ctx, {
+        let state = &mut ctx.accounts.state;
+        require!(state.claimed_count == 0, ErrorCode::ClaimsAlreadyRecorded);
+        require!(state.claim_ledger == Pubkey::default(), ErrorCode::CompressedLedgerRequired);
+        state.wormhole_emitter_chain = emitter_chain;
+        state.wormhole_emitter = emitter;
+        emit_cpi!(WormholeEmitterUpdated {
+            emitter_chain,
+            emitter,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    })
+}
+
+/// `claim` for an allocation whose right was locked on the home chain; the
+/// posted VAA must carry exactly `(index, wallet, amount)`.
+pub fn claim_from_vaa<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimFromVaa<'info>>,
+    index: u64,
+    amount: u64,
+    proof: [[u8; 32]; MAX_PROOF_DEPTH],
+    proof_len: u8,
+    terms_hash: [u8; 32],
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let state = &ctx.accounts.state;
+    require!(state.wormhole_emitter_chain != 0, ErrorCode::CrossChainDisabled);
+    check_claim_route(state, ClaimRoute::Vaa)?;
+    require!(state.dispute_period == 0, ErrorCode::EscrowRequired);
+
+    let vaa = parse_posted_vaa(&ctx.accounts.posted_vaa)?;
+    require!(
+        vaa.emitter_chain == state.wormhole_emitter_chain
+            && vaa.emitter_address == state.wormhole_emitter,
+        ErrorCode::InvalidVaa
+    );
+    require!(
+        vaa.payload == claim_lock_payload(index, ctx.accounts.wallet.key, amount),
+        ErrorCode::VaaPayloadMismatch
+    );
+
+    let wallet = ctx.accounts.wallet.key;
+    let paid = process_claim(
+        &mut ctx.accounts.state,
+        ClaimChecks {
+            claimant: wallet,
+            stats: ctx.accounts.stats.as_mut(),
+            wallet_claims: ctx.accounts.wallet_claims.as_mut(),
+            family_claim: ctx.accounts.family_claim.as_deref(),
+            gate_token_account: ctx.accounts.gate_token_account.as_deref(),
+            gate_metadata: ctx.accounts.gate_metadata.as_ref(),
+        },
+        &ClaimPayout {
+            state_bump: ctx.bumps.state,
+            vault_auth_bump: ctx.bumps.vault_auth,
+            vault_auth: &ctx.accounts.vault_auth,
+            vault: &ctx.accounts.vault,
+            extra_vaults: ctx.remaining_accounts,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+            recipient: &ctx.accounts.user_ata,
+            fee_account: ctx.accounts.fee_account.as_ref(),
+            bonus_vault: ctx.accounts.bonus_vault.as_ref(),
+            relayer: None,
+            badge_mint: ctx.accounts.badge_mint.as_ref(),
+            badge_account: ctx.accounts.badge_account.as_ref(),
+            token_2022_program: ctx.accounts.token_2022_program.as_ref(),
+        },
+        now,
+        |state: &mut State| {
+            let leaf = keccak_leaf(state.leaf_encoding, index, wallet, amount);
+            record_bitmap_claim(state, index, &leaf, amount, &proof, proof_len, terms_hash, now)?;
+            Ok(amount)
+        },
+    )?;
+    if let Some(vault_balance) = paid.low_water {
+        emit_cpi!(VaultBelowLowWaterMark {
+            vault_balance,
+            low_water_mark: ctx.accounts.state.low_water_mark,
+            timestamp: now,
+        });
+    }
+
+    emit_cpi!(Claimed {
+        wallet: *ctx.accounts.wallet.key,
+        amount,
+        fee: paid.fee,
+        penalty: paid.penalty,
+        net_amount: paid.net_amount,
+        bonus: paid.bonus,
+        index,
+        terms_hash,
+        timestamp: now,
+        campaign: ctx.accounts.state.key(),
+        mint: ctx.accounts.state.mint,
+        root_version: ctx.accounts.state.root_version,
+        vault_balance: vault_balance_after(&ctx.accounts.state, &paid.vault, amount),
+    });
+    emit_cpi!(VaaClaimRedeemed {
+        index,
+        emitter_chain: vaa.emitter_chain,
+        sequence: vaa.sequence,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetWormholeEmitter<'info> {
+    #[account(mut, has_one = authority)]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimFromVaa<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        constraint = state.version == STATE_VERSION @ ErrorCode::StateNotMigrated
+    )]
+    pub state: Account<'info, State>,
+
+    pub wallet: Signer<'info>,
+
+    /// CHECK: core bridge `PostedVAA` account; owner and contents checked in `parse_posted_vaa`
+    pub posted_vaa: UncheckedAccount<'info>,
+
+    /// PDA authority
+    #[account(
+        seeds = [VAULT_SEED, state.snapshot_hash.as_ref()],
+        bump
+    )]
+    pub vault_auth: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = state.vault @ ErrorCode::InvalidVault,
+        token::mint = mint,
+        token::authority = vault_auth
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_ata.mint == mint.key() @ ErrorCode::WrongMint,
+        token::authority = wallet
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(mut, address = state.mint @ ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+
+    /// Operator fee destination; required while a fee is configured
+    #[account(mut)]
+    pub fee_account: Option<Account<'info, TokenAccount>>,
+
+    /// Hourly claim counters; required once `init_claim_stats` has run
+    #[account(mut, seeds = [STATS_SEED, state.key().as_ref()], bump = stats.bump)]
+    pub stats: Option<Account<'info, ClaimStats>>,
+
+    /// Claimant's running total for the round; required while a wallet cap is set
+    #[account(mut)]
+    pub wallet_claims: Option<Account<'info, WalletClaims>>,
+
+    /// Wallet's pick of this campaign; required while the campaign is in a family
+    pub family_claim: Option<Account<'info, FamilyClaim>>,
+
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Token Metadata account of the held NFT; owner and contents checked in `check_claim_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Early-claim bonus reserve; required while a bonus is being paid
+    #[account(mut, address = state.bonus_vault @ ErrorCode::InvalidBonusVault)]
+    pub bonus_vault: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: the non-transferable badge mint; required once `init_claim_badge` has run
+    #[account(mut, address = state.badge_mint @ ErrorCode::InvalidBadgeAccount)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// Wallet's Token-2022 account for the badge mint
+    #[account(
+        mut,
+        constraint = badge_account.mint == state.badge_mint @ ErrorCode::InvalidBadgeAccount,
+        constraint = badge_account.owner == wallet.key() @ ErrorCode::InvalidBadgeAccount
+    )]
+    pub badge_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+#[event]
+pub struct WormholeEmitterUpdated {
+    pub emitter_chain: u16,
+    pub emitter: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaaClaimRedeemed {
+    pub index: u64,
+    pub emitter_chain: u16,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
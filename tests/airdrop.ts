@@ -129,6 +129,20 @@ function buildMerkleTree(
   };
 }
 
+/**
+ * The program takes proofs as a fixed `[[u8;32]; MAX_PROOF_DEPTH]` array plus
+ * the number of meaningful nodes, so pad with zero nodes up to the max depth.
+ */
+const MAX_PROOF_DEPTH = 24;
+function padProof(proof: Buffer[]): [number[][], number] {
+  const padded = proof.map((p) => Array.from(p));
+  while (padded.length < MAX_PROOF_DEPTH) padded.push(Array(32).fill(0));
+  return [padded, proof.length];
+}
+
+/** Zero terms hash – accepted whenever the campaign doesn't require ToS. */
+const NO_TERMS = Array(32).fill(0);
+
 // --------------------------------------------------------------------------
 // TESTS
 // --------------------------------------------------------------------------
@@ -264,12 +278,23 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
         new BN(start),
         new BN(CLAIM_DURATION),
         Array.from(tree.root),
-        new BN(NUM_USERS)
+        new BN(NUM_USERS),
+        new BN(NUM_USERS),               // claim capacity
+        false,                           // transfer from the vault, don't mint
+        Array(32).fill(0x11),            // allocations file hash
+        "ipfs://bafy-allocations",       // allocations file URI
+        new BN(ALLOCATIONS.reduce((acc, a) => acc + a * 10 ** DECIMALS, 0)),
+        new BN(0),                       // no protocol config, so no creation fee
+        new BN(0)                        // no crank pool
       )
       .accounts({
         state: statePda,
         authority: deployer.publicKey,
+        vaultAuth,
+        vault: vaultAta,
+        mint,
         systemProgram: SystemProgram.programId,
+        feeTreasury: null,
       })
       .signers([deployer])
       .rpc();
@@ -290,10 +315,94 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
     );
     assert.isFalse(st.claimClosed, "should not be closed immediately");
     assert.equal(st.totalClaims.toNumber(), NUM_USERS, "totalClaims mismatch");
+    assert.equal(st.mint.toBase58(), mint.toBase58(), "mint not pinned");
+    assert.equal(st.vault.toBase58(), vaultAta.toBase58(), "vault not pinned");
+  });
+
+  // ------------------------------------------------------------------------
+  // 2. View instruction mirrors state + vault
+  // ------------------------------------------------------------------------
+  it("Returns campaign info via simulation", async () => {
+    const info = await program.methods
+      .getAirdropInfo()
+      .accounts({ state: statePda, vault: vaultAta })
+      .view();
+
+    assert.equal(info.totalClaims.toNumber(), NUM_USERS);
+    assert.equal(info.claimedCount.toNumber(), 0);
+    assert.isTrue(info.windowOpen, "window should be open after initialize");
+    const vault = await getAccount(connection, vaultAta);
+    assert.equal(info.vaultBalance.toString(), vault.amount.toString());
+    assert.equal(info.allocationsUri, "ipfs://bafy-allocations");
+    // Fully funded: the vault holds exactly the committed allocation
+    assert.equal(info.totalAllocation.toString(), vault.amount.toString());
+  });
+
+  // ------------------------------------------------------------------------
+  // 3. Campaign metadata for explorers / claim UIs
+  // ------------------------------------------------------------------------
+  it("Allows admin to publish campaign metadata", async () => {
+    const [metadataPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), statePda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .setCampaignMetadata(
+        "Dumbhead Drop",
+        "DBH",
+        "https://example.org/drop.json",
+        "https://example.org/drop.png"
+      )
+      .accounts({
+        state: statePda,
+        authority: deployer.publicKey,
+        metadata: metadataPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([deployer])
+      .rpc();
+
+    const md = await program.account.campaignMetadata.fetch(metadataPda);
+    assert.equal(md.name, "Dumbhead Drop");
+    assert.equal(md.symbol, "DBH");
+  });
+
+  // ------------------------------------------------------------------------
+  // 4. Migration is a no-op on an up-to-date layout
+  // ------------------------------------------------------------------------
+  it("Rejects migrating a state that is already current", async () => {
+    const st = await program.account.state.fetch(statePda);
+    assert.equal(st.version, 1, "fresh state should be at the current version");
+
+    try {
+      await program.methods
+        .migrateState()
+        .accounts({
+          state: statePda,
+          authority: deployer.publicKey,
+          vault: vaultAta,
+          legacyClaims: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([deployer])
+        .rpc();
+      assert.fail("Migrating a current state should revert");
+    } catch (err) {
+      if (
+        err instanceof AnchorError &&
+        err.error.errorCode.code === "AlreadyMigrated"
+      ) {
+        assert.ok(true);
+      } else {
+        console.error("Unexpected migrate error:", err);
+        throw err;
+      }
+    }
   });
 
   // ------------------------------------------------------------------------
-  // 2. Happy path – each eligible wallet successfully claims
+  // 5. Happy path – each eligible wallet successfully claims
   // ------------------------------------------------------------------------
   it("Allows wallets to claim tokens", async () => {
     for (let i = 0; i < NUM_USERS; i++) {
@@ -321,7 +430,8 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
         .claim(
           new BN(index),
           new BN(amount),
-          proof.map((p) => Array.from(p)) // Vec<u8>[] serde
+          ...padProof(proof),             // fixed [[u8;32];24] + len
+          NO_TERMS                        // campaign has no ToS requirement
         )
         .accounts({
           state: statePda,
@@ -347,14 +457,14 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
   });
 
   // ------------------------------------------------------------------------
-  // 3. No double‑dipping allowed
+  // 6. No double‑dipping allowed
   // ------------------------------------------------------------------------
   it("Rejects double claims", async () => {
     const { index, amount, proof } = claims[0];
 
     try {
       await program.methods
-        .claim(new BN(index), new BN(amount), proof.map((p) => Array.from(p)))
+        .claim(new BN(index), new BN(amount), ...padProof(proof), NO_TERMS)
         .accounts({
           state: statePda,
           wallet: users[0].publicKey,
@@ -383,7 +493,41 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
   });
 
   // ------------------------------------------------------------------------
-  // 4. Bad Merkle proof → rejected
+  // 7. Push mode – admin distributes straight from the vault
+  // ------------------------------------------------------------------------
+  it("Allows admin to push a batch distribution", async () => {
+    const recipients = [atas[0], atas[1]];
+    const amounts    = [10, 20];
+    const before     = await Promise.all(recipients.map((a) => getAccount(connection, a)));
+
+    await program.methods
+      .distributeBatch(amounts.map((a) => new BN(a)))
+      .accounts({
+        state: statePda,
+        authority: deployer.publicKey,
+        vaultAuth,
+        vault: vaultAta,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(
+        recipients.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+      )
+      .signers([deployer])
+      .rpc();
+
+    for (let k = 0; k < recipients.length; k++) {
+      const after = await getAccount(connection, recipients[k]);
+      assert.equal(
+        (after.amount - before[k].amount).toString(),
+        amounts[k].toString(),
+        "batch amount mismatch"
+      );
+    }
+  });
+
+  // ------------------------------------------------------------------------
+  // 8. Bad Merkle proof → rejected
   // ------------------------------------------------------------------------
   it("Rejects claims with invalid Merkle proof", async () => {
     const i = 8;
@@ -410,7 +554,7 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
 
     try {
       await program.methods
-        .claim(new BN(index), new BN(amount), badProof.map((p) => Array.from(p)))
+        .claim(new BN(index), new BN(amount), ...padProof(badProof), NO_TERMS)
         .accounts({
           state: statePda,
           wallet: users[i].publicKey,
@@ -444,7 +588,181 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
   });
 
   // ------------------------------------------------------------------------
-  // 5. Claim window expiration logic
+  // 9. Terms-of-service gate
+  // ------------------------------------------------------------------------
+  it("Rejects claims that don't accept the configured terms", async () => {
+    const terms = Array(32).fill(0x77);
+    await program.methods
+      .setTermsHash(terms)
+      .accounts({ state: statePda, authority: deployer.publicKey })
+      .signers([deployer])
+      .rpc();
+
+    const i = 8;
+    const { index, amount, proof } = claims[i];
+    try {
+      await program.methods
+        .claim(new BN(index), new BN(amount), ...padProof(proof), NO_TERMS)
+        .accounts({
+          state: statePda,
+          wallet: users[i].publicKey,
+          vaultAuth,
+          vault: vaultAta,
+          userAta: atas[i],
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([users[i]])
+        .rpc();
+      assert.fail("Claim without terms should revert");
+    } catch (err) {
+      if (
+        err instanceof AnchorError &&
+        err.error.errorCode.code === "TermsNotAccepted"
+      ) {
+        assert.ok(true);
+      } else {
+        console.error("Unexpected terms error:", err);
+        throw err;
+      }
+    } finally {
+      await program.methods
+        .setTermsHash(NO_TERMS)
+        .accounts({ state: statePda, authority: deployer.publicKey })
+        .signers([deployer])
+        .rpc();
+    }
+  });
+
+  // ------------------------------------------------------------------------
+  // 10. Token gate – only holders of the gating mint may claim
+  // ------------------------------------------------------------------------
+  it("Rejects claims from wallets that don't hold the gating token", async () => {
+    const gateMint = await createMint(
+      connection,
+      deployer,
+      deployer.publicKey,
+      null,
+      0
+    );
+    await program.methods
+      .setClaimGate({ token: {} }, gateMint, new BN(1))
+      .accounts({ state: statePda, authority: deployer.publicKey })
+      .signers([deployer])
+      .rpc();
+
+    const i = 8;
+    const { index, amount, proof } = claims[i];
+    try {
+      await program.methods
+        .claim(new BN(index), new BN(amount), ...padProof(proof), NO_TERMS)
+        .accounts({
+          state: statePda,
+          wallet: users[i].publicKey,
+          vaultAuth,
+          vault: vaultAta,
+          userAta: atas[i],
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          gateTokenAccount: null,
+          gateMetadata: null,
+        })
+        .signers([users[i]])
+        .rpc();
+      assert.fail("Claim without the gating token should revert");
+    } catch (err) {
+      if (
+        err instanceof AnchorError &&
+        err.error.errorCode.code === "GateNotSatisfied"
+      ) {
+        assert.ok(true);
+      } else {
+        console.error("Unexpected gate error:", err);
+        throw err;
+      }
+    } finally {
+      await program.methods
+        .setClaimGate({ none: {} }, PublicKey.default, new BN(0))
+        .accounts({ state: statePda, authority: deployer.publicKey })
+        .signers([deployer])
+        .rpc();
+    }
+  });
+
+  // ------------------------------------------------------------------------
+  // 11. Domain claims only accept name-service records
+  // ------------------------------------------------------------------------
+  it("Rejects domain claims against a non name-service account", async () => {
+    const i = 8;
+    const { index, amount, proof } = claims[i];
+    try {
+      await program.methods
+        .claimDomain(new BN(index), new BN(amount), ...padProof(proof), NO_TERMS)
+        .accounts({
+          state: statePda,
+          domain: users[i].publicKey,
+          owner: users[i].publicKey,
+          vaultAuth,
+          vault: vaultAta,
+          userAta: atas[i],
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          gateTokenAccount: null,
+          gateMetadata: null,
+        })
+        .signers([users[i]])
+        .rpc();
+      assert.fail("Domain claim on a wallet account should revert");
+    } catch (err) {
+      if (
+        err instanceof AnchorError &&
+        err.error.errorCode.code === "InvalidDomain"
+      ) {
+        assert.ok(true);
+      } else {
+        console.error("Unexpected domain error:", err);
+        throw err;
+      }
+    }
+  });
+
+  // ------------------------------------------------------------------------
+  // 12. Unwrap-on-claim is only for wrapped-SOL campaigns
+  // ------------------------------------------------------------------------
+  it("Rejects unwrapped claims when the mint isn't wrapped SOL", async () => {
+    const i = 8;
+    const { index, amount, proof } = claims[i];
+    try {
+      await program.methods
+        .claimUnwrapped(new BN(index), new BN(amount), ...padProof(proof), NO_TERMS)
+        .accounts({
+          state: statePda,
+          wallet: users[i].publicKey,
+          vaultAuth,
+          vault: vaultAta,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          gateTokenAccount: null,
+          gateMetadata: null,
+        })
+        .signers([users[i]])
+        .rpc();
+      assert.fail("Unwrapped claim on a non-wSOL mint should revert");
+    } catch (err) {
+      if (
+        err instanceof AnchorError &&
+        err.error.errorCode.code === "NotWrappedSol"
+      ) {
+        assert.ok(true);
+      } else {
+        console.error("Unexpected unwrap error:", err);
+        throw err;
+      }
+    }
+  });
+
+  // ------------------------------------------------------------------------
+  // 13. Claim window expiration logic
   // ------------------------------------------------------------------------
   it("Rejects claims after claim window expired", async () => {
     const now = Math.floor(Date.now() / 1000);
@@ -462,7 +780,7 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
 
     try {
       await program.methods
-        .claim(new BN(index), new BN(amount), proof.map((p) => Array.from(p)))
+        .claim(new BN(index), new BN(amount), ...padProof(proof), NO_TERMS)
         .accounts({
           state: statePda,
           wallet: users[i].publicKey,
@@ -478,9 +796,7 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
     } catch (err) {
       if (
         err instanceof AnchorError &&
-        (err.error.errorCode.code === "ClaimWindowClosed" ||
-          (err.error.errorCode.code === "Custom" &&
-            err.error.errorCode.number === 6000))
+        err.error.errorCode.code === "WindowEnded"
       ) {
         assert.ok(true);
       } else {
@@ -495,22 +811,42 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
   });
 
   // ------------------------------------------------------------------------
-  // 6. Admin‑only mutators
+  // 14. Anyone may crank-close an elapsed window
+  // ------------------------------------------------------------------------
+  it("Allows a permissionless crank to close an elapsed window", async () => {
+    await program.methods
+      .crankClose()
+      .accounts({ state: statePda, cranker: attacker.publicKey, vault: vaultAta })
+      .signers([attacker])
+      .rpc();
+
+    const st = await program.account.state.fetch(statePda);
+    assert.isTrue(st.claimClosed, "crank should close the campaign");
+  });
+
+  // ------------------------------------------------------------------------
+  // 15. Admin‑only mutators
   // ------------------------------------------------------------------------
   it("Allows admin to update claim window and Merkle root", async () => {
     const newStart    = Math.floor(Date.now() / 1000) + 10;
     const newDuration = 1_000;
 
+    // The crank above closed claims, which root updates require
     await program.methods
-      .updateClaimWindow(new BN(newStart), new BN(newDuration))
-      .accounts({ state: statePda, authority: deployer.publicKey })
+      .updateMerkleRoot(
+        Array(32).fill(0x44),
+        new BN(NUM_USERS),
+        Array(32).fill(0x45),
+        "ipfs://bafy-allocations-v2"
+      )
+      .accounts({ state: statePda, authority: deployer.publicKey, vault: vaultAta })
       .signers([deployer])
       .rpc();
 
     await sleep(100);
 
     await program.methods
-      .updateMerkleRoot(Array(32).fill(0x44), new BN(NUM_USERS))
+      .updateClaimWindow(new BN(newStart), new BN(newDuration))
       .accounts({ state: statePda, authority: deployer.publicKey })
       .signers([deployer])
       .rpc();
@@ -519,12 +855,41 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
   });
 
   // ------------------------------------------------------------------------
-  // 7. Close airdrop (stop further claims)
+  // 16. Resize never drops leaves of the current round
+  // ------------------------------------------------------------------------
+  it("Rejects shrinking the claim bitmap below the round's leaves", async () => {
+    try {
+      await program.methods
+        .resizeState(new BN(NUM_USERS - 1))
+        .accounts({
+          state: statePda,
+          authority: deployer.publicKey,
+          legacyClaims: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([deployer])
+        .rpc();
+      assert.fail("Resize below total_claims should revert");
+    } catch (err) {
+      if (
+        err instanceof AnchorError &&
+        err.error.errorCode.code === "InvalidCapacity"
+      ) {
+        assert.ok(true);
+      } else {
+        console.error("Unexpected resize error:", err);
+        throw err;
+      }
+    }
+  });
+
+  // ------------------------------------------------------------------------
+  // 17. Close airdrop (stop further claims)
   // ------------------------------------------------------------------------
   it("Allows admin to close airdrop", async () => {
     await program.methods
       .closeAirdrop()
-      .accounts({ state: statePda, authority: deployer.publicKey })
+      .accounts({ state: statePda, authority: deployer.publicKey, vault: vaultAta })
       .signers([deployer])
       .rpc();
     await sleep(100);
@@ -534,7 +899,7 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
   });
 
   // ------------------------------------------------------------------------
-  // 8. No claims once airdrop is closed
+  // 18. No claims once airdrop is closed
   // ------------------------------------------------------------------------
   it("Rejects claims after airdrop closed", async () => {
     const i = 9;
@@ -542,7 +907,7 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
 
     try {
       await program.methods
-        .claim(new BN(index), new BN(amount), proof.map((p) => Array.from(p)))
+        .claim(new BN(index), new BN(amount), ...padProof(proof), NO_TERMS)
         .accounts({
           state: statePda,
           wallet: users[i].publicKey,
@@ -575,13 +940,13 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
   });
 
   // ------------------------------------------------------------------------
-  // 9. Only authority can close airdrop (RBAC)
+  // 19. Only authority can close airdrop (RBAC)
   // ------------------------------------------------------------------------
   it("Rejects closeAirdrop from unauthorized wallet", async () => {
     try {
       await program.methods
         .closeAirdrop()
-        .accounts({ state: statePda, authority: attacker.publicKey })
+        .accounts({ state: statePda, authority: attacker.publicKey, vault: vaultAta })
         .signers([attacker])
         .rpc();
       assert.fail("Unauthorized close should revert");
@@ -604,7 +969,107 @@ describe("MerkleDrop RNS (RNS claim tracking)", () => {
   });
 
   // ------------------------------------------------------------------------
-  //  10. Close state account & reclaim rent (cleanup pattern)
+  // 20. Rollover – archive the round & move unclaimed supply forward
+  // ------------------------------------------------------------------------
+  it("Rolls unclaimed supply into a follow-up round", async () => {
+    const prev = await program.account.state.fetch(statePda);
+    const preVault = await getAccount(connection, vaultAta);
+
+    const nextSnapshot = Buffer.alloc(32, 0xbe);
+    const [nextVaultAuth] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), nextSnapshot],
+      program.programId
+    );
+    const nextVaultAta = getAssociatedTokenAddressSync(mint, nextVaultAuth, true);
+    await sendAndConfirmTransaction(
+      connection,
+      new web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          deployer.publicKey,
+          nextVaultAta,
+          nextVaultAuth,
+          mint
+        )
+      ),
+      [deployer]
+    );
+
+    const [summaryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("rollover"), Buffer.from(prev.snapshotHash)],
+      program.programId
+    );
+
+    await program.methods
+      .finalizeAndRollover(
+        Array.from(nextSnapshot),
+        Array(32).fill(0x55),
+        new BN(UNCLAIMED.length),
+        new BN(Math.floor(Date.now() / 1000)),
+        new BN(CLAIM_DURATION),
+        Array(32).fill(0x56),
+        "ipfs://bafy-allocations-round-2",
+        new BN(UNCLAIMED.reduce((acc, i) => acc + ALLOCATIONS[i] * 10 ** DECIMALS, 0))
+      )
+      .accounts({
+        state: statePda,
+        authority: deployer.publicKey,
+        summary: summaryPda,
+        vaultAuth,
+        vault: vaultAta,
+        newVaultAuth: nextVaultAuth,
+        newVault: nextVaultAta,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([deployer])
+      .rpc();
+
+    const summary = await program.account.rolloverSummary.fetch(summaryPda);
+    assert.equal(
+      summary.unclaimedAmount.toString(),
+      preVault.amount.toString(),
+      "summary should record the leftover supply"
+    );
+
+    const st = await program.account.state.fetch(statePda);
+    assert.equal(st.round, prev.round + 1, "round should advance");
+    assert.isFalse(st.claimClosed, "next round should be open");
+
+    const rolled = await getAccount(connection, nextVaultAta);
+    assert.equal(rolled.amount.toString(), preVault.amount.toString());
+  });
+
+  // ------------------------------------------------------------------------
+  // 21. Rent can only go to a plain wallet
+  // ------------------------------------------------------------------------
+  it("Rejects closing the state into a program-owned recipient", async () => {
+    try {
+      await program.methods
+        .closeState()
+        .accounts({
+          state: statePda,
+          authority: deployer.publicKey,
+          recipient: vaultAta,
+        })
+        .signers([deployer])
+        .rpc();
+      assert.fail("Closing into a token account should revert");
+    } catch (err) {
+      if (
+        err instanceof AnchorError &&
+        err.error.errorCode.code === "AccountNotSystemOwned"
+      ) {
+        assert.ok(true);
+      } else {
+        console.error("Unexpected recipient error:", err);
+        throw err;
+      }
+    }
+  });
+
+  // ------------------------------------------------------------------------
+  //  22. Close state account & reclaim rent (cleanup pattern)
   // ------------------------------------------------------------------------
   it("Allows admin to close the state and reclaim rent", async () => {
     const preBalance = await connection.getBalance(rentRecipient.publicKey);
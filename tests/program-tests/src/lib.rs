@@ -0,0 +1,502 @@
+//! LiteSVM harness for exercising the airdrop program without a local validator.
+//!
+//! The program must be built first (`anchor build`) so that the shared object
+//! exists at `target/deploy/airdrop0.so`.
+
+use airdrop0::{accounts, instruction, Config, State};
+use anchor_lang::{
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_pack::Pack,
+        system_program,
+    },
+    AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas,
+};
+use litesvm::{
+    types::{FailedTransactionMetadata, TransactionMetadata},
+    LiteSVM,
+};
+use litesvm_token::{spl_token, CreateAssociatedTokenAccount, CreateMint, MintTo};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    pubkey,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+pub const MAX_PROOF_DEPTH: usize = 24;
+pub const SNAPSHOT_HASH: [u8; 32] = [0xde; 32];
+pub const CLAIM_DURATION: i64 = 3_600;
+pub const VAULT_SUPPLY: u64 = 1_000_000_000;
+pub const TOKEN_2022_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PE9wt6dFqD1c");
+pub const ASSOCIATED_TOKEN_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+pub type TxResult = Result<TransactionMetadata, FailedTransactionMetadata>;
+
+/// A freshly initialized campaign with a funded vault.
+pub struct Harness {
+    pub svm: LiteSVM,
+    pub authority: Keypair,
+    pub state: Pubkey,
+    pub mint: Pubkey,
+    pub vault_auth: Pubkey,
+    pub vault: Pubkey,
+    /// Passed as `fee_account` by `claim_ix` once an operator fee is configured
+    pub fee_account: Option<Pubkey>,
+    /// Passed as `stats` by `claim_ix` once claim stats are enabled
+    pub stats: Option<Pubkey>,
+    /// Passed as `bonus_vault` by `claim_ix` while an early bonus is on
+    pub bonus_vault: Option<Pubkey>,
+    /// Passed as `badge_mint` by `claim_ix`, with the wallet's badge account,
+    /// once claim badges are enabled
+    pub badge_mint: Option<Pubkey>,
+    /// Whether `claim_ix` passes the claimant's `wallet_claims` PDA
+    pub track_wallet_claims: bool,
+    /// Family whose `family_claim` PDA `claim_ix` passes for the claimant
+    pub family_id: Option<[u8; 32]>,
+    /// Passed as `price_update` by `claim_ix` while leaves are USD-denominated
+    pub price_update: Option<Pubkey>,
+}
+
+impl Harness {
+    pub fn new(merkle_root: [u8; 32], total_claims: u64, claim_capacity: u64) -> Self {
+        Self::with_snapshot(SNAPSHOT_HASH, merkle_root, total_claims, claim_capacity)
+    }
+
+    /// `new` for a campaign initialized with `snapshot_hash`; the vault is
+    /// derived from it.
+    pub fn with_snapshot(
+        snapshot_hash: [u8; 32],
+        merkle_root: [u8; 32],
+        total_claims: u64,
+        claim_capacity: u64,
+    ) -> Self {
+        Self::build(snapshot_hash, merkle_root, total_claims, claim_capacity, VAULT_SUPPLY, None)
+    }
+
+    /// `new` with an empty vault, so the campaign starts as a Draft; see
+    /// `fund_vault`.
+    pub fn unfunded(merkle_root: [u8; 32], total_claims: u64, claim_capacity: u64) -> Self {
+        Self::build(SNAPSHOT_HASH, merkle_root, total_claims, claim_capacity, 0, None)
+    }
+
+    /// `new` on a deployment whose protocol `config` already exists, so
+    /// `initialize` pays its creation fee.
+    pub fn with_config(merkle_root: [u8; 32], total_claims: u64, claim_capacity: u64, config: Config) -> Self {
+        Self::build(SNAPSHOT_HASH, merkle_root, total_claims, claim_capacity, VAULT_SUPPLY, Some(config))
+    }
+
+    fn build(
+        snapshot_hash: [u8; 32],
+        merkle_root: [u8; 32],
+        total_claims: u64,
+        claim_capacity: u64,
+        vault_supply: u64,
+        config: Option<Config>,
+    ) -> Self {
+        let mut svm = LiteSVM::new();
+        let so_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/airdrop0.so");
+        svm.add_program_from_file(airdrop0::ID, so_path)
+            .expect("run `anchor build` before the program tests");
+
+        let authority = Keypair::new();
+        svm.airdrop(&authority.pubkey(), 100_000_000_000).unwrap();
+
+        let mint = CreateMint::new(&mut svm, &authority)
+            .decimals(0)
+            .send()
+            .unwrap();
+        let (vault_auth, _) =
+            Pubkey::find_program_address(&[b"vault", snapshot_hash.as_ref()], &airdrop0::ID);
+        let vault = CreateAssociatedTokenAccount::new(&mut svm, &authority, &mint)
+            .owner(&vault_auth)
+            .send()
+            .unwrap();
+        if vault_supply > 0 {
+            MintTo::new(&mut svm, &authority, &mint, &vault, vault_supply)
+                .send()
+                .unwrap();
+        }
+
+        let (state, _) = Pubkey::find_program_address(&[b"state"], &airdrop0::ID);
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], &airdrop0::ID);
+        // Written directly: `init_config` needs an upgradeable deployment
+        if let Some(config) = &config {
+            let mut data = Vec::new();
+            config.try_serialize(&mut data).unwrap();
+            let account = Account {
+                lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner: airdrop0::ID,
+                executable: false,
+                rent_epoch: 0,
+            };
+            svm.set_account(config_pda, account).unwrap();
+        }
+        let mut harness = Self {
+            svm,
+            authority,
+            state,
+            mint,
+            vault_auth,
+            vault,
+            fee_account: None,
+            stats: None,
+            bonus_vault: None,
+            badge_mint: None,
+            track_wallet_claims: false,
+            family_id: None,
+            price_update: None,
+        };
+
+        let now = harness.now();
+        let ix = Instruction {
+            program_id: airdrop0::ID,
+            accounts: accounts::Initialize {
+                state,
+                authority: harness.authority.pubkey(),
+                vault_auth,
+                vault,
+                mint,
+                system_program: system_program::ID,
+                config: config_pda,
+                fee_treasury: config.as_ref().map(|config| config.fee_treasury),
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Initialize {
+                snapshot_hash,
+                claim_start_ts: now - 60,
+                claim_duration: CLAIM_DURATION,
+                merkle_root,
+                total_claims,
+                claim_capacity,
+                mint_on_claim: false,
+                allocations_hash: [0; 32],
+                allocations_uri: String::new(),
+                total_allocation: VAULT_SUPPLY,
+                max_creation_fee: config.as_ref().map_or(0, |config| config.creation_fee),
+                crank_pool: 0,
+            }
+            .data(),
+        };
+        harness.send_as_authority(ix).expect("initialize");
+        harness
+    }
+
+    /// Mints `amount` into the campaign vault.
+    pub fn fund_vault(&mut self, amount: u64) {
+        MintTo::new(&mut self.svm, &self.authority, &self.mint, &self.vault, amount)
+            .send()
+            .unwrap();
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    /// Moves the cluster clock `seconds` forward (or back, if negative).
+    pub fn warp(&mut self, seconds: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += seconds;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// Sends `ix` with the first signer paying fees.
+    pub fn send(&mut self, ix: Instruction, signers: &[&Keypair]) -> TxResult {
+        self.svm.expire_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&signers[0].pubkey()),
+            signers,
+            self.svm.latest_blockhash(),
+        );
+        self.svm.send_transaction(tx)
+    }
+
+    pub fn send_as_authority(&mut self, ix: Instruction) -> TxResult {
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority])
+    }
+
+    /// A funded wallet with an empty token account for the campaign mint.
+    pub fn new_claimant(&mut self) -> (Keypair, Pubkey) {
+        self.new_claimant_with(Keypair::new())
+    }
+
+    /// Funds `wallet` and opens its token account; for leaves built before the harness.
+    pub fn new_claimant_with(&mut self, wallet: Keypair) -> (Keypair, Pubkey) {
+        self.svm.airdrop(&wallet.pubkey(), 1_000_000_000).unwrap();
+        let ata = CreateAssociatedTokenAccount::new(&mut self.svm, &wallet, &self.mint)
+            .send()
+            .unwrap();
+        (wallet, ata)
+    }
+
+    pub fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        let account = self.svm.get_account(token_account).unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    /// Creates the fee treasury; returns `(treasury, treasury_tokens)`.
+    pub fn init_treasury(&mut self) -> (Pubkey, Pubkey) {
+        let find = |seed: &[u8]| Pubkey::find_program_address(&[seed, self.state.as_ref()], &airdrop0::ID).0;
+        let (treasury, treasury_tokens) = (find(b"treasury"), find(b"treasury_tokens"));
+        let ix = Instruction {
+            program_id: airdrop0::ID,
+            accounts: accounts::InitTreasury {
+                state: self.state,
+                authority: self.authority.pubkey(),
+                treasury,
+                treasury_tokens,
+                mint: self.mint,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::InitTreasury {}.data(),
+        };
+        self.send_as_authority(ix).expect("init_treasury");
+        (treasury, treasury_tokens)
+    }
+
+    /// `init_claim_badge`, with the `["badge", state]` mint it creates.
+    pub fn init_claim_badge_ix(&self) -> (Pubkey, Instruction) {
+        let (badge_mint, _) = Pubkey::find_program_address(&[b"badge", self.state.as_ref()], &airdrop0::ID);
+        let ix = Instruction {
+            program_id: airdrop0::ID,
+            accounts: accounts::InitClaimBadge {
+                state: self.state,
+                authority: self.authority.pubkey(),
+                badge_mint,
+                token_2022_program: TOKEN_2022_ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::InitClaimBadge {}.data(),
+        };
+        (badge_mint, ix)
+    }
+
+    /// Opens `wallet`'s badge account with the ATA program's `CreateIdempotent`.
+    pub fn create_badge_account(&mut self, wallet: &Keypair, badge_mint: &Pubkey) -> Pubkey {
+        let account = badge_account(&wallet.pubkey(), badge_mint);
+        let ix = Instruction {
+            program_id: ASSOCIATED_TOKEN_ID,
+            accounts: vec![
+                AccountMeta::new(wallet.pubkey(), true),
+                AccountMeta::new(account, false),
+                AccountMeta::new_readonly(wallet.pubkey(), false),
+                AccountMeta::new_readonly(*badge_mint, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(TOKEN_2022_ID, false),
+            ],
+            data: vec![1],
+        };
+        self.send(ix, &[wallet]).expect("create badge account");
+        account
+    }
+
+    /// Token-2022 account amount; extensions make the data longer than the
+    /// base layout, so it is read at its fixed offset.
+    pub fn badge_balance(&self, account: &Pubkey) -> u64 {
+        let data = self.svm.get_account(account).unwrap().data;
+        u64::from_le_bytes(data[64..72].try_into().unwrap())
+    }
+
+    pub fn claim_ix(
+        &self,
+        wallet: &Pubkey,
+        user_ata: &Pubkey,
+        index: u64,
+        amount: u64,
+        proof: &[[u8; 32]],
+    ) -> Instruction {
+        let (padded, proof_len) = pad_proof(proof);
+        Instruction {
+            program_id: airdrop0::ID,
+            accounts: accounts::Claim {
+                state: self.state,
+                wallet: *wallet,
+                vault_auth: self.vault_auth,
+                vault: self.vault,
+                user_ata: *user_ata,
+                mint: self.mint,
+                token_program: spl_token::ID,
+                fee_account: self.fee_account,
+                stats: self.stats,
+                wallet_claims: self
+                    .track_wallet_claims
+                    .then(|| wallet_claims(&self.state, wallet)),
+                family_claim: self
+                    .family_id
+                    .map(|family_id| family_claim(&airdrop0::ID, &family_id, wallet)),
+                gate_token_account: None,
+                gate_metadata: None,
+                bonus_vault: self.bonus_vault,
+                badge_mint: self.badge_mint,
+                badge_account: self.badge_mint.map(|badge_mint| badge_account(wallet, &badge_mint)),
+                token_2022_program: self.badge_mint.map(|_| TOKEN_2022_ID),
+                price_update: self.price_update,
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Claim {
+                index,
+                amount,
+                proof: padded,
+                proof_len,
+                terms_hash: [0; 32],
+            }
+            .data(),
+        }
+    }
+
+    pub fn close_airdrop_ix(&self) -> Instruction {
+        Instruction {
+            program_id: airdrop0::ID,
+            accounts: accounts::CloseAirdrop {
+                state: self.state,
+                authority: self.authority.pubkey(),
+                vault: self.vault,
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::CloseAirdrop {}.data(),
+        }
+    }
+
+    pub fn lock_config_ix(&self) -> Instruction {
+        Instruction {
+            program_id: airdrop0::ID,
+            accounts: accounts::LockConfig {
+                state: self.state,
+                authority: self.authority.pubkey(),
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::LockConfig {}.data(),
+        }
+    }
+
+    pub fn update_claim_window_ix(&self, new_start_ts: i64, new_duration: i64) -> Instruction {
+        Instruction {
+            program_id: airdrop0::ID,
+            accounts: accounts::UpdateClaimWindow {
+                state: self.state,
+                authority: self.authority.pubkey(),
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::UpdateClaimWindow { new_start_ts, new_duration }.data(),
+        }
+    }
+
+    pub fn state(&self) -> State {
+        let data = self.svm.get_account(&self.state).unwrap().data;
+        State::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    /// Closes claims, swaps the root and reopens the same window in one
+    /// transaction, since `update_merkle_root` needs claims stopped.
+    pub fn set_merkle_root(&mut self, new_root: [u8; 32], new_total_claims: u64) -> TxResult {
+        let state = self.state();
+        let ixs = [
+            self.close_airdrop_ix(),
+            self.update_merkle_root_ix(new_root, new_total_claims),
+            self.update_claim_window_ix(state.claim_start_ts, state.claim_duration),
+        ];
+        self.svm.expire_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&self.authority.pubkey()),
+            &[&self.authority],
+            self.svm.latest_blockhash(),
+        );
+        self.svm.send_transaction(tx)
+    }
+
+    pub fn update_merkle_root_ix(&self, new_root: [u8; 32], new_total_claims: u64) -> Instruction {
+        Instruction {
+            program_id: airdrop0::ID,
+            accounts: accounts::UpdateMerkleRoot {
+                state: self.state,
+                authority: self.authority.pubkey(),
+                vault: self.vault,
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::UpdateMerkleRoot {
+                new_root,
+                new_total_claims,
+                new_allocations_hash: [0; 32],
+                new_allocations_uri: String::new(),
+            }
+            .data(),
+        }
+    }
+}
+
+/// Fails unless `result` is an error whose logs mention `error`.
+pub fn assert_fails_with(result: TxResult, error: &str) {
+    let failed = result.expect_err(error);
+    assert!(
+        failed.meta.logs.iter().any(|log| log.contains(error)),
+        "expected {error}, logs: {:#?}",
+        failed.meta.logs
+    );
+}
+
+/// PDA that signs the program's self-CPI events (`emit_cpi!`).
+pub fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &airdrop0::ID).0
+}
+
+/// `wallet`'s per-round claim total under the wallet cap.
+pub fn wallet_claims(state: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"wallet_claims", state.as_ref(), wallet.as_ref()],
+        &airdrop0::ID,
+    )
+    .0
+}
+
+/// `wallet`'s pick of a campaign within `family_id`, under that campaign's `program`.
+pub fn family_claim(program: &Pubkey, family_id: &[u8; 32], wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"family_claim", family_id, wallet.as_ref()], program).0
+}
+
+/// `wallet`'s Token-2022 associated account for the badge mint.
+pub fn badge_account(wallet: &Pubkey, badge_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), TOKEN_2022_ID.as_ref(), badge_mint.as_ref()],
+        &ASSOCIATED_TOKEN_ID,
+    )
+    .0
+}
+
+/// Same leaf encoding as the on-chain `keccak_leaf`.
+pub fn leaf_hash(index: u64, wallet: &Pubkey, amount: u64) -> [u8; 32] {
+    merkle::leaf(index, &wallet.to_bytes(), amount)
+}
+
+/// Folds a leaf up through `proof` with sorted-pair hashing, yielding the root.
+pub fn fold_proof(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    merkle::root_from_proof(&leaf, proof)
+}
+
+pub fn pad_proof(proof: &[[u8; 32]]) -> ([[u8; 32]; MAX_PROOF_DEPTH], u8) {
+    let mut padded = [[0u8; 32]; MAX_PROOF_DEPTH];
+    padded[..proof.len()].copy_from_slice(proof);
+    (padded, proof.len() as u8)
+}
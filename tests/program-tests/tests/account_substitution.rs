@@ -0,0 +1,48 @@
+//! Wrong-account substitution on `claim`: the vault authority must be the
+//! seeds-derived, system-owned PDA of the current snapshot.
+
+use anchor_lang::solana_program::instruction::Instruction;
+use program_tests::{leaf_hash, Harness};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 1_000;
+
+fn with_vault_auth(mut ix: Instruction, h: &Harness, substitute: Pubkey) -> Instruction {
+    let meta = ix
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == h.vault_auth)
+        .expect("claim passes vault_auth");
+    meta.pubkey = substitute;
+    ix
+}
+
+fn claim_fails_with(h: &mut Harness, substitute: Pubkey, error: &str) {
+    let (wallet, ata) = h.new_claimant();
+    let root = leaf_hash(0, &wallet.pubkey(), AMOUNT);
+    h.set_merkle_root(root, 1).expect("set_merkle_root");
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    let ix = with_vault_auth(ix, h, substitute);
+    let failed = h.send(ix, &[&wallet]).expect_err("substituted vault_auth");
+    assert!(
+        failed.meta.logs.iter().any(|log| log.contains(error)),
+        "expected {error}, logs: {:#?}",
+        failed.meta.logs
+    );
+    assert_eq!(h.token_balance(&ata), 0);
+}
+
+#[test]
+fn claim_rejects_vault_auth_from_other_seeds() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (other_round, _) = Pubkey::find_program_address(&[b"vault", &[0xaa; 32]], &airdrop0::ID);
+    claim_fails_with(&mut h, other_round, "ConstraintSeeds");
+}
+
+#[test]
+fn claim_rejects_program_owned_vault_auth() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let vault = h.vault;
+    claim_fails_with(&mut h, vault, "AccountNotSystemOwned");
+}
@@ -0,0 +1,284 @@
+//! Bonus lottery: claims enter tickets instead of taking the early bonus,
+//! each ticket draws from its entry slot's hash once that slot is over, and
+//! settlement pays the lowest draws in full from a reserve too small for
+//! all of them.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, keccak, system_program, sysvar},
+    InstructionData, ToAccountMetas,
+};
+use litesvm_token::{spl_token, MintTo};
+use merkle::MerkleTree;
+use program_tests::{
+    assert_fails_with, event_authority, leaf_hash, pad_proof, Harness, SNAPSHOT_HASH,
+};
+use solana_sdk::{
+    clock::Clock,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    slot_hashes::SlotHashes,
+};
+
+const AMOUNT: u64 = 10_000;
+const BONUS_BPS: u16 = 2_000; // 2_000 per ticket
+const RESERVE: u64 = 3_000; // enough for one and a half tickets
+const BONUS_WINDOW: i64 = 600;
+
+fn lottery(h: &Harness) -> Pubkey {
+    Pubkey::find_program_address(&[b"bonus_lottery", h.state.as_ref()], &airdrop0::ID).0
+}
+
+fn ticket(index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"bonus_ticket", SNAPSHOT_HASH.as_ref(), &index.to_le_bytes()],
+        &airdrop0::ID,
+    )
+    .0
+}
+
+// Mirrors the program's draw
+fn bucket(slot_hash: &[u8; 32], index: u64) -> u8 {
+    let draw = keccak::hashv(&[slot_hash, &index.to_le_bytes()]).to_bytes();
+    (draw[0] as usize * 64 / 256) as u8
+}
+
+fn init_bonus_vault(h: &mut Harness) -> Pubkey {
+    let bonus_vault =
+        Pubkey::find_program_address(&[b"bonus_vault", h.state.as_ref()], &airdrop0::ID).0;
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::InitBonusVault {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            bonus_vault,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitBonusVault {}.data(),
+    };
+    h.send_as_authority(ix).expect("init_bonus_vault");
+    bonus_vault
+}
+
+fn set_early_bonus_ix(h: &Harness, bonus_bps: u16, end_ts: i64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetEarlyBonus {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetEarlyBonus { bonus_bps, end_ts }.data(),
+    }
+}
+
+fn set_bonus_lottery_ix(h: &Harness, enabled: bool) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetBonusLottery {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            lottery: lottery(h),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetBonusLottery { enabled }.data(),
+    }
+}
+
+fn enter_ix(h: &Harness, wallet: &Pubkey, index: u64, proof: &[[u8; 32]]) -> Instruction {
+    let (proof, proof_len) = pad_proof(proof);
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::EnterBonusLottery {
+            state: h.state,
+            lottery: lottery(h),
+            wallet: *wallet,
+            ticket: ticket(index),
+            voided_index: merkledrop_interface::pda::voided_index(&SNAPSHOT_HASH, index).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::EnterBonusLottery {
+            index,
+            amount: AMOUNT,
+            proof,
+            proof_len,
+        }
+        .data(),
+    }
+}
+
+fn reveal_ix(h: &Harness, index: u64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RevealBonusTicket {
+            state: h.state,
+            lottery: lottery(h),
+            ticket: ticket(index),
+            slot_hashes: sysvar::slot_hashes::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RevealBonusTicket { index }.data(),
+    }
+}
+
+fn settle_ix(h: &Harness, bonus_vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SettleBonusLottery {
+            state: h.state,
+            lottery: lottery(h),
+            bonus_vault,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SettleBonusLottery {}.data(),
+    }
+}
+
+fn redeem_ix(
+    h: &Harness,
+    index: u64,
+    wallet: &Pubkey,
+    recipient: Pubkey,
+    bonus_vault: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RedeemBonusTicket {
+            state: h.state,
+            lottery: lottery(h),
+            ticket: ticket(index),
+            wallet: *wallet,
+            recipient,
+            bonus_vault,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RedeemBonusTicket { index }.data(),
+    }
+}
+
+#[test]
+fn lowest_draw_wins_in_full_and_the_cutoff_bucket_takes_the_rest() {
+    let (first, second) = (Keypair::new(), Keypair::new());
+    let tree = MerkleTree::from_leaves(vec![
+        leaf_hash(0, &first.pubkey(), AMOUNT),
+        leaf_hash(1, &second.pubkey(), AMOUNT),
+    ]);
+    let mut h = Harness::new(tree.root(), 2, 2);
+    let claimants = [h.new_claimant_with(first), h.new_claimant_with(second)];
+
+    let bonus_vault = init_bonus_vault(&mut h);
+    let authority = h.authority.insecure_clone();
+    MintTo::new(&mut h.svm, &authority, &h.mint, &bonus_vault, RESERVE)
+        .send()
+        .unwrap();
+    let bonus_end_ts = h.now() + BONUS_WINDOW;
+    let ix = set_early_bonus_ix(&h, BONUS_BPS, bonus_end_ts);
+    h.send_as_authority(ix).expect("set_early_bonus");
+    h.send_as_authority(set_bonus_lottery_ix(&h, true)).expect("set_bonus_lottery");
+    assert!(h.state().bonus_lottery);
+
+    // Claims pay no bonus while the lottery is on; a leaf enters only once claimed
+    let entry_slot = h.svm.get_sysvar::<Clock>().slot;
+    for (index, (wallet, ata)) in claimants.iter().enumerate() {
+        let index = index as u64;
+        let proof = tree.proof(index as usize).unwrap();
+        let ix = enter_ix(&h, &wallet.pubkey(), index, &proof);
+        assert_fails_with(h.send(ix, &[wallet]), "NotClaimed");
+        let ix = h.claim_ix(&wallet.pubkey(), ata, index, AMOUNT, &proof);
+        h.send(ix, &[wallet]).expect("claim");
+        assert_eq!(h.token_balance(ata), AMOUNT);
+        let ix = enter_ix(&h, &wallet.pubkey(), index, &proof);
+        h.send(ix, &[wallet]).expect("enter_bonus_lottery");
+    }
+
+    // The entry slot's hash doesn't exist until a later slot
+    assert_fails_with(h.send_as_authority(reveal_ix(&h, 0)), "InvalidBonusTicket");
+    h.svm.warp_to_slot(entry_slot + 1);
+    // Some slot hash that puts the two tickets in different buckets
+    let slot_hash = (0u8..)
+        .map(|seed| [seed; 32])
+        .find(|hash| bucket(hash, 0) != bucket(hash, 1))
+        .unwrap();
+    h.svm.set_sysvar(&SlotHashes::new(&[(entry_slot, Hash::new_from_array(slot_hash))]));
+    h.send_as_authority(reveal_ix(&h, 0)).expect("reveal 0");
+    h.send_as_authority(reveal_ix(&h, 1)).expect("reveal 1");
+
+    assert_fails_with(h.send_as_authority(settle_ix(&h, bonus_vault)), "LotteryNotSettleable");
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = bonus_end_ts;
+    h.svm.set_sysvar(&clock);
+    h.send_as_authority(settle_ix(&h, bonus_vault)).expect("settle_bonus_lottery");
+
+    // The reserve can't be switched off or withdrawn from under the winners
+    assert_fails_with(h.send_as_authority(set_bonus_lottery_ix(&h, false)), "BonusLotteryActive");
+
+    let winner = if bucket(&slot_hash, 0) < bucket(&slot_hash, 1) { 0 } else { 1 };
+    for (index, (wallet, ata)) in claimants.iter().enumerate() {
+        let ix = redeem_ix(&h, index as u64, &wallet.pubkey(), *ata, bonus_vault);
+        h.send_as_authority(ix).expect("redeem_bonus_ticket");
+        let bonus = if index == winner { 2_000 } else { RESERVE - 2_000 };
+        assert_eq!(h.token_balance(ata), AMOUNT + bonus);
+        assert!(h.svm.get_account(&ticket(index as u64)).map_or(true, |a| a.lamports == 0));
+    }
+    assert_eq!(h.token_balance(&bonus_vault), 0);
+    assert_eq!(h.state().bonuses_paid, RESERVE);
+
+    h.send_as_authority(set_bonus_lottery_ix(&h, false)).expect("switch off");
+    assert!(!h.state().bonus_lottery);
+}
+
+#[test]
+fn voided_leaf_cannot_enter() {
+    let (first, second) = (Keypair::new(), Keypair::new());
+    let tree = MerkleTree::from_leaves(vec![
+        leaf_hash(0, &first.pubkey(), AMOUNT),
+        leaf_hash(1, &second.pubkey(), AMOUNT),
+    ]);
+    let mut h = Harness::new(tree.root(), 2, 2);
+    let (wallet, _) = h.new_claimant_with(second);
+    init_bonus_vault(&mut h);
+    let ix = set_early_bonus_ix(&h, BONUS_BPS, h.now() + BONUS_WINDOW);
+    h.send_as_authority(ix).expect("set_early_bonus");
+    h.send_as_authority(set_bonus_lottery_ix(&h, true)).expect("set_bonus_lottery");
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::VoidIndex {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            voided_index: merkledrop_interface::pda::voided_index(&SNAPSHOT_HASH, 1).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::VoidIndex { index: 1, reason_hash: [7; 32] }.data(),
+    };
+    h.send_as_authority(ix).expect("void_index");
+
+    // The void set the index's bitmap bit, but the wallet never claimed
+    let ix = enter_ix(&h, &wallet.pubkey(), 1, &tree.proof(1).unwrap());
+    assert_fails_with(h.send(ix, &[&wallet]), "AllocationVoided");
+}
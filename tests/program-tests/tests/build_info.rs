@@ -0,0 +1,37 @@
+//! Build metadata: only the program's upgrade authority can record what the
+//! deployed bytecode was built from.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{bpf_loader_upgradeable, instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use program_tests::{event_authority, Harness};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[test]
+fn record_build_info_needs_the_upgrade_authority() {
+    let mut h = Harness::new([0; 32], 1, 1_000);
+    let build_info = merkledrop_interface::pda::build_info().0;
+    // The harness loads the program without ProgramData, so no signer qualifies
+    let program_data = Pubkey::find_program_address(&[airdrop0::ID.as_ref()], &bpf_loader_upgradeable::ID).0;
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RecordBuildInfo {
+            build_info,
+            upgrade_authority: h.authority.pubkey(),
+            program_data,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RecordBuildInfo {
+            git_commit: [0xab; 20],
+            toolchain: "anchor 0.31.1, solana 2.1.21".to_string(),
+        }
+        .data(),
+    };
+    assert!(h.send_as_authority(ix).is_err());
+    assert!(h.svm.get_account(&build_info).is_none());
+}
@@ -0,0 +1,107 @@
+//! Campaign families: a wallet joins one campaign of its family and claims
+//! there only; a sibling that holds the wallet's family claim bars the join.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        system_program,
+    },
+    InstructionData, ToAccountMetas,
+};
+use merkle::MerkleTree;
+use program_tests::{assert_fails_with, event_authority, family_claim, leaf_hash, Harness};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+const FAMILY_ID: [u8; 32] = [7; 32];
+
+fn set_family_ix(h: &Harness, family_id: [u8; 32], siblings: Vec<Pubkey>) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetFamily {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetFamily { family_id, siblings }.data(),
+    }
+}
+
+fn join_family_ix(h: &Harness, wallet: &Pubkey, sibling: &Pubkey) -> Instruction {
+    let mut accounts = accounts::JoinFamily {
+        state: h.state,
+        wallet: *wallet,
+        family_claim: family_claim(&airdrop0::ID, &FAMILY_ID, wallet),
+        system_program: system_program::ID,
+        event_authority: event_authority(),
+        program: airdrop0::ID,
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new(family_claim(sibling, &FAMILY_ID, wallet), false));
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts,
+        data: instruction::JoinFamily {}.data(),
+    }
+}
+
+#[test]
+fn wallet_claims_only_in_the_campaign_it_joined() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let tree = MerkleTree::from_leaves(vec![
+        leaf_hash(0, &alice.pubkey(), AMOUNT),
+        leaf_hash(1, &bob.pubkey(), AMOUNT),
+    ]);
+    let mut h = Harness::new(tree.root(), 2, 2);
+    let (alice, alice_ata) = h.new_claimant_with(alice);
+    let (bob, bob_ata) = h.new_claimant_with(bob);
+    let sibling = Pubkey::new_unique();
+
+    assert_fails_with(
+        h.send_as_authority(set_family_ix(&h, FAMILY_ID, vec![airdrop0::ID])),
+        "InvalidFamily",
+    );
+    h.send_as_authority(set_family_ix(&h, FAMILY_ID, vec![sibling])).expect("set_family");
+    assert_eq!(h.state().family_sibling_count, 1);
+
+    // No family claim yet
+    let ix = h.claim_ix(&alice.pubkey(), &alice_ata, 0, AMOUNT, &tree.proof(0).unwrap());
+    assert_fails_with(h.send(ix, &[&alice]), "FamilyClaimMissing");
+
+    // Alice already joined the sibling campaign
+    let taken = Account {
+        lamports: 1_000_000,
+        data: vec![0; 73],
+        owner: sibling,
+        executable: false,
+        rent_epoch: 0,
+    };
+    h.svm.set_account(family_claim(&sibling, &FAMILY_ID, &alice.pubkey()), taken).unwrap();
+    let ix = join_family_ix(&h, &alice.pubkey(), &sibling);
+    assert_fails_with(h.send(ix, &[&alice]), "JoinedSiblingCampaign");
+
+    // Lamports sent to Bob's sibling address don't bar him
+    h.svm.airdrop(&family_claim(&sibling, &FAMILY_ID, &bob.pubkey()), 1_000_000).unwrap();
+    let ix = join_family_ix(&h, &bob.pubkey(), &sibling);
+    h.send(ix, &[&bob]).expect("join_family");
+    h.family_id = Some(FAMILY_ID);
+    let ix = h.claim_ix(&bob.pubkey(), &bob_ata, 1, AMOUNT, &tree.proof(1).unwrap());
+    h.send(ix, &[&bob]).expect("claim after joining");
+    assert_eq!(h.token_balance(&bob_ata), AMOUNT);
+
+    let ix = h.claim_ix(&alice.pubkey(), &alice_ata, 0, AMOUNT, &tree.proof(0).unwrap());
+    assert_fails_with(h.send(ix, &[&alice]), "AccountNotInitialized");
+
+    // The family is fixed once claims have begun
+    assert_fails_with(
+        h.send_as_authority(set_family_ix(&h, FAMILY_ID, vec![])),
+        "ClaimsAlreadyRecorded",
+    );
+}
@@ -0,0 +1,137 @@
+//! The lifecycle status gates claims and `close_state`, and moves Draft →
+//! Funded → Live → Ended → Finalized with a `StatusChanged` per step.
+
+use airdrop0::{accounts, instruction, CampaignStatus};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use merkledrop_interface::{CampaignStatus as Status, MerkledropEvent, StatusChanged};
+use program_tests::{
+    assert_fails_with, event_authority, leaf_hash, Harness, TxResult, CLAIM_DURATION,
+    SNAPSHOT_HASH, VAULT_SUPPLY,
+};
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+
+fn advance_status(h: &mut Harness) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::AdvanceStatus {
+            state: h.state,
+            vault: h.vault,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AdvanceStatus {}.data(),
+    };
+    h.send_as_authority(ix)
+}
+
+fn finalize_airdrop(h: &mut Harness) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::FinalizeAirdrop {
+            state: h.state,
+            payer: h.authority.pubkey(),
+            summary: Pubkey::find_program_address(&[b"summary", SNAPSHOT_HASH.as_ref()], &airdrop0::ID).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::FinalizeAirdrop {}.data(),
+    };
+    h.send_as_authority(ix)
+}
+
+fn close_state(h: &mut Harness) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::CloseState {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            recipient: Pubkey::new_unique(),
+        }
+        .to_account_metas(None),
+        data: instruction::CloseState {}.data(),
+    };
+    h.send_as_authority(ix)
+}
+
+fn status_changed(result: TxResult) -> StatusChanged {
+    result
+        .expect("transition")
+        .inner_instructions
+        .iter()
+        .flatten()
+        .find_map(|inner| match MerkledropEvent::decode_cpi(&inner.instruction.data) {
+            Some(MerkledropEvent::StatusChanged(changed)) => Some(changed),
+            _ => None,
+        })
+        .expect("StatusChanged event")
+}
+
+#[test]
+fn draft_campaign_claims_once_funded() {
+    let wallet = Keypair::new();
+    let mut h = Harness::unfunded(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    assert_eq!(h.state().status, CampaignStatus::Draft);
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "NotFunded");
+    assert_fails_with(advance_status(&mut h), "NotFunded");
+
+    h.fund_vault(VAULT_SUPPLY);
+    let changed = status_changed(advance_status(&mut h));
+    assert_eq!((changed.from, changed.to), (Status::Draft, Status::Funded));
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+}
+
+#[test]
+fn status_follows_the_round() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    assert_eq!(h.state().status, CampaignStatus::Funded);
+
+    // The window opened a minute ago; it closes an hour from then
+    advance_status(&mut h).expect("Funded -> Live");
+    assert_eq!(h.state().status, CampaignStatus::Live);
+    assert_fails_with(advance_status(&mut h), "InvalidStatus");
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+    assert_fails_with(close_state(&mut h), "InvalidStatus");
+
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += CLAIM_DURATION;
+    h.svm.set_sysvar(&clock);
+    advance_status(&mut h).expect("Live -> Ended");
+    let state = h.state();
+    assert!(state.claim_closed);
+    assert_eq!(state.status, CampaignStatus::Ended);
+
+    // Reopening makes it live again, closing ends it again
+    let ix = h.update_claim_window_ix(h.now() - 60, CLAIM_DURATION);
+    h.send_as_authority(ix).expect("update_claim_window");
+    assert_eq!(h.state().status, CampaignStatus::Live);
+    let ix = h.close_airdrop_ix();
+    h.send_as_authority(ix).expect("close_airdrop");
+    assert_eq!(h.state().status, CampaignStatus::Ended);
+
+    let changed = status_changed(finalize_airdrop(&mut h));
+    assert_eq!(changed.to, Status::Finalized);
+    let ix = h.update_claim_window_ix(h.now() - 60, CLAIM_DURATION);
+    assert_fails_with(h.send_as_authority(ix), "InvalidStatus");
+    let ix = h.close_airdrop_ix();
+    assert_fails_with(h.send_as_authority(ix), "InvalidStatus");
+    close_state(&mut h).expect("close_state");
+}
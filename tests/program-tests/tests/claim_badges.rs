@@ -0,0 +1,59 @@
+//! Claim badges: once enabled, every claim mints a non-transferable
+//! Token-2022 receipt to the claimant, and the badge can't be moved on.
+
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use program_tests::{assert_fails_with, leaf_hash, Harness, TOKEN_2022_ID};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+
+#[test]
+fn claim_mints_a_soulbound_badge() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let (badge_mint, ix) = h.init_claim_badge_ix();
+    h.send_as_authority(ix).expect("init_claim_badge");
+    assert_eq!(h.state().badge_mint, badge_mint);
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "ClaimBadgeMissing");
+
+    let badge = h.create_badge_account(&wallet, &badge_mint);
+    h.badge_mint = Some(badge_mint);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert_eq!(h.badge_balance(&badge), 1);
+
+    // TransferChecked of the badge to someone else is refused by Token-2022
+    let (other, _) = h.new_claimant();
+    let other_badge = h.create_badge_account(&other, &badge_mint);
+    let mut data = vec![12];
+    data.extend_from_slice(&1u64.to_le_bytes());
+    data.push(0);
+    let ix = Instruction {
+        program_id: TOKEN_2022_ID,
+        accounts: vec![
+            AccountMeta::new(badge, false),
+            AccountMeta::new_readonly(badge_mint, false),
+            AccountMeta::new(other_badge, false),
+            AccountMeta::new_readonly(wallet.pubkey(), true),
+        ],
+        data,
+    };
+    h.send(ix, &[&wallet]).expect_err("badge transfer");
+    assert_eq!(h.badge_balance(&badge), 1);
+}
+
+#[test]
+fn badges_only_before_the_first_claim() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+
+    let (_, ix) = h.init_claim_badge_ix();
+    assert_fails_with(h.send_as_authority(ix), "ClaimBadgeTooLate");
+}
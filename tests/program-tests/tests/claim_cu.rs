@@ -0,0 +1,54 @@
+//! Compute-unit budgets for `claim` across the supported proof depths.
+//!
+//! Proofs are synthetic: each depth gets random-looking siblings and the root
+//! is folded from the leaf, so no 2^24-leaf tree has to be built.
+
+use anchor_lang::solana_program::keccak;
+use program_tests::{fold_proof, leaf_hash, Harness};
+use solana_sdk::signature::Signer;
+
+/// Ceiling for a depth-24 claim including the token transfer CPI.
+const CLAIM_CU_BUDGET: u64 = 60_000;
+/// Marginal cost of one extra proof level (one keccak syscall plus copies).
+const CU_PER_PROOF_LEVEL: u64 = 1_000;
+
+#[test]
+fn claim_stays_within_cu_budget_for_depths_10_to_24() {
+    let total_claims = 32;
+    let mut h = Harness::new([0; 32], total_claims, total_claims);
+    let mut baseline: Option<(u64, u64)> = None;
+
+    for depth in 10..=24u64 {
+        let (wallet, ata) = h.new_claimant();
+        let index = depth;
+        let amount = 1_000;
+        let proof: Vec<[u8; 32]> = (0..depth)
+            .map(|level| keccak::hashv(&[&depth.to_le_bytes(), &level.to_le_bytes()]).to_bytes())
+            .collect();
+        let root = fold_proof(leaf_hash(index, &wallet.pubkey(), amount), &proof);
+
+        h.set_merkle_root(root, total_claims).expect("set_merkle_root");
+
+        let ix = h.claim_ix(&wallet.pubkey(), &ata, index, amount, &proof);
+        let meta = h.send(ix, &[&wallet]).expect("claim");
+        let cu = meta.compute_units_consumed;
+        println!("depth {depth:>2}: {cu} CU");
+
+        assert!(
+            cu <= CLAIM_CU_BUDGET,
+            "claim at depth {depth} used {cu} CU, budget is {CLAIM_CU_BUDGET}"
+        );
+        match baseline {
+            None => baseline = Some((depth, cu)),
+            Some((base_depth, base_cu)) => {
+                let allowed = (depth - base_depth) * CU_PER_PROOF_LEVEL;
+                assert!(
+                    cu.saturating_sub(base_cu) <= allowed,
+                    "depth {depth} costs {} CU over depth {base_depth}, allowed {allowed}",
+                    cu - base_cu
+                );
+            }
+        }
+        assert_eq!(h.token_balance(&ata), amount);
+    }
+}
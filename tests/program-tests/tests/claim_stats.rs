@@ -0,0 +1,52 @@
+//! Once enabled, claim stats can't be skipped and each claim lands in the
+//! bucket for its hour.
+
+use airdrop0::{accounts, instruction, ClaimStats};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use program_tests::{event_authority, leaf_hash, Harness};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 4_200;
+const STATS_BUCKETS: i64 = 72;
+
+#[test]
+fn claim_updates_the_current_hour_bucket() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let (stats, _) = Pubkey::find_program_address(&[b"stats", h.state.as_ref()], &airdrop0::ID);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::InitClaimStats {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            stats,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitClaimStats {}.data(),
+    };
+    h.send_as_authority(ix).expect("init_claim_stats");
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    let failed = h.send(ix, &[&wallet]).expect_err("stats account omitted");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("StatsAccountMissing")));
+
+    h.stats = Some(stats);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+
+    let data = h.svm.get_account(&stats).unwrap().data;
+    let stats = ClaimStats::try_deserialize(&mut data.as_slice()).unwrap();
+    let hour = h.now().div_euclid(3_600);
+    let bucket = stats.buckets[hour.rem_euclid(STATS_BUCKETS) as usize];
+    assert_eq!(bucket.hour, hour);
+    assert_eq!(bucket.claims, 1);
+    assert_eq!(bucket.amount, AMOUNT);
+}
@@ -0,0 +1,77 @@
+//! `assert_claimed` / `assert_not_claimed` succeed or fail on an index's claim
+//! status, so downstream programs can gate on it with a bare CPI; the
+//! interface crate's `ClaimSet` gives off-chain readers the same answer.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use merkle::MerkleTree;
+use merkledrop_interface::ClaimSet;
+use program_tests::{assert_fails_with, leaf_hash, Harness};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+
+fn status_ix(h: &Harness, index: u64, claimed: bool) -> Instruction {
+    let data = if claimed {
+        instruction::AssertClaimed { index }.data()
+    } else {
+        instruction::AssertNotClaimed { index }.data()
+    };
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimStatus { state: h.state }.to_account_metas(None),
+        data,
+    }
+}
+
+#[test]
+fn status_follows_claims() {
+    let wallets = [Keypair::new(), Keypair::new()];
+    let tree = MerkleTree::from_leaves(
+        wallets.iter().enumerate().map(|(i, w)| leaf_hash(i as u64, &w.pubkey(), AMOUNT)).collect(),
+    );
+    let mut h = Harness::new(tree.root(), 2, 1_000);
+    let [first, _] = wallets;
+    let (wallet, ata) = h.new_claimant_with(first);
+
+    let ix = status_ix(&h, 0, false);
+    h.send_as_authority(ix).expect("unclaimed before the claim");
+    let ix = status_ix(&h, 0, true);
+    assert_fails_with(h.send_as_authority(ix), "NotClaimed");
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &tree.proof(0).unwrap());
+    h.send(ix, &[&wallet]).expect("claim");
+
+    let ix = status_ix(&h, 0, true);
+    h.send_as_authority(ix).expect("claimed after the claim");
+    let ix = status_ix(&h, 0, false);
+    assert_fails_with(h.send_as_authority(ix), "AlreadyClaimed");
+
+    let ix = status_ix(&h, 1, false);
+    h.send_as_authority(ix).expect("other indices stay unclaimed");
+    let ix = status_ix(&h, 2, false);
+    assert_fails_with(h.send_as_authority(ix), "InvalidIndex");
+}
+
+#[test]
+fn claim_set_reads_status_from_account_data() {
+    let wallets = [Keypair::new(), Keypair::new()];
+    let tree = MerkleTree::from_leaves(
+        wallets.iter().enumerate().map(|(i, w)| leaf_hash(i as u64, &w.pubkey(), AMOUNT)).collect(),
+    );
+    let mut h = Harness::new(tree.root(), 2, 1_000);
+    let [_, second] = wallets;
+    let (wallet, ata) = h.new_claimant_with(second);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 1, AMOUNT, &tree.proof(1).unwrap());
+    h.send(ix, &[&wallet]).expect("claim");
+
+    let data = h.svm.get_account(&h.state).unwrap().data;
+    let is_claimed = ClaimSet::from_account_data(&data).expect("State account");
+    assert!(!is_claimed(0));
+    assert!(is_claimed(1));
+    assert!(!is_claimed(2));
+    assert!(ClaimSet::from_account_data(&data[1..]).is_err());
+
+    let state = merkledrop_interface::State::decode(&data).unwrap();
+    assert_eq!(ClaimSet::from_state(&state).unwrap().claimed_ranges(), [1..=1]);
+}
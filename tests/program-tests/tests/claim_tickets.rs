@@ -0,0 +1,152 @@
+//! Commit-reveal tickets: with a reveal delay set, a claim is committed with
+//! `commit_claim` and only pays through `claim_ticketed` once the delay has
+//! passed and the salt reproduces the commitment; `claim` is refused.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use litesvm_token::spl_token;
+use program_tests::{assert_fails_with, event_authority, leaf_hash, pad_proof, Harness, TxResult};
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+const REVEAL_DELAY: u64 = 20;
+const SALT: [u8; 32] = [7; 32];
+
+fn ticket(h: &Harness, wallet: &Pubkey) -> Pubkey {
+    merkledrop_interface::pda::ticket(&h.state, wallet).0
+}
+
+fn set_reveal_delay_ix(h: &Harness, reveal_delay: u64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetRevealDelay {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetRevealDelay { reveal_delay }.data(),
+    }
+}
+
+fn commit_claim_ix(h: &Harness, wallet: &Pubkey, salt: &[u8; 32]) -> Instruction {
+    let commitment = merkle::claim_commitment(0, &wallet.to_bytes(), AMOUNT, salt);
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::CommitClaim {
+            state: h.state,
+            wallet: *wallet,
+            ticket: ticket(h, wallet),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CommitClaim { commitment }.data(),
+    }
+}
+
+fn claim_ticketed(h: &mut Harness, wallet: &Keypair, user_ata: &Pubkey, salt: [u8; 32]) -> TxResult {
+    let (proof, proof_len) = pad_proof(&[]);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimTicketed {
+            state: h.state,
+            wallet: wallet.pubkey(),
+            ticket: ticket(h, &wallet.pubkey()),
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            fee_account: None,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            bonus_vault: None,
+            badge_mint: None,
+            badge_account: None,
+            token_2022_program: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimTicketed {
+            index: 0,
+            amount: AMOUNT,
+            salt,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    };
+    h.send(ix, &[wallet])
+}
+
+fn warp_slots(h: &mut Harness, slots: u64) {
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.slot += slots;
+    h.svm.set_sysvar(&clock);
+}
+
+#[test]
+fn ticket_opens_after_the_reveal_delay() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+
+    // Committing needs ticketing to be on
+    let ix = commit_claim_ix(&h, &wallet.pubkey(), &SALT);
+    assert_fails_with(h.send(ix, &[&wallet]), "TicketingDisabled");
+    let ix = set_reveal_delay_ix(&h, 151);
+    assert_fails_with(h.send_as_authority(ix), "InvalidRevealDelay");
+    let ix = set_reveal_delay_ix(&h, REVEAL_DELAY);
+    h.send_as_authority(ix).expect("set_reveal_delay");
+    assert_eq!(h.state().reveal_delay, REVEAL_DELAY);
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "TicketRequired");
+
+    let ix = commit_claim_ix(&h, &wallet.pubkey(), &SALT);
+    h.send(ix, &[&wallet]).expect("commit_claim");
+    assert_fails_with(claim_ticketed(&mut h, &wallet, &ata, SALT), "RevealTooEarly");
+
+    warp_slots(&mut h, REVEAL_DELAY);
+    assert_fails_with(claim_ticketed(&mut h, &wallet, &ata, [8; 32]), "InvalidTicket");
+    claim_ticketed(&mut h, &wallet, &ata, SALT).expect("claim_ticketed");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert!(h.svm.get_account(&ticket(&h, &wallet.pubkey())).map_or(true, |a| a.lamports == 0));
+}
+
+#[test]
+fn recommitting_restarts_the_delay() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let ix = set_reveal_delay_ix(&h, REVEAL_DELAY);
+    h.send_as_authority(ix).expect("set_reveal_delay");
+
+    let ix = commit_claim_ix(&h, &wallet.pubkey(), &[1; 32]);
+    h.send(ix, &[&wallet]).expect("commit_claim");
+    warp_slots(&mut h, REVEAL_DELAY);
+    let ix = commit_claim_ix(&h, &wallet.pubkey(), &SALT);
+    h.send(ix, &[&wallet]).expect("commit_claim again");
+
+    // The replaced ticket no longer opens, and the new one has to wait
+    assert_fails_with(claim_ticketed(&mut h, &wallet, &ata, SALT), "RevealTooEarly");
+    warp_slots(&mut h, REVEAL_DELAY);
+    assert_fails_with(claim_ticketed(&mut h, &wallet, &ata, [1; 32]), "InvalidTicket");
+    claim_ticketed(&mut h, &wallet, &ata, SALT).expect("claim_ticketed");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+}
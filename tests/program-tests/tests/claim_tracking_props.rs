@@ -0,0 +1,110 @@
+//! Property tests for the claim ledger: random claim sequences (repeats
+//! included) against random campaign sizes and capacities, checked against a
+//! model in which every index pays exactly once. They go through `claim`, so
+//! they exercise whichever tracking backend `initialize` configures; today
+//! that is the claim bitmap, sized by the capacity.
+
+use std::collections::HashSet;
+
+use merkle::MerkleTree;
+use program_tests::{leaf_hash, Harness, TxResult, VAULT_SUPPLY};
+use proptest::prelude::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const MAX_CLAIMS: u64 = 24;
+
+struct Campaign {
+    h: Harness,
+    claimants: Vec<(Keypair, Pubkey)>,
+    tree: MerkleTree,
+}
+
+fn amount(index: u64) -> u64 {
+    1_000 + index
+}
+
+fn campaign(total_claims: u64, capacity: u64) -> Campaign {
+    let wallets: Vec<Keypair> = (0..total_claims).map(|_| Keypair::new()).collect();
+    let leaves = wallets
+        .iter()
+        .enumerate()
+        .map(|(index, wallet)| leaf_hash(index as u64, &wallet.pubkey(), amount(index as u64)))
+        .collect();
+    let tree = MerkleTree::from_leaves(leaves);
+
+    let mut h = Harness::new(tree.root(), total_claims, capacity);
+    let claimants = wallets.into_iter().map(|wallet| h.new_claimant_with(wallet)).collect();
+    Campaign { h, claimants, tree }
+}
+
+impl Campaign {
+    fn claim(&mut self, index: u64) -> TxResult {
+        let (wallet, ata) = &self.claimants[index as usize];
+        let proof = self.tree.proof(index as usize).unwrap();
+        let ix = self.h.claim_ix(&wallet.pubkey(), ata, index, amount(index), &proof);
+        self.h.send(ix, &[wallet])
+    }
+}
+
+/// Campaign size, a capacity at or above it, and a claim sequence over its indices.
+fn claim_sequences() -> impl Strategy<Value = (u64, u64, Vec<u64>)> {
+    (2..=MAX_CLAIMS).prop_flat_map(|total_claims| {
+        (
+            Just(total_claims),
+            total_claims..=total_claims * 64,
+            prop::collection::vec(0..total_claims, 1..=3 * total_claims as usize),
+        )
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn no_index_is_paid_twice((total_claims, capacity, sequence) in claim_sequences()) {
+        let mut c = campaign(total_claims, capacity);
+        let mut paid = HashSet::new();
+        for &index in &sequence {
+            let result = c.claim(index);
+            if paid.contains(&index) {
+                let failed = result.expect_err("repeat claim");
+                prop_assert!(failed.meta.logs.iter().any(|log| log.contains("AlreadyClaimed")));
+            } else if result.is_ok() {
+                paid.insert(index);
+            }
+        }
+
+        for (index, (_, ata)) in c.claimants.iter().enumerate() {
+            let expected = if paid.contains(&(index as u64)) { amount(index as u64) } else { 0 };
+            prop_assert_eq!(c.h.token_balance(ata), expected);
+        }
+        let total_paid: u64 = paid.iter().map(|&index| amount(index)).sum();
+        prop_assert_eq!(c.h.token_balance(&c.h.vault), VAULT_SUPPLY - total_paid);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    /// No two distinct indices share a record, so each one's first claim
+    /// goes through whatever was claimed before it.
+    #[test]
+    fn no_legitimate_claim_is_rejected((total_claims, capacity, sequence) in claim_sequences()) {
+        let mut c = campaign(total_claims, capacity);
+        let mut paid = HashSet::<u64>::new();
+        for &index in &sequence {
+            let result = c.claim(index);
+            if paid.insert(index) {
+                prop_assert!(
+                    result.is_ok(),
+                    "first claim of index {} rejected after {:?}",
+                    index,
+                    paid
+                );
+            }
+        }
+    }
+}
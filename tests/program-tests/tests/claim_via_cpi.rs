@@ -0,0 +1,69 @@
+//! Attributed CPI claims: only a caller PDA signed by the credited program is
+//! accepted, so a wallet can't claim while posing as an integration.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use litesvm_token::spl_token;
+use program_tests::{event_authority, leaf_hash, pad_proof, Harness};
+use solana_sdk::{pubkey::Pubkey, signature::{Keypair, Signer}};
+
+const AMOUNT: u64 = 1_000;
+
+fn claim_via_cpi_ix(
+    h: &Harness,
+    wallet: &Pubkey,
+    user_ata: &Pubkey,
+    caller_program: Pubkey,
+    caller_authority: Pubkey,
+) -> Instruction {
+    let (proof, proof_len) = pad_proof(&[]);
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimViaCpi {
+            state: h.state,
+            wallet: *wallet,
+            caller_program,
+            caller_authority,
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            fee_account: None,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            bonus_vault: None,
+            badge_mint: None,
+            badge_account: None,
+            token_2022_program: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimViaCpi {
+            index: 0,
+            amount: AMOUNT,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    }
+}
+
+#[test]
+fn claim_via_cpi_rejects_an_impersonated_caller() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    // A keypair signing as the token program's caller PDA
+    let impostor = Keypair::new();
+    let ix = claim_via_cpi_ix(&h, &wallet.pubkey(), &ata, spl_token::ID, impostor.pubkey());
+    let failed = h.send(ix, &[&wallet, &impostor]).expect_err("impersonated caller");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("ConstraintSeeds")));
+    assert_eq!(h.token_balance(&ata), 0);
+}
@@ -0,0 +1,48 @@
+//! `ClaimWindow` reports the bounds `claim` enforces: a claim landing at
+//! `closes_at` goes through and one a second later doesn't.
+
+use merkle::MerkleTree;
+use merkledrop_interface::{ClaimWindow, State, WindowStatus};
+use program_tests::{assert_fails_with, leaf_hash, Harness, CLAIM_DURATION};
+use solana_sdk::{
+    clock::Clock,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+
+fn set_time(h: &mut Harness, unix_timestamp: i64) {
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = unix_timestamp;
+    h.svm.set_sysvar(&clock);
+}
+
+#[test]
+fn window_matches_the_program() {
+    let wallets = [Keypair::new(), Keypair::new()];
+    let tree = MerkleTree::from_leaves(
+        wallets.iter().enumerate().map(|(i, w)| leaf_hash(i as u64, &w.pubkey(), AMOUNT)).collect(),
+    );
+    let mut h = Harness::new(tree.root(), 2, 1_000);
+    let data = h.svm.get_account(&h.state).unwrap().data;
+    let window = ClaimWindow::of(&State::decode(&data).unwrap());
+    assert_eq!(window.closes_at - window.opens_at, CLAIM_DURATION);
+
+    let now = h.svm.get_sysvar::<Clock>().unix_timestamp;
+    assert_eq!(window.status(now), WindowStatus::Open { remaining: window.closes_at - now });
+    assert_eq!(window.status(window.opens_at - 5), WindowStatus::NotStarted { opens_in: 5 });
+    assert!(window.lands_in_window(window.closes_at - 10, 10));
+    assert!(!window.lands_in_window(window.closes_at - 10, 11));
+
+    set_time(&mut h, window.closes_at);
+    assert_eq!(window.status(window.closes_at), WindowStatus::Open { remaining: 0 });
+    let (wallet, ata) = h.new_claimant_with(wallets[0].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &tree.proof(0).unwrap());
+    h.send(ix, &[&wallet]).expect("claim at the last second");
+
+    set_time(&mut h, window.closes_at + 1);
+    assert_eq!(window.status(window.closes_at + 1), WindowStatus::Ended);
+    let (wallet, ata) = h.new_claimant_with(wallets[1].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 1, AMOUNT, &tree.proof(1).unwrap());
+    assert_fails_with(h.send(ix, &[&wallet]), "WindowEnded");
+}
@@ -0,0 +1,111 @@
+//! Close grace: with `close_grace` set, `close_airdrop` ends the open window
+//! that many seconds later instead of at once, so claims in flight still
+//! land; the cutoff is reported in `AirdropClosed`. A locked window is never
+//! moved, so a locked campaign closes at once.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use merkle::MerkleTree;
+use merkledrop_interface::MerkledropEvent;
+use program_tests::{assert_fails_with, event_authority, leaf_hash, Harness, TxResult};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+const GRACE: i64 = 90;
+
+fn set_close_grace_ix(h: &Harness, close_grace: i64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetCloseGrace {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetCloseGrace { close_grace }.data(),
+    }
+}
+
+fn claim(h: &mut Harness, tree: &MerkleTree, claimants: &[(Keypair, Pubkey)], index: usize) -> TxResult {
+    let (wallet, ata) = &claimants[index];
+    let proof = tree.proof(index).unwrap();
+    let ix = h.claim_ix(&wallet.pubkey(), ata, index as u64, AMOUNT, &proof);
+    h.send(ix, &[wallet])
+}
+
+#[test]
+fn claims_land_until_the_cutoff() {
+    let wallets: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let tree = MerkleTree::from_leaves(
+        wallets
+            .iter()
+            .enumerate()
+            .map(|(index, w)| leaf_hash(index as u64, &w.pubkey(), AMOUNT))
+            .collect(),
+    );
+    let mut h = Harness::new(tree.root(), 3, 1_000);
+    let claimants: Vec<_> = wallets.into_iter().map(|w| h.new_claimant_with(w)).collect();
+
+    let ix = set_close_grace_ix(&h, 3_601);
+    assert_fails_with(h.send_as_authority(ix), "InvalidCloseGrace");
+    let ix = set_close_grace_ix(&h, GRACE);
+    h.send_as_authority(ix).expect("set_close_grace");
+
+    let ix = h.close_airdrop_ix();
+    let meta = h.send_as_authority(ix).expect("close_airdrop");
+    let closed = meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .find_map(|inner| match MerkledropEvent::decode_cpi(&inner.instruction.data) {
+            Some(MerkledropEvent::AirdropClosed(closed)) => Some(closed),
+            _ => None,
+        })
+        .expect("AirdropClosed event");
+    assert_eq!(closed.closes_at, h.now() + GRACE);
+    assert!(!h.state().claim_closed);
+
+    // Within the grace period claims still go through
+    h.warp(GRACE - 1);
+    claim(&mut h, &tree, &claimants, 0).expect("claim in grace");
+
+    h.warp(2);
+    assert_fails_with(claim(&mut h, &tree, &claimants, 1), "WindowEnded");
+}
+
+#[test]
+fn zero_grace_closes_at_once() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+
+    let ix = h.close_airdrop_ix();
+    h.send_as_authority(ix).expect("close_airdrop");
+    assert!(h.state().claim_closed);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "ClaimClosed");
+}
+
+#[test]
+fn locked_window_closes_at_once() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let ix = set_close_grace_ix(&h, GRACE);
+    h.send_as_authority(ix).expect("set_close_grace");
+    let ix = h.lock_config_ix();
+    h.send_as_authority(ix).expect("lock_config");
+    let duration = h.state().claim_duration;
+
+    let ix = h.close_airdrop_ix();
+    h.send_as_authority(ix).expect("close_airdrop");
+    let state = h.state();
+    assert!(state.claim_closed);
+    assert_eq!(state.claim_duration, duration);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "ClaimClosed");
+}
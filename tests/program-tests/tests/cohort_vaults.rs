@@ -0,0 +1,222 @@
+//! Per-cohort vaults: a cohort with its own vault is paid only from it, and
+//! what it leaves unclaimed is clawed back without touching other cohorts.
+
+use airdrop0::{accounts, instruction, VestingSchedule};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, program_pack::Pack, system_program},
+    InstructionData, ToAccountMetas,
+};
+use litesvm_token::spl_token::state::Account as TokenAccount;
+use merkle::MerkleTree;
+use program_tests::{
+    assert_fails_with, event_authority, pad_proof, Harness, TxResult, CLAIM_DURATION, SNAPSHOT_HASH,
+};
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 10_000;
+const TEAM: u32 = 1;
+const COMMUNITY: u32 = 2;
+const IMMEDIATE: VestingSchedule = VestingSchedule { cliff: 0, duration: 0, tge_bps: 0 };
+const HALF_AT_START: VestingSchedule = VestingSchedule { cliff: 0, duration: 1_000, tge_bps: 5_000 };
+
+fn cohort_account(h: &Harness, cohort_id: u32) -> Pubkey {
+    Pubkey::find_program_address(&[b"cohort", h.state.as_ref(), &cohort_id.to_le_bytes()], &airdrop0::ID).0
+}
+
+fn vesting_account(index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vesting", SNAPSHOT_HASH.as_ref(), &index.to_le_bytes()],
+        &airdrop0::ID,
+    )
+    .0
+}
+
+// Copies the main vault's token account to `address` with `amount` tokens;
+// same mint and vault authority, as an operator-created vault would have
+fn put_vault(h: &mut Harness, address: Pubkey, amount: u64) {
+    let mut account = h.svm.get_account(&h.vault).unwrap();
+    let mut token = TokenAccount::unpack(&account.data).unwrap();
+    token.amount = amount;
+    TokenAccount::pack(token, &mut account.data).unwrap();
+    h.svm.set_account(address, account).unwrap();
+}
+
+fn set_cohort_ix(h: &Harness, cohort_id: u32, claim_start_ts: i64, schedule: VestingSchedule) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetCohort {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            cohort: cohort_account(h, cohort_id),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetCohort {
+            cohort_id,
+            claim_start_ts,
+            claim_duration: CLAIM_DURATION,
+            schedule,
+        }
+        .data(),
+    }
+}
+
+fn set_cohort_vault_ix(h: &Harness, cohort_id: u32, vault: Pubkey, clawback_ts: i64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetCohortVault {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            cohort: cohort_account(h, cohort_id),
+            vault_auth: h.vault_auth,
+            vault,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetCohortVault { cohort_id, clawback_ts }.data(),
+    }
+}
+
+fn clawback_cohort_ix(
+    h: &Harness,
+    cohort_id: u32,
+    cohort_vault: Pubkey,
+    destination: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClawbackCohort {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            cohort: cohort_account(h, cohort_id),
+            vault_auth: h.vault_auth,
+            cohort_vault,
+            destination,
+            mint: h.mint,
+            token_program: litesvm_token::spl_token::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClawbackCohort { cohort_id }.data(),
+    }
+}
+
+fn claim_cohort(
+    h: &mut Harness,
+    wallet: &Keypair,
+    user_ata: &Pubkey,
+    index: u64,
+    cohort_id: u32,
+    cohort_vault: Option<Pubkey>,
+    proof: &[[u8; 32]],
+) -> TxResult {
+    let (proof, proof_len) = pad_proof(proof);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimCohort {
+            state: h.state,
+            cohort: cohort_account(h, cohort_id),
+            wallet: wallet.pubkey(),
+            vesting: vesting_account(index),
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            cohort_vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: litesvm_token::spl_token::ID,
+            system_program: system_program::ID,
+            fee_account: None,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            badge_mint: None,
+            badge_account: None,
+            token_2022_program: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimCohort {
+            index,
+            amount: AMOUNT,
+            cohort_id,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    };
+    h.send(ix, &[wallet])
+}
+
+#[test]
+fn cohort_vault_pays_its_cohort_and_is_clawed_back_alone() {
+    let (founder, member) = (Keypair::new(), Keypair::new());
+    let tree = MerkleTree::from_leaves(vec![
+        merkle::cohort_leaf(0, &founder.pubkey().to_bytes(), AMOUNT, TEAM),
+        merkle::cohort_leaf(1, &member.pubkey().to_bytes(), AMOUNT, COMMUNITY),
+        merkle::cohort_leaf(2, &Pubkey::new_unique().to_bytes(), AMOUNT, TEAM),
+    ]);
+    let mut h = Harness::new(tree.root(), 3, 3);
+    let (founder, founder_ata) = h.new_claimant_with(founder);
+    let (member, member_ata) = h.new_claimant_with(member);
+    let (_, treasury_ata) = h.new_claimant();
+
+    let now = h.now();
+    let ix = set_cohort_ix(&h, TEAM, now - 60, HALF_AT_START);
+    h.send_as_authority(ix).expect("set_cohort team");
+    let ix = set_cohort_ix(&h, COMMUNITY, now - 60, IMMEDIATE);
+    h.send_as_authority(ix).expect("set_cohort community");
+    let team_vault = Pubkey::new_unique();
+    put_vault(&mut h, team_vault, 2 * AMOUNT);
+
+    // Clawback can't reach into the cohort's window
+    let ix = set_cohort_vault_ix(&h, TEAM, team_vault, now);
+    assert_fails_with(h.send_as_authority(ix), "InvalidCohortVault");
+    let clawback_ts = now + CLAIM_DURATION;
+    let ix = set_cohort_vault_ix(&h, TEAM, team_vault, clawback_ts);
+    h.send_as_authority(ix).expect("set_cohort_vault");
+    assert_eq!(h.state().cohort_vault_count, 1);
+
+    // Team leaves are paid from the team vault only; the locked half moves
+    // to the campaign vault for `release_vested`
+    let proof = tree.proof(0).unwrap();
+    assert_fails_with(
+        claim_cohort(&mut h, &founder, &founder_ata, 0, TEAM, None, &proof),
+        "InvalidCohortVault",
+    );
+    let main_before = h.token_balance(&h.vault);
+    claim_cohort(&mut h, &founder, &founder_ata, 0, TEAM, Some(team_vault), &proof)
+        .expect("team claim");
+    assert_eq!(h.token_balance(&founder_ata), AMOUNT / 2);
+    assert_eq!(h.token_balance(&team_vault), AMOUNT);
+    assert_eq!(h.token_balance(&h.vault), main_before + AMOUNT / 2);
+
+    // Community leaves still draw on the campaign vault
+    let proof = tree.proof(1).unwrap();
+    claim_cohort(&mut h, &member, &member_ata, 1, COMMUNITY, None, &proof).expect("community claim");
+    assert_eq!(h.token_balance(&member_ata), AMOUNT);
+    assert_eq!(h.token_balance(&h.vault), main_before - AMOUNT / 2);
+
+    let ix = clawback_cohort_ix(&h, TEAM, team_vault, treasury_ata);
+    assert_fails_with(h.send_as_authority(ix), "ClawbackNotDue");
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = clawback_ts;
+    h.svm.set_sysvar(&clock);
+    let ix = clawback_cohort_ix(&h, TEAM, team_vault, treasury_ata);
+    h.send_as_authority(ix).expect("clawback_cohort");
+    assert_eq!(h.token_balance(&treasury_ata), AMOUNT);
+    assert_eq!(h.token_balance(&team_vault), 0);
+    assert_eq!(h.token_balance(&h.vault), main_before - AMOUNT / 2);
+    assert_eq!(h.state().cohort_vault_count, 0);
+}
@@ -0,0 +1,135 @@
+//! Cohorts sharing one root: each leaf's cohort, not the campaign, decides
+//! when it can be claimed and how it vests.
+
+use airdrop0::{accounts, instruction, VestingSchedule};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use merkle::MerkleTree;
+use program_tests::{
+    assert_fails_with, event_authority, pad_proof, Harness, TxResult, CLAIM_DURATION, SNAPSHOT_HASH,
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 10_000;
+const IMMEDIATE: VestingSchedule = VestingSchedule { cliff: 0, duration: 0, tge_bps: 0 };
+const HALF_AT_START: VestingSchedule = VestingSchedule { cliff: 0, duration: 1_000, tge_bps: 5_000 };
+
+fn cohort_account(h: &Harness, cohort_id: u32) -> Pubkey {
+    Pubkey::find_program_address(&[b"cohort", h.state.as_ref(), &cohort_id.to_le_bytes()], &airdrop0::ID).0
+}
+
+fn vesting_account(index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vesting", SNAPSHOT_HASH.as_ref(), &index.to_le_bytes()],
+        &airdrop0::ID,
+    )
+    .0
+}
+
+fn set_cohort_ix(h: &Harness, cohort_id: u32, claim_start_ts: i64, schedule: VestingSchedule) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetCohort {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            cohort: cohort_account(h, cohort_id),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetCohort {
+            cohort_id,
+            claim_start_ts,
+            claim_duration: CLAIM_DURATION,
+            schedule,
+        }
+        .data(),
+    }
+}
+
+fn claim_cohort(
+    h: &mut Harness,
+    wallet: &Keypair,
+    user_ata: &Pubkey,
+    index: u64,
+    cohort_id: u32,
+    proof: &[[u8; 32]],
+) -> TxResult {
+    let (proof, proof_len) = pad_proof(proof);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimCohort {
+            state: h.state,
+            cohort: cohort_account(h, cohort_id),
+            wallet: wallet.pubkey(),
+            vesting: vesting_account(index),
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            cohort_vault: None,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: litesvm_token::spl_token::ID,
+            system_program: system_program::ID,
+            fee_account: None,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            badge_mint: None,
+            badge_account: None,
+            token_2022_program: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimCohort {
+            index,
+            amount: AMOUNT,
+            cohort_id,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    };
+    h.send(ix, &[wallet])
+}
+
+#[test]
+fn each_cohort_claims_on_its_own_timing() {
+    let (early, late) = (Keypair::new(), Keypair::new());
+    let tree = MerkleTree::from_leaves(vec![
+        merkle::cohort_leaf(0, &early.pubkey().to_bytes(), AMOUNT, 1),
+        merkle::cohort_leaf(1, &late.pubkey().to_bytes(), AMOUNT, 2),
+    ]);
+    let mut h = Harness::new(tree.root(), 2, 2);
+    let (early, early_ata) = h.new_claimant_with(early);
+    let (late, late_ata) = h.new_claimant_with(late);
+
+    // Cohort 2 opens only after the campaign's own window has ended
+    let now = h.now();
+    let late_start = now + CLAIM_DURATION + 1_000;
+    let ix = set_cohort_ix(&h, 1, now - 60, IMMEDIATE);
+    h.send_as_authority(ix).expect("set_cohort 1");
+    let ix = set_cohort_ix(&h, 2, late_start, HALF_AT_START);
+    h.send_as_authority(ix).expect("set_cohort 2");
+
+    // The cohort is part of the leaf; naming another one doesn't verify
+    let proof = tree.proof(0).unwrap();
+    assert_fails_with(claim_cohort(&mut h, &early, &early_ata, 0, 2, &proof), "InvalidProof");
+    claim_cohort(&mut h, &early, &early_ata, 0, 1, &proof).expect("claim_cohort 1");
+    assert_eq!(h.token_balance(&early_ata), AMOUNT);
+
+    let proof = tree.proof(1).unwrap();
+    assert_fails_with(claim_cohort(&mut h, &late, &late_ata, 1, 2, &proof), "WindowNotStarted");
+    h.warp(late_start - now);
+    claim_cohort(&mut h, &late, &late_ata, 1, 2, &proof).expect("claim_cohort 2");
+    assert_eq!(h.token_balance(&late_ata), AMOUNT / 2);
+}
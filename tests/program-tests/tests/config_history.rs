@@ -0,0 +1,110 @@
+//! Settings history: an admin instruction emits `ConfigChanged` with the old
+//! and new encoding of exactly the fields it changed, and nothing when it
+//! left them as they were.
+
+use airdrop0::{accounts, instruction, RelayerUpdated};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, keccak, system_program},
+    AnchorSerialize, InstructionData, ToAccountMetas,
+};
+use merkledrop_interface::{ConfigChanged, FieldChange, MerkledropEvent};
+use program_tests::{event_authority, Harness, TxResult};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+fn config_changes(result: TxResult) -> Vec<ConfigChanged> {
+    result
+        .expect("admin instruction")
+        .inner_instructions
+        .iter()
+        .flatten()
+        .filter_map(|inner| match MerkledropEvent::decode_cpi(&inner.instruction.data) {
+            Some(MerkledropEvent::ConfigChanged(changed)) => Some(changed),
+            _ => None,
+        })
+        .collect()
+}
+
+fn change(field: &str, old: impl AnchorSerialize, new: impl AnchorSerialize) -> FieldChange {
+    FieldChange {
+        field: field.to_string(),
+        old: old.try_to_vec().unwrap(),
+        new: new.try_to_vec().unwrap(),
+    }
+}
+
+#[test]
+fn window_update_reports_old_and_new_values() {
+    let mut h = Harness::new([0; 32], 1, 1_000);
+    let state = h.state();
+    let new_start = state.claim_start_ts + 600;
+
+    let ix = h.update_claim_window_ix(new_start, state.claim_duration);
+    let changed = config_changes(h.send_as_authority(ix));
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].authority, state.authority);
+    assert_eq!(
+        changed[0].changes,
+        vec![change("claim_start_ts", state.claim_start_ts, new_start)]
+    );
+}
+
+#[test]
+fn unchanged_settings_emit_nothing() {
+    let mut h = Harness::new([0; 32], 1, 1_000);
+    let state = h.state();
+
+    let ix = h.update_claim_window_ix(state.claim_start_ts, state.claim_duration);
+    assert!(config_changes(h.send_as_authority(ix)).is_empty());
+
+    let ix = h.close_airdrop_ix();
+    let changed = config_changes(h.send_as_authority(ix));
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].changes, vec![change("claim_closed", false, true)]);
+}
+
+#[test]
+fn relayer_changes_report_the_relayers_hash() {
+    let mut h = Harness::new([0; 32], 1, 1_000);
+    let relayer = Pubkey::new_unique();
+    let relayer_account = Pubkey::find_program_address(
+        &[b"relayer", h.state.as_ref(), relayer.as_ref()],
+        &airdrop0::ID,
+    )
+    .0;
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RegisterRelayer {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            relayer_account,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RegisterRelayer { relayer }.data(),
+    };
+    let changed = config_changes(h.send_as_authority(ix));
+
+    let updated = RelayerUpdated { relayer, registered: true, timestamp: h.now() };
+    let chained = keccak::hashv(&[&[0; 32], &updated.try_to_vec().unwrap()]).to_bytes();
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].changes, vec![change("relayers_hash", [0u8; 32], chained)]);
+    assert_eq!(h.state().relayers_hash, chained);
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RevokeRelayer {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            relayer_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RevokeRelayer { relayer }.data(),
+    };
+    let changed = config_changes(h.send_as_authority(ix));
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].changes[0].field, "relayers_hash");
+}
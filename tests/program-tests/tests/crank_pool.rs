@@ -0,0 +1,79 @@
+//! Crank bounties: once the round has ended, exporting the next page of
+//! unclaimed indices pays the cranker from the state's lamport pool, once
+//! per page.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use merkledrop_interface::MerkledropEvent;
+use program_tests::{event_authority, Harness, TxResult, CLAIM_DURATION};
+use solana_sdk::{
+    clock::Clock,
+    signature::{Keypair, Signer},
+};
+
+const BOUNTY: u64 = 10_000;
+
+fn export_page(h: &mut Harness, cranker: &Keypair, range_start: u64, range_end: u64) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::EmitUnclaimed {
+            state: h.state,
+            cranker: Some(cranker.pubkey()),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::EmitUnclaimed { range_start, range_end }.data(),
+    };
+    h.send(ix, &[cranker])
+}
+
+// Lamports the transaction's `CrankBountyPaid` reports, if it paid one
+fn bounty_paid(result: TxResult) -> Option<u64> {
+    let meta = result.expect("emit_unclaimed");
+    meta.inner_instructions
+        .iter()
+        .flatten()
+        .find_map(|inner| match MerkledropEvent::decode_cpi(&inner.instruction.data) {
+            Some(MerkledropEvent::CrankBountyPaid(paid)) => Some(paid.lamports),
+            _ => None,
+        })
+}
+
+#[test]
+fn next_export_page_of_an_ended_round_earns_the_bounty_once() {
+    let mut h = Harness::new([0; 32], 3, 1_000);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetCrankBounty {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetCrankBounty { lamports: BOUNTY }.data(),
+    };
+    h.send_as_authority(ix).expect("set_crank_bounty");
+    // Anyone tops the pool up with a plain transfer
+    h.svm.airdrop(&h.state, 3 * BOUNTY / 2).unwrap();
+    let cranker = Keypair::new();
+    h.svm.airdrop(&cranker.pubkey(), 1_000_000_000).unwrap();
+
+    // Round still open
+    assert_eq!(bounty_paid(export_page(&mut h, &cranker, 0, 3)), None);
+
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += CLAIM_DURATION;
+    h.svm.set_sysvar(&clock);
+    // Not the next page
+    assert_eq!(bounty_paid(export_page(&mut h, &cranker, 1, 3)), None);
+
+    let pool_before = h.svm.get_account(&h.state).unwrap().lamports;
+    assert_eq!(bounty_paid(export_page(&mut h, &cranker, 0, 3)), Some(BOUNTY));
+    assert_eq!(h.svm.get_account(&h.state).unwrap().lamports, pool_before - BOUNTY);
+    assert_eq!(h.state().unclaimed_export_cursor, 3);
+
+    // Replaying the page earns nothing
+    assert_eq!(bounty_paid(export_page(&mut h, &cranker, 0, 3)), None);
+}
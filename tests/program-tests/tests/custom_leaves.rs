@@ -0,0 +1,101 @@
+//! Custom leaf formats: `claim_with_leaf` pays a leaf only when its schema
+//! rebuilds it from the claiming wallet and amount, and reports the leaf's
+//! extra fields.
+
+use airdrop0::{instruction, LeafSchema};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData};
+use merkle::MerkleTree;
+use merkledrop_interface::MerkledropEvent;
+use program_tests::{assert_fails_with, pad_proof, Harness};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+const LOCKUP: &[u8] = b"lockup:90d";
+
+// `claim_ix`'s accounts with `claim_with_leaf` as the instruction
+#[allow(clippy::too_many_arguments)]
+fn claim_with_leaf_ix(
+    h: &Harness,
+    wallet: &Pubkey,
+    ata: &Pubkey,
+    index: u64,
+    leaf: [u8; 32],
+    amount: u64,
+    schema: LeafSchema,
+    proof: &[[u8; 32]],
+) -> Instruction {
+    let mut ix = h.claim_ix(wallet, ata, index, amount, proof);
+    let (proof, proof_len) = pad_proof(proof);
+    ix.data = instruction::ClaimWithLeaf {
+        index,
+        leaf,
+        amount,
+        schema,
+        proof,
+        proof_len,
+        terms_hash: [0; 32],
+    }
+    .data();
+    ix
+}
+
+#[test]
+fn schema_binds_a_custom_leaf_to_its_wallet_and_amount() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let alice_leaf = merkle::tagged_leaf(0, &alice.pubkey().to_bytes(), AMOUNT, LOCKUP);
+    let bob_leaf = merkle::evm_tagged_leaf(1, bob.pubkey().as_ref(), AMOUNT, LOCKUP);
+    let tree = MerkleTree::from_leaves(vec![alice_leaf, bob_leaf]);
+    let mut h = Harness::new(tree.root(), 2, 2);
+    let (alice, alice_ata) = h.new_claimant_with(alice);
+    let (bob, bob_ata) = h.new_claimant_with(bob);
+    let tagged = || LeafSchema::Tagged { fields: LOCKUP.to_vec() };
+    let alice_proof = tree.proof(0).unwrap();
+
+    // Another wallet, or another amount, doesn't rebuild the leaf
+    let ix = claim_with_leaf_ix(
+        &h, &bob.pubkey(), &bob_ata,
+        0, alice_leaf, AMOUNT, tagged(), &alice_proof,
+    );
+    assert_fails_with(h.send(ix, &[&bob]), "LeafMismatch");
+    let ix = claim_with_leaf_ix(
+        &h, &alice.pubkey(), &alice_ata,
+        0, alice_leaf, 2 * AMOUNT, tagged(), &alice_proof,
+    );
+    assert_fails_with(h.send(ix, &[&alice]), "LeafMismatch");
+
+    let ix = claim_with_leaf_ix(
+        &h, &alice.pubkey(), &alice_ata,
+        0, alice_leaf, AMOUNT, tagged(), &alice_proof,
+    );
+    let meta = h.send(ix, &[&alice]).expect("claim_with_leaf");
+    assert_eq!(h.token_balance(&alice_ata), AMOUNT);
+    let claimed = meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .find_map(|inner| match MerkledropEvent::decode_cpi(&inner.instruction.data) {
+            Some(MerkledropEvent::LeafClaimed(claimed)) => Some(claimed),
+            _ => None,
+        })
+        .expect("LeafClaimed event");
+    assert_eq!(claimed.leaf, alice_leaf);
+    assert_eq!(
+        claimed.schema,
+        merkledrop_interface::LeafSchema::Tagged { fields: LOCKUP.to_vec() }
+    );
+
+    let bob_proof = tree.proof(1).unwrap();
+    let empty = LeafSchema::EvmPacked { fields: vec![] };
+    let ix = claim_with_leaf_ix(
+        &h, &bob.pubkey(), &bob_ata,
+        1, bob_leaf, AMOUNT, empty, &bob_proof,
+    );
+    assert_fails_with(h.send(ix, &[&bob]), "InvalidLeafFields");
+    let evm = LeafSchema::EvmPacked { fields: LOCKUP.to_vec() };
+    let ix = claim_with_leaf_ix(&h, &bob.pubkey(), &bob_ata, 1, bob_leaf, AMOUNT, evm, &bob_proof);
+    h.send(ix, &[&bob]).expect("claim_with_leaf, EVM-packed");
+    assert_eq!(h.token_balance(&bob_ata), AMOUNT);
+}
@@ -0,0 +1,83 @@
+//! `distribute_batch` pushes tokens from the vault within the campaign's
+//! allocation, never into what reserved or vesting claimants are owed, and
+//! not at all once the config is locked. Recipients count as claims, down to
+//! tripping the low-water mark.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::instruction::{AccountMeta, Instruction},
+    InstructionData, ToAccountMetas,
+};
+use litesvm_token::spl_token;
+use program_tests::{assert_fails_with, event_authority, Harness, TxResult, VAULT_SUPPLY};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 1_000;
+
+fn distribute_batch(h: &mut Harness, recipient: Pubkey, amount: u64) -> TxResult {
+    let mut accounts = accounts::DistributeBatch {
+        state: h.state,
+        authority: h.authority.pubkey(),
+        vault_auth: h.vault_auth,
+        vault: h.vault,
+        mint: h.mint,
+        token_program: spl_token::ID,
+        event_authority: event_authority(),
+        program: airdrop0::ID,
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new(recipient, false));
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts,
+        data: instruction::DistributeBatch { amounts: vec![amount] }.data(),
+    };
+    h.send_as_authority(ix)
+}
+
+#[test]
+fn batch_counts_against_the_allocation() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (_, ata) = h.new_claimant();
+
+    distribute_batch(&mut h, ata, AMOUNT).expect("distribute_batch");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert_eq!((h.state().claimed_amount, h.state().claimed_count), (AMOUNT, 1));
+
+    // The vault holds the whole allocation, less what was just pushed
+    let result = distribute_batch(&mut h, ata, VAULT_SUPPLY - AMOUNT + 1);
+    assert_fails_with(result, "BatchExceedsUnreserved");
+}
+
+#[test]
+fn locked_config_refuses_batches() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (_, ata) = h.new_claimant();
+    h.send_as_authority(h.lock_config_ix()).expect("lock_config");
+
+    assert_fails_with(distribute_batch(&mut h, ata, AMOUNT), "ConfigLocked");
+    assert_eq!(h.token_balance(&ata), 0);
+}
+
+#[test]
+fn batch_below_low_water_mark_flags_a_refill() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (_, ata) = h.new_claimant();
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetLowWaterMark {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetLowWaterMark { low_water_mark: VAULT_SUPPLY - AMOUNT }.data(),
+    };
+    h.send_as_authority(ix).expect("set_low_water_mark");
+
+    distribute_batch(&mut h, ata, AMOUNT).expect("at the mark");
+    assert!(!h.state().needs_refill);
+    distribute_batch(&mut h, ata, 1).expect("under the mark");
+    assert!(h.state().needs_refill);
+}
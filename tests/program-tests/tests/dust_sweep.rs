@@ -0,0 +1,122 @@
+//! Dust sweeps: with a dust threshold set, leaves below it can't be claimed
+//! one by one, and `sweep_dust` pays a batch of them to the community pool
+//! while counting them as claimed.
+
+use airdrop0::{accounts, instruction, DustLeaf};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use merkle::MerkleTree;
+use program_tests::{assert_fails_with, event_authority, leaf_hash, Harness};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const THRESHOLD: u64 = 100;
+// Two dust leaves and one regular allocation
+const AMOUNTS: [u64; 3] = [10, 99, 1_000];
+
+fn set_dust_threshold_ix(h: &Harness, dust_threshold: u64, community_pool: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetDustThreshold {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetDustThreshold {
+            dust_threshold,
+            community_pool,
+        }
+        .data(),
+    }
+}
+
+fn sweep_dust_ix(h: &Harness, community_pool: Pubkey, leaves: Vec<DustLeaf>) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SweepDust {
+            state: h.state,
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            community_pool,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SweepDust { leaves }.data(),
+    }
+}
+
+fn dust_leaf(tree: &MerkleTree, wallets: &[Keypair], index: usize) -> DustLeaf {
+    DustLeaf {
+        index: index as u64,
+        wallet: wallets[index].pubkey(),
+        amount: AMOUNTS[index],
+        proof: tree.proof(index).unwrap(),
+    }
+}
+
+#[test]
+fn dust_goes_to_the_community_pool() {
+    let wallets: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let tree = MerkleTree::from_leaves(
+        wallets
+            .iter()
+            .enumerate()
+            .map(|(index, w)| leaf_hash(index as u64, &w.pubkey(), AMOUNTS[index]))
+            .collect(),
+    );
+    let mut h = Harness::new(tree.root(), 3, 1_000);
+    let (_, pool) = h.new_claimant();
+
+    let ix = set_dust_threshold_ix(&h, THRESHOLD, Pubkey::default());
+    assert_fails_with(h.send_as_authority(ix), "InvalidCommunityPool");
+    let ix = set_dust_threshold_ix(&h, THRESHOLD, pool);
+    h.send_as_authority(ix).expect("set_dust_threshold");
+
+    // Dust can't be claimed on its own
+    let (wallet, ata) = h.new_claimant_with(wallets[0].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNTS[0], &tree.proof(0).unwrap());
+    assert_fails_with(h.send(ix, &[&wallet]), "BelowDustThreshold");
+
+    // Nor can a regular allocation be swept
+    let ix = sweep_dust_ix(&h, pool, vec![dust_leaf(&tree, &wallets, 2)]);
+    assert_fails_with(h.send_as_authority(ix), "NotDust");
+
+    let leaves = vec![dust_leaf(&tree, &wallets, 0), dust_leaf(&tree, &wallets, 1)];
+    let ix = sweep_dust_ix(&h, pool, leaves);
+    h.send_as_authority(ix).expect("sweep_dust");
+    assert_eq!(h.token_balance(&pool), AMOUNTS[0] + AMOUNTS[1]);
+    let state = h.state();
+    assert_eq!((state.claimed_count, state.claimed_amount), (2, AMOUNTS[0] + AMOUNTS[1]));
+
+    // Swept leaves are spent
+    let ix = sweep_dust_ix(&h, pool, vec![dust_leaf(&tree, &wallets, 1)]);
+    assert_fails_with(h.send_as_authority(ix), "AlreadyClaimed");
+
+    let (wallet, ata) = h.new_claimant_with(wallets[2].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 2, AMOUNTS[2], &tree.proof(2).unwrap());
+    h.send(ix, &[&wallet]).expect("claim above the threshold");
+}
+
+#[test]
+fn sweeps_need_a_threshold() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNTS[0]), 1, 1_000);
+    let (_, pool) = h.new_claimant();
+    // Zero turns the threshold off but keeps the pool on record
+    let ix = set_dust_threshold_ix(&h, 0, pool);
+    h.send_as_authority(ix).expect("set_dust_threshold");
+    let leaf = DustLeaf {
+        index: 0,
+        wallet: wallet.pubkey(),
+        amount: AMOUNTS[0],
+        proof: vec![],
+    };
+    let ix = sweep_dust_ix(&h, pool, vec![leaf]);
+    assert_fails_with(h.send_as_authority(ix), "DustSweepDisabled");
+}
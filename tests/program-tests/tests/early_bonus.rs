@@ -0,0 +1,134 @@
+//! Early-claim bonus: claims before the cut-off are topped up from the bonus
+//! reserve, capped at what the reserve holds, without touching the claim
+//! vault; the reserve stays locked until the bonus window ends.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use litesvm_token::{spl_token, MintTo};
+use program_tests::{event_authority, leaf_hash, Harness, VAULT_SUPPLY};
+use solana_sdk::{clock::Clock, pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 10_000;
+const BONUS_BPS: u16 = 2_000;
+const RESERVE: u64 = 1_500; // less than the 2_000 the bonus schedule asks for
+const BONUS_WINDOW: i64 = 600;
+
+fn init_bonus_vault(h: &mut Harness) -> Pubkey {
+    let bonus_vault =
+        Pubkey::find_program_address(&[b"bonus_vault", h.state.as_ref()], &airdrop0::ID).0;
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::InitBonusVault {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            bonus_vault,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitBonusVault {}.data(),
+    };
+    h.send_as_authority(ix).expect("init_bonus_vault");
+    bonus_vault
+}
+
+fn set_early_bonus_ix(h: &Harness, bonus_bps: u16, end_ts: i64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetEarlyBonus {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetEarlyBonus { bonus_bps, end_ts }.data(),
+    }
+}
+
+fn withdraw_bonus_reserve_ix(h: &Harness, bonus_vault: Pubkey, destination: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::WithdrawBonusReserve {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            bonus_vault,
+            destination,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::WithdrawBonusReserve { amount: h.token_balance(&bonus_vault) }.data(),
+    }
+}
+
+#[test]
+fn early_claim_bonus_is_capped_by_the_reserve() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let bonus_vault = init_bonus_vault(&mut h);
+    let authority = h.authority.insecure_clone();
+    MintTo::new(&mut h.svm, &authority, &h.mint, &bonus_vault, RESERVE)
+        .send()
+        .unwrap();
+    let ix = set_early_bonus_ix(&h, BONUS_BPS, h.now() + BONUS_WINDOW);
+    h.send_as_authority(ix).expect("set_early_bonus");
+
+    // Omitting the reserve while a bonus is due is rejected
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    let failed = h.send(ix, &[&wallet]).expect_err("missing bonus vault");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("InvalidBonusVault")));
+
+    h.bonus_vault = Some(bonus_vault);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("early claim");
+
+    // The bonus shrinks to what the reserve holds; the claim vault pays only the base
+    assert_eq!(h.token_balance(&ata), AMOUNT + RESERVE);
+    assert_eq!(h.token_balance(&bonus_vault), 0);
+    assert_eq!(h.token_balance(&h.vault), VAULT_SUPPLY - AMOUNT);
+}
+
+#[test]
+fn bonus_reserve_is_locked_until_the_window_ends() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let bonus_vault = init_bonus_vault(&mut h);
+    let authority = h.authority.insecure_clone();
+    MintTo::new(&mut h.svm, &authority, &h.mint, &bonus_vault, RESERVE)
+        .send()
+        .unwrap();
+    let ix = set_early_bonus_ix(&h, BONUS_BPS, h.now() + BONUS_WINDOW);
+    h.send_as_authority(ix).expect("set_early_bonus");
+
+    let (_, destination) = h.new_claimant();
+    let ix = withdraw_bonus_reserve_ix(&h, bonus_vault, destination);
+    let failed = h.send_as_authority(ix).expect_err("bonus window open");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("BonusWindowActive")));
+
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += BONUS_WINDOW;
+    h.svm.set_sysvar(&clock);
+
+    let ix = withdraw_bonus_reserve_ix(&h, bonus_vault, destination);
+    h.send_as_authority(ix).expect("withdraw_bonus_reserve");
+    assert_eq!(h.token_balance(&destination), RESERVE);
+}
+
+#[test]
+fn early_bonus_is_capped() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    init_bonus_vault(&mut h);
+    let ix = set_early_bonus_ix(&h, 10_001, h.now() + BONUS_WINDOW);
+    let failed = h.send_as_authority(ix).expect_err("bonus above cap");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("BonusTooHigh")));
+}
@@ -0,0 +1,61 @@
+//! `emit_unclaimed` pages out the round's unclaimed indices as bitmap events.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use merkle::MerkleTree;
+use merkledrop_interface::{MerkledropEvent, UnclaimedIndices};
+use program_tests::{assert_fails_with, event_authority, Harness, TxResult};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+
+fn emit_unclaimed(h: &mut Harness, range_start: u64, range_end: u64) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::EmitUnclaimed {
+            state: h.state,
+            cranker: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::EmitUnclaimed { range_start, range_end }.data(),
+    };
+    let (cranker, _) = h.new_claimant();
+    h.send(ix, &[&cranker])
+}
+
+#[test]
+fn bitmap_lists_unclaimed_indices() {
+    let wallets: Vec<Keypair> = (0..10).map(|_| Keypair::new()).collect();
+    let tree = MerkleTree::from_leaves(
+        wallets
+            .iter()
+            .enumerate()
+            .map(|(index, wallet)| merkle::leaf(index as u64, &wallet.pubkey().to_bytes(), AMOUNT))
+            .collect(),
+    );
+    let mut h = Harness::new(tree.root(), 10, 1_000);
+    for index in [1usize, 8] {
+        let (wallet, ata) = h.new_claimant_with(wallets[index].insecure_clone());
+        let ix = h.claim_ix(&wallet.pubkey(), &ata, index as u64, AMOUNT, &tree.proof(index).unwrap());
+        h.send(ix, &[&wallet]).expect("claim");
+    }
+
+    assert_fails_with(emit_unclaimed(&mut h, 4, 4), "InvalidRange");
+    assert_fails_with(emit_unclaimed(&mut h, 0, 11), "InvalidRange");
+
+    let meta = emit_unclaimed(&mut h, 0, 10).expect("emit_unclaimed");
+    let page: UnclaimedIndices = meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .find_map(|inner| match MerkledropEvent::decode_cpi(&inner.instruction.data) {
+            Some(MerkledropEvent::UnclaimedIndices(page)) => Some(page),
+            _ => None,
+        })
+        .expect("UnclaimedIndices event");
+    assert_eq!((page.range_start, page.range_end, page.unclaimed_count), (0, 10, 8));
+    // Every index but 1 and 8
+    assert_eq!(page.unclaimed, vec![0b1111_1101, 0b10]);
+}
@@ -0,0 +1,270 @@
+//! Recurring distributions: each epoch has its own root and claim ledger, so
+//! the same index can be paid once per epoch from the shared vault.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        system_program,
+    },
+    InstructionData, ToAccountMetas,
+};
+use program_tests::{
+    assert_fails_with, badge_account, event_authority, family_claim, leaf_hash, pad_proof,
+    wallet_claims, Harness, CLAIM_DURATION, TOKEN_2022_ID,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 5_000;
+
+fn epoch_root(h: &Harness, epoch: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"epoch", h.state.as_ref(), &epoch.to_le_bytes()],
+        &airdrop0::ID,
+    )
+    .0
+}
+
+fn create_epoch_ix(h: &Harness, epoch: u64, merkle_root: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::CreateEpoch {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            epoch_root: epoch_root(h, epoch),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CreateEpoch {
+            epoch,
+            merkle_root,
+            total_claims: 1,
+            claim_start_ts: h.now() - 60,
+            claim_duration: CLAIM_DURATION,
+            total_allocation: AMOUNT,
+        }
+        .data(),
+    }
+}
+
+fn claim_epoch_ix(h: &Harness, epoch: u64, wallet: &Pubkey, user_ata: &Pubkey) -> Instruction {
+    let (proof, proof_len) = pad_proof(&[]);
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimEpoch {
+            state: h.state,
+            epoch_root: epoch_root(h, epoch),
+            wallet: *wallet,
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: litesvm_token::spl_token::ID,
+            fee_account: None,
+            stats: None,
+            wallet_claims: h.track_wallet_claims.then(|| wallet_claims(&h.state, wallet)),
+            family_claim: h
+                .family_id
+                .map(|family_id| family_claim(&airdrop0::ID, &family_id, wallet)),
+            gate_token_account: None,
+            gate_metadata: None,
+            badge_mint: h.badge_mint,
+            badge_account: h.badge_mint.map(|badge_mint| badge_account(wallet, &badge_mint)),
+            token_2022_program: h.badge_mint.map(|_| TOKEN_2022_ID),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimEpoch {
+            epoch,
+            index: 0,
+            amount: AMOUNT,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    }
+}
+
+#[test]
+fn each_epoch_pays_an_index_once() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let root = leaf_hash(0, &wallet.pubkey(), AMOUNT);
+
+    for epoch in [1, 2] {
+        let ix = create_epoch_ix(&h, epoch, root);
+        h.send_as_authority(ix).expect("create_epoch");
+
+        let ix = claim_epoch_ix(&h, epoch, &wallet.pubkey(), &ata);
+        h.send(ix, &[&wallet]).expect("claim_epoch");
+        assert_eq!(h.token_balance(&ata), AMOUNT * epoch);
+
+        let ix = claim_epoch_ix(&h, epoch, &wallet.pubkey(), &ata);
+        let failed = h.send(ix, &[&wallet]).expect_err("second claim in the epoch");
+        assert!(failed.meta.logs.iter().any(|log| log.contains("AlreadyClaimed")));
+    }
+}
+
+#[test]
+fn epoch_claims_count_toward_the_wallet_cap() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let root = leaf_hash(0, &wallet.pubkey(), AMOUNT);
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetWalletCap {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetWalletCap { wallet_cap: AMOUNT }.data(),
+    };
+    h.send_as_authority(ix).expect("set_wallet_cap");
+    let ix = create_epoch_ix(&h, 1, root);
+    h.send_as_authority(ix).expect("create_epoch");
+    let ix = claim_epoch_ix(&h, 1, &wallet.pubkey(), &ata);
+    let failed = h.send(ix, &[&wallet]).expect_err("claim without wallet_claims");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("WalletClaimsMissing")));
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::InitWalletClaims {
+            state: h.state,
+            payer: wallet.pubkey(),
+            wallet_claims: wallet_claims(&h.state, &wallet.pubkey()),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitWalletClaims { wallet: wallet.pubkey() }.data(),
+    };
+    h.send(ix, &[&wallet]).expect("init_wallet_claims");
+    h.track_wallet_claims = true;
+
+    let ix = claim_epoch_ix(&h, 1, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("first epoch, at the cap");
+    let ix = create_epoch_ix(&h, 2, root);
+    h.send_as_authority(ix).expect("create_epoch");
+    let ix = claim_epoch_ix(&h, 2, &wallet.pubkey(), &ata);
+    let failed = h.send(ix, &[&wallet]).expect_err("second epoch past the cap");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("WalletCapExceeded")));
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+}
+
+#[test]
+fn epoch_claims_need_the_family_claim() {
+    const FAMILY_ID: [u8; 32] = [7; 32];
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let sibling = Pubkey::new_unique();
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetFamily {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetFamily { family_id: FAMILY_ID, siblings: vec![sibling] }.data(),
+    };
+    h.send_as_authority(ix).expect("set_family");
+    let ix = create_epoch_ix(&h, 1, leaf_hash(0, &wallet.pubkey(), AMOUNT));
+    h.send_as_authority(ix).expect("create_epoch");
+    let ix = claim_epoch_ix(&h, 1, &wallet.pubkey(), &ata);
+    let failed = h.send(ix, &[&wallet]).expect_err("claim without a family claim");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("FamilyClaimMissing")));
+
+    let mut accounts = accounts::JoinFamily {
+        state: h.state,
+        wallet: wallet.pubkey(),
+        family_claim: family_claim(&airdrop0::ID, &FAMILY_ID, &wallet.pubkey()),
+        system_program: system_program::ID,
+        event_authority: event_authority(),
+        program: airdrop0::ID,
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new(family_claim(&sibling, &FAMILY_ID, &wallet.pubkey()), false));
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts,
+        data: instruction::JoinFamily {}.data(),
+    };
+    h.send(ix, &[&wallet]).expect("join_family");
+    h.family_id = Some(FAMILY_ID);
+    let ix = claim_epoch_ix(&h, 1, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("claim after joining");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+}
+
+#[test]
+fn epoch_claims_mint_the_claim_badge() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let (badge_mint, ix) = h.init_claim_badge_ix();
+    h.send_as_authority(ix).expect("init_claim_badge");
+    let ix = create_epoch_ix(&h, 1, leaf_hash(0, &wallet.pubkey(), AMOUNT));
+    h.send_as_authority(ix).expect("create_epoch");
+
+    let ix = claim_epoch_ix(&h, 1, &wallet.pubkey(), &ata);
+    let failed = h.send(ix, &[&wallet]).expect_err("claim without the badge accounts");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("ClaimBadgeMissing")));
+
+    let badge = h.create_badge_account(&wallet, &badge_mint);
+    h.badge_mint = Some(badge_mint);
+    let ix = claim_epoch_ix(&h, 1, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("claim_epoch");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert_eq!(h.badge_balance(&badge), 1);
+}
+
+#[test]
+fn epoch_claims_respect_campaign_switches() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let ix = create_epoch_ix(&h, 1, leaf_hash(0, &wallet.pubkey(), AMOUNT));
+    h.send_as_authority(ix).expect("create_epoch");
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::FreezeIndex {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::FreezeIndex { index: 0 }.data(),
+    };
+    h.send_as_authority(ix).expect("freeze_index");
+    let ix = claim_epoch_ix(&h, 1, &wallet.pubkey(), &ata);
+    assert_fails_with(h.send(ix, &[&wallet]), "IndexFrozen");
+
+    // A campaign homed on another chain pays only against a VAA
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetWormholeEmitter {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetWormholeEmitter { emitter_chain: 2, emitter: [9; 32] }.data(),
+    };
+    h.send_as_authority(ix).expect("set_wormhole_emitter");
+    let ix = create_epoch_ix(&h, 1, leaf_hash(0, &wallet.pubkey(), AMOUNT));
+    h.send_as_authority(ix).expect("create_epoch");
+    let ix = claim_epoch_ix(&h, 1, &wallet.pubkey(), &ata);
+    assert_fails_with(h.send(ix, &[&wallet]), "CrossChainClaimRequired");
+    assert_eq!(h.token_balance(&ata), 0);
+}
@@ -0,0 +1,146 @@
+//! Two-phase claims: in escrow mode `claim` is refused, `reserve_claim` parks
+//! the entitlement and `withdraw` only pays once the dispute period is over.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use program_tests::{
+    assert_fails_with, badge_account, event_authority, leaf_hash, pad_proof, Harness,
+    SNAPSHOT_HASH, TOKEN_2022_ID,
+};
+use solana_sdk::{clock::Clock, pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 2_500;
+const DISPUTE_PERIOD: i64 = 86_400;
+
+fn escrow(index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"escrow", SNAPSHOT_HASH.as_ref(), &index.to_le_bytes()],
+        &airdrop0::ID,
+    )
+    .0
+}
+
+fn set_dispute_period_ix(h: &Harness, dispute_period: i64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetDisputePeriod {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetDisputePeriod { dispute_period }.data(),
+    }
+}
+
+fn reserve_claim_ix(h: &Harness, wallet: &Pubkey) -> Instruction {
+    let (proof, proof_len) = pad_proof(&[]);
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ReserveClaim {
+            state: h.state,
+            wallet: *wallet,
+            escrow: escrow(0),
+            system_program: system_program::ID,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ReserveClaim {
+            index: 0,
+            amount: AMOUNT,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    }
+}
+
+fn withdraw_ix(h: &Harness, wallet: &Pubkey, user_ata: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::Withdraw {
+            state: h.state,
+            escrow: escrow(0),
+            wallet: *wallet,
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: litesvm_token::spl_token::ID,
+            fee_account: None,
+            bonus_vault: None,
+            badge_mint: h.badge_mint,
+            badge_account: h.badge_mint.map(|badge_mint| badge_account(wallet, &badge_mint)),
+            token_2022_program: h.badge_mint.map(|_| TOKEN_2022_ID),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Withdraw {}.data(),
+    }
+}
+
+#[test]
+fn reserved_claim_pays_only_after_dispute_period() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+    let ix = set_dispute_period_ix(&h, DISPUTE_PERIOD);
+    h.send_as_authority(ix).expect("set_dispute_period");
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "EscrowRequired");
+
+    let ix = reserve_claim_ix(&h, &wallet.pubkey());
+    h.send(ix, &[&wallet]).expect("reserve_claim");
+    assert_eq!(h.token_balance(&ata), 0);
+
+    let ix = withdraw_ix(&h, &wallet.pubkey(), &ata);
+    assert_fails_with(h.send(ix, &[&wallet]), "DisputePeriodActive");
+
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += DISPUTE_PERIOD;
+    h.svm.set_sysvar(&clock);
+
+    let ix = withdraw_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("withdraw");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert!(h.svm.get_account(&escrow(0)).map_or(true, |a| a.lamports == 0));
+}
+
+#[test]
+fn withdraw_mints_the_claim_badge() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+    let ix = set_dispute_period_ix(&h, DISPUTE_PERIOD);
+    h.send_as_authority(ix).expect("set_dispute_period");
+    let (badge_mint, ix) = h.init_claim_badge_ix();
+    h.send_as_authority(ix).expect("init_claim_badge");
+
+    let ix = reserve_claim_ix(&h, &wallet.pubkey());
+    h.send(ix, &[&wallet]).expect("reserve_claim");
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += DISPUTE_PERIOD;
+    h.svm.set_sysvar(&clock);
+
+    let ix = withdraw_ix(&h, &wallet.pubkey(), &ata);
+    assert_fails_with(h.send(ix, &[&wallet]), "ClaimBadgeMissing");
+    let badge = h.create_badge_account(&wallet, &badge_mint);
+    h.badge_mint = Some(badge_mint);
+    let ix = withdraw_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("withdraw");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert_eq!(h.badge_balance(&badge), 1);
+}
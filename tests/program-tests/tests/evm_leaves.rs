@@ -0,0 +1,67 @@
+//! One tree shared with a Solidity distributor: EVM-address and Solana
+//! leaves side by side, claimable on Solana once the campaign switches to
+//! `abi.encodePacked` leaves.
+
+use airdrop0::{accounts, instruction, LeafEncoding};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use merkle::MerkleTree;
+use program_tests::{assert_fails_with, event_authority, Harness};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+const EVM_ACCOUNT: [u8; 20] = [0xab; 20];
+
+fn set_leaf_encoding_ix(h: &Harness, encoding: LeafEncoding) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetLeafEncoding {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetLeafEncoding { encoding }.data(),
+    }
+}
+
+#[test]
+fn evm_leaf_is_abi_encode_packed() {
+    let mut preimage = [0u8; 32 + 20 + 32];
+    preimage[24..32].copy_from_slice(&7u64.to_be_bytes());
+    preimage[32..52].copy_from_slice(&EVM_ACCOUNT);
+    preimage[76..].copy_from_slice(&AMOUNT.to_be_bytes());
+    assert_eq!(merkle::evm_leaf(7, &EVM_ACCOUNT, AMOUNT), merkle::hashv(&[&preimage]));
+}
+
+#[test]
+fn shared_tree_claims_after_switching_encoding() {
+    let wallet = Keypair::new();
+    let tree = MerkleTree::from_leaves(vec![
+        merkle::evm_leaf(0, &EVM_ACCOUNT, AMOUNT),
+        merkle::evm_leaf(1, wallet.pubkey().as_ref(), AMOUNT),
+    ]);
+    let mut h = Harness::new(tree.root(), 2, 2);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let proof = tree.proof(1).unwrap();
+
+    // Native hashing doesn't reproduce the packed leaf
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 1, AMOUNT, &proof);
+    assert_fails_with(h.send(ix, &[&wallet]), "InvalidProof");
+
+    let ix = set_leaf_encoding_ix(&h, LeafEncoding::EvmPacked);
+    assert_fails_with(h.send_as_authority(ix), "ClaimsNotPaused");
+
+    let state = h.state();
+    let ix = h.close_airdrop_ix();
+    h.send_as_authority(ix).expect("close_airdrop");
+    let ix = set_leaf_encoding_ix(&h, LeafEncoding::EvmPacked);
+    h.send_as_authority(ix).expect("set_leaf_encoding");
+    let ix = h.update_claim_window_ix(state.claim_start_ts, state.claim_duration);
+    h.send_as_authority(ix).expect("update_claim_window");
+    assert_eq!(h.state().leaf_encoding, LeafEncoding::EvmPacked);
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 1, AMOUNT, &proof);
+    h.send(ix, &[&wallet]).expect("claim");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+}
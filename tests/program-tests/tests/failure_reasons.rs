@@ -0,0 +1,26 @@
+//! Claims fail with the specific error a claim UI can act on.
+
+use litesvm_token::{CreateAssociatedTokenAccount, CreateMint};
+use program_tests::{assert_fails_with, leaf_hash, Harness, CLAIM_DURATION};
+use solana_sdk::signature::Signer;
+
+const AMOUNT: u64 = 1_000;
+
+#[test]
+fn window_not_started_and_wrong_mint_are_distinct() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let other_mint = CreateMint::new(&mut h.svm, &wallet).decimals(0).send().unwrap();
+    let other_ata = CreateAssociatedTokenAccount::new(&mut h.svm, &wallet, &other_mint)
+        .send()
+        .unwrap();
+    let ix = h.claim_ix(&wallet.pubkey(), &other_ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "WrongMint");
+
+    let ix = h.update_claim_window_ix(h.now() + 600, CLAIM_DURATION);
+    h.send_as_authority(ix).expect("update_claim_window");
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "WindowNotStarted");
+}
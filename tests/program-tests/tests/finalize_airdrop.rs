@@ -0,0 +1,68 @@
+//! `finalize_airdrop` records a finished round once, in an account that
+//! outlives it.
+
+use airdrop0::{accounts, instruction, Summary};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use program_tests::{
+    assert_fails_with, event_authority, leaf_hash, Harness, TxResult, CLAIM_DURATION,
+    SNAPSHOT_HASH, VAULT_SUPPLY,
+};
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+
+fn summary_account() -> Pubkey {
+    Pubkey::find_program_address(&[b"summary", SNAPSHOT_HASH.as_ref()], &airdrop0::ID).0
+}
+
+fn finalize_airdrop(h: &mut Harness, payer: &Keypair) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::FinalizeAirdrop {
+            state: h.state,
+            payer: payer.pubkey(),
+            summary: summary_account(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::FinalizeAirdrop {}.data(),
+    };
+    h.send(ix, &[payer])
+}
+
+#[test]
+fn finalize_records_round_once() {
+    let wallet = Keypair::new();
+    let root = leaf_hash(0, &wallet.pubkey(), AMOUNT);
+    let mut h = Harness::new(root, 2, 2);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let (cranker, _) = h.new_claimant();
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+    assert_fails_with(finalize_airdrop(&mut h, &cranker), "CampaignStillActive");
+
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += CLAIM_DURATION;
+    h.svm.set_sysvar(&clock);
+    finalize_airdrop(&mut h, &cranker).expect("finalize_airdrop");
+
+    let data = h.svm.get_account(&summary_account()).unwrap().data;
+    let summary = Summary::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(summary.merkle_root, root);
+    assert_eq!((summary.claimed_count, summary.claimed_amount), (1, AMOUNT));
+    assert_eq!(summary.unclaimed_amount, VAULT_SUPPLY - AMOUNT);
+    assert_eq!(summary.finalized_ts, clock.unix_timestamp);
+
+    // The summary is written once
+    assert!(finalize_airdrop(&mut h, &cranker).is_err());
+}
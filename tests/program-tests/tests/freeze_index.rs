@@ -0,0 +1,65 @@
+//! Incident response: a frozen index can't be claimed while the rest of the
+//! campaign keeps working, and unfreezing restores it.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use program_tests::{event_authority, fold_proof, leaf_hash, Harness};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+
+fn freeze_ix(h: &Harness, index: u64, frozen: bool) -> Instruction {
+    let (accounts, data) = if frozen {
+        (
+            accounts::FreezeIndex {
+                state: h.state,
+                authority: h.authority.pubkey(),
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            instruction::FreezeIndex { index }.data(),
+        )
+    } else {
+        (
+            accounts::UnfreezeIndex {
+                state: h.state,
+                authority: h.authority.pubkey(),
+                event_authority: event_authority(),
+                program: airdrop0::ID,
+            }
+            .to_account_metas(None),
+            instruction::UnfreezeIndex { index }.data(),
+        )
+    };
+    Instruction { program_id: airdrop0::ID, accounts, data }
+}
+
+#[test]
+fn frozen_index_is_blocked_until_unfrozen() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let alice_leaf = leaf_hash(0, &alice.pubkey(), AMOUNT);
+    let bob_leaf = leaf_hash(1, &bob.pubkey(), AMOUNT);
+    let root = fold_proof(alice_leaf, &[bob_leaf]);
+
+    let mut h = Harness::new(root, 2, 2);
+    let (alice, alice_ata) = h.new_claimant_with(alice);
+    let (bob, bob_ata) = h.new_claimant_with(bob);
+
+    let ix = freeze_ix(&h, 0, true);
+    h.send_as_authority(ix).expect("freeze_index");
+
+    let ix = h.claim_ix(&alice.pubkey(), &alice_ata, 0, AMOUNT, &[bob_leaf]);
+    let failed = h.send(ix, &[&alice]).expect_err("frozen index");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("IndexFrozen")));
+
+    // Everyone else is unaffected
+    let ix = h.claim_ix(&bob.pubkey(), &bob_ata, 1, AMOUNT, &[alice_leaf]);
+    h.send(ix, &[&bob]).expect("claim of another index");
+
+    let ix = freeze_ix(&h, 0, false);
+    h.send_as_authority(ix).expect("unfreeze_index");
+    let ix = h.claim_ix(&alice.pubkey(), &alice_ata, 0, AMOUNT, &[bob_leaf]);
+    h.send(ix, &[&alice]).expect("claim after unfreeze");
+    assert_eq!(h.token_balance(&alice_ata), AMOUNT);
+}
@@ -0,0 +1,31 @@
+//! A claim into a frozen token account fails with `RecipientAccountFrozen`
+//! rather than the token program's generic error.
+
+use anchor_lang::solana_program::program_pack::Pack;
+use litesvm_token::spl_token::state::{Account as TokenAccount, AccountState};
+use program_tests::{leaf_hash, Harness};
+use solana_sdk::signature::Signer;
+
+const AMOUNT: u64 = 1_000;
+
+#[test]
+fn claim_into_frozen_account_reports_recipient_frozen() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    // Freeze in place, as a freeze authority would
+    let mut account = h.svm.get_account(&ata).unwrap();
+    let mut token = TokenAccount::unpack(&account.data).unwrap();
+    token.state = AccountState::Frozen;
+    TokenAccount::pack(token, &mut account.data).unwrap();
+    h.svm.set_account(ata, account).unwrap();
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    let failed = h.send(ix, &[&wallet]).expect_err("frozen recipient");
+    assert!(failed
+        .meta
+        .logs
+        .iter()
+        .any(|log| log.contains("RecipientAccountFrozen")));
+}
@@ -0,0 +1,156 @@
+//! Claims fail over to a registered extra vault when the main vault can't
+//! cover them, on `claim` and on the other payout paths alike.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::{AccountMeta, Instruction}, program_pack::Pack, system_program},
+    InstructionData, ToAccountMetas,
+};
+use litesvm_token::spl_token::state::Account as TokenAccount;
+use program_tests::{event_authority, leaf_hash, pad_proof, Harness, SNAPSHOT_HASH};
+use solana_sdk::{clock::Clock, pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 1_000;
+const DISPUTE_PERIOD: i64 = 86_400;
+
+// Copies the main vault's token account to `address` with `amount` tokens;
+// same mint and vault authority, as an operator-created vault would have
+fn put_vault(h: &mut Harness, address: Pubkey, amount: u64) {
+    let mut account = h.svm.get_account(&h.vault).unwrap();
+    let mut token = TokenAccount::unpack(&account.data).unwrap();
+    token.amount = amount;
+    TokenAccount::pack(token, &mut account.data).unwrap();
+    h.svm.set_account(address, account).unwrap();
+}
+
+fn add_funding_vault(h: &mut Harness, vault: Pubkey) {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::AddFundingVault {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            vault_auth: h.vault_auth,
+            vault,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AddFundingVault {}.data(),
+    };
+    h.send_as_authority(ix).expect("add_funding_vault");
+}
+
+#[test]
+fn claim_draws_from_first_extra_vault_that_covers_it() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let (short, funded) = (Pubkey::new_unique(), Pubkey::new_unique());
+    put_vault(&mut h, short, AMOUNT - 1);
+    put_vault(&mut h, funded, 5 * AMOUNT);
+    for vault in [short, funded] {
+        add_funding_vault(&mut h, vault);
+    }
+
+    // Drain the main vault below the claim
+    let vault = h.vault;
+    put_vault(&mut h, vault, AMOUNT / 2);
+
+    let mut ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    ix.accounts.push(AccountMeta::new(short, false));
+    ix.accounts.push(AccountMeta::new(funded, false));
+    h.send(ix, &[&wallet]).expect("claim");
+
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert_eq!(h.token_balance(&h.vault), AMOUNT / 2);
+    assert_eq!(h.token_balance(&short), AMOUNT - 1);
+    assert_eq!(h.token_balance(&funded), 4 * AMOUNT);
+}
+
+#[test]
+fn escrow_withdrawal_fails_over_too() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetDisputePeriod {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetDisputePeriod { dispute_period: DISPUTE_PERIOD }.data(),
+    };
+    h.send_as_authority(ix).expect("set_dispute_period");
+
+    let escrow = Pubkey::find_program_address(
+        &[b"escrow", SNAPSHOT_HASH.as_ref(), &0u64.to_le_bytes()],
+        &airdrop0::ID,
+    )
+    .0;
+    let (proof, proof_len) = pad_proof(&[]);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ReserveClaim {
+            state: h.state,
+            wallet: wallet.pubkey(),
+            escrow,
+            system_program: system_program::ID,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ReserveClaim {
+            index: 0,
+            amount: AMOUNT,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    };
+    h.send(ix, &[&wallet]).expect("reserve_claim");
+
+    let funded = Pubkey::new_unique();
+    put_vault(&mut h, funded, 2 * AMOUNT);
+    add_funding_vault(&mut h, funded);
+    let vault = h.vault;
+    put_vault(&mut h, vault, AMOUNT / 2);
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += DISPUTE_PERIOD;
+    h.svm.set_sysvar(&clock);
+
+    let mut ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::Withdraw {
+            state: h.state,
+            escrow,
+            wallet: wallet.pubkey(),
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: ata,
+            mint: h.mint,
+            token_program: litesvm_token::spl_token::ID,
+            fee_account: None,
+            bonus_vault: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Withdraw {}.data(),
+    };
+    ix.accounts.push(AccountMeta::new(funded, false));
+    h.send(ix, &[&wallet]).expect("withdraw");
+
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert_eq!(h.token_balance(&h.vault), AMOUNT / 2);
+    assert_eq!(h.token_balance(&funded), AMOUNT);
+}
@@ -0,0 +1,298 @@
+//! `merkledrop-interface` must stay byte-compatible with the program: the
+//! `State` mirror re-encodes to the same bytes, every event mirror carries the
+//! program's discriminator, emitted events decode through it, and the
+//! published seeds and limits agree.
+
+use anchor_lang::{AnchorSerialize, Discriminator};
+use merkledrop_interface::{Event, MerkledropEvent};
+use program_tests::{leaf_hash, Harness};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+
+fn encode(value: &impl AnchorSerialize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.serialize(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn state_mirror_matches_program_layout() {
+    let h = Harness::new([0; 32], 1, 1_000);
+    let data = h.svm.get_account(&h.state).unwrap().data;
+    let mirror = merkledrop_interface::State::decode(&data).expect("decode");
+
+    assert_eq!(encode(&mirror), encode(&h.state()));
+    assert_eq!(&merkledrop_interface::STATE_DISCRIMINATOR[..], airdrop0::State::DISCRIMINATOR);
+    assert_eq!(merkledrop_interface::pda::state().0, h.state);
+    assert_eq!(merkledrop_interface::pda::vault_authority(&mirror.snapshot_hash).0, h.vault_auth);
+}
+
+#[test]
+fn constants_match() {
+    macro_rules! check {
+        ($($constant:ident),* $(,)?) => {$(
+            assert_eq!(merkledrop_interface::$constant, airdrop0::$constant, stringify!($constant));
+        )*};
+    }
+    check!(
+        STATE_SEED,
+        VAULT_SEED,
+        WALLET_CLAIMS_SEED,
+        RELAYER_SEED,
+        METADATA_SEED,
+        STATS_SEED,
+        TREASURY_SEED,
+        TREASURY_TOKENS_SEED,
+        BONUS_VAULT_SEED,
+        BADGE_MINT_SEED,
+        LEDGER_AUTHORITY_SEED,
+        COHORT_SEED,
+        ESCROW_SEED,
+        VESTING_SEED,
+        SUMMARY_SEED,
+        ROLLOVER_SEED,
+        EPOCH_SEED,
+        RAFFLE_SEED,
+        RAFFLE_PRIZE_SEED,
+        UNWRAP_SEED,
+        TICKET_SEED,
+        CONFIG_SEED,
+        VOID_SEED,
+        BUILD_INFO_SEED,
+        ROOT_SCHEDULE_SEED,
+        FAMILY_CLAIM_SEED,
+        LEGACY_CLAIMS_SEED,
+        CALLER_AUTHORITY_SEED,
+        LEAF_FORMAT_VERSION,
+        MAX_CLAIM_CAPACITY,
+        MAX_INIT_CLAIM_CAPACITY,
+        MAX_PROOF_LEN,
+        MAX_UNCLAIMED_PAGE,
+    );
+}
+
+#[test]
+fn claim_error_codes_match() {
+    use merkledrop_interface::ClaimError;
+
+    macro_rules! check {
+        ($($error:ident),* $(,)?) => {$(
+            assert_eq!(ClaimError::$error.code(), u32::from(airdrop0::ErrorCode::$error), stringify!($error));
+            assert_eq!(ClaimError::from_code(ClaimError::$error.code()), Some(ClaimError::$error));
+        )*};
+    }
+    check!(
+        AlreadyClaimed,
+        InvalidProof,
+        InvalidIndex,
+        ClaimClosed,
+        CapacityExceeded,
+        StateNotMigrated,
+        ProofTooLong,
+        TermsNotAccepted,
+        GateNotSatisfied,
+        AllocationExceeded,
+        VaultNeedsRefill,
+        RecipientAccountFrozen,
+        InvalidFeeAccount,
+        EscrowRequired,
+        IndexFrozen,
+        StatsAccountMissing,
+        CompressedLedgerRequired,
+        WindowNotStarted,
+        WindowEnded,
+        WrongMint,
+        CrossChainClaimRequired,
+        ClaimBadgeMissing,
+        WalletClaimsMissing,
+        WalletCapExceeded,
+        UsdPriceRequired,
+        StalePrice,
+        PriceTooUncertain,
+        NotFunded,
+        TicketRequired,
+        RevealTooEarly,
+        BelowDustThreshold,
+        JoinedSiblingCampaign,
+        FamilyClaimMissing,
+        LeafMismatch,
+        VestingHandoffRequired,
+        LeavesPastClaimBitmap,
+    );
+    assert_eq!(
+        ClaimError::TokenAccountMissing.code(),
+        u32::from(anchor_lang::error::ErrorCode::AccountNotInitialized)
+    );
+    assert_eq!(ClaimError::from_code(u32::from(airdrop0::ErrorCode::Unauthorized)), None);
+}
+
+#[test]
+fn event_discriminators_match() {
+    macro_rules! check {
+        ($($event:ident),* $(,)?) => {$(
+            assert_eq!(
+                &<merkledrop_interface::$event as Event>::DISCRIMINATOR[..],
+                <airdrop0::$event as Discriminator>::DISCRIMINATOR,
+                stringify!($event)
+            );
+        )*};
+    }
+    check!(
+        AirdropInitialized,
+        Claimed,
+        CpiClaimed,
+        DomainClaimed,
+        BatchDistributed,
+        AirdropClosed,
+        CrankBountyPaid,
+        ConfigLocked,
+        AuthorityRenounced,
+        VaultAuthorityRotated,
+        FundingVaultUpdated,
+        CrankBountyUpdated,
+        CampaignMetadataUpdated,
+        TermsHashUpdated,
+        ClaimGateUpdated,
+        LeafEncodingUpdated,
+        EpochCreated,
+        EpochClaimed,
+        RaffleRequested,
+        RaffleSettled,
+        RafflePrizeClaimed,
+        VaultBelowLowWaterMark,
+        ClaimReserved,
+        VestingStarted,
+        VestedReleased,
+        EscrowWithdrawn,
+        DisputePeriodUpdated,
+        LatePenaltyUpdated,
+        EarlyBonusUpdated,
+        BonusReserveWithdrawn,
+        FeesWithdrawn,
+        OperatorFeeUpdated,
+        IndexFreezeUpdated,
+        LowWaterMarkUpdated,
+        WalletCapUpdated,
+        CloseGraceUpdated,
+        ClaimsResumed,
+        ClaimWindowUpdated,
+        MaxStartLeadUpdated,
+        MerkleRootUpdated,
+        StateResized,
+        StateMigrated,
+        CampaignRolledOver,
+        UnclaimedIndices,
+        CompressedLedgerInitialized,
+        WormholeEmitterUpdated,
+        VaaClaimRedeemed,
+        ClaimBadgeEnabled,
+        RelayerFeeUpdated,
+        RelayerUpdated,
+        RelayedClaim,
+        UsdPricingUpdated,
+        UsdClaimPriced,
+        CohortUpdated,
+        CohortClaimed,
+        Finalized,
+        StatusChanged,
+        RevealDelayUpdated,
+        ClaimCommitted,
+        ConfigUpdated,
+        CreationFeePaid,
+        ConfigChanged,
+        IndexVoided,
+        DustThresholdUpdated,
+        DustSwept,
+        BuildInfoRecorded,
+        AllocationsAppended,
+        RootScheduled,
+        RootScheduleCleared,
+        RoundingUpdated,
+        FamilyUpdated,
+        FamilyJoined,
+        LeafClaimed,
+        CohortVaultUpdated,
+        CohortClawedBack,
+        VestingHandoffUpdated,
+        VestingHandedOff,
+        BonusLotteryUpdated,
+        BonusTicketEntered,
+        BonusTicketRevealed,
+        BonusLotterySettled,
+        BonusTicketRedeemed,
+    );
+}
+
+#[test]
+fn emitted_claim_decodes() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    let meta = h.send(ix, &[&wallet]).expect("claim");
+
+    let claimed = meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .find_map(|inner| match MerkledropEvent::decode_cpi(&inner.instruction.data) {
+            Some(MerkledropEvent::Claimed(claimed)) => Some(claimed),
+            _ => None,
+        })
+        .expect("Claimed event");
+    assert_eq!(claimed.wallet, wallet.pubkey());
+    assert_eq!(claimed.amount, AMOUNT);
+    assert_eq!(claimed.index, 0);
+    assert_eq!((claimed.campaign, claimed.mint, claimed.root_version), (h.state, h.mint, 0));
+    assert_eq!(claimed.vault_balance, h.token_balance(&h.vault));
+}
+
+#[test]
+fn root_update_events_carry_the_campaign() {
+    let mut h = Harness::new([0; 32], 1, 1_000);
+    let meta = h.set_merkle_root([1; 32], 1).expect("set_merkle_root");
+    let events: Vec<_> = meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .filter_map(|inner| MerkledropEvent::decode_cpi(&inner.instruction.data))
+        .collect();
+
+    let closed = events
+        .iter()
+        .find_map(|event| match event {
+            MerkledropEvent::AirdropClosed(closed) => Some(closed),
+            _ => None,
+        })
+        .expect("AirdropClosed event");
+    assert_eq!((closed.campaign, closed.mint, closed.root_version), (h.state, h.mint, 0));
+    assert_eq!(closed.vault_balance, h.token_balance(&h.vault));
+    let updated = events
+        .iter()
+        .find_map(|event| match event {
+            MerkledropEvent::MerkleRootUpdated(updated) => Some(updated),
+            _ => None,
+        })
+        .expect("MerkleRootUpdated event");
+    assert_eq!((updated.campaign, updated.root_version), (h.state, 1));
+    assert_eq!(h.state().root_version, 1);
+}
+
+#[test]
+fn lookup_addresses_cover_claim_accounts() {
+    let mut h = Harness::new([0; 32], 1, 1_000);
+    let (wallet, ata) = h.new_claimant();
+    let data = h.svm.get_account(&h.state).unwrap().data;
+    let state = merkledrop_interface::State::decode(&data).unwrap();
+    let table = merkledrop_interface::pda::claim_lookup_addresses(&state);
+
+    // Everything but the claimant's own accounts (and unused optional slots,
+    // which Anchor fills with the program id) comes from the table
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    for meta in ix.accounts {
+        if meta.pubkey != wallet.pubkey() && meta.pubkey != ata {
+            assert!(table.contains(&meta.pubkey), "{} missing from the table", meta.pubkey);
+        }
+    }
+}
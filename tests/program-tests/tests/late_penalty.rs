@@ -0,0 +1,94 @@
+//! Late-claim penalty: once the penalty window has fully ramped, the
+//! configured basis points are withheld from the claim and paid into the
+//! treasury.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use program_tests::{event_authority, leaf_hash, Harness};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 10_000;
+const PENALTY_BPS: u16 = 1_000;
+
+// Zero-fee operator config, so the penalty has a treasury account to go to
+fn set_treasury_ix(h: &Harness, treasury: Pubkey, fee_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetOperatorFee {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetOperatorFee { fee_bps: 0 }.data(),
+    }
+}
+
+fn set_late_penalty_ix(
+    h: &Harness,
+    treasury: Pubkey,
+    fee_account: Pubkey,
+    penalty_bps: u16,
+) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetLatePenalty {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetLatePenalty {
+            start_ts: h.now() - 1,
+            ramp: 0,
+            penalty_bps,
+        }
+        .data(),
+    }
+}
+
+#[test]
+fn late_claim_pays_penalty_into_the_treasury() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let (treasury, treasury_tokens) = h.init_treasury();
+    h.send_as_authority(set_treasury_ix(&h, treasury, treasury_tokens)).expect("set_operator_fee");
+    let ix = set_late_penalty_ix(&h, treasury, treasury_tokens, PENALTY_BPS);
+    h.send_as_authority(ix).expect("set_late_penalty");
+
+    h.fee_account = Some(treasury_tokens);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("late claim");
+
+    let penalty = AMOUNT * PENALTY_BPS as u64 / 10_000;
+    assert_eq!(h.token_balance(&treasury_tokens), penalty);
+    assert_eq!(h.token_balance(&ata), AMOUNT - penalty);
+}
+
+#[test]
+fn late_penalty_is_capped() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (treasury, treasury_tokens) = h.init_treasury();
+    h.send_as_authority(set_treasury_ix(&h, treasury, treasury_tokens)).expect("set_operator_fee");
+    let ix = set_late_penalty_ix(&h, treasury, treasury_tokens, 5_001);
+    let failed = h.send_as_authority(ix).expect_err("penalty above cap");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("PenaltyTooHigh")));
+}
+
+#[test]
+fn late_penalty_keeps_the_configured_fee_account() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (treasury, treasury_tokens) = h.init_treasury();
+    let ix = set_late_penalty_ix(&h, treasury, treasury_tokens, PENALTY_BPS);
+    let failed = h.send_as_authority(ix).expect_err("no fee account configured yet");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("InvalidFeeAccount")));
+    assert_eq!(h.state().fee_account, Pubkey::default());
+}
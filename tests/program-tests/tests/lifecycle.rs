@@ -0,0 +1,146 @@
+//! End-to-end campaign lifecycle on a real tree: claim, double-claim, root
+//! rotation, window expiry and reopening, admin and cranked closes, and
+//! closing the state for its rent once the round is finalized.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use merkle::MerkleTree;
+use program_tests::{
+    assert_fails_with, event_authority, leaf_hash, Harness, TxResult, CLAIM_DURATION, SNAPSHOT_HASH,
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const CAPACITY: u64 = 65_536;
+
+fn amount(index: usize) -> u64 {
+    1_000 * (index as u64 + 1)
+}
+
+fn tree_of(wallets: &[Keypair]) -> MerkleTree {
+    MerkleTree::from_leaves(
+        wallets
+            .iter()
+            .enumerate()
+            .map(|(index, w)| leaf_hash(index as u64, &w.pubkey(), amount(index)))
+            .collect(),
+    )
+}
+
+fn claim(
+    h: &mut Harness,
+    tree: &MerkleTree,
+    claimants: &[(Keypair, Pubkey)],
+    index: usize,
+) -> TxResult {
+    let (wallet, ata) = &claimants[index];
+    let proof = tree.proof(index).unwrap();
+    let ix = h.claim_ix(&wallet.pubkey(), ata, index as u64, amount(index), &proof);
+    h.send(ix, &[wallet])
+}
+
+#[test]
+fn campaign_lifecycle() {
+    let wallets: Vec<Keypair> = (0..5).map(|_| Keypair::new()).collect();
+    let first_round = tree_of(&wallets[..4]);
+    let mut h = Harness::new(first_round.root(), 4, CAPACITY);
+    let claimants: Vec<_> = wallets.into_iter().map(|w| h.new_claimant_with(w)).collect();
+
+    claim(&mut h, &first_round, &claimants, 0).expect("claim");
+    assert_eq!(h.token_balance(&claimants[0].1), amount(0));
+    assert_fails_with(claim(&mut h, &first_round, &claimants, 0), "AlreadyClaimed");
+
+    // A valid proof for a different amount doesn't verify
+    let (wallet, ata) = &claimants[1];
+    let proof = first_round.proof(1).unwrap();
+    let ix = h.claim_ix(&wallet.pubkey(), ata, 1, amount(1) + 1, &proof);
+    assert_fails_with(h.send(ix, &[wallet]), "InvalidProof");
+
+    // Rotating the root needs claims stopped; it adds index 4 and keeps the ledger
+    let wallets: Vec<Keypair> = claimants.iter().map(|(w, _)| w.insecure_clone()).collect();
+    let second_round = tree_of(&wallets);
+    let ix = h.update_merkle_root_ix(second_round.root(), 5);
+    assert_fails_with(h.send_as_authority(ix), "ClaimsNotPaused");
+    h.set_merkle_root(second_round.root(), 5).expect("set_merkle_root");
+    claim(&mut h, &second_round, &claimants, 4).expect("claim under the new root");
+    assert_fails_with(claim(&mut h, &second_round, &claimants, 0), "AlreadyClaimed");
+
+    // Window expiry, then reopening
+    h.warp(CLAIM_DURATION);
+    assert_fails_with(claim(&mut h, &second_round, &claimants, 1), "WindowEnded");
+    let ix = h.update_claim_window_ix(h.now() - 60, CLAIM_DURATION);
+    h.send_as_authority(ix).expect("update_claim_window");
+    claim(&mut h, &second_round, &claimants, 1).expect("claim after reopening");
+
+    // Admin close
+    let ix = h.close_airdrop_ix();
+    h.send_as_authority(ix).expect("close_airdrop");
+    assert_fails_with(claim(&mut h, &second_round, &claimants, 2), "ClaimClosed");
+
+    // Closing the state returns its rent, once the round is finalized
+    let recipient = Pubkey::new_unique();
+    let close_state_ix = |h: &Harness| Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::CloseState {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            recipient,
+        }
+        .to_account_metas(None),
+        data: instruction::CloseState {}.data(),
+    };
+    let ix = close_state_ix(&h);
+    assert_fails_with(h.send_as_authority(ix), "InvalidStatus");
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::FinalizeAirdrop {
+            state: h.state,
+            payer: h.authority.pubkey(),
+            summary: Pubkey::find_program_address(&[b"summary", SNAPSHOT_HASH.as_ref()], &airdrop0::ID).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::FinalizeAirdrop {}.data(),
+    };
+    h.send_as_authority(ix).expect("finalize_airdrop");
+    let rent = h.svm.get_account(&h.state).unwrap().lamports;
+    let ix = close_state_ix(&h);
+    h.send_as_authority(ix).expect("close_state");
+    assert_eq!(h.svm.get_account(&recipient).unwrap().lamports, rent);
+    assert!(h.svm.get_account(&h.state).map_or(true, |a| a.lamports == 0));
+}
+
+#[test]
+fn anyone_cranks_close_after_the_window() {
+    let mut h = Harness::new([0; 32], 1, CAPACITY);
+    let cranker = Keypair::new();
+    h.svm.airdrop(&cranker.pubkey(), 1_000_000_000).unwrap();
+    let crank_ix = |h: &Harness| Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::CrankClose {
+            state: h.state,
+            cranker: cranker.pubkey(),
+            vault: h.vault,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CrankClose {}.data(),
+    };
+
+    let ix = crank_ix(&h);
+    assert_fails_with(h.send(ix, &[&cranker]), "CampaignStillActive");
+
+    h.warp(CLAIM_DURATION);
+    let ix = crank_ix(&h);
+    h.send(ix, &[&cranker]).expect("crank_close");
+    let ix = crank_ix(&h);
+    assert_fails_with(h.send(ix, &[&cranker]), "ClaimClosed");
+}
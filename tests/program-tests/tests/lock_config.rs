@@ -0,0 +1,26 @@
+//! `lock_config` permanently disables root and window updates.
+
+use program_tests::{Harness, CLAIM_DURATION};
+
+#[test]
+fn locked_config_rejects_root_and_window_updates() {
+    let mut h = Harness::new([0; 32], 1, 1);
+
+    // Still mutable before the lock
+    h.set_merkle_root([1; 32], 1).expect("set_merkle_root");
+
+    let ix = h.lock_config_ix();
+    h.send_as_authority(ix).expect("lock_config");
+
+    let ix = h.update_merkle_root_ix([2; 32], 1);
+    let failed = h.send_as_authority(ix).expect_err("root update after lock");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("ConfigLocked")));
+
+    let ix = h.update_claim_window_ix(h.now(), 2 * CLAIM_DURATION);
+    let failed = h.send_as_authority(ix).expect_err("window update after lock");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("ConfigLocked")));
+
+    let ix = h.lock_config_ix();
+    let failed = h.send_as_authority(ix).expect_err("second lock");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("ConfigLocked")));
+}
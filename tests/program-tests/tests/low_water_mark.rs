@@ -0,0 +1,77 @@
+//! A claim that drains the vault under its low-water mark pauses the campaign
+//! until the authority tops the vault up and resumes claims.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use litesvm_token::MintTo;
+use program_tests::{event_authority, fold_proof, leaf_hash, Harness, VAULT_SUPPLY};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+
+fn set_low_water_mark_ix(h: &Harness, low_water_mark: u64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetLowWaterMark {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetLowWaterMark { low_water_mark }.data(),
+    }
+}
+
+fn resume_claims_ix(h: &Harness) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ResumeClaims {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            vault: h.vault,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ResumeClaims {}.data(),
+    }
+}
+
+#[test]
+fn claims_pause_below_low_water_mark_until_refilled() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let alice_leaf = leaf_hash(0, &alice.pubkey(), AMOUNT);
+    let bob_leaf = leaf_hash(1, &bob.pubkey(), AMOUNT);
+    let root = fold_proof(alice_leaf, &[bob_leaf]);
+
+    let mut h = Harness::new(root, 2, 2);
+    let (alice, alice_ata) = h.new_claimant_with(alice);
+    let (bob, bob_ata) = h.new_claimant_with(bob);
+
+    let ix = set_low_water_mark_ix(&h, VAULT_SUPPLY - AMOUNT / 2);
+    h.send_as_authority(ix).expect("set_low_water_mark");
+
+    // The tripping claim itself is paid
+    let ix = h.claim_ix(&alice.pubkey(), &alice_ata, 0, AMOUNT, &[bob_leaf]);
+    h.send(ix, &[&alice]).expect("claim that trips the mark");
+    assert_eq!(h.token_balance(&alice_ata), AMOUNT);
+
+    let ix = h.claim_ix(&bob.pubkey(), &bob_ata, 1, AMOUNT, &[alice_leaf]);
+    let failed = h.send(ix, &[&bob]).expect_err("claims are paused");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("VaultNeedsRefill")));
+
+    let ix = resume_claims_ix(&h);
+    h.send_as_authority(ix).expect_err("vault still under the mark");
+
+    let authority = h.authority.insecure_clone();
+    MintTo::new(&mut h.svm, &authority, &h.mint, &h.vault, AMOUNT)
+        .send()
+        .unwrap();
+    let ix = resume_claims_ix(&h);
+    h.send_as_authority(ix).expect("resume_claims");
+
+    let ix = h.claim_ix(&bob.pubkey(), &bob_ata, 1, AMOUNT, &[alice_leaf]);
+    h.send(ix, &[&bob]).expect("claim after refill");
+    assert_eq!(h.token_balance(&bob_ata), AMOUNT);
+}
@@ -0,0 +1,139 @@
+//! `migrate_state` from the pre-version layout: claims recorded as residues
+//! become bitmap bits, the leaves past the first bitmap through
+//! `resize_state`, and a round without claims starts on a full-size bitmap.
+
+use airdrop0::{accounts, instruction, MAX_INIT_CLAIM_CAPACITY, MAX_LEGACY_CONVERSION};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, pubkey::Pubkey, system_program},
+    Discriminator, InstructionData, ToAccountMetas,
+};
+use program_tests::{event_authority, Harness, TxResult, SNAPSHOT_HASH};
+use solana_sdk::{account::Account, signature::Signer};
+
+const MODULI: [u64; 3] = [971, 311, 601];
+const RESIDUE_BYTES: [usize; 3] = [122, 39, 76];
+
+// Overwrites the campaign with the layout from before `State::version`,
+// with `claimed` recorded as residues
+fn write_legacy_state(h: &mut Harness, leaves: u64, claimed: &[u64]) {
+    let mut data = airdrop0::State::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(h.authority.pubkey().as_ref());
+    data.extend_from_slice(&SNAPSHOT_HASH);
+    data.extend_from_slice(&(h.now() - 60).to_le_bytes());
+    data.extend_from_slice(&3_600i64.to_le_bytes());
+    data.push(0);
+    data.extend_from_slice(&[0; 32]);
+    data.extend_from_slice(&leaves.to_le_bytes());
+    for (modulus, len) in MODULI.iter().zip(RESIDUE_BYTES) {
+        let mut residues = vec![0u8; len];
+        for index in claimed {
+            let residue = index % modulus;
+            residues[(residue / 8) as usize] |= 1 << (residue % 8);
+        }
+        data.extend_from_slice(&residues);
+    }
+    let account = Account {
+        lamports: h.svm.minimum_balance_for_rent_exemption(data.len()),
+        data,
+        owner: airdrop0::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    h.svm.set_account(h.state, account).unwrap();
+}
+
+fn legacy_claims(h: &Harness) -> Pubkey {
+    Pubkey::find_program_address(&[b"legacy_claims", h.state.as_ref()], &airdrop0::ID).0
+}
+
+fn migrate_state(h: &mut Harness, with_legacy_claims: bool) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::MigrateState {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            vault: h.vault,
+            legacy_claims: with_legacy_claims.then(|| legacy_claims(h)),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::MigrateState {}.data(),
+    };
+    h.send_as_authority(ix)
+}
+
+fn resize_state(h: &mut Harness, new_capacity: u64) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ResizeState {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            legacy_claims: Some(legacy_claims(h)),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ResizeState { new_capacity }.data(),
+    };
+    h.send_as_authority(ix)
+}
+
+fn is_claimed(h: &Harness, index: u64) -> bool {
+    let bitmap = h.state().claim_bitmap;
+    bitmap[(index / 8) as usize] & (1 << (index % 8)) != 0
+}
+
+#[test]
+fn claimed_round_converts_as_the_bitmap_grows() {
+    let leaves = 2 * MAX_LEGACY_CONVERSION + 100;
+    let claimed = [3, MAX_LEGACY_CONVERSION + 7, 2 * MAX_LEGACY_CONVERSION + 50];
+    let mut h = Harness::new([0; 32], 1, 1);
+    write_legacy_state(&mut h, leaves, &claimed);
+
+    let failed = migrate_state(&mut h, false).expect_err("claims past the bitmap need legacy_claims");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("LeavesPastClaimBitmap")));
+
+    migrate_state(&mut h, true).expect("migrate_state");
+    let state = h.state();
+    assert_eq!(state.total_claims, leaves);
+    assert_eq!(state.claim_capacity, MAX_LEGACY_CONVERSION);
+    assert!(state.legacy_claims);
+    assert!(is_claimed(&h, 3));
+    assert!(!is_claimed(&h, 4));
+
+    // One step is capped while claims are converted
+    let failed = resize_state(&mut h, leaves).expect_err("step past MAX_LEGACY_CONVERSION");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("InvalidCapacity")));
+
+    resize_state(&mut h, 2 * MAX_LEGACY_CONVERSION).expect("first step");
+    assert!(is_claimed(&h, MAX_LEGACY_CONVERSION + 7));
+    assert!(h.state().legacy_claims);
+    resize_state(&mut h, leaves).expect("last step");
+    assert!(is_claimed(&h, 2 * MAX_LEGACY_CONVERSION + 50));
+    assert!(!h.state().legacy_claims);
+    assert!(h.svm.get_account(&legacy_claims(&h)).is_none_or(|account| account.lamports == 0));
+}
+
+#[test]
+fn unclaimed_round_migrates_onto_a_full_bitmap() {
+    let leaves = 100_000;
+    let mut h = Harness::new([0; 32], 1, 1);
+    write_legacy_state(&mut h, leaves, &[]);
+
+    let failed = migrate_state(&mut h, true).expect_err("no claims to keep");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("LeavesPastClaimBitmap")));
+
+    migrate_state(&mut h, false).expect("migrate_state");
+    let state = h.state();
+    assert_eq!(state.total_claims, leaves);
+    // `resize_state` grows it the rest of the way
+    assert_eq!(state.claim_capacity, MAX_INIT_CLAIM_CAPACITY);
+    assert!(!state.legacy_claims);
+    assert_eq!(state.claimed_count, 0);
+
+    let failed = migrate_state(&mut h, false).expect_err("already migrated");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("AlreadyMigrated")));
+}
@@ -0,0 +1,91 @@
+//! Fee-in-kind: the operator's basis-point cut leaves the vault alongside the
+//! claim into the fee treasury, and claims without the configured fee account
+//! are rejected.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use litesvm_token::{spl_token, CreateAssociatedTokenAccount};
+use program_tests::{event_authority, leaf_hash, Harness};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 10_000;
+const FEE_BPS: u16 = 250;
+
+fn set_operator_fee_ix(h: &Harness, treasury: Pubkey, fee_account: Pubkey, fee_bps: u16) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetOperatorFee {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetOperatorFee { fee_bps }.data(),
+    }
+}
+
+#[test]
+fn claim_splits_operator_fee_into_the_treasury() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let (treasury, treasury_tokens) = h.init_treasury();
+    let ix = set_operator_fee_ix(&h, treasury, treasury_tokens, FEE_BPS);
+    h.send_as_authority(ix).expect("set_operator_fee");
+
+    // Omitting the fee account is rejected
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    let failed = h.send(ix, &[&wallet]).expect_err("missing fee account");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("InvalidFeeAccount")));
+
+    h.fee_account = Some(treasury_tokens);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim with fee");
+
+    let fee = AMOUNT * FEE_BPS as u64 / 10_000;
+    assert_eq!(h.token_balance(&treasury_tokens), fee);
+    assert_eq!(h.token_balance(&ata), AMOUNT - fee);
+
+    // The authority sweeps the treasury
+    let operator = Keypair::new();
+    let authority = h.authority.insecure_clone();
+    let operator_ata = CreateAssociatedTokenAccount::new(&mut h.svm, &authority, &h.mint)
+        .owner(&operator.pubkey())
+        .send()
+        .unwrap();
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::WithdrawFees {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            treasury_tokens,
+            destination: operator_ata,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::WithdrawFees { token_amount: fee, lamports: 0 }.data(),
+    };
+    h.send_as_authority(ix).expect("withdraw_fees");
+    assert_eq!(h.token_balance(&operator_ata), fee);
+    assert_eq!(h.token_balance(&treasury_tokens), 0);
+}
+
+#[test]
+fn operator_fee_is_capped() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (treasury, treasury_tokens) = h.init_treasury();
+    let ix = set_operator_fee_ix(&h, treasury, treasury_tokens, 1_001);
+    let failed = h.send_as_authority(ix).expect_err("fee above cap");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("FeeTooHigh")));
+}
@@ -0,0 +1,120 @@
+//! Protocol config: `initialize` pays the configured creation fee to the fee
+//! treasury, only the admin can change the schedule, and only the upgrade
+//! authority can create the config in the first place.
+
+use airdrop0::{accounts, instruction, Config};
+use anchor_lang::{
+    solana_program::{bpf_loader_upgradeable, instruction::Instruction, system_program},
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use program_tests::{assert_fails_with, event_authority, Harness, TxResult};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const CREATION_FEE: u64 = 50_000_000;
+
+fn config_pda() -> (Pubkey, u8) {
+    merkledrop_interface::pda::config()
+}
+
+fn config(h: &Harness) -> Config {
+    let data = h.svm.get_account(&config_pda().0).unwrap().data;
+    Config::try_deserialize(&mut data.as_slice()).unwrap()
+}
+
+fn set_config(h: &mut Harness, admin: &Keypair, fee_treasury: Pubkey, creation_fee: u64) -> TxResult {
+    h.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetConfig {
+            config: config_pda().0,
+            admin: admin.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetConfig {
+            admin: admin.pubkey(),
+            fee_treasury,
+            creation_fee,
+        }
+        .data(),
+    };
+    h.send(ix, &[admin])
+}
+
+#[test]
+fn initialize_pays_the_creation_fee() {
+    let admin = Keypair::new();
+    let fee_treasury = Pubkey::new_unique();
+    let h = Harness::with_config(
+        [0; 32],
+        1,
+        1_000,
+        Config {
+            admin: admin.pubkey(),
+            fee_treasury,
+            creation_fee: CREATION_FEE,
+            bump: config_pda().1,
+        },
+    );
+    assert_eq!(h.svm.get_balance(&fee_treasury), Some(CREATION_FEE));
+}
+
+#[test]
+fn only_the_admin_changes_the_schedule() {
+    let admin = Keypair::new();
+    let fee_treasury = Pubkey::new_unique();
+    let mut h = Harness::with_config(
+        [0; 32],
+        1,
+        1_000,
+        Config {
+            admin: admin.pubkey(),
+            fee_treasury,
+            creation_fee: 0,
+            bump: config_pda().1,
+        },
+    );
+    // No fee configured, so nothing was charged
+    assert_eq!(h.svm.get_balance(&fee_treasury), None);
+
+    let stranger = Keypair::new();
+    assert_fails_with(set_config(&mut h, &stranger, fee_treasury, 0), "Unauthorized");
+    assert_fails_with(
+        set_config(&mut h, &admin, Pubkey::default(), CREATION_FEE),
+        "InvalidFeeTreasury"
+    );
+    set_config(&mut h, &admin, fee_treasury, CREATION_FEE).expect("set_config");
+    let config = config(&h);
+    assert_eq!((config.fee_treasury, config.creation_fee), (fee_treasury, CREATION_FEE));
+}
+
+#[test]
+fn init_config_needs_the_upgrade_authority() {
+    let mut h = Harness::new([0; 32], 1, 1_000);
+    // The harness loads the program without ProgramData, so no signer qualifies
+    let program_data = Pubkey::find_program_address(&[airdrop0::ID.as_ref()], &bpf_loader_upgradeable::ID).0;
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::InitConfig {
+            config: config_pda().0,
+            upgrade_authority: h.authority.pubkey(),
+            program_data,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitConfig {
+            admin: h.authority.pubkey(),
+            fee_treasury: h.authority.pubkey(),
+            creation_fee: CREATION_FEE,
+        }
+        .data(),
+    };
+    assert!(h.send_as_authority(ix).is_err());
+    assert!(h.svm.get_account(&config_pda().0).is_none());
+}
@@ -0,0 +1,301 @@
+//! Unclaimed-supply raffle: `settle_raffle` draws exactly `winner_count`
+//! distinct claimed indices from the committed randomness, and the vault
+//! can't move until every winner is paid.
+
+use airdrop0::{accounts, instruction, Raffle};
+use anchor_lang::{
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        keccak, system_program,
+    },
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use litesvm_token::{spl_token, CreateAssociatedTokenAccount};
+use merkle::MerkleTree;
+use program_tests::{
+    assert_fails_with, event_authority, pad_proof, Harness, SNAPSHOT_HASH, VAULT_SUPPLY,
+};
+use solana_sdk::{
+    account::Account,
+    pubkey,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+const VALUE: [u8; 32] = [7; 32];
+const SWITCHBOARD_ON_DEMAND: Pubkey = pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+const RANDOMNESS_DISCRIMINATOR: [u8; 8] = [10, 66, 229, 135, 220, 239, 217, 114];
+
+fn raffle_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"raffle", SNAPSHOT_HASH.as_ref()], &airdrop0::ID).0
+}
+
+fn raffle(h: &Harness) -> Raffle {
+    let data = h.svm.get_account(&raffle_address()).unwrap().data;
+    Raffle::try_deserialize(&mut data.as_slice()).unwrap()
+}
+
+// A Switchboard on-demand `RandomnessAccountData` seeded at `seed_slot`
+// and revealed at `reveal_slot`
+fn set_randomness(
+    h: &mut Harness,
+    address: Pubkey,
+    seed_slot: u64,
+    reveal_slot: u64,
+    value: [u8; 32],
+) {
+    let mut data = RANDOMNESS_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&[0; 32 + 32 + 32]); // authority, queue, seed_slothash
+    data.extend_from_slice(&seed_slot.to_le_bytes());
+    data.extend_from_slice(&[0; 32]); // oracle
+    data.extend_from_slice(&reveal_slot.to_le_bytes());
+    data.extend_from_slice(&value);
+    data.extend_from_slice(&[0; 96 + 128]);
+    let account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: SWITCHBOARD_ON_DEMAND,
+        executable: false,
+        rent_epoch: 0,
+    };
+    h.svm.set_account(address, account).unwrap();
+}
+
+fn request_ix(h: &Harness, randomness_account: Pubkey, winner_count: u32) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RequestRaffle {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            raffle: raffle_address(),
+            randomness_account,
+            vault: h.vault,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RequestRaffle { winner_count }.data(),
+    }
+}
+
+// `void_records` are the `VoidedIndex` addresses the call's draws need
+fn settle_ix(h: &Harness, randomness_account: Pubkey, void_records: &[Pubkey]) -> Instruction {
+    let mut accounts = accounts::SettleRaffle {
+        state: h.state,
+        raffle: raffle_address(),
+        randomness_account,
+        event_authority: event_authority(),
+        program: airdrop0::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(void_records.iter().map(|record| AccountMeta::new_readonly(*record, false)));
+    Instruction { program_id: airdrop0::ID, accounts, data: instruction::SettleRaffle {}.data() }
+}
+
+// Mirrors the program's draw: the void records of every recorded index the
+// tickets land on, until `winner_count` unvoided ones have won
+fn void_records(
+    total_claims: u64,
+    recorded: &[u64],
+    voided: &[u64],
+    winner_count: usize,
+) -> Vec<Pubkey> {
+    let mut winners = Vec::new();
+    let mut records = Vec::new();
+    for draw in 0u64.. {
+        if winners.len() == winner_count {
+            break;
+        }
+        let ticket = keccak::hashv(&[&VALUE, &draw.to_le_bytes()]).to_bytes();
+        let index = u64::from_le_bytes(ticket[..8].try_into().unwrap()) % total_claims;
+        if recorded.contains(&index) && !winners.contains(&index) {
+            records.push(merkledrop_interface::pda::voided_index(&SNAPSHOT_HASH, index).0);
+            if !voided.contains(&index) {
+                winners.push(index);
+            }
+        }
+    }
+    records
+}
+
+fn prize_ix(
+    h: &Harness,
+    wallet: &Pubkey,
+    ata: &Pubkey,
+    index: u64,
+    proof: &[[u8; 32]],
+) -> Instruction {
+    let (prize, _) = Pubkey::find_program_address(
+        &[b"raffle_prize", raffle_address().as_ref(), &index.to_le_bytes()],
+        &airdrop0::ID,
+    );
+    let (proof, proof_len) = pad_proof(proof);
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimRafflePrize {
+            state: h.state,
+            raffle: raffle_address(),
+            prize,
+            wallet: *wallet,
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *ata,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimRafflePrize { index, amount: AMOUNT, proof, proof_len }.data(),
+    }
+}
+
+fn rotate_ix(h: &mut Harness) -> Instruction {
+    let new_snapshot_hash = [0xab; 32];
+    let (new_vault_auth, _) =
+        Pubkey::find_program_address(&[b"vault", new_snapshot_hash.as_ref()], &airdrop0::ID);
+    let authority = h.authority.insecure_clone();
+    let new_vault = CreateAssociatedTokenAccount::new(&mut h.svm, &authority, &h.mint)
+        .owner(&new_vault_auth)
+        .send()
+        .unwrap();
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RotateVaultAuthority {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            new_vault_auth,
+            new_vault,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RotateVaultAuthority { new_snapshot_hash }.data(),
+    }
+}
+
+fn void_index_ix(h: &Harness, index: u64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::VoidIndex {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            voided_index: merkledrop_interface::pda::voided_index(&SNAPSHOT_HASH, index).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::VoidIndex { index, reason_hash: [1; 32] }.data(),
+    }
+}
+
+// A round of eight leaves where the `claimed` ones claimed; returns
+// the claimants as `(index, wallet, token account)`
+fn claimed_round(claimed: &[u64]) -> (Harness, MerkleTree, Vec<(u64, Keypair, Pubkey)>) {
+    let wallets: Vec<Keypair> = (0..8).map(|_| Keypair::new()).collect();
+    let tree = MerkleTree::from_leaves(
+        wallets
+            .iter()
+            .enumerate()
+            .map(|(index, wallet)| merkle::leaf(index as u64, &wallet.pubkey().to_bytes(), AMOUNT))
+            .collect(),
+    );
+    let mut h = Harness::new(tree.root(), 8, 8);
+    let mut claimants = Vec::new();
+    for &index in claimed {
+        let (wallet, ata) = h.new_claimant_with(wallets[index as usize].insecure_clone());
+        let proof = tree.proof(index as usize).unwrap();
+        let ix = h.claim_ix(&wallet.pubkey(), &ata, index, AMOUNT, &proof);
+        h.send(ix, &[&wallet]).expect("claim");
+        claimants.push((index, wallet, ata));
+    }
+    (h, tree, claimants)
+}
+
+// Requests a raffle in slot 10 and reveals `VALUE` in slot 12
+fn request_and_reveal(h: &mut Harness, winner_count: u32) -> Pubkey {
+    h.svm.warp_to_slot(10);
+    let randomness_account = Pubkey::new_unique();
+    set_randomness(h, randomness_account, 9, 0, [0; 32]);
+    h.send_as_authority(request_ix(h, randomness_account, winner_count)).expect("request_raffle");
+    h.svm.warp_to_slot(12);
+    set_randomness(h, randomness_account, 9, 12, VALUE);
+    randomness_account
+}
+
+#[test]
+fn raffle_pays_exactly_winner_count_claimants() {
+    let (mut h, tree, claimants) = claimed_round(&[0, 2, 4, 6]);
+    h.send_as_authority(h.close_airdrop_ix()).expect("close_airdrop");
+
+    h.svm.warp_to_slot(10);
+    let randomness_account = Pubkey::new_unique();
+    set_randomness(&mut h, randomness_account, 9, 0, [0; 32]);
+    assert_fails_with(
+        h.send_as_authority(request_ix(&h, randomness_account, 5)),
+        "InvalidWinnerCount",
+    );
+    let randomness_account = request_and_reveal(&mut h, 3);
+    assert_ne!(h.state().raffle_deadline, 0);
+    let ix = rotate_ix(&mut h);
+    assert_fails_with(h.send_as_authority(ix), "RaffleOpen");
+    assert_fails_with(h.send_as_authority(void_index_ix(&h, 1)), "RaffleOpen");
+
+    let records = void_records(8, &[0, 2, 4, 6], &[], 3);
+    h.send_as_authority(settle_ix(&h, randomness_account, &records)).expect("settle_raffle");
+    let drawn = raffle(&h);
+    assert!(drawn.settled);
+    let mut winners = drawn.winners[..drawn.drawn as usize].to_vec();
+    winners.sort_unstable();
+    winners.dedup();
+    assert_eq!(winners.len(), 3);
+    assert!(winners.iter().all(|index| index % 2 == 0));
+
+    let prize = (VAULT_SUPPLY - 4 * AMOUNT) / 3;
+    for (index, wallet, ata) in &claimants {
+        let ix = prize_ix(&h, &wallet.pubkey(), ata, *index, &tree.proof(*index as usize).unwrap());
+        if winners.contains(index) {
+            h.send(ix, &[wallet]).expect("claim_raffle_prize");
+            assert_eq!(h.token_balance(ata), AMOUNT + prize);
+        } else {
+            assert_fails_with(h.send(ix, &[wallet]), "NotRaffleWinner");
+        }
+    }
+    assert_eq!(raffle(&h).paid_count, 3);
+    assert_eq!(h.state().raffle_deadline, 0);
+}
+
+#[test]
+fn voided_index_is_never_drawn() {
+    let (mut h, _, _) = claimed_round(&[0, 2, 4]);
+    h.send_as_authority(void_index_ix(&h, 6)).expect("void_index");
+    h.send_as_authority(h.close_airdrop_ix()).expect("close_airdrop");
+    let randomness_account = request_and_reveal(&mut h, 3);
+
+    // Without void records the draw stops at the first recorded index
+    h.send_as_authority(settle_ix(&h, randomness_account, &[])).expect("settle without records");
+    let stalled = raffle(&h);
+    assert_eq!(stalled.drawn, 0);
+    assert!(stalled.revealed && !stalled.settled);
+    let wrong = [Pubkey::new_unique()];
+    assert_fails_with(
+        h.send_as_authority(settle_ix(&h, randomness_account, &wrong)),
+        "InvalidVoidRecord",
+    );
+
+    let records = void_records(8, &[0, 2, 4, 6], &[6], 3);
+    h.send_as_authority(settle_ix(&h, randomness_account, &records)).expect("settle_raffle");
+    let drawn = raffle(&h);
+    assert!(drawn.settled);
+    let mut winners = drawn.winners[..drawn.drawn as usize].to_vec();
+    winners.sort_unstable();
+    assert_eq!(winners, vec![0, 2, 4]);
+}
@@ -0,0 +1,64 @@
+//! Reconciliation: a campaign's claims against its allocation file, from
+//! `Claimed` events or from the `ClaimSet` in `State`.
+
+use merkle::MerkleTree;
+use merkledrop_interface::{Anomaly, ClaimSet, Claimed, MerkledropEvent, Reconciliation};
+use program_tests::{leaf_hash, Harness};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNTS: [u64; 3] = [1_000, 2_000, 3_000];
+
+#[test]
+fn claims_reconcile_against_the_allocations() {
+    let wallets: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let tree = MerkleTree::from_leaves(
+        wallets
+            .iter()
+            .enumerate()
+            .map(|(index, w)| leaf_hash(index as u64, &w.pubkey(), AMOUNTS[index]))
+            .collect(),
+    );
+    let mut h = Harness::new(tree.root(), 3, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallets[1].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 1, AMOUNTS[1], &tree.proof(1).unwrap());
+    let meta = h.send(ix, &[&wallet]).expect("claim");
+    let claimed: Claimed = meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .find_map(|inner| match MerkledropEvent::decode_cpi(&inner.instruction.data) {
+            Some(MerkledropEvent::Claimed(claimed)) => Some(claimed),
+            _ => None,
+        })
+        .expect("Claimed event");
+
+    let allocations: Vec<(u64, u64)> = (0..3).zip(AMOUNTS).collect();
+    let from_events = Reconciliation::from_events(&allocations, [&claimed]);
+    assert_eq!((from_events.claimed_count, from_events.claimed_amount), (1, AMOUNTS[1]));
+    assert_eq!(from_events.unclaimed_count, 2);
+    assert_eq!(from_events.unclaimed_amount, AMOUNTS[0] + AMOUNTS[2]);
+    assert!(from_events.anomalies.is_empty());
+    assert_eq!(
+        from_events.to_csv(),
+        "index,allocated,claimed,status\n0,1000,,unclaimed\n1,2000,2000,claimed\n2,3000,,unclaimed\n"
+    );
+
+    let data = h.svm.get_account(&h.state).unwrap().data;
+    let state = merkledrop_interface::State::decode(&data).unwrap();
+    let claim_set = ClaimSet::from_state(&state).unwrap();
+    assert_eq!(Reconciliation::from_claim_set(&allocations, &claim_set), from_events);
+
+    // An allocation file that disagrees with what was paid
+    let understated = [(0, AMOUNTS[0]), (1, AMOUNTS[1] - 1)];
+    let report = Reconciliation::from_events(&understated, [&claimed, &claimed]);
+    assert_eq!(
+        report.anomalies,
+        [
+            Anomaly::ExceedsAllocation { index: 1, allocated: AMOUNTS[1] - 1, claimed: AMOUNTS[1] },
+            Anomaly::ClaimedTwice { index: 1 },
+        ]
+    );
+    assert!(report.to_csv().contains("1,1999,2000,exceeds_allocation\n"));
+    let report = Reconciliation::from_events(&understated[..1], [&claimed]);
+    assert_eq!(report.anomalies, [Anomaly::UnknownIndex { index: 1, claimed: AMOUNTS[1] }]);
+}
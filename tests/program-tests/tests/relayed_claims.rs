@@ -0,0 +1,191 @@
+//! Gasless claims: a registered relayer pays the transaction and is
+//! reimbursed `relayer_fee` out of the allocation; the claimant holds no SOL.
+//! Each relayer's account counts what it submitted, and survives revocation.
+
+use airdrop0::{accounts, instruction, Relayer};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use litesvm_token::{spl_token, CreateAssociatedTokenAccount};
+use program_tests::{assert_fails_with, event_authority, leaf_hash, pad_proof, Harness, TxResult};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+const RELAYER_FEE: u64 = 25;
+
+fn relayer_account(h: &Harness, relayer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"relayer", h.state.as_ref(), relayer.as_ref()], &airdrop0::ID).0
+}
+
+fn relayer_tally(h: &Harness, relayer: &Pubkey) -> Relayer {
+    let data = h.svm.get_account(&relayer_account(h, relayer)).unwrap().data;
+    Relayer::try_deserialize(&mut data.as_slice()).unwrap()
+}
+
+fn set_relayer_fee_ix(h: &Harness, relayer_fee: u64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetRelayerFee {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetRelayerFee { relayer_fee }.data(),
+    }
+}
+
+fn register_relayer_ix(h: &Harness, relayer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RegisterRelayer {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            relayer_account: relayer_account(h, &relayer),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RegisterRelayer { relayer }.data(),
+    }
+}
+
+fn revoke_relayer_ix(h: &Harness, relayer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RevokeRelayer {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            relayer_account: relayer_account(h, &relayer),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RevokeRelayer { relayer }.data(),
+    }
+}
+
+fn claim_relayed(
+    h: &mut Harness,
+    relayer: &Keypair,
+    relayer_ata: &Pubkey,
+    wallet: &Keypair,
+    user_ata: &Pubkey,
+    max_relayer_fee: u64,
+) -> TxResult {
+    let (proof, proof_len) = pad_proof(&[]);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimRelayed {
+            state: h.state,
+            wallet: wallet.pubkey(),
+            relayer: relayer.pubkey(),
+            relayer_token_account: *relayer_ata,
+            relayer_account: relayer_account(h, &relayer.pubkey()),
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            fee_account: None,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            bonus_vault: None,
+            badge_mint: None,
+            badge_account: None,
+            token_2022_program: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimRelayed {
+            index: 0,
+            amount: AMOUNT,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+            max_relayer_fee,
+        }
+        .data(),
+    };
+    // The relayer signs first and so pays the fee
+    h.send(ix, &[relayer, wallet])
+}
+
+#[test]
+fn relayer_submits_and_is_reimbursed() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (relayer, relayer_ata) = h.new_claimant();
+    let (stranger, stranger_ata) = h.new_claimant();
+    let user_ata = CreateAssociatedTokenAccount::new(&mut h.svm, &relayer, &h.mint)
+        .owner(&wallet.pubkey())
+        .send()
+        .unwrap();
+
+    let ix = set_relayer_fee_ix(&h, RELAYER_FEE);
+    h.send_as_authority(ix).expect("set_relayer_fee");
+    let ix = register_relayer_ix(&h, relayer.pubkey());
+    h.send_as_authority(ix).expect("register_relayer");
+
+    // The stranger has no relayer account at all
+    let result = claim_relayed(&mut h, &stranger, &stranger_ata, &wallet, &user_ata, RELAYER_FEE);
+    assert!(result.is_err());
+    let result = claim_relayed(&mut h, &relayer, &relayer_ata, &wallet, &user_ata, RELAYER_FEE - 1);
+    assert_fails_with(result, "RelayerFeeTooHigh");
+
+    claim_relayed(&mut h, &relayer, &relayer_ata, &wallet, &user_ata, RELAYER_FEE).expect("claim_relayed");
+    assert_eq!(h.token_balance(&user_ata), AMOUNT - RELAYER_FEE);
+    assert_eq!(h.token_balance(&relayer_ata), RELAYER_FEE);
+    assert!(h.svm.get_account(&wallet.pubkey()).map_or(true, |a| a.lamports == 0));
+    let tally = relayer_tally(&h, &relayer.pubkey());
+    assert_eq!((tally.claims, tally.fees_earned), (1, RELAYER_FEE));
+}
+
+#[test]
+fn revoked_relayer_is_refused_but_keeps_its_tally() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1_000);
+    let (relayer, relayer_ata) = h.new_claimant();
+    let user_ata = CreateAssociatedTokenAccount::new(&mut h.svm, &relayer, &h.mint)
+        .owner(&wallet.pubkey())
+        .send()
+        .unwrap();
+    let ix = register_relayer_ix(&h, relayer.pubkey());
+    h.send_as_authority(ix).expect("register_relayer");
+    let ix = revoke_relayer_ix(&h, relayer.pubkey());
+    h.send_as_authority(ix).expect("revoke_relayer");
+    let ix = revoke_relayer_ix(&h, relayer.pubkey());
+    assert_fails_with(h.send_as_authority(ix), "UnknownRelayer");
+
+    let result = claim_relayed(&mut h, &relayer, &relayer_ata, &wallet, &user_ata, 0);
+    assert_fails_with(result, "UnknownRelayer");
+
+    // Registering again reactivates the same account
+    let ix = register_relayer_ix(&h, relayer.pubkey());
+    h.send_as_authority(ix).expect("register_relayer again");
+    claim_relayed(&mut h, &relayer, &relayer_ata, &wallet, &user_ata, 0).expect("claim_relayed");
+    let tally = relayer_tally(&h, &relayer.pubkey());
+    assert!(tally.active);
+    assert_eq!((tally.claims, tally.fees_earned), (1, 0));
+}
+
+#[test]
+fn relayer_fee_is_fixed_once_the_config_is_locked() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    h.send_as_authority(set_relayer_fee_ix(&h, RELAYER_FEE)).expect("set_relayer_fee");
+    h.send_as_authority(h.lock_config_ix()).expect("lock_config");
+
+    let ix = set_relayer_fee_ix(&h, 2 * RELAYER_FEE);
+    assert_fails_with(h.send_as_authority(ix), "ConfigLocked");
+    assert_eq!(h.state().relayer_fee, RELAYER_FEE);
+}
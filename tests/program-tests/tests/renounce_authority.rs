@@ -0,0 +1,39 @@
+//! After `renounce_authority` no admin instruction can be signed, while
+//! claims keep working.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use program_tests::{event_authority, leaf_hash, Harness};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 1_000;
+
+#[test]
+fn renounced_campaign_has_no_admin() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RenounceAuthority {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RenounceAuthority {}.data(),
+    };
+    h.send_as_authority(ix).expect("renounce_authority");
+
+    assert_eq!(h.state().authority, Pubkey::default());
+
+    let ix = h.update_merkle_root_ix([1; 32], 1);
+    let failed = h.send_as_authority(ix).expect_err("admin after renounce");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("ConstraintHasOne")));
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim after renounce");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+}
@@ -0,0 +1,132 @@
+//! `finalize_and_rollover` archives an ended round and seeds the next one
+//! in place, carrying the unclaimed supply into a new vault.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    AccountSerialize, InstructionData, ToAccountMetas,
+};
+use litesvm_token::{spl_token, CreateAssociatedTokenAccount};
+use program_tests::{
+    assert_fails_with, event_authority, Harness, TxResult, CLAIM_DURATION, SNAPSHOT_HASH,
+    VAULT_SUPPLY,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const NEW_SNAPSHOT_HASH: [u8; 32] = [0xab; 32];
+
+fn emit_unclaimed(h: &mut Harness, range_start: u64, range_end: u64) -> TxResult {
+    let (cranker, _) = h.new_claimant();
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::EmitUnclaimed {
+            state: h.state,
+            cranker: Some(cranker.pubkey()),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::EmitUnclaimed { range_start, range_end }.data(),
+    };
+    h.send(ix, &[&cranker])
+}
+
+/// Opens the next round's vault; returns `(new_vault_auth, new_vault)`.
+fn open_new_vault(h: &mut Harness) -> (Pubkey, Pubkey) {
+    let (new_vault_auth, _) =
+        Pubkey::find_program_address(&[b"vault", NEW_SNAPSHOT_HASH.as_ref()], &airdrop0::ID);
+    let authority = h.authority.insecure_clone();
+    let new_vault = CreateAssociatedTokenAccount::new(&mut h.svm, &authority, &h.mint)
+        .owner(&new_vault_auth)
+        .send()
+        .unwrap();
+    (new_vault_auth, new_vault)
+}
+
+fn rollover_ix(
+    h: &Harness,
+    new_vault_auth: Pubkey,
+    new_vault: Pubkey,
+    new_total_claims: u64,
+) -> Instruction {
+    let (summary, _) =
+        Pubkey::find_program_address(&[b"rollover", SNAPSHOT_HASH.as_ref()], &airdrop0::ID);
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::FinalizeAndRollover {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            summary,
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            new_vault_auth,
+            new_vault,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::FinalizeAndRollover {
+            new_snapshot_hash: NEW_SNAPSHOT_HASH,
+            new_merkle_root: [1; 32],
+            new_total_claims,
+            new_start_ts: h.now(),
+            new_duration: CLAIM_DURATION,
+            new_allocations_hash: [0; 32],
+            new_allocations_uri: String::new(),
+            new_total_allocation: 1,
+        }
+        .data(),
+    }
+}
+
+#[test]
+fn rollover_waits_for_the_unclaimed_export() {
+    let mut h = Harness::new([0; 32], 3_000, 4_096);
+    let ix = h.close_airdrop_ix();
+    h.send_as_authority(ix).expect("close_airdrop");
+    let (new_vault_auth, new_vault) = open_new_vault(&mut h);
+
+    let ix = rollover_ix(&h, new_vault_auth, new_vault, 10);
+    assert_fails_with(h.send_as_authority(ix), "ExportIncomplete");
+    // Half an export still leaves indices only the bitmap records
+    emit_unclaimed(&mut h, 0, 2_048).expect("first page");
+    let ix = rollover_ix(&h, new_vault_auth, new_vault, 10);
+    assert_fails_with(h.send_as_authority(ix), "ExportIncomplete");
+
+    emit_unclaimed(&mut h, 2_048, 3_000).expect("last page");
+    let ix = rollover_ix(&h, new_vault_auth, new_vault, 10);
+    h.send_as_authority(ix).expect("finalize_and_rollover");
+    assert_eq!(h.token_balance(&new_vault), VAULT_SUPPLY);
+
+    let state = h.state();
+    assert_eq!((state.round, state.total_claims, state.unclaimed_export_cursor), (1, 10, 0));
+    assert_eq!(state.snapshot_hash, NEW_SNAPSHOT_HASH);
+}
+
+#[test]
+fn compressed_round_rolls_over_within_the_bitmap() {
+    let mut h = Harness::new([0; 32], 10, 64);
+    // Stand in for `init_compressed_ledger`: the ledger's capacity outgrows the bitmap
+    let mut state = h.state();
+    state.claim_ledger = Pubkey::new_unique();
+    state.claim_capacity = 1 << 20;
+    let mut account = h.svm.get_account(&h.state).unwrap();
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    account.data[..data.len()].copy_from_slice(&data);
+    h.svm.set_account(h.state, account).unwrap();
+    let ix = h.close_airdrop_ix();
+    h.send_as_authority(ix).expect("close_airdrop");
+    let (new_vault_auth, new_vault) = open_new_vault(&mut h);
+
+    // The next round is back on the 64-bit bitmap
+    let ix = rollover_ix(&h, new_vault_auth, new_vault, 65);
+    assert_fails_with(h.send_as_authority(ix), "CapacityExceeded");
+    let ix = rollover_ix(&h, new_vault_auth, new_vault, 64);
+    h.send_as_authority(ix).expect("finalize_and_rollover");
+    let state = h.state();
+    assert_eq!((state.claim_ledger, state.claim_capacity), (Pubkey::default(), 64));
+}
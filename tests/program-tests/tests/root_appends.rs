@@ -0,0 +1,80 @@
+//! Root appends: `append_allocations` adds leaves after the last one while
+//! claims stay open, and proofs published before the append keep working.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use merkle::{MerkleTree, SegmentedTree};
+use program_tests::{assert_fails_with, event_authority, leaf_hash, Harness};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+
+fn append_allocations_ix(h: &Harness, segment_root: [u8; 32], segment_claims: u64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::AppendAllocations {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AppendAllocations {
+            segment_root,
+            segment_claims,
+            segment_amount: segment_claims * AMOUNT,
+            new_allocations_hash: [0; 32],
+            new_allocations_uri: String::new(),
+        }
+        .data(),
+    }
+}
+
+#[test]
+fn published_proofs_survive_an_append() {
+    let wallets: Vec<Keypair> = (0..4).map(|_| Keypair::new()).collect();
+    let leaves: Vec<[u8; 32]> = wallets
+        .iter()
+        .enumerate()
+        .map(|(index, w)| leaf_hash(index as u64, &w.pubkey(), AMOUNT))
+        .collect();
+    let base = MerkleTree::from_leaves(leaves[..2].to_vec());
+    let mut h = Harness::new(base.root(), 2, 4);
+
+    let (wallet, ata) = h.new_claimant_with(wallets[0].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &base.proof(0).unwrap());
+    h.send(ix, &[&wallet]).expect("claim before the append");
+
+    let mut tree = SegmentedTree::new(leaves[..2].to_vec());
+    let segment_root = tree.append(leaves[2..].to_vec());
+    let ix = append_allocations_ix(&h, segment_root, 2);
+    h.send_as_authority(ix).expect("append_allocations");
+    let state = h.state();
+    assert_eq!((state.merkle_root, state.total_claims), (tree.root(), 4));
+    assert_eq!(state.prior_root_count, 1);
+
+    // The proof published against the base tree still works
+    let (wallet, ata) = h.new_claimant_with(wallets[1].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 1, AMOUNT, &base.proof(1).unwrap());
+    h.send(ix, &[&wallet]).expect("claim with a pre-append proof");
+
+    let (wallet, ata) = h.new_claimant_with(wallets[2].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 2, AMOUNT, &tree.proof(2).unwrap());
+    h.send(ix, &[&wallet]).expect("claim an appended leaf");
+
+    // A segment alone isn't a root the campaign has had
+    let segment = MerkleTree::from_leaves(leaves[2..].to_vec());
+    let (wallet, ata) = h.new_claimant_with(wallets[3].insecure_clone());
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 3, AMOUNT, &segment.proof(1).unwrap());
+    assert_fails_with(h.send(ix, &[&wallet]), "InvalidProof");
+}
+
+#[test]
+fn appends_stay_within_capacity() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 2);
+    let ix = append_allocations_ix(&h, [7; 32], 0);
+    assert_fails_with(h.send_as_authority(ix), "InvalidAppend");
+    let ix = append_allocations_ix(&h, [7; 32], 2);
+    assert_fails_with(h.send_as_authority(ix), "CapacityExceeded");
+}
@@ -0,0 +1,162 @@
+//! Scheduled roots: the authority queues a later wave's root, and the
+//! permissionless `activate_scheduled_root` switches to it once it's due,
+//! including when it leads the first claim transaction after the switch.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use merkle::MerkleTree;
+use program_tests::{assert_fails_with, event_authority, leaf_hash, Harness, VAULT_SUPPLY};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+const AMOUNT: u64 = 1_000;
+const DELAY: i64 = 600;
+
+fn root_schedule(h: &Harness) -> Pubkey {
+    merkledrop_interface::pda::root_schedule(&h.state).0
+}
+
+fn schedule_root_ix(
+    h: &Harness,
+    merkle_root: [u8; 32],
+    total_claims: u64,
+    total_allocation: u64,
+    activation_ts: i64,
+) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ScheduleRoot {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            vault: h.vault,
+            root_schedule: root_schedule(h),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ScheduleRoot {
+            merkle_root,
+            total_claims,
+            total_allocation,
+            activation_ts,
+            allocations_hash: [0; 32],
+            allocations_uri: String::new(),
+        }
+        .data(),
+    }
+}
+
+fn activate_ix(h: &Harness) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ActivateScheduledRoot {
+            state: h.state,
+            root_schedule: root_schedule(h),
+            vault: h.vault,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ActivateScheduledRoot {}.data(),
+    }
+}
+
+#[test]
+fn scheduled_root_takes_over_on_time() {
+    let wallets = [Keypair::new(), Keypair::new()];
+    let first_wave = leaf_hash(0, &wallets[0].pubkey(), AMOUNT);
+    let second_wave = MerkleTree::from_leaves(
+        wallets.iter().enumerate().map(|(i, w)| leaf_hash(i as u64, &w.pubkey(), AMOUNT)).collect(),
+    );
+    let mut h = Harness::new(first_wave, 1, 4);
+
+    let ix = schedule_root_ix(&h, second_wave.root(), 2, 2 * AMOUNT, h.now());
+    assert_fails_with(h.send_as_authority(ix), "InvalidActivationTime");
+    let activation_ts = h.now() + DELAY;
+    let ix = schedule_root_ix(&h, second_wave.root(), 2, 2 * AMOUNT, activation_ts);
+    h.send_as_authority(ix).expect("schedule_root");
+    let ix = schedule_root_ix(&h, [7; 32], 2, 2 * AMOUNT, activation_ts);
+    assert_fails_with(h.send_as_authority(ix), "InvalidActivationTime");
+
+    // Not due yet: the crank changes nothing
+    let ix = activate_ix(&h);
+    h.send_as_authority(ix).expect("early crank is a no-op");
+    assert_eq!((h.state().merkle_root, h.state().root_version), (first_wave, 0));
+
+    // The first claim after the switch carries the crank
+    h.warp(DELAY);
+    let (wallet, ata) = h.new_claimant_with(wallets[1].insecure_clone());
+    let claim = h.claim_ix(&wallet.pubkey(), &ata, 1, AMOUNT, &second_wave.proof(1).unwrap());
+    h.svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[activate_ix(&h), claim],
+        Some(&wallet.pubkey()),
+        &[&wallet],
+        h.svm.latest_blockhash(),
+    );
+    h.svm.send_transaction(tx).expect("activate and claim");
+    let state = h.state();
+    assert_eq!((state.merkle_root, state.total_claims), (second_wave.root(), 2));
+    assert_eq!((state.total_allocation, state.scheduled_root_count), (2 * AMOUNT, 0));
+    assert_eq!(state.root_version, 1);
+
+    // Nothing left to activate, so later bundles still go through
+    let (wallet, ata) = h.new_claimant_with(wallets[0].insecure_clone());
+    let claim = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &second_wave.proof(0).unwrap());
+    h.svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[activate_ix(&h), claim],
+        Some(&wallet.pubkey()),
+        &[&wallet],
+        h.svm.latest_blockhash(),
+    );
+    h.svm.send_transaction(tx).expect("crank with nothing due");
+    assert_eq!(h.state().root_version, 1);
+}
+
+#[test]
+fn cleared_schedule_never_activates() {
+    let mut h = Harness::new([0; 32], 1, 4);
+    let activation_ts = h.now() + DELAY;
+    let ix = schedule_root_ix(&h, [7; 32], 1, AMOUNT, activation_ts);
+    h.send_as_authority(ix).expect("schedule_root");
+    assert_eq!(h.state().scheduled_root_count, 1);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClearRootSchedule {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            root_schedule: root_schedule(&h),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClearRootSchedule {}.data(),
+    };
+    h.send_as_authority(ix).expect("clear_root_schedule");
+    assert_eq!(h.state().scheduled_root_count, 0);
+
+    h.warp(DELAY);
+    let ix = activate_ix(&h);
+    h.send_as_authority(ix).expect("activate_scheduled_root");
+    assert_eq!(h.state().merkle_root, [0; 32]);
+}
+
+#[test]
+fn scheduled_allocation_must_be_covered_by_the_vault() {
+    let mut h = Harness::new([0; 32], 1, 4);
+    let activation_ts = h.now() + DELAY;
+    let ix = schedule_root_ix(&h, [7; 32], 2, 0, activation_ts);
+    assert_fails_with(h.send_as_authority(ix), "InvalidTotalAllocation");
+    let ix = schedule_root_ix(&h, [7; 32], 2, VAULT_SUPPLY + 1, activation_ts);
+    assert_fails_with(h.send_as_authority(ix), "ScheduledRootUnderfunded");
+    let ix = schedule_root_ix(&h, [7; 32], 2, VAULT_SUPPLY, activation_ts);
+    h.send_as_authority(ix).expect("schedule_root");
+}
@@ -0,0 +1,91 @@
+//! `rotate_vault_authority` moves the running round's vault under a new
+//! snapshot hash; claims keep working against the rotated accounts. A locked
+//! config pins the vault where it is.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use litesvm_token::{spl_token, CreateAssociatedTokenAccount};
+use program_tests::{assert_fails_with, event_authority, leaf_hash, Harness, VAULT_SUPPLY};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const AMOUNT: u64 = 1_000;
+const NEW_SNAPSHOT_HASH: [u8; 32] = [0xab; 32];
+
+fn rotate_ix(
+    h: &Harness,
+    new_vault_auth: Pubkey,
+    new_vault: Pubkey,
+    new_snapshot_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::RotateVaultAuthority {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            new_vault_auth,
+            new_vault,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RotateVaultAuthority { new_snapshot_hash }.data(),
+    }
+}
+
+#[test]
+fn rotated_vault_keeps_balance_and_ledger() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+
+    let (new_vault_auth, _) =
+        Pubkey::find_program_address(&[b"vault", NEW_SNAPSHOT_HASH.as_ref()], &airdrop0::ID);
+    let authority = h.authority.insecure_clone();
+    let new_vault = CreateAssociatedTokenAccount::new(&mut h.svm, &authority, &h.mint)
+        .owner(&new_vault_auth)
+        .send()
+        .unwrap();
+
+    let ix = rotate_ix(&h, new_vault_auth, new_vault, NEW_SNAPSHOT_HASH);
+    h.send_as_authority(ix).expect("rotate_vault_authority");
+    assert_eq!(h.token_balance(&new_vault), VAULT_SUPPLY - AMOUNT);
+    assert!(h.svm.get_account(&h.vault).map_or(true, |a| a.lamports == 0));
+
+    let state = h.state();
+    assert_eq!(state.snapshot_hash, NEW_SNAPSHOT_HASH);
+    assert_eq!(state.vault, new_vault);
+
+    // The ledger is on the state, so the claim stays recorded
+    h.vault_auth = new_vault_auth;
+    h.vault = new_vault;
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    let failed = h.send(ix, &[&wallet]).expect_err("claim after rotation");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("AlreadyClaimed")));
+
+    let ix = rotate_ix(&h, new_vault_auth, new_vault, NEW_SNAPSHOT_HASH);
+    let failed = h.send_as_authority(ix).expect_err("rotation to the same hash");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("SnapshotReused")));
+}
+
+#[test]
+fn locked_config_refuses_rotation() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    h.send_as_authority(h.lock_config_ix()).expect("lock_config");
+
+    let (new_vault_auth, _) =
+        Pubkey::find_program_address(&[b"vault", NEW_SNAPSHOT_HASH.as_ref()], &airdrop0::ID);
+    let authority = h.authority.insecure_clone();
+    let new_vault = CreateAssociatedTokenAccount::new(&mut h.svm, &authority, &h.mint)
+        .owner(&new_vault_auth)
+        .send()
+        .unwrap();
+    let ix = rotate_ix(&h, new_vault_auth, new_vault, NEW_SNAPSHOT_HASH);
+    assert_fails_with(h.send_as_authority(ix), "ConfigLocked");
+    assert_eq!(h.token_balance(&h.vault), VAULT_SUPPLY);
+}
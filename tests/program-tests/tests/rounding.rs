@@ -0,0 +1,118 @@
+//! Rounding policy: the campaign's `rounding` decides which side of a fee
+//! split a fractional base unit lands on, and the split always adds up to
+//! the gross claim.
+
+use airdrop0::{accounts, instruction, Rounding};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use program_tests::{event_authority, leaf_hash, Harness};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+// 2.5% of 1_001 is 25.025
+const AMOUNT: u64 = 1_001;
+const FEE_BPS: u16 = 250;
+
+fn set_rounding_ix(h: &Harness, rounding: Rounding) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetRounding {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetRounding { rounding }.data(),
+    }
+}
+
+fn set_operator_fee_ix(h: &Harness, treasury: Pubkey, fee_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetOperatorFee {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetOperatorFee { fee_bps: FEE_BPS }.data(),
+    }
+}
+
+// Fee taken from one claim of `AMOUNT` under `rounding`, checked against
+// what the claimant received
+fn claimed_fee(rounding: Rounding) -> u64 {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+    let (treasury, treasury_tokens) = h.init_treasury();
+    let ix = set_operator_fee_ix(&h, treasury, treasury_tokens);
+    h.send_as_authority(ix).expect("set_operator_fee");
+    h.send_as_authority(set_rounding_ix(&h, rounding)).expect("set_rounding");
+    assert_eq!(h.state().rounding, rounding);
+
+    h.fee_account = Some(treasury_tokens);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+    let fee = h.token_balance(&treasury_tokens);
+    assert_eq!(fee + h.token_balance(&ata), AMOUNT);
+    fee
+}
+
+#[test]
+fn rounding_moves_the_remainder_of_a_fee() {
+    assert_eq!(claimed_fee(Rounding::Floor), 25);
+    assert_eq!(claimed_fee(Rounding::Ceil), 26);
+    assert_eq!(claimed_fee(Rounding::HalfEven), 25);
+}
+
+// Rounding up both the fee and the penalty of a 1-unit claim would take 2
+#[test]
+fn ceil_penalty_is_capped_at_what_the_fee_leaves() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), 1), 1).expect("set_merkle_root");
+    let (treasury, treasury_tokens) = h.init_treasury();
+    let ix = set_operator_fee_ix(&h, treasury, treasury_tokens);
+    h.send_as_authority(ix).expect("set_operator_fee");
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetLatePenalty {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account: treasury_tokens,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetLatePenalty {
+            start_ts: h.now() - 1,
+            ramp: 0,
+            penalty_bps: 1_000,
+        }
+        .data(),
+    };
+    h.send_as_authority(ix).expect("set_late_penalty");
+    h.send_as_authority(set_rounding_ix(&h, Rounding::Ceil)).expect("set_rounding");
+
+    h.fee_account = Some(treasury_tokens);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, 1, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+    assert_eq!(h.token_balance(&treasury_tokens), 1);
+    assert_eq!(h.token_balance(&ata), 0);
+    assert_eq!(h.state().fees_collected, 1);
+    assert_eq!(h.state().penalties_collected, 0);
+}
+
+#[test]
+fn half_even_breaks_ties_to_even() {
+    assert_eq!(Rounding::HalfEven.div(5, 2), 2);
+    assert_eq!(Rounding::HalfEven.div(7, 2), 4);
+    assert_eq!(Rounding::HalfEven.div(2, 3), 1);
+    assert_eq!(Rounding::HalfEven.div(1, 3), 0);
+    assert_eq!(Rounding::Ceil.div(1, 3), 1);
+    assert_eq!(Rounding::Floor.div(2, 3), 0);
+}
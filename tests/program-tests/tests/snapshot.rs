@@ -0,0 +1,56 @@
+//! Canonical snapshots: the same allocations encode to the same bytes in any
+//! input order, decoding re-checks the root, and the root is the one a
+//! campaign built from them carries on-chain.
+
+use merkle::{Allocation, LeafKind, Snapshot, SnapshotError};
+use program_tests::Harness;
+use solana_sdk::pubkey::Pubkey;
+
+fn allocations() -> Vec<Allocation> {
+    (0..5)
+        .map(|index| Allocation {
+            index,
+            account: Pubkey::new_unique().to_bytes().to_vec(),
+            amount: 1_000 + index,
+        })
+        .collect()
+}
+
+#[test]
+fn encoding_is_canonical() {
+    let allocations = allocations();
+    let mut shuffled = allocations.clone();
+    shuffled.reverse();
+    let snapshot = Snapshot::new(LeafKind::Native, allocations).unwrap();
+    let bytes = snapshot.encode();
+    assert_eq!(Snapshot::new(LeafKind::Native, shuffled).unwrap().encode(), bytes);
+    assert_eq!(Snapshot::decode(&bytes).unwrap(), snapshot);
+    assert_eq!(merkle::LEAF_FORMAT_VERSION, airdrop0::LEAF_FORMAT_VERSION);
+}
+
+#[test]
+fn root_matches_the_campaign() {
+    let snapshot = Snapshot::new(LeafKind::Native, allocations()).unwrap();
+    let h = Harness::new(snapshot.root(), 5, 1_000);
+    let data = h.svm.get_account(&h.state).unwrap().data;
+    let state = merkledrop_interface::State::decode(&data).unwrap();
+    assert_eq!(Snapshot::decode(&snapshot.encode()).unwrap().root(), state.merkle_root);
+}
+
+#[test]
+fn tampering_is_rejected() {
+    let mut allocations = allocations();
+    let bytes = Snapshot::new(LeafKind::Native, allocations.clone()).unwrap().encode();
+
+    // Amount of the first entry: header (16) + index (8) + length (1) + account (32)
+    let mut tampered = bytes.clone();
+    tampered[16 + 8 + 1 + 32] ^= 1;
+    assert_eq!(Snapshot::decode(&tampered), Err(SnapshotError::RootMismatch));
+    assert_eq!(Snapshot::decode(&bytes[..bytes.len() - 1]), Err(SnapshotError::Truncated));
+
+    allocations.remove(2);
+    assert_eq!(
+        Snapshot::new(LeafKind::Native, allocations).map(|_| ()),
+        Err(SnapshotError::IndexGap(2))
+    );
+}
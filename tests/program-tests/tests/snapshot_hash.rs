@@ -0,0 +1,44 @@
+//! `assert_snapshot_hash`: a snapshot hash derived from the allocation list
+//! passes; an arbitrary one, or a root swapped in afterwards, fails.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use merkle::MerkleTree;
+use program_tests::{assert_fails_with, Harness, TxResult, VAULT_SUPPLY};
+use solana_sdk::pubkey::Pubkey;
+
+fn assert_snapshot_hash(h: &mut Harness) -> TxResult {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::AssertSnapshotHash { state: h.state }.to_account_metas(None),
+        data: instruction::AssertSnapshotHash {}.data(),
+    };
+    let payer = h.authority.insecure_clone();
+    h.send(ix, &[&payer])
+}
+
+#[test]
+fn derived_snapshot_hash_matches_root() {
+    // The harness funds and commits to `VAULT_SUPPLY`, so the list sums to it
+    let amounts = [VAULT_SUPPLY / 4, VAULT_SUPPLY / 4 * 3];
+    let leaves = amounts
+        .iter()
+        .enumerate()
+        .map(|(index, amount)| merkle::leaf(index as u64, &Pubkey::new_unique().to_bytes(), *amount))
+        .collect();
+    let tree = MerkleTree::from_leaves(leaves);
+    let snapshot_hash = merkle::snapshot_hash(&tree.root(), 2, VAULT_SUPPLY);
+
+    let mut h = Harness::with_snapshot(snapshot_hash, tree.root(), 2, 2);
+    assert_snapshot_hash(&mut h).expect("assert_snapshot_hash");
+
+    // A root from some other list no longer matches the snapshot
+    h.set_merkle_root([7; 32], 2).expect("set_merkle_root");
+    assert_fails_with(assert_snapshot_hash(&mut h), "SnapshotMismatch");
+}
+
+#[test]
+fn arbitrary_snapshot_hash_fails() {
+    let mut h = Harness::new([1; 32], 1, 1);
+    assert_fails_with(assert_snapshot_hash(&mut h), "SnapshotMismatch");
+}
@@ -0,0 +1,146 @@
+//! USD-denominated leaves: `claim` pays them in tokens at a posted Pyth
+//! price, refusing stale, uncertain or foreign price updates, and
+//! `validate_claim` dry-runs them at the same price.
+
+use airdrop0::{accounts, instruction, ClaimValidation, ErrorCode, PYTH_RECEIVER};
+use anchor_lang::{
+    solana_program::instruction::Instruction, AnchorDeserialize, InstructionData, ToAccountMetas,
+};
+use program_tests::{assert_fails_with, event_authority, leaf_hash, pad_proof, Harness, TxResult};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+const FEED: [u8; 32] = [0x42; 32];
+const USD_AMOUNT: u64 = 50_000_000; // $50
+const PRICE: i64 = 200_000_000; // $2.00 at exponent -8
+const MAX_PRICE_AGE: u64 = 60;
+const MAX_PRICE_CONF_BPS: u16 = 100;
+
+fn set_usd_pricing_ix(h: &Harness, price_feed: [u8; 32], max_price_age: u64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetUsdPricing {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetUsdPricing {
+            price_feed,
+            max_price_age,
+            max_price_conf_bps: MAX_PRICE_CONF_BPS,
+        }
+        .data(),
+    }
+}
+
+// A fully verified receiver `PriceUpdateV2` account
+fn post_price(h: &mut Harness, feed_id: [u8; 32], conf: u64, publish_time: i64) -> Pubkey {
+    let mut data = vec![34, 241, 35, 99, 157, 126, 244, 205];
+    data.extend_from_slice(&[0; 32]); // write authority
+    data.push(1); // VerificationLevel::Full
+    data.extend_from_slice(&feed_id);
+    data.extend_from_slice(&PRICE.to_le_bytes());
+    data.extend_from_slice(&conf.to_le_bytes());
+    data.extend_from_slice(&(-8i32).to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&[0; 8 + 8 + 8 + 8]); // prev_publish_time, ema_price, ema_conf, posted_slot
+
+    let address = Pubkey::new_unique();
+    let account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: PYTH_RECEIVER,
+        executable: false,
+        rent_epoch: 0,
+    };
+    h.svm.set_account(address, account).unwrap();
+    address
+}
+
+fn claim_at(h: &mut Harness, wallet: &Keypair, ata: &Pubkey, price_update: Option<Pubkey>) -> TxResult {
+    h.price_update = price_update;
+    let ix = h.claim_ix(&wallet.pubkey(), ata, 0, USD_AMOUNT, &[]);
+    h.send(ix, &[wallet])
+}
+
+fn validate(h: &mut Harness, wallet: &Pubkey, price_update: Option<Pubkey>) -> ClaimValidation {
+    let (proof, proof_len) = pad_proof(&[]);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ValidateClaim {
+            state: h.state,
+            wallet: *wallet,
+            vault: h.vault,
+            gate_token_account: None,
+            gate_metadata: None,
+            wallet_claims: None,
+            family_claim: None,
+            price_update,
+            mint: Some(h.mint),
+        }
+        .to_account_metas(None),
+        data: instruction::ValidateClaim {
+            index: 0,
+            amount: USD_AMOUNT,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    };
+    let payer = Keypair::new();
+    h.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        h.svm.latest_blockhash(),
+    );
+    let simulated = h.svm.simulate_transaction(tx).expect("validate_claim");
+    ClaimValidation::try_from_slice(&simulated.meta.return_data.data).unwrap()
+}
+
+#[test]
+fn usd_leaf_pays_at_posted_price() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), USD_AMOUNT), 1, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+
+    let ix = set_usd_pricing_ix(&h, FEED, MAX_PRICE_AGE);
+    assert_fails_with(h.send_as_authority(ix), "ClaimsNotPaused");
+    let state = h.state();
+    let ix = h.close_airdrop_ix();
+    h.send_as_authority(ix).expect("close_airdrop");
+    let ix = set_usd_pricing_ix(&h, FEED, 0);
+    assert_fails_with(h.send_as_authority(ix), "InvalidPricingConfig");
+    let ix = set_usd_pricing_ix(&h, FEED, MAX_PRICE_AGE);
+    h.send_as_authority(ix).expect("set_usd_pricing");
+    let ix = h.update_claim_window_ix(state.claim_start_ts, state.claim_duration);
+    h.send_as_authority(ix).expect("update_claim_window");
+
+    let now = h.now();
+    assert_fails_with(claim_at(&mut h, &wallet, &ata, None), "UsdPriceRequired");
+    let stale = post_price(&mut h, FEED, 0, now - MAX_PRICE_AGE as i64 - 1);
+    assert_fails_with(claim_at(&mut h, &wallet, &ata, Some(stale)), "StalePrice");
+    let uncertain = post_price(&mut h, FEED, PRICE as u64 / 50, now);
+    assert_fails_with(claim_at(&mut h, &wallet, &ata, Some(uncertain)), "PriceTooUncertain");
+    let foreign = post_price(&mut h, [0x43; 32], 0, now);
+    assert_fails_with(claim_at(&mut h, &wallet, &ata, Some(foreign)), "InvalidPriceUpdate");
+
+    let fresh = post_price(&mut h, FEED, PRICE as u64 / 1_000, now);
+    let unpriced = validate(&mut h, &wallet.pubkey(), None);
+    assert_eq!(unpriced.error_code, u32::from(ErrorCode::UsdPriceRequired));
+    let priced = validate(&mut h, &wallet.pubkey(), Some(fresh));
+    assert_eq!(priced.error_code, 0);
+    assert_eq!(priced.net_amount, 25);
+    claim_at(&mut h, &wallet, &ata, Some(fresh)).expect("claim");
+    // $50 at $2.00 a token, zero decimals
+    assert_eq!(h.token_balance(&ata), 25);
+    assert_eq!(h.state().claimed_amount, 25);
+}
@@ -0,0 +1,169 @@
+//! `validate_claim` reports, via simulation return data, the error `claim`
+//! would fail with, or the fee and penalty it would take, without changing
+//! anything.
+
+use airdrop0::{accounts, instruction, ClaimValidation, ErrorCode};
+use anchor_lang::{
+    solana_program::instruction::Instruction, AnchorDeserialize, AnchorSerialize, InstructionData,
+    ToAccountMetas,
+};
+use merkledrop_interface::{
+    ClaimError, ClaimValidation as InterfaceValidation, Remediation, State,
+};
+use program_tests::{event_authority, leaf_hash, pad_proof, Harness};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+const AMOUNT: u64 = 1_000;
+const FEE_BPS: u16 = 100;
+const PENALTY_BPS: u16 = 1_000;
+
+fn set_operator_fee_ix(h: &Harness, treasury: Pubkey, fee_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetOperatorFee {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetOperatorFee { fee_bps: FEE_BPS }.data(),
+    }
+}
+
+fn set_late_penalty_ix(h: &Harness, treasury: Pubkey, fee_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetLatePenalty {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetLatePenalty {
+            start_ts: h.now() - 1,
+            ramp: 0,
+            penalty_bps: PENALTY_BPS,
+        }
+        .data(),
+    }
+}
+
+fn encode(value: &impl AnchorSerialize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.serialize(&mut bytes).unwrap();
+    bytes
+}
+
+fn validate(h: &mut Harness, wallet: &Pubkey, amount: u64) -> ClaimValidation {
+    let (proof, proof_len) = pad_proof(&[]);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ValidateClaim {
+            state: h.state,
+            wallet: *wallet,
+            vault: h.vault,
+            gate_token_account: None,
+            gate_metadata: None,
+            wallet_claims: None,
+            family_claim: None,
+            price_update: None,
+            mint: None,
+        }
+        .to_account_metas(None),
+        data: instruction::ValidateClaim {
+            index: 0,
+            amount,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    };
+    let payer = Keypair::new();
+    h.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        h.svm.latest_blockhash(),
+    );
+    let simulated = h.svm.simulate_transaction(tx).expect("validate_claim");
+    ClaimValidation::try_from_slice(&simulated.meta.return_data.data).unwrap()
+}
+
+#[test]
+fn reports_what_claim_would_do() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let ok = validate(&mut h, &wallet.pubkey(), AMOUNT);
+    assert_eq!(ok.error_code, 0);
+    assert_eq!(ok.net_amount, AMOUNT);
+
+    let wrong_amount = validate(&mut h, &wallet.pubkey(), AMOUNT + 1);
+    assert_eq!(wrong_amount.error_code, u32::from(ErrorCode::InvalidProof));
+
+    // The dry run recorded nothing, so the real claim still goes through
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+
+    let repeat = validate(&mut h, &wallet.pubkey(), AMOUNT);
+    assert_eq!(repeat.error_code, u32::from(ErrorCode::AlreadyClaimed));
+}
+
+#[test]
+fn failures_map_to_remediation() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, _) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+    let data = h.svm.get_account(&h.state).unwrap().data;
+    let state = State::decode(&data).unwrap();
+
+    let validation = validate(&mut h, &wallet.pubkey(), AMOUNT + 1);
+    let mirror = InterfaceValidation::try_from_slice(&encode(&validation)).unwrap();
+    let code = mirror.check().expect_err("wrong amount fails");
+    let error = ClaimError::from_code(code).expect("claimant-facing error");
+    assert_eq!(error, ClaimError::InvalidProof);
+    assert_eq!(error.remediation(&state), Remediation::RebuildProof);
+
+    let ok = validate(&mut h, &wallet.pubkey(), AMOUNT);
+    let mirror = InterfaceValidation::try_from_slice(&encode(&ok)).unwrap();
+    assert_eq!(mirror.check(), Ok(()));
+    assert_eq!(mirror.net_amount, AMOUNT);
+}
+
+#[test]
+fn reports_the_fee_and_penalty_claim_would_take() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+    let (treasury, treasury_tokens) = h.init_treasury();
+    let ix = set_operator_fee_ix(&h, treasury, treasury_tokens);
+    h.send_as_authority(ix).expect("set_operator_fee");
+    let ix = set_late_penalty_ix(&h, treasury, treasury_tokens);
+    h.send_as_authority(ix).expect("set_late_penalty");
+
+    let checked = validate(&mut h, &wallet.pubkey(), AMOUNT);
+    assert_eq!(checked.error_code, 0);
+    assert_eq!(checked.fee, AMOUNT * FEE_BPS as u64 / 10_000);
+    assert_eq!(checked.penalty, AMOUNT * PENALTY_BPS as u64 / 10_000);
+    assert_eq!(checked.net_amount, AMOUNT - checked.fee - checked.penalty);
+
+    // The real claim splits the allocation the same way
+    h.fee_account = Some(treasury_tokens);
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    h.send(ix, &[&wallet]).expect("claim");
+    assert_eq!(h.token_balance(&ata), checked.net_amount);
+    assert_eq!(h.token_balance(&treasury_tokens), checked.fee + checked.penalty);
+}
@@ -0,0 +1,246 @@
+//! Leaf-encoded vesting: the schedule is part of the leaf, so it can't be
+//! claimed as a plain allocation, and `release_vested` pays the remainder as
+//! it unlocks.
+
+use airdrop0::{accounts, instruction, VestedAmount, VestingSchedule};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    AnchorDeserialize, InstructionData, ToAccountMetas,
+};
+use program_tests::{event_authority, pad_proof, Harness, SNAPSHOT_HASH};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+const AMOUNT: u64 = 10_000;
+const SCHEDULE: VestingSchedule = VestingSchedule { cliff: 100, duration: 1_000, tge_bps: 2_000 };
+
+fn vesting_leaf(index: u64, wallet: &Pubkey, amount: u64, schedule: &VestingSchedule) -> [u8; 32] {
+    let VestingSchedule { cliff, duration, tge_bps } = *schedule;
+    merkle::vesting_leaf(index, &wallet.to_bytes(), amount, cliff, duration, tge_bps)
+}
+
+fn vesting_account(index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vesting", SNAPSHOT_HASH.as_ref(), &index.to_le_bytes()],
+        &airdrop0::ID,
+    )
+    .0
+}
+
+fn claim_vested_ix(h: &Harness, wallet: &Pubkey, user_ata: &Pubkey) -> Instruction {
+    let (proof, proof_len) = pad_proof(&[]);
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimVested {
+            state: h.state,
+            wallet: *wallet,
+            vesting: vesting_account(0),
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: litesvm_token::spl_token::ID,
+            system_program: system_program::ID,
+            fee_account: h.fee_account,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            badge_mint: None,
+            badge_account: None,
+            token_2022_program: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimVested {
+            index: 0,
+            amount: AMOUNT,
+            schedule: SCHEDULE,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    }
+}
+
+// Zero-fee operator config, so the penalty has a treasury account to go to
+fn set_treasury_ix(h: &Harness, treasury: Pubkey, fee_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetOperatorFee {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetOperatorFee { fee_bps: 0 }.data(),
+    }
+}
+
+fn set_late_penalty_ix(
+    h: &Harness,
+    treasury: Pubkey,
+    fee_account: Pubkey,
+    penalty_bps: u16,
+) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetLatePenalty {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            treasury,
+            fee_account,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetLatePenalty {
+            start_ts: h.now() - 1,
+            ramp: 0,
+            penalty_bps,
+        }
+        .data(),
+    }
+}
+
+fn release_vested_ix(h: &Harness, wallet: &Pubkey, user_ata: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ReleaseVested {
+            state: h.state,
+            vesting: vesting_account(0),
+            wallet: *wallet,
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: litesvm_token::spl_token::ID,
+            fee_account: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ReleaseVested {}.data(),
+    }
+}
+
+fn get_vested_amount(h: &mut Harness, at: Option<i64>) -> VestedAmount {
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::GetVestedAmount {
+            state: h.state,
+            vesting: vesting_account(0),
+        }
+        .to_account_metas(None),
+        data: instruction::GetVestedAmount { index: 0, at }.data(),
+    };
+    let payer = Keypair::new();
+    h.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        h.svm.latest_blockhash(),
+    );
+    let simulated = h.svm.simulate_transaction(tx).expect("get_vested_amount");
+    VestedAmount::try_from_slice(&simulated.meta.return_data.data).unwrap()
+}
+
+#[test]
+fn vesting_leaf_releases_on_schedule() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let root = vesting_leaf(0, &wallet.pubkey(), AMOUNT, &SCHEDULE);
+    h.set_merkle_root(root, 1).expect("set_merkle_root");
+
+    // The schedule is part of the leaf; a plain claim can't skip it
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    let failed = h.send(ix, &[&wallet]).expect_err("plain claim of a vesting leaf");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("InvalidProof")));
+
+    // The harness opens the window 60s ago: before the cliff, only the TGE share
+    let ix = claim_vested_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("claim_vested");
+    assert_eq!(h.token_balance(&ata), 2_000);
+
+    let ix = release_vested_ix(&h, &wallet.pubkey(), &ata);
+    let failed = h.send(ix, &[&wallet]).expect_err("nothing new before the cliff");
+    assert!(failed.meta.logs.iter().any(|log| log.contains("NothingVested")));
+
+    // Halfway through the duration: TGE plus half of the rest
+    h.warp(440);
+    let ix = release_vested_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("release_vested");
+    assert_eq!(h.token_balance(&ata), 6_000);
+
+    h.warp(1_000);
+    let ix = release_vested_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("final release");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+    assert!(h.svm.get_account(&vesting_account(0)).map_or(true, |a| a.lamports == 0));
+}
+
+#[test]
+fn vested_amount_reports_the_schedule() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let root = vesting_leaf(0, &wallet.pubkey(), AMOUNT, &SCHEDULE);
+    h.set_merkle_root(root, 1).expect("set_merkle_root");
+    let ix = claim_vested_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("claim_vested");
+    let start_ts = h.state().claim_start_ts;
+
+    // 60s in, before the cliff: the TGE share is out, the cliff unlocks next
+    let now = get_vested_amount(&mut h, None);
+    assert_eq!((now.vested, now.released, now.withdrawable), (2_000, 2_000, 0));
+    assert_eq!(now.next_unlock_ts, start_ts + SCHEDULE.cliff);
+    assert_eq!(now.fully_vested_ts, start_ts + SCHEDULE.duration);
+
+    // Halfway: matches what `release_vested` pays there, 8 tokens a second
+    let half = get_vested_amount(&mut h, Some(start_ts + 500));
+    assert_eq!((half.vested, half.withdrawable), (6_000, 4_000));
+    assert_eq!(half.next_unlock_ts, start_ts + 501);
+    h.warp(440);
+    let ix = release_vested_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("release_vested");
+    assert_eq!(h.token_balance(&ata), 2_000 + half.withdrawable);
+
+    let done = get_vested_amount(&mut h, Some(start_ts + SCHEDULE.duration));
+    assert_eq!((done.vested, done.withdrawable, done.next_unlock_ts), (AMOUNT, 4_000, 0));
+    let before = get_vested_amount(&mut h, Some(start_ts - 1));
+    assert_eq!((before.vested, before.next_unlock_ts), (0, start_ts));
+}
+
+#[test]
+fn late_vested_claim_forfeits_the_penalty_up_front() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    let root = vesting_leaf(0, &wallet.pubkey(), AMOUNT, &SCHEDULE);
+    h.set_merkle_root(root, 1).expect("set_merkle_root");
+    let (treasury, treasury_tokens) = h.init_treasury();
+    h.send_as_authority(set_treasury_ix(&h, treasury, treasury_tokens)).expect("set_operator_fee");
+    let ix = set_late_penalty_ix(&h, treasury, treasury_tokens, 1_000);
+    h.send_as_authority(ix).expect("set_late_penalty");
+
+    // 10% of the whole allocation goes at the claim; the TGE share is of the rest
+    h.fee_account = Some(treasury_tokens);
+    let ix = claim_vested_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("claim_vested");
+    assert_eq!(h.token_balance(&treasury_tokens), 1_000);
+    assert_eq!(h.token_balance(&ata), 1_800);
+
+    h.warp(1_000);
+    let ix = release_vested_ix(&h, &wallet.pubkey(), &ata);
+    h.send(ix, &[&wallet]).expect("final release");
+    assert_eq!(h.token_balance(&ata), AMOUNT - 1_000);
+    assert_eq!(h.token_balance(&treasury_tokens), 1_000);
+}
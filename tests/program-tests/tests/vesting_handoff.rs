@@ -0,0 +1,142 @@
+//! Vesting handoff: while a handoff is set, claims reach the wallet only
+//! through the configured program, invoked from the template with the
+//! claim's amount written in. SPL Token's `Transfer` stands in for a
+//! vesting program's "create stream" here.
+
+use airdrop0::{accounts, instruction, HandoffAccount};
+use anchor_lang::{
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        system_program,
+    },
+    InstructionData, ToAccountMetas,
+};
+use litesvm_token::{spl_token, CreateAssociatedTokenAccount};
+use program_tests::{assert_fails_with, event_authority, leaf_hash, pad_proof, Harness};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+// `Transfer` is tag 3 followed by the amount
+const TRANSFER: [u8; 9] = [3, 0, 0, 0, 0, 0, 0, 0, 0];
+
+fn handoff(h: &Harness) -> Pubkey {
+    Pubkey::find_program_address(&[b"vesting_handoff", h.state.as_ref()], &airdrop0::ID).0
+}
+
+fn sender(h: &Harness) -> Pubkey {
+    Pubkey::find_program_address(&[b"vesting_sender", h.state.as_ref()], &airdrop0::ID).0
+}
+
+fn set_vesting_handoff_ix(h: &Harness, amount_offset: u16) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetVestingHandoff {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            handoff: handoff(h),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetVestingHandoff {
+            program: spl_token::ID,
+            data: TRANSFER.to_vec(),
+            amount_offset,
+            accounts: vec![
+                HandoffAccount::SenderTokens,
+                HandoffAccount::Free { is_writable: true, is_signer: false },
+                HandoffAccount::Sender,
+                HandoffAccount::Recipient { is_writable: false },
+            ],
+        }
+        .data(),
+    }
+}
+
+// Claims leaf 0, filling the template's free slot with `lock`
+fn claim_to_vesting_ix(
+    h: &Harness,
+    wallet: &Pubkey,
+    sender_tokens: Pubkey,
+    lock: Pubkey,
+) -> Instruction {
+    let (proof, proof_len) = pad_proof(&[]);
+    let mut accounts = accounts::ClaimToVesting {
+        state: h.state,
+        handoff: handoff(h),
+        vesting_program: spl_token::ID,
+        sender: sender(h),
+        sender_tokens,
+        wallet: *wallet,
+        vault_auth: h.vault_auth,
+        vault: h.vault,
+        mint: h.mint,
+        token_program: spl_token::ID,
+        fee_account: None,
+        stats: None,
+        wallet_claims: None,
+        family_claim: None,
+        gate_token_account: None,
+        gate_metadata: None,
+        bonus_vault: None,
+        badge_mint: None,
+        badge_account: None,
+        token_2022_program: None,
+        event_authority: event_authority(),
+        program: airdrop0::ID,
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new(lock, false));
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts,
+        data: instruction::ClaimToVesting {
+            index: 0,
+            amount: AMOUNT,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    }
+}
+
+#[test]
+fn claim_opens_the_templated_lock_instead_of_paying_out() {
+    let wallet = Keypair::new();
+    let mut h = Harness::new(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1, 1);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let sender = sender(&h);
+    let sender_tokens = CreateAssociatedTokenAccount::new(&mut h.svm, &h.authority, &h.mint)
+        .owner(&sender)
+        .send()
+        .unwrap();
+    // Stands in for the stream's escrow
+    let lock = CreateAssociatedTokenAccount::new(&mut h.svm, &h.authority, &h.mint)
+        .owner(&Pubkey::new_unique())
+        .send()
+        .unwrap();
+
+    // The amount must fit inside the template
+    assert_fails_with(h.send_as_authority(set_vesting_handoff_ix(&h, 2)), "InvalidVestingHandoff");
+    h.send_as_authority(set_vesting_handoff_ix(&h, 1)).expect("set_vesting_handoff");
+    assert_eq!(h.state().vesting_program, spl_token::ID);
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "VestingHandoffRequired");
+
+    let vault_before = h.token_balance(&h.vault);
+    let ix = claim_to_vesting_ix(&h, &wallet.pubkey(), sender_tokens, lock);
+    h.send(ix, &[&wallet]).expect("claim_to_vesting");
+    assert_eq!(h.token_balance(&lock), AMOUNT);
+    assert_eq!(h.token_balance(&sender_tokens), 0);
+    assert_eq!(h.token_balance(&ata), 0);
+    assert_eq!(h.token_balance(&h.vault), vault_before - AMOUNT);
+
+    // The handoff is fixed once claims have begun
+    assert_fails_with(h.send_as_authority(set_vesting_handoff_ix(&h, 1)), "ClaimsAlreadyRecorded");
+}
@@ -0,0 +1,86 @@
+//! Voided indices: `void_index` retires an allocation for good, keeps its
+//! tokens in the unclaimed supply, and records the authority's reason hash.
+
+use airdrop0::{accounts, instruction, VoidedIndex};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use merkle::MerkleTree;
+use program_tests::{assert_fails_with, event_authority, leaf_hash, Harness};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+const REASON: [u8; 32] = [7; 32];
+
+fn void_index_ix(h: &Harness, authority: &Keypair, index: u64) -> Instruction {
+    let voided_index = merkledrop_interface::pda::voided_index(&h.state().snapshot_hash, index).0;
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::VoidIndex {
+            state: h.state,
+            authority: authority.pubkey(),
+            voided_index,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::VoidIndex {
+            index,
+            reason_hash: REASON,
+        }
+        .data(),
+    }
+}
+
+#[test]
+fn voided_index_can_never_be_claimed() {
+    let wallets: Vec<Keypair> = (0..2).map(|_| Keypair::new()).collect();
+    let tree = MerkleTree::from_leaves(
+        wallets
+            .iter()
+            .enumerate()
+            .map(|(index, w)| leaf_hash(index as u64, &w.pubkey(), AMOUNT))
+            .collect(),
+    );
+    let mut h = Harness::new(tree.root(), 2, 1_000);
+    let claimants: Vec<_> = wallets.into_iter().map(|w| h.new_claimant_with(w)).collect();
+
+    let authority = h.authority.insecure_clone();
+    let ix = void_index_ix(&h, &authority, 1);
+    h.send_as_authority(ix).expect("void_index");
+
+    let record = merkledrop_interface::pda::voided_index(&h.state().snapshot_hash, 1).0;
+    let data = h.svm.get_account(&record).unwrap().data;
+    let voided = VoidedIndex::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!((voided.index, voided.reason_hash), (1, REASON));
+
+    // Not counted as claimed, so the allocation stays in the unclaimed supply
+    let state = h.state();
+    assert_eq!((state.claimed_count, state.claimed_amount), (0, 0));
+
+    let (wallet, ata) = &claimants[1];
+    let ix = h.claim_ix(&wallet.pubkey(), ata, 1, AMOUNT, &tree.proof(1).unwrap());
+    assert_fails_with(h.send(ix, &[wallet]), "AlreadyClaimed");
+
+    let (wallet, ata) = &claimants[0];
+    let ix = h.claim_ix(&wallet.pubkey(), ata, 0, AMOUNT, &tree.proof(0).unwrap());
+    h.send(ix, &[wallet]).expect("claim of another index");
+    // A claimed index can't be voided after the fact
+    let ix = void_index_ix(&h, &authority, 0);
+    assert_fails_with(h.send_as_authority(ix), "AlreadyClaimed");
+}
+
+#[test]
+fn only_the_authority_voids() {
+    let mut h = Harness::new([0; 32], 2, 1_000);
+    let stranger = Keypair::new();
+    h.svm.airdrop(&stranger.pubkey(), 1_000_000_000).unwrap();
+    let ix = void_index_ix(&h, &stranger, 0);
+    assert_fails_with(h.send(ix, &[&stranger]), "ConstraintHasOne");
+
+    let authority = h.authority.insecure_clone();
+    let ix = void_index_ix(&h, &authority, 2);
+    assert_fails_with(h.send_as_authority(ix), "InvalidIndex");
+}
@@ -0,0 +1,59 @@
+//! The per-wallet cap bounds what one wallet claims per round across all of
+//! its leaves, however many the snapshot handed it.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use merkle::MerkleTree;
+use program_tests::{assert_fails_with, event_authority, leaf_hash, wallet_claims, Harness};
+use solana_sdk::signature::{Keypair, Signer};
+
+const AMOUNT: u64 = 1_000;
+
+#[test]
+fn cap_spans_a_wallets_leaves() {
+    // A buggy snapshot gave one wallet three leaves
+    let wallet = Keypair::new();
+    let tree = MerkleTree::from_leaves((0..3).map(|i| leaf_hash(i, &wallet.pubkey(), AMOUNT)).collect());
+    let mut h = Harness::new(tree.root(), 3, 1_000);
+    let (wallet, ata) = h.new_claimant_with(wallet);
+    let claim = |h: &mut Harness, index: usize| {
+        let ix = h.claim_ix(&wallet.pubkey(), &ata, index as u64, AMOUNT, &tree.proof(index).unwrap());
+        h.send(ix, &[&wallet])
+    };
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetWalletCap {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetWalletCap { wallet_cap: 2 * AMOUNT }.data(),
+    };
+    h.send_as_authority(ix).expect("set_wallet_cap");
+    assert_fails_with(claim(&mut h, 0), "WalletClaimsMissing");
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::InitWalletClaims {
+            state: h.state,
+            payer: wallet.pubkey(),
+            wallet_claims: wallet_claims(&h.state, &wallet.pubkey()),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitWalletClaims { wallet: wallet.pubkey() }.data(),
+    };
+    h.send(ix, &[&wallet]).expect("init_wallet_claims");
+    h.track_wallet_claims = true;
+
+    claim(&mut h, 0).expect("first leaf");
+    claim(&mut h, 1).expect("second leaf, at the cap");
+    assert_fails_with(claim(&mut h, 2), "WalletCapExceeded");
+    assert_eq!(h.token_balance(&ata), 2 * AMOUNT);
+}
@@ -0,0 +1,82 @@
+//! `update_claim_window` rejects windows whose end overflows i64 or that
+//! start implausibly far from the current time, such as a start given in
+//! milliseconds; `set_max_start_lead` narrows how far ahead a start may be.
+
+use airdrop0::{accounts, instruction};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use program_tests::{assert_fails_with, event_authority, Harness, CLAIM_DURATION};
+use solana_sdk::{clock::Clock, signature::Signer};
+
+const YEAR: i64 = 365 * 86_400;
+const DAY: i64 = 86_400;
+
+fn set_max_start_lead_ix(h: &Harness, max_start_lead: i64) -> Instruction {
+    Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetMaxStartLead {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetMaxStartLead { max_start_lead }.data(),
+    }
+}
+
+#[test]
+fn rejects_out_of_range_windows() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    // A mainnet-like clock, so zero and millisecond starts are far from it
+    let mut clock = h.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = 1_750_000_000;
+    h.svm.set_sysvar(&clock);
+    let now = h.now();
+
+    for (start, duration, error) in [
+        (now, i64::MAX, "WindowOverflow"),
+        (now + 20 * YEAR, CLAIM_DURATION, "ClaimStartOutOfRange"),
+        (now - 20 * YEAR, CLAIM_DURATION, "ClaimStartOutOfRange"),
+        (now * 1_000, CLAIM_DURATION, "ClaimStartOutOfRange"),
+        (0, CLAIM_DURATION, "ClaimStartOutOfRange"),
+        (i64::MIN, CLAIM_DURATION, "ClaimStartOutOfRange"),
+        (now, 0, "InvalidDuration"),
+    ] {
+        let ix = h.update_claim_window_ix(start, duration);
+        let failed = h.send_as_authority(ix).expect_err(error);
+        assert!(
+            failed.meta.logs.iter().any(|log| log.contains(error)),
+            "start {start}, duration {duration}: expected {error}"
+        );
+    }
+
+    let ix = h.update_claim_window_ix(now + YEAR, 5 * YEAR);
+    h.send_as_authority(ix).expect("window within bounds");
+}
+
+#[test]
+fn max_start_lead_narrows_the_horizon() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let now = h.now();
+
+    let ix = set_max_start_lead_ix(&h, 11 * YEAR);
+    assert_fails_with(h.send_as_authority(ix), "InvalidStartLead");
+    let ix = set_max_start_lead_ix(&h, -1);
+    assert_fails_with(h.send_as_authority(ix), "InvalidStartLead");
+    let ix = set_max_start_lead_ix(&h, 30 * DAY);
+    h.send_as_authority(ix).expect("set_max_start_lead");
+    assert_eq!(h.state().max_start_lead, 30 * DAY);
+
+    let ix = h.update_claim_window_ix(now + 60 * DAY, CLAIM_DURATION);
+    assert_fails_with(h.send_as_authority(ix), "ClaimStartOutOfRange");
+    let ix = h.update_claim_window_ix(now + 30 * DAY, CLAIM_DURATION);
+    h.send_as_authority(ix).expect("start within the lead");
+    // The past bound is unchanged: an open window can still be extended
+    let ix = h.update_claim_window_ix(now - YEAR, 2 * YEAR);
+    h.send_as_authority(ix).expect("start in the past");
+
+    let ix = set_max_start_lead_ix(&h, 0);
+    h.send_as_authority(ix).expect("reset to default");
+    let ix = h.update_claim_window_ix(now + 60 * DAY, CLAIM_DURATION);
+    h.send_as_authority(ix).expect("default horizon");
+}
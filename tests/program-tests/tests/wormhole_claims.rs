@@ -0,0 +1,116 @@
+//! A Wormhole-homed campaign pays only against a posted VAA from its emitter,
+//! and each index only once.
+
+use airdrop0::{accounts, instruction, WORMHOLE_CORE_BRIDGE};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use litesvm_token::spl_token;
+use program_tests::{assert_fails_with, event_authority, leaf_hash, pad_proof, Harness, TxResult};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const AMOUNT: u64 = 1_000;
+const EMITTER_CHAIN: u16 = 2;
+const EMITTER: [u8; 32] = [7; 32];
+
+// A core bridge `PostedVAA` account carrying a claim-lock payload
+fn post_vaa(h: &mut Harness, emitter: [u8; 32], wallet: &Pubkey, sequence: u64) -> Pubkey {
+    let mut payload = vec![1u8];
+    payload.extend_from_slice(&0u64.to_be_bytes());
+    payload.extend_from_slice(wallet.as_ref());
+    payload.extend_from_slice(&AMOUNT.to_be_bytes());
+
+    let mut data = b"vaa".to_vec();
+    data.extend_from_slice(&[1, 32]); // version, consistency level
+    data.extend_from_slice(&[0; 4 + 32 + 4 + 4]); // vaa_time, signature set, submission_time, nonce
+    data.extend_from_slice(&sequence.to_le_bytes());
+    data.extend_from_slice(&EMITTER_CHAIN.to_le_bytes());
+    data.extend_from_slice(&emitter);
+    data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    data.extend_from_slice(&payload);
+
+    let address = Pubkey::new_unique();
+    let account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: WORMHOLE_CORE_BRIDGE,
+        executable: false,
+        rent_epoch: 0,
+    };
+    h.svm.set_account(address, account).unwrap();
+    address
+}
+
+fn claim_from_vaa(h: &mut Harness, wallet: &Keypair, user_ata: &Pubkey, posted_vaa: Pubkey) -> TxResult {
+    let (proof, proof_len) = pad_proof(&[]);
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::ClaimFromVaa {
+            state: h.state,
+            wallet: wallet.pubkey(),
+            posted_vaa,
+            vault_auth: h.vault_auth,
+            vault: h.vault,
+            user_ata: *user_ata,
+            mint: h.mint,
+            token_program: spl_token::ID,
+            fee_account: None,
+            stats: None,
+            wallet_claims: None,
+            family_claim: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            bonus_vault: None,
+            badge_mint: None,
+            badge_account: None,
+            token_2022_program: None,
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimFromVaa {
+            index: 0,
+            amount: AMOUNT,
+            proof,
+            proof_len,
+            terms_hash: [0; 32],
+        }
+        .data(),
+    };
+    h.send(ix, &[wallet])
+}
+
+#[test]
+fn vaa_claims_pay_once_and_direct_claims_are_off() {
+    let mut h = Harness::new([0; 32], 1, 1);
+    let (wallet, ata) = h.new_claimant();
+    h.set_merkle_root(leaf_hash(0, &wallet.pubkey(), AMOUNT), 1).expect("set_merkle_root");
+
+    let ix = Instruction {
+        program_id: airdrop0::ID,
+        accounts: accounts::SetWormholeEmitter {
+            state: h.state,
+            authority: h.authority.pubkey(),
+            event_authority: event_authority(),
+            program: airdrop0::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetWormholeEmitter { emitter_chain: EMITTER_CHAIN, emitter: EMITTER }.data(),
+    };
+    h.send_as_authority(ix).expect("set_wormhole_emitter");
+
+    let ix = h.claim_ix(&wallet.pubkey(), &ata, 0, AMOUNT, &[]);
+    assert_fails_with(h.send(ix, &[&wallet]), "CrossChainClaimRequired");
+
+    let impostor = post_vaa(&mut h, [8; 32], &wallet.pubkey(), 1);
+    assert_fails_with(claim_from_vaa(&mut h, &wallet, &ata, impostor), "InvalidVaa");
+
+    let vaa = post_vaa(&mut h, EMITTER, &wallet.pubkey(), 2);
+    claim_from_vaa(&mut h, &wallet, &ata, vaa).expect("claim_from_vaa");
+    assert_eq!(h.token_balance(&ata), AMOUNT);
+
+    let replay = post_vaa(&mut h, EMITTER, &wallet.pubkey(), 3);
+    assert_fails_with(claim_from_vaa(&mut h, &wallet, &ata, replay), "AlreadyClaimed");
+}